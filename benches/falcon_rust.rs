@@ -28,10 +28,10 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
         .map(|_| rng.gen::<[u8; 15]>())
         .collect_vec();
     let mut sigs512 = (0..NUM_KEYS * SIGS_PER_KEY)
-        .map(|i| falcon_rust::falcon512::sign(&msgs512[i], &keys512[i % NUM_KEYS].0))
+        .map(|i| falcon_rust::falcon512::sign(msgs512[i], &keys512[i % NUM_KEYS].0))
         .collect_vec();
     let mut sigs1024 = (0..NUM_KEYS * SIGS_PER_KEY)
-        .map(|i| falcon_rust::falcon1024::sign(&msgs1024[i], &keys1024[i % NUM_KEYS].0))
+        .map(|i| falcon_rust::falcon1024::sign(msgs1024[i], &keys1024[i % NUM_KEYS].0))
         .collect_vec();
     let mut expanded_sigs512 = Vec::new();
     let mut expanded_sigs1024 = Vec::new();
@@ -39,12 +39,12 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
         let sig512 = &sigs512[i];
         let sig1024 = &sigs1024[i];
         let expanded_sig512 = falcon_rust::falcon512::ExpandedSignature::from_signature(
-            &msgs512[i],
+            msgs512[i],
             sig512,
             &keys512[i % NUM_KEYS].1,
         );
         let expanded_sig1024 = falcon_rust::falcon1024::ExpandedSignature::from_signature(
-            &msgs1024[i],
+            msgs1024[i],
             sig1024,
             &keys1024[i % NUM_KEYS].1,
         );
@@ -78,7 +78,7 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
     group.bench_function("sign 512", |b| {
         b.iter(|| {
             falcon_rust::falcon512::sign(
-                &msgs512[iterator_sign_512 % (NUM_KEYS * SIGS_PER_KEY)],
+                msgs512[iterator_sign_512 % (NUM_KEYS * SIGS_PER_KEY)],
                 &keys512[iterator_sign_512 % NUM_KEYS].0,
             );
             iterator_sign_512 += 1;
@@ -88,12 +88,44 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
     group.bench_function("sign 1024", |b| {
         b.iter(|| {
             falcon_rust::falcon1024::sign(
-                &msgs1024[iterator_sign_1024 % (NUM_KEYS * SIGS_PER_KEY)],
+                msgs1024[iterator_sign_1024 % (NUM_KEYS * SIGS_PER_KEY)],
                 &keys1024[iterator_sign_1024 % NUM_KEYS].0,
             );
             iterator_sign_1024 += 1;
         })
     });
+    let expanded_keys512 = keys512
+        .iter()
+        .map(|(sk, _)| falcon_rust::falcon512::ExpandedSecretKey::new(sk))
+        .collect_vec();
+    let mut scratch512 = falcon_rust::falcon512::SignScratch::new();
+    let mut iterator_sign_with_scratch_512 = 0;
+    group.bench_function("sign with scratch 512", |b| {
+        b.iter(|| {
+            falcon_rust::falcon512::sign_with_scratch(
+                &msgs512[iterator_sign_with_scratch_512 % (NUM_KEYS * SIGS_PER_KEY)],
+                &expanded_keys512[iterator_sign_with_scratch_512 % NUM_KEYS],
+                &mut scratch512,
+            );
+            iterator_sign_with_scratch_512 += 1;
+        })
+    });
+    let expanded_keys1024 = keys1024
+        .iter()
+        .map(|(sk, _)| falcon_rust::falcon1024::ExpandedSecretKey::new(sk))
+        .collect_vec();
+    let mut scratch1024 = falcon_rust::falcon1024::SignScratch::new();
+    let mut iterator_sign_with_scratch_1024 = 0;
+    group.bench_function("sign with scratch 1024", |b| {
+        b.iter(|| {
+            falcon_rust::falcon1024::sign_with_scratch(
+                &msgs1024[iterator_sign_with_scratch_1024 % (NUM_KEYS * SIGS_PER_KEY)],
+                &expanded_keys1024[iterator_sign_with_scratch_1024 % NUM_KEYS],
+                &mut scratch1024,
+            );
+            iterator_sign_with_scratch_1024 += 1;
+        })
+    });
     group.finish();
 
     let mut group = c.benchmark_group("falcon-rust");
@@ -102,7 +134,7 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
     group.bench_function("verify 512", |b| {
         b.iter(|| {
             assert!(falcon_rust::falcon512::verify(
-                &msgs512[iterator_verify_512 % msgs512.len()],
+                msgs512[iterator_verify_512 % msgs512.len()],
                 &sigs512[iterator_verify_512 % sigs512.len()],
                 &keys512[iterator_verify_512 % NUM_KEYS].1,
             ));
@@ -113,7 +145,7 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
     group.bench_function("verify 1024", |b| {
         b.iter(|| {
             assert!(falcon_rust::falcon1024::verify(
-                &msgs1024[iterator_verify_1024 % msgs1024.len()],
+                msgs1024[iterator_verify_1024 % msgs1024.len()],
                 &sigs1024[iterator_verify_1024 % sigs1024.len()],
                 &keys1024[iterator_verify_1024 % NUM_KEYS].1,
             ));
@@ -131,7 +163,7 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
                 &msgs512[iterator_verify_512 % msgs512.len()],
                 &expanded_sigs512[iterator_verify_512 % expanded_sigs512.len()],
                 &keys512[iterator_verify_512 % NUM_KEYS].1,
-                &vec![0]
+                &[0]
             ));
             iterator_verify_512 += 1;
         })
@@ -143,7 +175,7 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
                 &msgs1024[iterator_verify_1024 % msgs1024.len()],
                 &expanded_sigs1024[iterator_verify_1024 % expanded_sigs1024.len()],
                 &keys1024[iterator_verify_1024 % NUM_KEYS].1,
-                &vec![0]
+                &[0]
             ));
             iterator_verify_1024 += 1;
         })
@@ -22,10 +22,10 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
         .map(|_| rng.gen::<[u8; 15]>())
         .collect_vec();
     let sigs512 = (0..NUM_KEYS * SIGS_PER_KEY)
-        .map(|i| falcon_rust::falcon512::sign(&msgs512[i], &keys512[i % NUM_KEYS].0))
+        .map(|i| falcon_rust::falcon512::sign(msgs512[i], &keys512[i % NUM_KEYS].0))
         .collect_vec();
     let sigs1024 = (0..NUM_KEYS * SIGS_PER_KEY)
-        .map(|i| falcon_rust::falcon1024::sign(&msgs1024[i], &keys1024[i % NUM_KEYS].0))
+        .map(|i| falcon_rust::falcon1024::sign(msgs1024[i], &keys1024[i % NUM_KEYS].0))
         .collect_vec();
     let mut expanded_sigs512 = Vec::new();
     let mut expanded_sigs1024 = Vec::new();
@@ -33,12 +33,12 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
         let sig512 = &sigs512[i];
         let sig1024 = &sigs1024[i];
         let expanded_sig512 = falcon_rust::falcon512::ExpandedSignature::from_signature(
-            &msgs512[i],
+            msgs512[i],
             sig512,
             &keys512[i % NUM_KEYS].1,
         );
         let expanded_sig1024 = falcon_rust::falcon1024::ExpandedSignature::from_signature(
-            &msgs1024[i],
+            msgs1024[i],
             sig1024,
             &keys1024[i % NUM_KEYS].1,
         );
@@ -52,7 +52,7 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
     group.bench_function("verify 512", |b| {
         b.iter(|| {
             assert!(falcon_rust::falcon512::verify(
-                &msgs512[iterator_verify_512 % msgs512.len()],
+                msgs512[iterator_verify_512 % msgs512.len()],
                 &sigs512[iterator_verify_512 % sigs512.len()],
                 &keys512[iterator_verify_512 % NUM_KEYS].1,
             ));
@@ -63,7 +63,7 @@ pub fn falcon_rust_operation(c: &mut Criterion) {
     group.bench_function("verify 1024", |b| {
         b.iter(|| {
             assert!(falcon_rust::falcon1024::verify(
-                &msgs1024[iterator_verify_1024 % msgs1024.len()],
+                msgs1024[iterator_verify_1024 % msgs1024.len()],
                 &sigs1024[iterator_verify_1024 % sigs1024.len()],
                 &keys1024[iterator_verify_1024 % NUM_KEYS].1,
             ));
@@ -0,0 +1,60 @@
+use std::{sync::Arc, thread};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use falcon_rust::falcon512;
+use itertools::Itertools;
+use rand::{thread_rng, Rng};
+
+const NUM_SIGS: usize = 64;
+const NUM_THREADS: usize = 4;
+
+pub fn shared_verifier_context(c: &mut Criterion) {
+    let mut rng = thread_rng();
+    let (sk, pk) = falcon512::keygen(rng.gen());
+    let msgs = (0..NUM_SIGS).map(|_| rng.gen::<[u8; 15]>()).collect_vec();
+    let sigs = msgs
+        .iter()
+        .map(|msg| falcon512::sign(msg, &sk))
+        .collect_vec();
+    let context = Arc::new(falcon512::VerifierContext::new(&pk));
+
+    let mut group = c.benchmark_group("verifier context");
+    group.throughput(Throughput::Elements(NUM_SIGS as u64));
+    group.bench_function("verify 512, public key", |b| {
+        b.iter(|| {
+            for (msg, sig) in msgs.iter().zip(sigs.iter()) {
+                assert!(falcon512::verify(msg, sig, &pk));
+            }
+        })
+    });
+    group.bench_function("verify 512, shared context, 1 thread", |b| {
+        b.iter(|| {
+            for (msg, sig) in msgs.iter().zip(sigs.iter()) {
+                assert!(context.verify(msg, sig));
+            }
+        })
+    });
+    group.bench_function(
+        format!("verify 512, shared context, {NUM_THREADS} threads"),
+        |b| {
+            b.iter(|| {
+                thread::scope(|scope| {
+                    for chunk in (0..NUM_SIGS).chunks(NUM_SIGS / NUM_THREADS).into_iter() {
+                        let indices = chunk.collect_vec();
+                        let context = Arc::clone(&context);
+                        let (msgs, sigs) = (&msgs, &sigs);
+                        scope.spawn(move || {
+                            for i in indices {
+                                assert!(context.verify(&msgs[i], &sigs[i]));
+                            }
+                        });
+                    }
+                })
+            })
+        },
+    );
+    group.finish();
+}
+
+criterion_group!(benches, shared_verifier_context);
+criterion_main!(benches);
@@ -0,0 +1,477 @@
+//! AVX2 kernels for the NTT over Z_q and the complex FFT, which
+//! [`FastFft`](crate::fast_fft::FastFft) dispatches to when the CPU
+//! supports AVX2.
+//!
+//! The kernels compute exactly what the generic loops of
+//! [`CyclotomicFourier`] compute. The NTT is exact anyway; the FFT performs
+//! the same floating-point operations in the same order, without fused
+//! multiply-adds, so signatures do not depend on the CPU either.
+//!
+//! Products modulo q are reduced with Shoup's method: for a twiddle factor
+//! s, precompute s' = floor(s 2^16 / q). For v < 2q, the quotient
+//! (v s') >> 16 underestimates v s / q by less than 2, so v s minus that
+//! quotient times q lies in [0, 2q), and fits 32-bit lanes throughout.
+
+// with `fixed-point`, the complex FFT runs on emulated arithmetic instead
+#![cfg_attr(feature = "fixed-point", allow(dead_code))]
+
+use core::arch::x86_64::*;
+
+use num::Zero;
+use num_complex::Complex64;
+
+use crate::{
+    cyclotomic_fourier::CyclotomicFourier,
+    falcon_field::{Felt, Q},
+    fast_fft::{FELT_BITREVERSED_POWERS_1024, FELT_BITREVERSED_POWERS_INVERSE_1024},
+};
+
+/// Whether the kernels can run on this CPU.
+pub(crate) fn is_available() -> bool {
+    is_x86_feature_detected!("avx2")
+}
+
+/// The Shoup companions of the given twiddle factors.
+const fn shoup_table(powers: &[Felt; 1024]) -> [u32; 1024] {
+    let mut table = [0; 1024];
+    let mut i = 0;
+    while i < 1024 {
+        table[i] = shoup(powers[i].value() as u32);
+        i += 1;
+    }
+    table
+}
+
+const fn shoup(s: u32) -> u32 {
+    (s << 16) / Q
+}
+
+static SHOUP_POWERS_1024: [u32; 1024] = shoup_table(&FELT_BITREVERSED_POWERS_1024);
+static SHOUP_POWERS_INVERSE_1024: [u32; 1024] = shoup_table(&FELT_BITREVERSED_POWERS_INVERSE_1024);
+
+/// Transform to NTT domain in place, like [`CyclotomicFourier::fft`] with
+/// the powers of [`FELT_BITREVERSED_POWERS_1024`].
+///
+/// Panics if AVX2 is not available.
+pub(crate) fn ntt(a: &mut [Felt]) {
+    assert!(is_available());
+    if a.len() < 16 {
+        Felt::fft(a, &FELT_BITREVERSED_POWERS_1024);
+        return;
+    }
+    // SAFETY: AVX2 is available, and `Felt` is a transparent `u32`.
+    unsafe { ntt_avx2(as_u32s(a)) }
+}
+
+/// Transform back from NTT domain in place, like [`CyclotomicFourier::ifft`]
+/// with the powers of [`FELT_BITREVERSED_POWERS_INVERSE_1024`] and the
+/// inverse of the length.
+///
+/// Panics if AVX2 is not available.
+pub(crate) fn intt(a: &mut [Felt], ninv: Felt) {
+    assert!(is_available());
+    if a.len() < 16 {
+        Felt::ifft(a, &FELT_BITREVERSED_POWERS_INVERSE_1024, ninv);
+        return;
+    }
+    // SAFETY: AVX2 is available, and `Felt` is a transparent `u32`.
+    unsafe { intt_avx2(as_u32s(a), ninv.value() as u32) }
+}
+
+/// Transform to FFT domain in place, like [`CyclotomicFourier::fft`] with
+/// the given powers.
+///
+/// Panics if AVX2 is not available.
+pub(crate) fn fft(a: &mut [Complex64], psi_rev: &[Complex64]) {
+    assert!(is_available());
+    assert!(psi_rev.len() >= a.len());
+    // SAFETY: AVX2 is available.
+    unsafe { fft_avx2(a, psi_rev) }
+}
+
+/// Transform back from FFT domain in place, like [`CyclotomicFourier::ifft`]
+/// with the conjugates of the given powers and the inverse of the length.
+///
+/// Panics if AVX2 is not available.
+pub(crate) fn ifft(a: &mut [Complex64], psi_rev: &[Complex64]) {
+    assert!(is_available());
+    assert!(psi_rev.len() >= a.len());
+    // SAFETY: AVX2 is available.
+    unsafe { ifft_avx2(a, psi_rev) }
+}
+
+fn as_u32s(a: &mut [Felt]) -> &mut [u32] {
+    // SAFETY: `Felt` is a transparent `u32`.
+    unsafe { core::slice::from_raw_parts_mut(a.as_mut_ptr().cast(), a.len()) }
+}
+
+/// Reduce lanes in [0, 2q) to [0, q): if x < q, then x - q wraps around to
+/// a larger value.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn reduce(x: __m256i) -> __m256i {
+    _mm256_min_epu32(x, _mm256_sub_epi32(x, _mm256_set1_epi32(Q as i32)))
+}
+
+/// Multiply lanes in [0, 2q) by the twiddle factors `s` with Shoup
+/// companions `s_shoup`, modulo q.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn mul_shoup(v: __m256i, s: __m256i, s_shoup: __m256i) -> __m256i {
+    let quotient = _mm256_srli_epi32::<16>(_mm256_mullo_epi32(v, s_shoup));
+    let product = _mm256_mullo_epi32(v, s);
+    let r = _mm256_sub_epi32(
+        product,
+        _mm256_mullo_epi32(quotient, _mm256_set1_epi32(Q as i32)),
+    );
+    reduce(r)
+}
+
+/// Cooley-Tukey butterflies `(u, v) -> (u + s v, u - s v)`, lane-wise.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn forward_butterfly(u: __m256i, v: __m256i, s: __m256i, s_shoup: __m256i) -> (__m256i, __m256i) {
+    let w = mul_shoup(v, s, s_shoup);
+    let q = _mm256_set1_epi32(Q as i32);
+    let sum = reduce(_mm256_add_epi32(u, w));
+    let difference = reduce(_mm256_sub_epi32(_mm256_add_epi32(u, q), w));
+    (sum, difference)
+}
+
+/// Gentleman-Sande butterflies `(u, v) -> (u + v, (u - v) s)`, lane-wise.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn inverse_butterfly(u: __m256i, v: __m256i, s: __m256i, s_shoup: __m256i) -> (__m256i, __m256i) {
+    let q = _mm256_set1_epi32(Q as i32);
+    let sum = reduce(_mm256_add_epi32(u, v));
+    let difference = mul_shoup(_mm256_sub_epi32(_mm256_add_epi32(u, q), v), s, s_shoup);
+    (sum, difference)
+}
+
+/// Load the twiddle factors at the given indices, and their companions.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn twiddles(powers: &[Felt; 1024], shoup: &[u32; 1024], idx: [usize; 8]) -> (__m256i, __m256i) {
+    let s = idx.map(|i| powers[i].value() as i32);
+    let s_shoup = idx.map(|i| shoup[i] as i32);
+    (
+        _mm256_setr_epi32(s[0], s[1], s[2], s[3], s[4], s[5], s[6], s[7]),
+        _mm256_setr_epi32(
+            s_shoup[0], s_shoup[1], s_shoup[2], s_shoup[3], s_shoup[4], s_shoup[5], s_shoup[6],
+            s_shoup[7],
+        ),
+    )
+}
+
+/// The butterflies of one layer, where each block of `2 t` coefficients
+/// pairs its halves under the twiddle factor at index `m + block`. For
+/// `t < 8`, the pairs within two vectors are gathered into one vector of
+/// first and one of second halves by shuffles, and scattered back after.
+///
+/// # Safety
+///
+/// AVX2 must be available, and the length must be a multiple of 16 and
+/// of `2 t`.
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn layer(
+    a: &mut [u32],
+    t: usize,
+    m: usize,
+    powers: &[Felt; 1024],
+    shoup: &[u32; 1024],
+    butterfly: impl Fn(__m256i, __m256i, __m256i, __m256i) -> (__m256i, __m256i),
+) {
+    let p = a.as_mut_ptr();
+    let load = |i: usize| _mm256_loadu_si256(p.add(i).cast());
+    let store = |i: usize, x: __m256i| _mm256_storeu_si256(p.add(i).cast(), x);
+    match t {
+        1 => {
+            for base in (0..a.len()).step_by(16) {
+                let block = m + base / 2;
+                let (x, y) = (load(base), load(base + 8));
+                // even and odd lanes of x then y, within 128-bit halves
+                let u = _mm256_castps_si256(_mm256_shuffle_ps::<0b10_00_10_00>(
+                    _mm256_castsi256_ps(x),
+                    _mm256_castsi256_ps(y),
+                ));
+                let v = _mm256_castps_si256(_mm256_shuffle_ps::<0b11_01_11_01>(
+                    _mm256_castsi256_ps(x),
+                    _mm256_castsi256_ps(y),
+                ));
+                let idx = [0, 1, 4, 5, 2, 3, 6, 7].map(|k| block + k);
+                let (s, s_shoup) = twiddles(powers, shoup, idx);
+                let (u, v) = butterfly(u, v, s, s_shoup);
+                store(base, _mm256_unpacklo_epi32(u, v));
+                store(base + 8, _mm256_unpackhi_epi32(u, v));
+            }
+        }
+        2 => {
+            for base in (0..a.len()).step_by(16) {
+                let block = m + base / 4;
+                let (x, y) = (load(base), load(base + 8));
+                let u = _mm256_unpacklo_epi64(x, y);
+                let v = _mm256_unpackhi_epi64(x, y);
+                let idx = [0, 0, 2, 2, 1, 1, 3, 3].map(|k| block + k);
+                let (s, s_shoup) = twiddles(powers, shoup, idx);
+                let (u, v) = butterfly(u, v, s, s_shoup);
+                store(base, _mm256_unpacklo_epi64(u, v));
+                store(base + 8, _mm256_unpackhi_epi64(u, v));
+            }
+        }
+        4 => {
+            for base in (0..a.len()).step_by(16) {
+                let block = m + base / 8;
+                let (x, y) = (load(base), load(base + 8));
+                let u = _mm256_permute2x128_si256::<0x20>(x, y);
+                let v = _mm256_permute2x128_si256::<0x31>(x, y);
+                let idx = [0, 0, 0, 0, 1, 1, 1, 1].map(|k| block + k);
+                let (s, s_shoup) = twiddles(powers, shoup, idx);
+                let (u, v) = butterfly(u, v, s, s_shoup);
+                store(base, _mm256_permute2x128_si256::<0x20>(u, v));
+                store(base + 8, _mm256_permute2x128_si256::<0x31>(u, v));
+            }
+        }
+        _ => {
+            for (i, j1) in (0..a.len()).step_by(2 * t).enumerate() {
+                let s = _mm256_set1_epi32(powers[m + i].value() as i32);
+                let s_shoup = _mm256_set1_epi32(shoup[m + i] as i32);
+                for j in (j1..j1 + t).step_by(8) {
+                    let (u, v) = butterfly(load(j), load(j + t), s, s_shoup);
+                    store(j, u);
+                    store(j + t, v);
+                }
+            }
+        }
+    }
+}
+
+/// # Safety
+///
+/// AVX2 must be available, and the length must be a power of two from 16
+/// to 1024.
+#[target_feature(enable = "avx2")]
+unsafe fn ntt_avx2(a: &mut [u32]) {
+    let n = a.len();
+    assert!(n.is_power_of_two() && (16..=1024).contains(&n));
+    let mut t = n;
+    let mut m = 1;
+    while m < n {
+        t >>= 1;
+        layer(
+            a,
+            t,
+            m,
+            &FELT_BITREVERSED_POWERS_1024,
+            &SHOUP_POWERS_1024,
+            |u, v, s, s_shoup| forward_butterfly(u, v, s, s_shoup),
+        );
+        m <<= 1;
+    }
+}
+
+/// # Safety
+///
+/// AVX2 must be available, and the length must be a power of two from 16
+/// to 1024.
+#[target_feature(enable = "avx2")]
+unsafe fn intt_avx2(a: &mut [u32], ninv: u32) {
+    let n = a.len();
+    assert!(n.is_power_of_two() && (16..=1024).contains(&n));
+    let mut t = 1;
+    let mut m = n;
+    while m > 1 {
+        let h = m / 2;
+        layer(
+            a,
+            t,
+            h,
+            &FELT_BITREVERSED_POWERS_INVERSE_1024,
+            &SHOUP_POWERS_INVERSE_1024,
+            |u, v, s, s_shoup| inverse_butterfly(u, v, s, s_shoup),
+        );
+        t <<= 1;
+        m >>= 1;
+    }
+    let s = _mm256_set1_epi32(ninv as i32);
+    let s_shoup = _mm256_set1_epi32(shoup(ninv) as i32);
+    for chunk in a.chunks_exact_mut(8) {
+        let p = chunk.as_mut_ptr().cast();
+        _mm256_storeu_si256(p, mul_shoup(_mm256_loadu_si256(p), s, s_shoup));
+    }
+}
+
+/// Multiply the two complex numbers in `v` by `s`, given as `[s.re, s.im,
+/// s.re, s.im]` and `[s.im, s.re, s.im, s.re]`, exactly as `Complex64`
+/// multiplies: `(v.re s.re - v.im s.im, v.re s.im + v.im s.re)`.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn complex_mul(v: __m256d, s: __m256d, s_swapped: __m256d) -> __m256d {
+    let re = _mm256_movedup_pd(v);
+    let im = _mm256_permute_pd::<0b1111>(v);
+    _mm256_addsub_pd(_mm256_mul_pd(re, s), _mm256_mul_pd(im, s_swapped))
+}
+
+/// Like [`complex_mul`], for one complex number.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn complex_mul_128(v: __m128d, s: __m128d, s_swapped: __m128d) -> __m128d {
+    let re = _mm_movedup_pd(v);
+    let im = _mm_unpackhi_pd(v, v);
+    _mm_addsub_pd(_mm_mul_pd(re, s), _mm_mul_pd(im, s_swapped))
+}
+
+/// # Safety
+///
+/// AVX2 must be available, and the length of `a` must be a power of two
+/// not larger than that of `psi_rev`.
+#[target_feature(enable = "avx2")]
+unsafe fn fft_avx2(a: &mut [Complex64], psi_rev: &[Complex64]) {
+    let n = a.len();
+    // `Complex64` is `#[repr(C)]`, so the slice is interleaved `f64`s
+    let p = a.as_mut_ptr().cast::<f64>();
+    let mut t = n;
+    let mut m = 1;
+    while m < n {
+        t >>= 1;
+        for i in 0..m {
+            let j1 = 2 * i * t;
+            let s = psi_rev[m + i];
+            if t == 1 {
+                let s_pair = _mm_setr_pd(s.re, s.im);
+                let s_swapped = _mm_setr_pd(s.im, s.re);
+                let u = _mm_loadu_pd(p.add(2 * j1));
+                let v = complex_mul_128(_mm_loadu_pd(p.add(2 * j1 + 2)), s_pair, s_swapped);
+                _mm_storeu_pd(p.add(2 * j1), _mm_add_pd(u, v));
+                _mm_storeu_pd(p.add(2 * j1 + 2), _mm_sub_pd(u, v));
+                continue;
+            }
+            let s_pair = _mm256_setr_pd(s.re, s.im, s.re, s.im);
+            let s_swapped = _mm256_setr_pd(s.im, s.re, s.im, s.re);
+            for j in (j1..j1 + t).step_by(2) {
+                let u = _mm256_loadu_pd(p.add(2 * j));
+                let v = complex_mul(_mm256_loadu_pd(p.add(2 * (j + t))), s_pair, s_swapped);
+                _mm256_storeu_pd(p.add(2 * j), _mm256_add_pd(u, v));
+                _mm256_storeu_pd(p.add(2 * (j + t)), _mm256_sub_pd(u, v));
+            }
+        }
+        m <<= 1;
+    }
+}
+
+/// # Safety
+///
+/// AVX2 must be available, and the length of `a` must be a power of two
+/// not larger than that of `psi_rev`.
+#[target_feature(enable = "avx2")]
+unsafe fn ifft_avx2(a: &mut [Complex64], psi_rev: &[Complex64]) {
+    let n = a.len();
+    let p = a.as_mut_ptr().cast::<f64>();
+    let mut t = 1;
+    let mut m = n;
+    while m > 1 {
+        let h = m / 2;
+        for i in 0..h {
+            let j1 = 2 * i * t;
+            let s = psi_rev[h + i].conj();
+            if t == 1 {
+                let s_pair = _mm_setr_pd(s.re, s.im);
+                let s_swapped = _mm_setr_pd(s.im, s.re);
+                let u = _mm_loadu_pd(p.add(2 * j1));
+                let v = _mm_loadu_pd(p.add(2 * j1 + 2));
+                _mm_storeu_pd(p.add(2 * j1), _mm_add_pd(u, v));
+                let d = complex_mul_128(_mm_sub_pd(u, v), s_pair, s_swapped);
+                _mm_storeu_pd(p.add(2 * j1 + 2), d);
+                continue;
+            }
+            let s_pair = _mm256_setr_pd(s.re, s.im, s.re, s.im);
+            let s_swapped = _mm256_setr_pd(s.im, s.re, s.im, s.re);
+            for j in (j1..j1 + t).step_by(2) {
+                let u = _mm256_loadu_pd(p.add(2 * j));
+                let v = _mm256_loadu_pd(p.add(2 * (j + t)));
+                _mm256_storeu_pd(p.add(2 * j), _mm256_add_pd(u, v));
+                let d = complex_mul(_mm256_sub_pd(u, v), s_pair, s_swapped);
+                _mm256_storeu_pd(p.add(2 * (j + t)), d);
+            }
+        }
+        t <<= 1;
+        m >>= 1;
+    }
+    // like `*ai *= ninv`, with the zero imaginary part of ninv multiplied
+    // in, which affects the signs of zeros
+    let ninv = Complex64::new(1.0 / (n as f64), f64::zero());
+    let s_pair = _mm256_setr_pd(ninv.re, ninv.im, ninv.re, ninv.im);
+    let s_swapped = _mm256_setr_pd(ninv.im, ninv.re, ninv.im, ninv.re);
+    if n == 1 {
+        a[0] *= ninv;
+        return;
+    }
+    for j in (0..n).step_by(2) {
+        let x = _mm256_loadu_pd(p.add(2 * j));
+        _mm256_storeu_pd(p.add(2 * j), complex_mul(x, s_pair, s_swapped));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_complex::Complex64;
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        cyclotomic_fourier::CyclotomicFourier,
+        falcon_field::Felt,
+        fast_fft::{
+            COMPLEX_BITREVERSED_POWERS_1024, FELT_BITREVERSED_POWERS_1024,
+            FELT_BITREVERSED_POWERS_INVERSE_1024,
+        },
+        inverse::Inverse,
+    };
+
+    use super::is_available;
+
+    fn bits(a: &[Complex64]) -> Vec<(u64, u64)> {
+        a.iter().map(|c| (c.re.to_bits(), c.im.to_bits())).collect()
+    }
+
+    #[test]
+    fn test_avx2_matches_generic() {
+        if !is_available() {
+            return;
+        }
+        let mut rng = thread_rng();
+        for logn in 0..=10 {
+            let n = 1 << logn;
+
+            let a: Vec<Felt> = (0..n).map(|_| rng.gen()).collect();
+            let mut expected = a.clone();
+            Felt::fft(&mut expected, &FELT_BITREVERSED_POWERS_1024);
+            let mut b = a.clone();
+            super::ntt(&mut b);
+            assert_eq!(b, expected, "ntt, n = {n}");
+
+            let ninv = Felt::new(n as i16).inverse_or_zero();
+            Felt::ifft(&mut expected, &FELT_BITREVERSED_POWERS_INVERSE_1024, ninv);
+            super::intt(&mut b, ninv);
+            assert_eq!(b, expected, "intt, n = {n}");
+            assert_eq!(b, a);
+
+            let a: Vec<Complex64> = (0..n)
+                .map(|_| Complex64::new(rng.gen_range(-1e3..1e3), rng.gen_range(-1e3..1e3)))
+                .collect();
+            let mut expected = a.clone();
+            Complex64::fft(&mut expected, &COMPLEX_BITREVERSED_POWERS_1024);
+            let mut b = a.clone();
+            super::fft(&mut b, &COMPLEX_BITREVERSED_POWERS_1024);
+            assert_eq!(bits(&b), bits(&expected), "fft, n = {n}");
+
+            let psi_inv_rev: Vec<Complex64> = COMPLEX_BITREVERSED_POWERS_1024
+                .iter()
+                .map(|c| c.conj())
+                .collect();
+            let ninv = Complex64::new(1.0 / n as f64, 0.0);
+            Complex64::ifft(&mut expected, &psi_inv_rev, ninv);
+            super::ifft(&mut b, &COMPLEX_BITREVERSED_POWERS_1024);
+            assert_eq!(bits(&b), bits(&expected), "ifft, n = {n}");
+        }
+    }
+}
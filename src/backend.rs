@@ -0,0 +1,585 @@
+//! Interchangeable implementations of the arithmetic behind verification.
+//!
+//! A [`Backend`] bundles the primitives that verification spends its time
+//! in: the NTT and its inverse, pointwise products and differences, hashing
+//! to a point, and the norm check. Three backends are available:
+//!
+//!  - [`Scalar`], the implementation the rest of the crate uses, whose NTT
+//!    runs on AVX2 where the CPU supports it;
+//!  - [`Simd`], which processes coefficients in fixed-width lanes that the
+//!    compiler lowers to vector instructions (SSE2, AVX2, NEON, ...), without
+//!    any `unsafe` code;
+//!  - `CFfi`, with feature `pqcrypto`, which hashes and checks norms with the
+//!    C reference implementation from PQClean.
+//!
+//! The backend used by [`verify`] is selected at runtime with [`select`],
+//! which first cross-checks the candidate against [`Scalar`] and keeps the
+//! current backend if they disagree, so that a misbehaving vectorized path
+//! falls back instead of accepting or rejecting the wrong signatures:
+//!
+//! ```
+//! use falcon_rust::{backend::{self, BackendKind}, falcon512};
+//! # use rand::{thread_rng, Rng};
+//! # let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//! # let sig = falcon512::sign(b"msg", &sk);
+//!
+//! backend::select(BackendKind::Simd).expect("SIMD backend agrees with scalar");
+//! assert_eq!(backend::active_kind(), BackendKind::Simd);
+//! assert!(backend::verify(b"msg", &sig, &pk));
+//!
+//! // A/B: the same signature under each backend
+//! for kind in BackendKind::ALL {
+//!     assert!(backend::verify_with(kind.backend(), b"msg", &sig, &pk));
+//! }
+//! # backend::select(BackendKind::Scalar).unwrap();
+//! ```
+//!
+//! Field elements are passed as `u16` in `[0, q)`, and short vectors as
+//! `i16` with balanced coefficients. All slices are of power-of-two lengths
+//! up to 1024, and the norm check is defined for lengths 512 and 1024 only.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use itertools::Itertools;
+
+use crate::{
+    encoding::decompress,
+    falcon::{FalconVariant, PublicKey, Signature},
+    falcon_field::{Felt, Q},
+    fast_fft::{FastFft, FELT_BITREVERSED_POWERS_1024, FELT_BITREVERSED_POWERS_INVERSE_1024},
+    polynomial::{hash_to_point_into, Polynomial},
+};
+
+/// The arithmetic primitives of verification.
+pub trait Backend: Send + Sync {
+    /// A short name for reports and benchmarks.
+    fn name(&self) -> &'static str;
+
+    /// Transform coefficients to NTT domain, in place.
+    fn ntt(&self, a: &mut [u16]);
+
+    /// Transform NTT-domain values back to coefficients, in place.
+    fn intt(&self, a: &mut [u16]);
+
+    /// Multiply pointwise: `out[i] = a[i] * b[i] mod q`.
+    fn pointwise_mul(&self, a: &[u16], b: &[u16], out: &mut [u16]);
+
+    /// Subtract pointwise: `out[i] = a[i] - b[i] mod q`.
+    fn pointwise_sub(&self, a: &[u16], b: &[u16], out: &mut [u16]);
+
+    /// Hash the concatenation of the given parts to a point, filling `out`.
+    fn hash_to_point(&self, parts: &[&[u8]], out: &mut [u16]);
+
+    /// Test whether the squared norm of `(s1, s2)` is below the signature
+    /// bound of the variant of their length.
+    fn is_short(&self, s1: &[i16], s2: &[i16]) -> bool;
+}
+
+/// The signature bound of the variant with the given degree.
+fn sig_bound(n: usize) -> i64 {
+    assert!(n == 512 || n == 1024, "norm check needs n = 512 or 1024");
+    FalconVariant::from_n(n).parameters().sig_bound
+}
+
+fn balanced(a: u16) -> i16 {
+    Felt::new(a as i16).balanced_value()
+}
+
+/// The implementation that the rest of the crate uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Scalar;
+
+impl Scalar {
+    fn apply(a: &mut [u16], transform: impl FnOnce(&mut Polynomial<Felt>)) {
+        let mut p = Polynomial::new(a.iter().map(|&x| Felt::new(x as i16)).collect_vec());
+        transform(&mut p);
+        for (x, y) in a.iter_mut().zip(p.coefficients) {
+            *x = y.value() as u16;
+        }
+    }
+}
+
+impl Backend for Scalar {
+    fn name(&self) -> &'static str {
+        "scalar"
+    }
+
+    fn ntt(&self, a: &mut [u16]) {
+        Self::apply(a, FastFft::fft_inplace);
+    }
+
+    fn intt(&self, a: &mut [u16]) {
+        Self::apply(a, FastFft::ifft_inplace);
+    }
+
+    fn pointwise_mul(&self, a: &[u16], b: &[u16], out: &mut [u16]) {
+        for ((o, &x), &y) in out.iter_mut().zip(a).zip(b) {
+            *o = (Felt::new(x as i16) * Felt::new(y as i16)).value() as u16;
+        }
+    }
+
+    fn pointwise_sub(&self, a: &[u16], b: &[u16], out: &mut [u16]) {
+        for ((o, &x), &y) in out.iter_mut().zip(a).zip(b) {
+            *o = (Felt::new(x as i16) - Felt::new(y as i16)).value() as u16;
+        }
+    }
+
+    fn hash_to_point(&self, parts: &[&[u8]], out: &mut [u16]) {
+        let mut c = vec![Felt::new(0); out.len()];
+        hash_to_point_into(parts, &mut c);
+        for (o, x) in out.iter_mut().zip(c) {
+            *o = x.value() as u16;
+        }
+    }
+
+    fn is_short(&self, s1: &[i16], s2: &[i16]) -> bool {
+        let length_squared = s1
+            .iter()
+            .chain(s2)
+            .map(|&i| i as i64)
+            .map(|i| i * i)
+            .sum::<i64>();
+        length_squared < sig_bound(s1.len())
+    }
+}
+
+/// The number of coefficients processed at once by [`Simd`].
+const LANES: usize = 16;
+
+/// An implementation that works on [`LANES`] coefficients at a time, in
+/// fixed-size arrays of `u32`, so that the compiler can keep them in vector
+/// registers. NTT layers whose butterflies span fewer than [`LANES`]
+/// coefficients, and hashing, which is inherently sequential, are done one
+/// coefficient at a time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Simd;
+
+impl Simd {
+    /// Apply `op` to the coefficients pairwise, a lane at a time.
+    fn lanes(a: &[u16], b: &[u16], out: &mut [u16], op: impl Fn(u32, u32) -> u32) {
+        assert!(a.len() == out.len() && b.len() == out.len());
+        let mut out_chunks = out.chunks_exact_mut(LANES);
+        let mut a_chunks = a.chunks_exact(LANES);
+        let mut b_chunks = b.chunks_exact(LANES);
+        for ((o, x), y) in (&mut out_chunks).zip(&mut a_chunks).zip(&mut b_chunks) {
+            let x: [u32; LANES] = std::array::from_fn(|k| x[k] as u32);
+            let y: [u32; LANES] = std::array::from_fn(|k| y[k] as u32);
+            let z: [u32; LANES] = std::array::from_fn(|k| op(x[k], y[k]));
+            for (o, z) in o.iter_mut().zip(z) {
+                *o = z as u16;
+            }
+        }
+        let tail = out_chunks.into_remainder().iter_mut();
+        for ((o, &x), &y) in tail.zip(a_chunks.remainder()).zip(b_chunks.remainder()) {
+            *o = op(x as u32, y as u32) as u16;
+        }
+    }
+
+    /// Cooley-Tukey butterflies `(u, v) -> (u + s v, u - s v)` across two
+    /// halves of a block.
+    fn forward_butterflies(lo: &mut [u32], hi: &mut [u32], s: u32) {
+        if lo.len() < LANES {
+            for (u, v) in lo.iter_mut().zip(hi.iter_mut()) {
+                let w = *v * s % Q;
+                (*u, *v) = ((*u + w) % Q, (*u + Q - w) % Q);
+            }
+            return;
+        }
+        for (u, v) in lo.chunks_exact_mut(LANES).zip(hi.chunks_exact_mut(LANES)) {
+            let u = <&mut [u32; LANES]>::try_from(u).unwrap();
+            let v = <&mut [u32; LANES]>::try_from(v).unwrap();
+            for (u, v) in u.iter_mut().zip(v.iter_mut()) {
+                let w = *v * s % Q;
+                (*u, *v) = ((*u + w) % Q, (*u + Q - w) % Q);
+            }
+        }
+    }
+
+    /// Gentleman-Sande butterflies `(u, v) -> (u + v, (u - v) s)` across
+    /// two halves of a block.
+    fn inverse_butterflies(lo: &mut [u32], hi: &mut [u32], s: u32) {
+        if lo.len() < LANES {
+            for (u, v) in lo.iter_mut().zip(hi.iter_mut()) {
+                (*u, *v) = ((*u + *v) % Q, (*u + Q - *v) * s % Q);
+            }
+            return;
+        }
+        for (u, v) in lo.chunks_exact_mut(LANES).zip(hi.chunks_exact_mut(LANES)) {
+            let u = <&mut [u32; LANES]>::try_from(u).unwrap();
+            let v = <&mut [u32; LANES]>::try_from(v).unwrap();
+            for (u, v) in u.iter_mut().zip(v.iter_mut()) {
+                (*u, *v) = ((*u + *v) % Q, (*u + Q - *v) * s % Q);
+            }
+        }
+    }
+}
+
+impl Backend for Simd {
+    fn name(&self) -> &'static str {
+        "simd"
+    }
+
+    fn ntt(&self, a: &mut [u16]) {
+        let n = a.len();
+        let mut x = a.iter().map(|&c| c as u32).collect_vec();
+        let mut t = n;
+        let mut m = 1;
+        while m < n {
+            t >>= 1;
+            for (i, block) in x.chunks_exact_mut(2 * t).enumerate() {
+                let s = FELT_BITREVERSED_POWERS_1024[m + i].value() as u32;
+                let (lo, hi) = block.split_at_mut(t);
+                Self::forward_butterflies(lo, hi, s);
+            }
+            m <<= 1;
+        }
+        for (c, x) in a.iter_mut().zip(x) {
+            *c = x as u16;
+        }
+    }
+
+    fn intt(&self, a: &mut [u16]) {
+        let n = a.len();
+        let mut x = a.iter().map(|&c| c as u32).collect_vec();
+        let mut t = 1;
+        let mut m = n;
+        while m > 1 {
+            let h = m / 2;
+            for (i, block) in x.chunks_exact_mut(2 * t).enumerate() {
+                let s = FELT_BITREVERSED_POWERS_INVERSE_1024[h + i].value() as u32;
+                let (lo, hi) = block.split_at_mut(t);
+                Self::inverse_butterflies(lo, hi, s);
+            }
+            t <<= 1;
+            m >>= 1;
+        }
+        // n^-1 mod q, as q - 1 is divisible by n
+        let ninv = Q - (Q - 1) / n as u32;
+        for (c, x) in a.iter_mut().zip(x) {
+            *c = (x * ninv % Q) as u16;
+        }
+    }
+
+    fn pointwise_mul(&self, a: &[u16], b: &[u16], out: &mut [u16]) {
+        Self::lanes(a, b, out, |x, y| x * y % Q);
+    }
+
+    fn pointwise_sub(&self, a: &[u16], b: &[u16], out: &mut [u16]) {
+        Self::lanes(a, b, out, |x, y| (x + Q - y) % Q);
+    }
+
+    fn hash_to_point(&self, parts: &[&[u8]], out: &mut [u16]) {
+        Scalar.hash_to_point(parts, out);
+    }
+
+    fn is_short(&self, s1: &[i16], s2: &[i16]) -> bool {
+        // squares of 16-bit values summed in 64 bits cannot overflow
+        let sum_squares = |s: &[i16]| {
+            let mut chunks = s.chunks_exact(LANES);
+            let mut acc = [0i64; LANES];
+            for chunk in &mut chunks {
+                for (acc, &c) in acc.iter_mut().zip(chunk) {
+                    *acc += c as i64 * c as i64;
+                }
+            }
+            acc.iter().sum::<i64>()
+                + chunks
+                    .remainder()
+                    .iter()
+                    .map(|&c| c as i64 * c as i64)
+                    .sum::<i64>()
+        };
+        sum_squares(s1) + sum_squares(s2) < sig_bound(s1.len())
+    }
+}
+
+#[cfg(feature = "pqcrypto")]
+pub use ffi::CFfi;
+
+#[cfg(feature = "pqcrypto")]
+mod ffi {
+    use super::{sig_bound, Backend, Scalar};
+
+    /// The incremental SHAKE256 state of PQClean's `fips202.h`.
+    #[repr(C)]
+    struct Shake256IncCtx {
+        ctx: [u64; 26],
+    }
+
+    // Linked through `pqcrypto-falcon` and `pqcrypto-internals`.
+    extern "C" {
+        fn shake256_inc_init(state: *mut Shake256IncCtx);
+        fn shake256_inc_absorb(state: *mut Shake256IncCtx, input: *const u8, inlen: usize);
+        fn shake256_inc_finalize(state: *mut Shake256IncCtx);
+        fn shake256_inc_ctx_release(state: *mut Shake256IncCtx);
+        fn PQCLEAN_FALCON512_CLEAN_hash_to_point_vartime(
+            sc: *mut Shake256IncCtx,
+            x: *mut u16,
+            logn: u32,
+        );
+        fn PQCLEAN_FALCON1024_CLEAN_is_short(s1: *const i16, s2: *const i16, logn: u32) -> i32;
+        fn PQCLEAN_FALCON512_CLEAN_is_short(s1: *const i16, s2: *const i16, logn: u32) -> i32;
+    }
+
+    /// Hashing and norm checks by the C reference implementation in
+    /// PQClean; the NTT and pointwise operations are [`Scalar`]'s.
+    ///
+    /// The reference norm check accepts a squared norm equal to the bound,
+    /// which the other backends reject; no signature produced by a signer
+    /// lands exactly on the bound in practice.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct CFfi;
+
+    fn logn(n: usize) -> u32 {
+        assert!(
+            n.is_power_of_two() && (2..=1024).contains(&n),
+            "length must be a power of two between 2 and 1024"
+        );
+        n.trailing_zeros()
+    }
+
+    impl Backend for CFfi {
+        fn name(&self) -> &'static str {
+            "c-ffi"
+        }
+
+        fn ntt(&self, a: &mut [u16]) {
+            Scalar.ntt(a);
+        }
+
+        fn intt(&self, a: &mut [u16]) {
+            Scalar.intt(a);
+        }
+
+        fn pointwise_mul(&self, a: &[u16], b: &[u16], out: &mut [u16]) {
+            Scalar.pointwise_mul(a, b, out);
+        }
+
+        fn pointwise_sub(&self, a: &[u16], b: &[u16], out: &mut [u16]) {
+            Scalar.pointwise_sub(a, b, out);
+        }
+
+        fn hash_to_point(&self, parts: &[&[u8]], out: &mut [u16]) {
+            let logn = logn(out.len());
+            let mut state = Shake256IncCtx { ctx: [0; 26] };
+            // SAFETY: the state is initialized before use and released after,
+            // every input pointer is valid for its length, and `out` holds
+            // exactly 2^logn coefficients.
+            unsafe {
+                shake256_inc_init(&mut state);
+                for part in parts {
+                    shake256_inc_absorb(&mut state, part.as_ptr(), part.len());
+                }
+                shake256_inc_finalize(&mut state);
+                PQCLEAN_FALCON512_CLEAN_hash_to_point_vartime(&mut state, out.as_mut_ptr(), logn);
+                shake256_inc_ctx_release(&mut state);
+            }
+        }
+
+        fn is_short(&self, s1: &[i16], s2: &[i16]) -> bool {
+            sig_bound(s1.len());
+            assert_eq!(s1.len(), s2.len());
+            let logn = logn(s1.len());
+            // SAFETY: both vectors hold exactly 2^logn coefficients.
+            let short = unsafe {
+                match logn {
+                    9 => PQCLEAN_FALCON512_CLEAN_is_short(s1.as_ptr(), s2.as_ptr(), logn),
+                    _ => PQCLEAN_FALCON1024_CLEAN_is_short(s1.as_ptr(), s2.as_ptr(), logn),
+                }
+            };
+            short != 0
+        }
+    }
+}
+
+/// The available backends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BackendKind {
+    Scalar,
+    Simd,
+    #[cfg(feature = "pqcrypto")]
+    CFfi,
+}
+
+impl BackendKind {
+    /// All backends available in this build.
+    pub const ALL: &'static [BackendKind] = &[
+        BackendKind::Scalar,
+        BackendKind::Simd,
+        #[cfg(feature = "pqcrypto")]
+        BackendKind::CFfi,
+    ];
+
+    /// The backend of this kind.
+    pub fn backend(self) -> &'static dyn Backend {
+        match self {
+            BackendKind::Scalar => &Scalar,
+            BackendKind::Simd => &Simd,
+            #[cfg(feature = "pqcrypto")]
+            BackendKind::CFfi => &CFfi,
+        }
+    }
+
+    /// Find a backend by its [`Backend::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.backend().name() == name)
+    }
+
+    fn index(self) -> u8 {
+        Self::ALL.iter().position(|&kind| kind == self).unwrap() as u8
+    }
+}
+
+/// Why a backend could not be selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendError {
+    /// The backend disagrees with [`Scalar`] on the self-test.
+    SelfTestFailed,
+}
+
+/// The index into [`BackendKind::ALL`] of the active backend.
+static ACTIVE: AtomicU8 = AtomicU8::new(0);
+
+/// The kind of the backend used by [`verify`]; [`BackendKind::Scalar`]
+/// unless another one was selected.
+pub fn active_kind() -> BackendKind {
+    BackendKind::ALL[ACTIVE.load(Ordering::Relaxed) as usize]
+}
+
+/// The backend used by [`verify`].
+pub fn active() -> &'static dyn Backend {
+    active_kind().backend()
+}
+
+/// Make the backend of the given kind the one used by [`verify`], for the
+/// whole process, if it passes [`self_test`]. Otherwise the active backend
+/// stays as it is.
+pub fn select(kind: BackendKind) -> Result<(), BackendError> {
+    if !self_test(kind.backend()) {
+        return Err(BackendError::SelfTestFailed);
+    }
+    ACTIVE.store(kind.index(), Ordering::Relaxed);
+    Ok(())
+}
+
+/// Cross-check every primitive of the backend against [`Scalar`] on fixed
+/// pseudorandom inputs of both degrees.
+pub fn self_test(backend: &dyn Backend) -> bool {
+    [512, 1024].into_iter().all(|n| {
+        // a small LCG suffices to spread the inputs over the field
+        let mut state = n as u32;
+        let mut next = || {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16) as u16
+        };
+        let a = (0..n).map(|_| next() % Q as u16).collect_vec();
+        let b = (0..n).map(|_| next() % Q as u16).collect_vec();
+        let short = (0..n).map(|_| (next() % 301) as i16 - 150).collect_vec();
+        let long = short.iter().map(|&c| c * 100).collect_vec();
+
+        primitive_outputs(backend, &a, &b) == primitive_outputs(&Scalar, &a, &b)
+            && backend.is_short(&short, &short)
+            && !backend.is_short(&long, &long)
+    })
+}
+
+/// The results of the NTT, inverse NTT, pointwise product and difference,
+/// and hash to point on the given inputs.
+fn primitive_outputs(backend: &dyn Backend, a: &[u16], b: &[u16]) -> [Vec<u16>; 5] {
+    let mut ntt = a.to_vec();
+    backend.ntt(&mut ntt);
+    let mut intt = a.to_vec();
+    backend.intt(&mut intt);
+    let mut product = vec![0; a.len()];
+    backend.pointwise_mul(a, b, &mut product);
+    let mut difference = vec![0; a.len()];
+    backend.pointwise_sub(a, b, &mut difference);
+    let mut hash = vec![0; a.len()];
+    backend.hash_to_point(&[b"backend", b" self-test"], &mut hash);
+    [ntt, intt, product, difference, hash]
+}
+
+/// Verify a signature with the active backend. Whichever backend that is,
+/// the result is that of [`falcon512::verify`](crate::falcon512::verify) or
+/// its Falcon-1024 counterpart.
+pub fn verify<const N: usize>(m: &[u8], sig: &Signature<N>, pk: &PublicKey<N>) -> bool {
+    verify_with(active(), m, sig, pk)
+}
+
+/// Verify a signature with the given backend.
+pub fn verify_with<const N: usize>(
+    backend: &dyn Backend,
+    m: &[u8],
+    sig: &Signature<N>,
+    pk: &PublicKey<N>,
+) -> bool {
+    let Some(s2) = decompress(&sig.s, N) else {
+        return false;
+    };
+    let mut c = vec![0; N];
+    backend.hash_to_point(&[&sig.r, m], &mut c);
+    let mut h_ntt =
+        pk.h.coefficients
+            .iter()
+            .map(|x| x.value() as u16)
+            .collect_vec();
+    backend.ntt(&mut h_ntt);
+    let mut s2_ntt = s2
+        .iter()
+        .map(|&x| Felt::new(x).value() as u16)
+        .collect_vec();
+    backend.ntt(&mut s2_ntt);
+    backend.ntt(&mut c);
+
+    // s1 = c - s2 * h
+    let mut product = vec![0; N];
+    backend.pointwise_mul(&s2_ntt, &h_ntt, &mut product);
+    let mut s1 = vec![0; N];
+    backend.pointwise_sub(&c, &product, &mut s1);
+    backend.intt(&mut s1);
+    let s1 = s1.into_iter().map(balanced).collect_vec();
+    backend.is_short(&s1, &s2)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon1024, falcon512};
+
+    use super::{self_test, verify_with, BackendKind};
+
+    #[test]
+    fn test_backends() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let sig = falcon512::sign(b"msg", &sk);
+        let (sk1024, pk1024) = falcon1024::keygen(rng.gen());
+        let sig1024 = falcon1024::sign(b"msg", &sk1024);
+
+        for &kind in BackendKind::ALL {
+            let backend = kind.backend();
+            assert!(self_test(backend), "{}", backend.name());
+            assert_eq!(BackendKind::from_name(backend.name()), Some(kind));
+            assert!(verify_with(backend, b"msg", &sig, &pk));
+            assert!(!verify_with(backend, b"other", &sig, &pk));
+            assert!(verify_with(backend, b"msg", &sig1024, &pk1024));
+
+            // the NTT round-trips on every length
+            let mut n = 2;
+            while n <= 1024 {
+                let a = (0..n)
+                    .map(|_| rng.gen_range(0..12289))
+                    .collect::<Vec<u16>>();
+                let mut b = a.clone();
+                backend.ntt(&mut b);
+                backend.intt(&mut b);
+                assert_eq!(a, b, "{} at n = {n}", backend.name());
+                n *= 2;
+            }
+        }
+    }
+}
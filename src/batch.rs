@@ -0,0 +1,545 @@
+//! Verification of many signatures at once.
+
+#[cfg(any(not(feature = "rayon"), test))]
+use std::thread;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
+
+use rand::seq::index;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use sha3::{Digest, Sha3_256};
+
+use crate::{
+    entropy,
+    falcon::{self, ExpandedSignature, PublicKey, SecretKey, Signature},
+    fast_fft::FastFft,
+    receipt::{Receipt, ReceiptBuilder, VerificationMode, VerificationParameters},
+};
+
+/// How far [`verify_batch_until`] got with an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    /// The signature is valid.
+    Valid,
+    /// The signature is invalid.
+    Invalid,
+    /// The signature passed the fast screening check, but the deadline came
+    /// before it was fully verified.
+    Screened,
+    /// The deadline came before the item was looked at.
+    Unchecked,
+}
+
+/// The number of coefficients [`verify_batch_until`] checks when screening.
+const SCREENING_INDICES: usize = 8;
+
+/// The smallest batch that [`verify_many`] hands to other threads. Smaller
+/// batches take less time to verify than to distribute.
+const MIN_PARALLEL_BATCH: usize = 16;
+
+/// Verify a batch of signatures, each by its own signer, in parallel.
+///
+/// Returns one result per item, in order. Batches of fewer than 16 items
+/// are verified on the calling thread. Larger ones are spread over the
+/// rayon thread pool with the `rayon` feature, and otherwise over as many
+/// scoped threads as the platform reports available parallelism.
+pub fn verify_many<const N: usize, M: AsRef<[u8]> + Sync>(
+    items: &[(M, Signature<N>, PublicKey<N>)],
+) -> Vec<bool> {
+    map_many(items, |(m, sig, pk)| falcon::verify(m.as_ref(), sig, pk))
+}
+
+/// Apply `f` to every item, in parallel unless the batch is small.
+fn map_many<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Send + Sync) -> Vec<R> {
+    if items.len() < MIN_PARALLEL_BATCH {
+        return items.iter().map(f).collect();
+    }
+    #[cfg(feature = "rayon")]
+    {
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        map_on_threads(items, threads, f)
+    }
+}
+
+/// Apply `f` to every item, on the given number of scoped threads.
+#[cfg(any(not(feature = "rayon"), test))]
+fn map_on_threads<T: Sync, R: Send>(
+    items: &[T],
+    threads: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    if threads == 1 || items.len() < 2 {
+        return items.iter().map(f).collect();
+    }
+
+    let f = &f;
+    let chunk_size = items.len().div_ceil(threads);
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("verification does not panic"))
+            .collect()
+    })
+}
+
+/// Verify a batch of signatures by one signer.
+///
+/// Returns one result per signature, in order. The public key is brought
+/// into NTT domain once for the whole batch instead of once per signature.
+///
+/// # Panics
+///
+/// Panics if `msgs` and `sigs` differ in length.
+pub fn verify_batch<const N: usize>(
+    msgs: &[&[u8]],
+    sigs: &[Signature<N>],
+    pk: &PublicKey<N>,
+) -> Vec<bool> {
+    assert_eq!(msgs.len(), sigs.len(), "one message per signature");
+    if sigs.is_empty() {
+        return vec![];
+    }
+    let h_ntt = pk.h.fft();
+    msgs.iter()
+        .zip(sigs)
+        .map(|(m, sig)| falcon::verify_with_h_ntt(&[m], sig, &h_ntt))
+        .collect()
+}
+
+/// Like [`verify_batch`], but with each signature checked against its own
+/// public key. The NTT of each distinct key is computed once, however many
+/// signatures in the batch it signed.
+///
+/// # Panics
+///
+/// Panics if `msgs`, `sigs` and `pks` differ in length.
+pub fn verify_batch_multi<const N: usize>(
+    msgs: &[&[u8]],
+    sigs: &[Signature<N>],
+    pks: &[PublicKey<N>],
+) -> Vec<bool> {
+    assert_eq!(msgs.len(), sigs.len(), "one message per signature");
+    assert_eq!(pks.len(), sigs.len(), "one public key per signature");
+    let mut h_ntts = HashMap::new();
+    msgs.iter()
+        .zip(sigs)
+        .zip(pks)
+        .map(|((m, sig), pk)| {
+            let h_ntt = h_ntts.entry(pk).or_insert_with(|| pk.h.fft());
+            falcon::verify_with_h_ntt(&[m], sig, h_ntt)
+        })
+        .collect()
+}
+
+/// Verify a batch of signatures by one signer on the rayon thread pool.
+///
+/// Returns the indices of the invalid signatures, in increasing order. The
+/// public key is brought into NTT domain once for the whole batch.
+#[cfg(feature = "rayon")]
+pub fn par_verify<const N: usize, M: AsRef<[u8]> + Sync>(
+    items: &[(M, Signature<N>)],
+    pk: &PublicKey<N>,
+) -> Vec<usize> {
+    let h_ntt = pk.h.fft();
+    items
+        .par_iter()
+        .enumerate()
+        .filter(|(_, (m, sig))| !falcon::verify_with_h_ntt(&[m.as_ref()], sig, &h_ntt))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Like [`par_verify`], but for expanded signatures, each checked with
+/// [`fverify_fullverify`](falcon::fverify_fullverify) on the given indices.
+#[cfg(feature = "rayon")]
+pub fn par_fverify_fullverify<const N: usize, M: AsRef<[u8]> + Sync>(
+    items: &[(M, ExpandedSignature<N>)],
+    pk: &PublicKey<N>,
+    indices: &[usize],
+) -> Vec<usize> {
+    let h_ntt = pk.h.fft();
+    items
+        .par_iter()
+        .enumerate()
+        .filter(|(_, (m, sig))| {
+            !falcon::fverify_fullverify_with_h_ntt(m.as_ref(), sig, pk, Some(&h_ntt), indices)
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Like [`verify_many`], but also sign a [`Receipt`] with the verifier's
+/// secret key, committing to the valid items in order.
+pub fn verify_many_attested<const N: usize, const V: usize, M: AsRef<[u8]> + Sync>(
+    items: &[(M, Signature<N>, PublicKey<N>)],
+    verifier_sk: &SecretKey<V>,
+) -> (Vec<bool>, Receipt<V>) {
+    let results = verify_many(items);
+    let mut receipt = ReceiptBuilder::new(VerificationParameters {
+        mode: VerificationMode::Full,
+        indices: 0,
+    });
+    for ((m, sig, pk), _) in items.iter().zip(&results).filter(|(_, valid)| **valid) {
+        receipt.accept(m.as_ref(), sig, pk);
+    }
+    (results, receipt.finish(verifier_sk))
+}
+
+/// Verify as many expanded signatures by one signer as possible before the
+/// deadline.
+///
+/// All items are first screened with [`fverify`](falcon::fverify) on a few
+/// coefficients, chosen at random per call so that signatures cannot be
+/// crafted to pass, which rejects most invalid signatures cheaply. The items
+/// that pass are then fully verified, in order. The deadline is checked
+/// before each step; items it cuts short are reported as
+/// [`Verdict::Screened`] or [`Verdict::Unchecked`].
+pub fn verify_batch_until<const N: usize, M: AsRef<[u8]>>(
+    items: &[(M, ExpandedSignature<N>)],
+    pk: &PublicKey<N>,
+    deadline: Instant,
+) -> Vec<Verdict> {
+    let mut verdicts = vec![Verdict::Unchecked; items.len()];
+
+    let indices = index::sample(&mut entropy::rng(), N, SCREENING_INDICES).into_vec();
+    for ((m, sig), verdict) in items.iter().zip(verdicts.iter_mut()) {
+        if Instant::now() >= deadline {
+            return verdicts;
+        }
+        *verdict = if falcon::fverify(m.as_ref(), sig, pk, &indices) {
+            Verdict::Screened
+        } else {
+            Verdict::Invalid
+        };
+    }
+
+    let mut h_ntt = None;
+    for ((m, sig), verdict) in items.iter().zip(verdicts.iter_mut()) {
+        if *verdict != Verdict::Screened {
+            continue;
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        let h_ntt = h_ntt.get_or_insert_with(|| pk.h.fft());
+        *verdict = if falcon::verify_expanded_with_h_ntt(m.as_ref(), sig, h_ntt) {
+            Verdict::Valid
+        } else {
+            Verdict::Invalid
+        };
+    }
+    verdicts
+}
+
+/// Filters repeated (message, signature) records out of a stream.
+///
+/// Records are identified by a SHA3-256 hash of the message and the
+/// canonical encoding of the signature, so memory use is 32 bytes per
+/// distinct record however long the messages are. Signatures are compared
+/// as encoded; two different encodings of the same signature count as
+/// distinct records.
+#[derive(Debug, Clone, Default)]
+pub struct Deduplicator {
+    seen: HashSet<[u8; 32]>,
+}
+
+impl Deduplicator {
+    /// Create a deduplicator that has seen nothing yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a message and signature, returning whether it is the first
+    /// time they are seen together.
+    pub fn insert<const N: usize>(&mut self, m: &[u8], sig: &Signature<N>) -> bool {
+        let mut hasher = Sha3_256::default();
+        hasher.update((m.len() as u64).to_le_bytes());
+        hasher.update(m);
+        hasher.update(sig.as_bytes());
+        self.seen.insert(hasher.finalize().into())
+    }
+
+    /// The number of distinct records seen.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Test whether no record has been seen.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    /// Forget all records seen so far.
+    pub fn clear(&mut self) {
+        self.seen.clear();
+    }
+}
+
+/// Drop repeated (message, signature) records from a stream, keeping the
+/// first occurrence of each in its original position. See [`Deduplicator`].
+///
+/// Deduplicating before verification saves verifying the same signature
+/// again for every copy of it that, e.g., gossip delivers.
+pub fn dedup<const N: usize, M: AsRef<[u8]>>(
+    records: impl IntoIterator<Item = (M, Signature<N>)>,
+) -> impl Iterator<Item = (M, Signature<N>)> {
+    let mut deduplicator = Deduplicator::new();
+    records
+        .into_iter()
+        .filter(move |(m, sig)| deduplicator.insert(m.as_ref(), sig))
+}
+
+/// Like [`verify_batch_until`], but also sign a [`Receipt`] with the
+/// verifier's secret key, committing to the items found
+/// [`Valid`](Verdict::Valid), in order.
+pub fn verify_batch_until_attested<const N: usize, const V: usize, M: AsRef<[u8]>>(
+    items: &[(M, ExpandedSignature<N>)],
+    pk: &PublicKey<N>,
+    deadline: Instant,
+    verifier_sk: &SecretKey<V>,
+) -> (Vec<Verdict>, Receipt<V>) {
+    let verdicts = verify_batch_until(items, pk, deadline);
+    let mut receipt = ReceiptBuilder::new(VerificationParameters {
+        mode: VerificationMode::ScreenedFull,
+        indices: SCREENING_INDICES as u16,
+    });
+    for ((m, sig), _) in items
+        .iter()
+        .zip(&verdicts)
+        .filter(|(_, verdict)| **verdict == Verdict::Valid)
+    {
+        receipt.accept_expanded(m.as_ref(), sig, pk);
+    }
+    (verdicts, receipt.finish(verifier_sk))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon512;
+
+    use super::{
+        dedup, map_many, map_on_threads, verify_batch, verify_batch_multi, verify_batch_until,
+        verify_batch_until_attested, verify_many, verify_many_attested, Deduplicator, Verdict,
+        MIN_PARALLEL_BATCH,
+    };
+
+    #[test]
+    fn test_verify_many() {
+        let mut rng = thread_rng();
+        let keys = (0..3)
+            .map(|_| falcon512::keygen(rng.gen()))
+            .collect::<Vec<_>>();
+        let mut items = (0..6)
+            .map(|i| {
+                let (sk, pk) = &keys[i % keys.len()];
+                let msg = vec![i as u8; 10];
+                let sig = falcon512::sign(&msg, sk);
+                (msg, sig, pk.clone())
+            })
+            .collect::<Vec<_>>();
+
+        // wrong signer, wrong message
+        items[1].2 = keys[0].1.clone();
+        items[4].0.push(0);
+
+        let expected = vec![true, false, true, true, false, true];
+        assert_eq!(verify_many(&items), expected);
+        let verify_item = |(m, sig, pk): &(Vec<u8>, _, _)| falcon512::verify(m, sig, pk);
+        for threads in [1, 2, 4, 8] {
+            assert_eq!(map_on_threads(&items, threads, verify_item), expected);
+        }
+        assert!(verify_many::<512, Vec<u8>>(&[]).is_empty());
+
+        // a batch large enough to be verified in parallel
+        let len = MIN_PARALLEL_BATCH + 2;
+        let large = items.iter().cycle().take(len).cloned().collect::<Vec<_>>();
+        let expected = expected.into_iter().cycle().take(len).collect::<Vec<_>>();
+        assert_eq!(verify_many(&large), expected);
+    }
+
+    #[test]
+    fn test_verify_many_inline() {
+        // small batches never leave the calling thread
+        let caller = thread::current().id();
+        let items = vec![(); MIN_PARALLEL_BATCH - 1];
+        let threads = map_many(&items, |_| thread::current().id());
+        assert_eq!(threads.len(), items.len());
+        assert!(threads.iter().all(|&id| id == caller));
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let (other_sk, other_pk) = falcon512::keygen(rng.gen());
+        let msgs = (0..5).map(|i| vec![i as u8; 10]).collect::<Vec<_>>();
+        let msgs = msgs.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let mut sigs = msgs
+            .iter()
+            .enumerate()
+            .map(|(i, msg)| falcon512::sign(msg, if i == 3 { &other_sk } else { &sk }))
+            .collect::<Vec<_>>();
+        sigs.swap(0, 1);
+
+        assert_eq!(
+            verify_batch(&msgs, &sigs, &pk),
+            [false, false, true, false, true]
+        );
+        assert!(falcon512::verify_batch(&[], &[], &pk).is_empty());
+
+        let pks = [&pk, &pk, &pk, &other_pk, &other_pk].map(Clone::clone);
+        assert_eq!(
+            verify_batch_multi(&msgs, &sigs, &pks),
+            [false, false, true, true, false]
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_verify() {
+        use super::{par_fverify_fullverify, par_verify};
+
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let (other_sk, _) = falcon512::keygen(rng.gen());
+        let items = (0..9)
+            .map(|i| {
+                let msg = vec![i as u8; 10];
+                let sig = falcon512::sign(&msg, if i % 4 == 1 { &other_sk } else { &sk });
+                (msg, sig)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(par_verify(&items, &pk), [1, 5]);
+        assert!(falcon512::par_verify::<Vec<u8>>(&[], &pk).is_empty());
+
+        let expanded = items
+            .iter()
+            .map(|(msg, sig)| {
+                let sig = falcon512::ExpandedSignature::from_signature(msg, sig, &pk);
+                (msg.clone(), sig)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(par_fverify_fullverify(&expanded, &pk, &[0, 7, 300]), [1, 5]);
+    }
+
+    #[test]
+    fn test_verify_batch_until() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let (other_sk, _) = falcon512::keygen(rng.gen());
+        let items = (0..4)
+            .map(|i| {
+                let msg = vec![i as u8; 10];
+                let sig = falcon512::sign(&msg, if i == 2 { &other_sk } else { &sk });
+                let sig = falcon512::ExpandedSignature::from_signature(&msg, &sig, &pk);
+                (msg, sig)
+            })
+            .collect::<Vec<_>>();
+
+        let far_future = Instant::now() + Duration::from_secs(3600);
+        assert_eq!(
+            verify_batch_until(&items, &pk, far_future),
+            [
+                Verdict::Valid,
+                Verdict::Valid,
+                Verdict::Invalid,
+                Verdict::Valid
+            ]
+        );
+        assert_eq!(
+            verify_batch_until(&items, &pk, Instant::now()),
+            [Verdict::Unchecked; 4]
+        );
+        assert!(falcon512::verify_batch_until::<Vec<u8>>(&[], &pk, far_future).is_empty());
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut rng = thread_rng();
+        let (sk, _) = falcon512::keygen(rng.gen());
+        let a = falcon512::sign(b"a", &sk);
+        let b = falcon512::sign(b"b", &sk);
+        let a2 = falcon512::sign(b"a", &sk);
+
+        let records = vec![
+            (b"a".to_vec(), a.clone()),
+            (b"b".to_vec(), b.clone()),
+            (b"a".to_vec(), a.clone()),
+            (b"a".to_vec(), a2.clone()),
+            (b"b".to_vec(), b.clone()),
+            // same signature, different message
+            (b"ab".to_vec(), a.clone()),
+        ];
+        let unique = dedup(records).collect::<Vec<_>>();
+        assert_eq!(
+            unique,
+            vec![
+                (b"a".to_vec(), a.clone()),
+                (b"b".to_vec(), b),
+                (b"a".to_vec(), a2),
+                (b"ab".to_vec(), a.clone()),
+            ]
+        );
+
+        let mut deduplicator = Deduplicator::new();
+        assert!(deduplicator.is_empty());
+        assert!(deduplicator.insert(b"a", &a));
+        assert!(!deduplicator.insert(b"a", &a));
+        assert_eq!(deduplicator.len(), 1);
+        deduplicator.clear();
+        assert!(deduplicator.insert(b"a", &a));
+    }
+
+    #[test]
+    fn test_attested() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let (other_sk, _) = falcon512::keygen(rng.gen());
+        let (verifier_sk, verifier_pk) = falcon512::keygen(rng.gen());
+        let items = (0..4)
+            .map(|i| {
+                let msg = vec![i as u8; 10];
+                let sig = falcon512::sign(&msg, if i == 2 { &other_sk } else { &sk });
+                (msg, sig, pk.clone())
+            })
+            .collect::<Vec<_>>();
+        let valid = [&items[..2], &items[3..]].concat();
+
+        let (results, receipt) = verify_many_attested(&items, &verifier_sk);
+        assert_eq!(results, [true, true, false, true]);
+        assert!(receipt.verify(&verifier_pk));
+        assert!(receipt.commits_to(&valid));
+
+        let expanded = items
+            .iter()
+            .map(|(msg, sig, pk)| {
+                let sig = falcon512::ExpandedSignature::from_signature(msg, sig, pk);
+                (msg.clone(), sig)
+            })
+            .collect::<Vec<_>>();
+        let far_future = Instant::now() + Duration::from_secs(3600);
+        let (_, screened_receipt) =
+            verify_batch_until_attested(&expanded, &pk, far_future, &verifier_sk);
+        assert!(screened_receipt.verify(&verifier_pk));
+        assert_eq!(screened_receipt.root(), receipt.root());
+        assert_ne!(screened_receipt.parameters(), receipt.parameters());
+    }
+}
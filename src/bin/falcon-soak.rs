@@ -0,0 +1,260 @@
+//! Determinism soak test.
+//!
+//! Repeatedly generates key pairs and signs messages, with all randomness
+//! derived from a fixed master seed, and records a transcript holding one
+//! digest per iteration. Since every step is derandomized, two runs with the
+//! same arguments must produce identical transcripts, on the same machine as
+//! well as across platforms. A divergence points at floating-point drift or
+//! nondeterminism in the samplers.
+//!
+//! ```text
+//! falcon-soak [--variant 512|1024] [--iterations N] [--seed HEX]
+//!             [--output FILE] [--compare FILE]
+//! ```
+//!
+//! The transcript is written to `--output` (or standard output). With
+//! `--compare`, the run is checked against a previously recorded transcript
+//! and the process exits with a nonzero status on the first divergence.
+
+use std::{fs, process::ExitCode};
+
+use falcon_rust::{falcon1024, falcon512};
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+const DEFAULT_ITERATIONS: usize = 100;
+const DEFAULT_SEED: [u8; 32] = [0u8; 32];
+
+struct Arguments {
+    variant: usize,
+    iterations: usize,
+    seed: [u8; 32],
+    output: Option<String>,
+    compare: Option<String>,
+}
+
+fn parse_arguments(args: &[String]) -> Result<Arguments, String> {
+    let mut arguments = Arguments {
+        variant: 512,
+        iterations: DEFAULT_ITERATIONS,
+        seed: DEFAULT_SEED,
+        output: None,
+        compare: None,
+    };
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--variant" => {
+                arguments.variant = match value.as_str() {
+                    "512" => 512,
+                    "1024" => 1024,
+                    _ => return Err(format!("unsupported variant {value}")),
+                }
+            }
+            "--iterations" => {
+                arguments.iterations = value
+                    .parse()
+                    .map_err(|_| format!("invalid iteration count {value}"))?
+            }
+            "--seed" => {
+                arguments.seed = hex::decode(value)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| "seed must be 32 hex-encoded bytes".to_string())?
+            }
+            "--output" => arguments.output = Some(value.clone()),
+            "--compare" => arguments.compare = Some(value.clone()),
+            _ => return Err(format!("unknown argument {flag}")),
+        }
+    }
+    Ok(arguments)
+}
+
+/// Randomness consumed by one iteration.
+struct IterationSeeds {
+    keygen: [u8; 32],
+    sign: [u8; 32],
+    message: [u8; 32],
+}
+
+/// Derive the seeds for the given iteration from the master seed.
+fn derive_seeds(master_seed: &[u8; 32], iteration: usize) -> IterationSeeds {
+    let mut hasher = Shake256::default();
+    hasher.update(b"falcon-soak");
+    hasher.update(master_seed);
+    hasher.update(&(iteration as u64).to_le_bytes());
+    let mut reader = hasher.finalize_xof();
+
+    let mut seeds = IterationSeeds {
+        keygen: [0u8; 32],
+        sign: [0u8; 32],
+        message: [0u8; 32],
+    };
+    reader.read(&mut seeds.keygen);
+    reader.read(&mut seeds.sign);
+    reader.read(&mut seeds.message);
+    seeds
+}
+
+fn transcript_digest(parts: &[&[u8]]) -> String {
+    let mut hasher = Shake256::default();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut digest = [0u8; 16];
+    hasher.finalize_xof().read(&mut digest);
+    hex::encode(digest)
+}
+
+/// Run one iteration: keygen, sign twice with the same seed, verify, and
+/// digest the resulting key pair and signature.
+macro_rules! soak_iteration {
+    ($variant:ident, $seeds:expr) => {{
+        let seeds = $seeds;
+        let (sk, pk) = $variant::keygen(seeds.keygen);
+        let sig = $variant::sign_with_seed(&seeds.message, &sk, seeds.sign);
+        if sig != $variant::sign_with_seed(&seeds.message, &sk, seeds.sign) {
+            Err("signing twice with the same seed gave different signatures".to_string())
+        } else if !$variant::verify(&seeds.message, &sig, &pk) {
+            Err("signature does not verify".to_string())
+        } else {
+            Ok(transcript_digest(&[
+                pk.as_bytes(),
+                &sk.to_bytes(),
+                sig.as_bytes(),
+            ]))
+        }
+    }};
+}
+
+fn header(arguments: &Arguments) -> String {
+    format!(
+        "# falcon-soak variant={} seed={} iterations={}",
+        arguments.variant,
+        hex::encode(arguments.seed),
+        arguments.iterations
+    )
+}
+
+/// Return the index of the first line in which the transcripts differ,
+/// ignoring trailing lines of the longer one.
+fn first_divergence(expected: &[&str], actual: &[&str]) -> Option<usize> {
+    expected.iter().zip(actual).position(|(e, a)| e != a)
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let arguments = match parse_arguments(&args) {
+        Ok(arguments) => arguments,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let expected = match &arguments.compare {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(contents) => Some(contents),
+            Err(error) => {
+                eprintln!("error: cannot read {path}: {error}");
+                return ExitCode::from(2);
+            }
+        },
+        None => None,
+    };
+    let expected_lines = expected
+        .as_deref()
+        .map(|contents| contents.lines().collect::<Vec<_>>());
+    if let Some(lines) = &expected_lines {
+        if lines.first() != Some(&header(&arguments).as_str()) {
+            eprintln!("error: transcript was recorded with different parameters");
+            return ExitCode::from(2);
+        }
+    }
+
+    let mut transcript = vec![header(&arguments)];
+    for iteration in 0..arguments.iterations {
+        let seeds = derive_seeds(&arguments.seed, iteration);
+        let digest = match arguments.variant {
+            512 => soak_iteration!(falcon512, seeds),
+            _ => soak_iteration!(falcon1024, seeds),
+        };
+        let line = match digest {
+            Ok(digest) => format!("{iteration} {digest}"),
+            Err(error) => {
+                eprintln!("iteration {iteration}: {error}");
+                return ExitCode::FAILURE;
+            }
+        };
+        transcript.push(line);
+
+        if let Some(lines) = &expected_lines {
+            let actual = transcript.iter().map(String::as_str).collect::<Vec<_>>();
+            if let Some(index) = first_divergence(lines, &actual) {
+                eprintln!("iteration {iteration}: transcript diverges");
+                eprintln!("  expected: {}", lines[index]);
+                eprintln!("  actual:   {}", actual[index]);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let contents = transcript.join("\n") + "\n";
+    match &arguments.output {
+        Some(path) => {
+            if let Err(error) = fs::write(path, contents) {
+                eprintln!("error: cannot write {path}: {error}");
+                return ExitCode::from(2);
+            }
+        }
+        None => print!("{contents}"),
+    }
+    if expected_lines.is_some() {
+        eprintln!("transcripts agree on {} iterations", arguments.iterations);
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::{derive_seeds, first_divergence, parse_arguments};
+
+    #[test]
+    fn test_derive_seeds() {
+        let master = [7u8; 32];
+        let a = derive_seeds(&master, 3);
+        let b = derive_seeds(&master, 3);
+        let c = derive_seeds(&master, 4);
+        assert_eq!(a.keygen, b.keygen);
+        assert_eq!(a.sign, b.sign);
+        assert_eq!(a.message, b.message);
+        assert_ne!(a.keygen, c.keygen);
+        assert_ne!(a.keygen, a.sign);
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        assert_eq!(first_divergence(&["h", "0 aa"], &["h", "0 aa"]), None);
+        assert_eq!(
+            first_divergence(&["h", "0 aa", "1 bb"], &["h", "0 aa"]),
+            None
+        );
+        assert_eq!(first_divergence(&["h", "0 aa"], &["h", "0 ab"]), Some(1));
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let args = ["--variant", "1024", "--iterations", "5"].map(String::from);
+        let arguments = parse_arguments(&args).unwrap();
+        assert_eq!(arguments.variant, 1024);
+        assert_eq!(arguments.iterations, 5);
+
+        assert!(parse_arguments(&["--variant".to_string()]).is_err());
+        assert!(parse_arguments(&["--seed", "00"].map(String::from)).is_err());
+    }
+}
@@ -0,0 +1,464 @@
+//! Stream verification comparison.
+//!
+//! Measures fast-then-full verification (`fverify_fullverify`) of a stream of
+//! expanded signatures against plain `verify` and `verify_expanded`, across a
+//! sweep of invalid fractions and fast-verify index counts, and emits one
+//! machine-readable row per measurement. Unlike the criterion bench of the
+//! same workload, the output is meant to be plotted and tracked over
+//! releases, so every row carries the crate version and the full parameter
+//! set.
+//!
+//! ```text
+//! falcon-stream-bench [--variant 512|1024] [--signatures N]
+//!                     [--indices 1,4,16] [--invalid 0,0.01,0.1]
+//!                     [--repetitions N] [--seed HEX]
+//!                     [--format json|csv] [--output FILE]
+//! ```
+//!
+//! Test data is derived from `--seed`, so two runs with the same arguments
+//! measure the same signatures and indices. Timings are reported in
+//! nanoseconds per signature, as the median and minimum over the
+//! repetitions.
+
+use std::{fs, process::ExitCode, time::Instant};
+
+use falcon_rust::{falcon1024, falcon512, test_utils};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+const DEFAULT_SIGNATURES: usize = 1000;
+const DEFAULT_REPETITIONS: usize = 5;
+const DEFAULT_SEED: [u8; 32] = [0u8; 32];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    Csv,
+}
+
+struct Arguments {
+    variant: usize,
+    signatures: usize,
+    index_counts: Vec<usize>,
+    invalid_fractions: Vec<f64>,
+    repetitions: usize,
+    seed: [u8; 32],
+    format: Format,
+    output: Option<String>,
+}
+
+fn parse_list<T: std::str::FromStr>(value: &str, what: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|item| {
+            item.trim()
+                .parse()
+                .map_err(|_| format!("invalid {what} {item}"))
+        })
+        .collect()
+}
+
+fn parse_arguments(args: &[String]) -> Result<Arguments, String> {
+    let mut arguments = Arguments {
+        variant: 512,
+        signatures: DEFAULT_SIGNATURES,
+        index_counts: vec![1, 4, 16],
+        invalid_fractions: vec![0.0, 0.01, 0.1],
+        repetitions: DEFAULT_REPETITIONS,
+        seed: DEFAULT_SEED,
+        format: Format::Json,
+        output: None,
+    };
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--variant" => {
+                arguments.variant = match value.as_str() {
+                    "512" => 512,
+                    "1024" => 1024,
+                    _ => return Err(format!("unsupported variant {value}")),
+                }
+            }
+            "--signatures" => {
+                arguments.signatures = value
+                    .parse()
+                    .map_err(|_| format!("invalid signature count {value}"))?
+            }
+            "--indices" => arguments.index_counts = parse_list(value, "index count")?,
+            "--invalid" => {
+                arguments.invalid_fractions = parse_list(value, "invalid fraction")?;
+                if arguments
+                    .invalid_fractions
+                    .iter()
+                    .any(|fraction| !(0.0..=1.0).contains(fraction))
+                {
+                    return Err("invalid fractions must lie in [0, 1]".to_string());
+                }
+            }
+            "--repetitions" => {
+                arguments.repetitions = value
+                    .parse()
+                    .ok()
+                    .filter(|&repetitions| repetitions > 0)
+                    .ok_or_else(|| format!("invalid repetition count {value}"))?
+            }
+            "--seed" => {
+                arguments.seed = hex::decode(value)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| "seed must be 32 hex-encoded bytes".to_string())?
+            }
+            "--format" => {
+                arguments.format = match value.as_str() {
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    _ => return Err(format!("unsupported format {value}")),
+                }
+            }
+            "--output" => arguments.output = Some(value.clone()),
+            _ => return Err(format!("unknown argument {flag}")),
+        }
+    }
+    Ok(arguments)
+}
+
+/// One measured configuration.
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    variant: usize,
+    method: &'static str,
+    signatures: usize,
+    /// Number of fast-verify indices; zero for the baselines.
+    indices: usize,
+    invalid_fraction: f64,
+    repetitions: usize,
+    median_ns_per_signature: f64,
+    min_ns_per_signature: f64,
+    /// Number of signatures the method accepted, to catch a method that is
+    /// fast because it is wrong.
+    accepted: usize,
+}
+
+const COLUMNS: [&str; 10] = [
+    "version",
+    "variant",
+    "method",
+    "signatures",
+    "indices",
+    "invalid_fraction",
+    "repetitions",
+    "median_ns_per_signature",
+    "min_ns_per_signature",
+    "accepted",
+];
+
+impl Row {
+    fn values(&self) -> [String; 10] {
+        [
+            env!("CARGO_PKG_VERSION").to_string(),
+            self.variant.to_string(),
+            self.method.to_string(),
+            self.signatures.to_string(),
+            self.indices.to_string(),
+            self.invalid_fraction.to_string(),
+            self.repetitions.to_string(),
+            format!("{:.1}", self.median_ns_per_signature),
+            format!("{:.1}", self.min_ns_per_signature),
+            self.accepted.to_string(),
+        ]
+    }
+}
+
+fn to_csv(rows: &[Row]) -> String {
+    let mut contents = COLUMNS.join(",") + "\n";
+    for row in rows {
+        contents += &(row.values().join(",") + "\n");
+    }
+    contents
+}
+
+fn to_json(rows: &[Row]) -> String {
+    let objects = rows
+        .iter()
+        .map(|row| {
+            let fields = COLUMNS
+                .iter()
+                .zip(row.values())
+                .map(|(column, value)| match *column {
+                    // none of the strings need escaping
+                    "version" | "method" => format!("\"{column}\": \"{value}\""),
+                    _ => format!("\"{column}\": {value}"),
+                })
+                .collect::<Vec<_>>();
+            format!("  {{{}}}", fields.join(", "))
+        })
+        .collect::<Vec<_>>();
+    if objects.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+}
+
+/// Time `pass` over all signatures `repetitions` times and return the median
+/// and minimum nanoseconds per signature, together with the number of
+/// signatures the last pass accepted.
+///
+/// Every pass gets fresh input from `setup`, which is not timed, so that no
+/// pass benefits from the hash-to-point memoized inside signatures verified
+/// by an earlier one.
+fn measure<T>(
+    signatures: usize,
+    repetitions: usize,
+    mut setup: impl FnMut() -> T,
+    mut pass: impl FnMut(&T) -> usize,
+) -> (f64, f64, usize) {
+    let mut accepted = 0;
+    let mut timings = (0..repetitions)
+        .map(|_| {
+            let input = setup();
+            let start = Instant::now();
+            accepted = pass(&input);
+            start.elapsed().as_nanos() as f64 / signatures.max(1) as f64
+        })
+        .collect::<Vec<_>>();
+    timings.sort_by(f64::total_cmp);
+    (timings[timings.len() / 2], timings[0], accepted)
+}
+
+/// Run the sweep for one variant module and test data generator.
+macro_rules! sweep {
+    ($variant:ident, $generate:path, $n:expr, $arguments:expr) => {{
+        let arguments = $arguments;
+        let mut rng = ChaCha20Rng::from_seed(arguments.seed);
+        let mut rows = vec![];
+        for &invalid_fraction in &arguments.invalid_fractions {
+            let num_invalid = (arguments.signatures as f64 * invalid_fraction) as usize;
+            let num_valid = arguments.signatures - num_invalid;
+            let test_data = $generate(&mut rng, num_valid, num_invalid, true);
+            let pk = &test_data.public_key;
+
+            let mut push = |method, indices, (median, min, accepted)| {
+                rows.push(Row {
+                    variant: $n,
+                    method,
+                    signatures: arguments.signatures,
+                    indices,
+                    invalid_fraction,
+                    repetitions: arguments.repetitions,
+                    median_ns_per_signature: median,
+                    min_ns_per_signature: min,
+                    accepted,
+                })
+            };
+
+            push(
+                "verify",
+                0,
+                measure(
+                    arguments.signatures,
+                    arguments.repetitions,
+                    || {
+                        test_data
+                            .items
+                            .iter()
+                            .map(|item| {
+                                $variant::Signature::from_bytes(item.signature.as_bytes()).unwrap()
+                            })
+                            .collect::<Vec<_>>()
+                    },
+                    |sigs| {
+                        test_data
+                            .items
+                            .iter()
+                            .zip(sigs)
+                            .filter(|(item, sig)| $variant::verify(&item.message, sig, pk))
+                            .count()
+                    },
+                ),
+            );
+            push(
+                "verify_expanded",
+                0,
+                measure(
+                    arguments.signatures,
+                    arguments.repetitions,
+                    || test_data.expanded_signatures(),
+                    |sigs| {
+                        test_data
+                            .items
+                            .iter()
+                            .zip(sigs)
+                            .filter(|(item, sig)| $variant::verify_expanded(&item.message, sig, pk))
+                            .count()
+                    },
+                ),
+            );
+            for &index_count in &arguments.index_counts {
+                let indices = (0..index_count)
+                    .map(|_| rng.gen_range(0..$n))
+                    .collect::<Vec<usize>>();
+                push(
+                    "fverify_fullverify",
+                    index_count,
+                    measure(
+                        arguments.signatures,
+                        arguments.repetitions,
+                        || test_data.expanded_signatures(),
+                        |sigs| {
+                            test_data
+                                .items
+                                .iter()
+                                .zip(sigs)
+                                .filter(|(item, sig)| {
+                                    $variant::fverify_fullverify(&item.message, sig, pk, &indices)
+                                })
+                                .count()
+                        },
+                    ),
+                );
+            }
+        }
+        rows
+    }};
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let arguments = match parse_arguments(&args) {
+        Ok(arguments) => arguments,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let rows = match arguments.variant {
+        512 => sweep!(
+            falcon512,
+            test_utils::generate_test_data_512,
+            512,
+            &arguments
+        ),
+        _ => sweep!(
+            falcon1024,
+            test_utils::generate_test_data_1024,
+            1024,
+            &arguments
+        ),
+    };
+    for row in &rows {
+        let expected = row.signatures - (row.signatures as f64 * row.invalid_fraction) as usize;
+        if row.accepted != expected {
+            eprintln!(
+                "{} accepted {} of {} signatures, expected {}",
+                row.method, row.accepted, row.signatures, expected
+            );
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let contents = match arguments.format {
+        Format::Json => to_json(&rows),
+        Format::Csv => to_csv(&rows),
+    };
+    match &arguments.output {
+        Some(path) => {
+            if let Err(error) = fs::write(path, contents) {
+                eprintln!("error: cannot write {path}: {error}");
+                return ExitCode::from(2);
+            }
+        }
+        None => print!("{contents}"),
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::{measure, parse_arguments, to_csv, to_json, Format, Row, COLUMNS};
+
+    fn row() -> Row {
+        Row {
+            variant: 512,
+            method: "fverify_fullverify",
+            signatures: 100,
+            indices: 4,
+            invalid_fraction: 0.01,
+            repetitions: 3,
+            median_ns_per_signature: 1234.56,
+            min_ns_per_signature: 1000.0,
+            accepted: 99,
+        }
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let args = [
+            "--variant",
+            "1024",
+            "--indices",
+            "1,8",
+            "--invalid",
+            "0.5, 1",
+            "--format",
+            "csv",
+        ]
+        .map(String::from);
+        let arguments = parse_arguments(&args).unwrap();
+        assert_eq!(arguments.variant, 1024);
+        assert_eq!(arguments.index_counts, vec![1, 8]);
+        assert_eq!(arguments.invalid_fractions, vec![0.5, 1.0]);
+        assert_eq!(arguments.format, Format::Csv);
+
+        assert!(parse_arguments(&["--invalid", "1.5"].map(String::from)).is_err());
+        assert!(parse_arguments(&["--indices", "1,x"].map(String::from)).is_err());
+        assert!(parse_arguments(&["--repetitions", "0"].map(String::from)).is_err());
+        assert!(parse_arguments(&["--format", "xml"].map(String::from)).is_err());
+    }
+
+    #[test]
+    fn test_output_formats() {
+        let rows = [row(), row()];
+
+        let csv = to_csv(&rows);
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], COLUMNS.join(","));
+        assert_eq!(
+            lines[1],
+            format!(
+                "{},512,fverify_fullverify,100,4,0.01,3,1234.6,1000.0,99",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&rows)).unwrap();
+        let objects = json.as_array().unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0]["method"], "fverify_fullverify");
+        assert_eq!(objects[0]["invalid_fraction"], 0.01);
+        assert_eq!(objects[0]["accepted"], 99);
+        assert_eq!(to_json(&[]), "[]\n");
+    }
+
+    #[test]
+    fn test_measure() {
+        let mut calls = 0;
+        let (median, min, accepted) = measure(
+            10,
+            3,
+            || {
+                calls += 1;
+                calls
+            },
+            |&input| input + 4,
+        );
+        assert_eq!(calls, 3);
+        assert!(min <= median);
+        assert_eq!(accepted, 7);
+    }
+}
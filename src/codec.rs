@@ -0,0 +1,368 @@
+//! Framing of signed messages on byte streams, with verification on decode.
+//!
+//! Each [`SignedMessage`] is sent as one frame:
+//!
+//! ```text
+//! length (4 bytes, big endian) || version (1 byte) || signed message
+//! ```
+//!
+//! where the length counts the version byte and the signed message, which is
+//! in the attached format of [`SignedMessage::to_bytes`]. The version is
+//! [`FRAME_VERSION`].
+//!
+//! [`SignedMessageCodec`] decodes from whatever is buffered: it returns
+//! `Ok(None)` until a whole frame has arrived, and consumes exactly the
+//! frames it returns or rejects, so that the stream can continue after a
+//! forged record. With feature `tokio`, it implements the `Decoder` and
+//! `Encoder` traits of `tokio_util::codec`, so that a `TcpStream` wrapped in
+//! `tokio_util::codec::Framed` becomes a stream of verified records and a
+//! sink of signed ones. Without it, the inherent methods work on `Vec<u8>`
+//! buffers:
+//!
+//! ```
+//! use falcon_rust::{codec::SignedMessageCodec, falcon512, signed_message::SignedMessage};
+//! # use rand::{thread_rng, Rng};
+//! # let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//!
+//! let mut codec = SignedMessageCodec::new(pk);
+//! let record = SignedMessage::attach(b"record", falcon512::sign(b"record", &sk));
+//! let mut wire = vec![];
+//! codec.encode(&record, &mut wire)?;
+//!
+//! // the frame arrives in two pieces
+//! let mut buffer = wire[..10].to_vec();
+//! assert!(codec.decode(&mut buffer)?.is_none());
+//! buffer.extend_from_slice(&wire[10..]);
+//! assert_eq!(codec.decode(&mut buffer)?, Some(record));
+//! assert!(buffer.is_empty());
+//! # Ok::<(), falcon_rust::codec::CodecError>(())
+//! ```
+
+use std::io;
+
+#[cfg(feature = "tokio")]
+use bytes::BytesMut;
+
+use crate::{
+    falcon::{FalconDeserializationError, PublicKey},
+    signed_message::SignedMessage,
+};
+
+/// The frame format version produced and accepted by [`SignedMessageCodec`].
+pub const FRAME_VERSION: u8 = 1;
+
+/// The default bound on the length of a frame: 1 MiB.
+pub const DEFAULT_MAX_FRAME_LENGTH: usize = 1 << 20;
+
+/// The length of the length prefix.
+const LENGTH_LEN: usize = 4;
+
+/// Reasons why a frame cannot be encoded or decoded.
+#[derive(Debug)]
+pub enum CodecError {
+    /// The frame is longer than the codec's maximum frame length. The frame
+    /// is not consumed, as its end cannot be trusted; the stream should be
+    /// closed.
+    FrameTooLarge,
+    /// The frame has a version other than [`FRAME_VERSION`].
+    UnsupportedVersion,
+    /// The frame does not hold a well-formed signed message of the codec's
+    /// variant.
+    Malformed(FalconDeserializationError),
+    /// The signature on the decoded message is invalid.
+    InvalidSignature,
+    /// The stream ended in the middle of a frame.
+    Truncated,
+    /// The underlying stream failed.
+    Io(io::Error),
+}
+
+impl From<io::Error> for CodecError {
+    fn from(error: io::Error) -> Self {
+        CodecError::Io(error)
+    }
+}
+
+/// Encodes signed messages into frames and decodes frames into signed
+/// messages whose signature is valid under a given public key.
+#[derive(Debug, Clone)]
+pub struct SignedMessageCodec<const N: usize> {
+    public_key: PublicKey<N>,
+    max_frame_length: usize,
+}
+
+impl<const N: usize> SignedMessageCodec<N> {
+    /// A codec that accepts messages signed under the given public key, in
+    /// frames of up to [`DEFAULT_MAX_FRAME_LENGTH`] bytes.
+    pub fn new(public_key: PublicKey<N>) -> Self {
+        Self {
+            public_key,
+            max_frame_length: DEFAULT_MAX_FRAME_LENGTH,
+        }
+    }
+
+    /// Bound the length of frames, as counted by the length prefix, in both
+    /// directions.
+    pub fn max_frame_length(mut self, max_frame_length: usize) -> Self {
+        self.max_frame_length = max_frame_length;
+        self
+    }
+
+    /// The public key that decoded messages are verified under.
+    pub fn public_key(&self) -> &PublicKey<N> {
+        &self.public_key
+    }
+
+    /// Append the frame of a signed message to `dst`.
+    pub fn encode(&mut self, item: &SignedMessage<N>, dst: &mut Vec<u8>) -> Result<(), CodecError> {
+        let payload = item.to_bytes();
+        let length = 1 + payload.len();
+        if length > self.max_frame_length || length > u32::MAX as usize {
+            return Err(CodecError::FrameTooLarge);
+        }
+        dst.reserve(LENGTH_LEN + length);
+        dst.extend_from_slice(&(length as u32).to_be_bytes());
+        dst.push(FRAME_VERSION);
+        dst.extend_from_slice(&payload);
+        Ok(())
+    }
+
+    /// Decode the first frame buffered in `src`, if it is complete, and
+    /// remove it from the buffer. Returns `Ok(None)`, leaving the buffer as
+    /// is, if more bytes are needed.
+    ///
+    /// A frame that is well delimited but does not hold a valid signed
+    /// message is consumed before the error is returned, so decoding can go
+    /// on with the next frame.
+    pub fn decode(&mut self, src: &mut Vec<u8>) -> Result<Option<SignedMessage<N>>, CodecError> {
+        let Some(length) = self.frame_length(src)? else {
+            return Ok(None);
+        };
+        if src.len() < length {
+            src.reserve(length - src.len());
+            return Ok(None);
+        }
+        let frame = src.drain(..length).collect::<Vec<_>>();
+        self.parse_frame(&frame).map(Some)
+    }
+
+    /// Like [`decode`](Self::decode), for when the stream has ended: bytes
+    /// left over after the last complete frame are an error.
+    pub fn decode_eof(
+        &mut self,
+        src: &mut Vec<u8>,
+    ) -> Result<Option<SignedMessage<N>>, CodecError> {
+        match self.decode(src)? {
+            Some(signed_message) => Ok(Some(signed_message)),
+            None if src.is_empty() => Ok(None),
+            None => Err(CodecError::Truncated),
+        }
+    }
+
+    /// The length of the first frame buffered in `src`, length prefix
+    /// included, once the prefix has arrived.
+    fn frame_length(&self, src: &[u8]) -> Result<Option<usize>, CodecError> {
+        let Some(prefix) = src.first_chunk::<LENGTH_LEN>() else {
+            return Ok(None);
+        };
+        let length = u32::from_be_bytes(*prefix) as usize;
+        if length > self.max_frame_length {
+            return Err(CodecError::FrameTooLarge);
+        }
+        Ok(Some(LENGTH_LEN + length))
+    }
+
+    /// Parse and verify a whole frame, length prefix included.
+    fn parse_frame(&self, frame: &[u8]) -> Result<SignedMessage<N>, CodecError> {
+        let Some((&version, payload)) = frame[LENGTH_LEN..].split_first() else {
+            return Err(CodecError::Malformed(
+                FalconDeserializationError::BadEncodingLength,
+            ));
+        };
+        if version != FRAME_VERSION {
+            return Err(CodecError::UnsupportedVersion);
+        }
+        let signed_message = SignedMessage::from_bytes(payload).map_err(CodecError::Malformed)?;
+        if !signed_message.verify(&self.public_key) {
+            return Err(CodecError::InvalidSignature);
+        }
+        Ok(signed_message)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<const N: usize> tokio_util::codec::Decoder for SignedMessageCodec<N> {
+    type Item = SignedMessage<N>;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let Some(length) = self.frame_length(src)? else {
+            return Ok(None);
+        };
+        if src.len() < length {
+            src.reserve(length - src.len());
+            return Ok(None);
+        }
+        let frame = src.split_to(length);
+        self.parse_frame(&frame).map(Some)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match tokio_util::codec::Decoder::decode(self, src)? {
+            Some(signed_message) => Ok(Some(signed_message)),
+            None if src.is_empty() => Ok(None),
+            None => Err(CodecError::Truncated),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<const N: usize> tokio_util::codec::Encoder<&SignedMessage<N>> for SignedMessageCodec<N> {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: &SignedMessage<N>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut frame = vec![];
+        SignedMessageCodec::encode(self, item, &mut frame)?;
+        dst.extend_from_slice(&frame);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<const N: usize> tokio_util::codec::Encoder<SignedMessage<N>> for SignedMessageCodec<N> {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: SignedMessage<N>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        tokio_util::codec::Encoder::encode(self, &item, dst)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon1024, falcon512, signed_message::SignedMessage};
+
+    use super::{CodecError, SignedMessageCodec, FRAME_VERSION};
+
+    #[test]
+    fn test_codec() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let (other_sk, _) = falcon512::keygen(rng.gen());
+        let mut codec = SignedMessageCodec::new(pk);
+
+        let records = (0..3u8)
+            .map(|i| {
+                let msg = vec![i; 100 * i as usize];
+                SignedMessage::attach(&msg, falcon512::sign(&msg, &sk))
+            })
+            .collect::<Vec<_>>();
+        let forged = SignedMessage::attach(b"forged", falcon512::sign(b"forged", &other_sk));
+
+        let mut wire = vec![];
+        codec.encode(&records[0], &mut wire).unwrap();
+        codec.encode(&forged, &mut wire).unwrap();
+        codec.encode(&records[1], &mut wire).unwrap();
+        codec.encode(&records[2], &mut wire).unwrap();
+
+        // feed the stream a few bytes at a time
+        let mut buffer = vec![];
+        let mut decoded = vec![];
+        for chunk in wire.chunks(97) {
+            buffer.extend_from_slice(chunk);
+            loop {
+                match codec.decode(&mut buffer) {
+                    Ok(Some(record)) => decoded.push(Ok(record)),
+                    Ok(None) => break,
+                    Err(error) => decoded.push(Err(error)),
+                }
+            }
+        }
+        assert!(codec.decode_eof(&mut buffer).unwrap().is_none());
+        assert_eq!(decoded.len(), 4);
+        assert_eq!(decoded[0].as_ref().unwrap(), &records[0]);
+        assert!(matches!(decoded[1], Err(CodecError::InvalidSignature)));
+        assert_eq!(decoded[2].as_ref().unwrap(), &records[1]);
+        assert_eq!(decoded[3].as_ref().unwrap(), &records[2]);
+    }
+
+    #[test]
+    fn test_malformed_frames() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let mut codec = SignedMessageCodec::new(pk).max_frame_length(1000);
+        let record = SignedMessage::attach(b"msg", falcon512::sign(b"msg", &sk));
+        let mut frame = vec![];
+        codec.encode(&record, &mut frame).unwrap();
+
+        let mut wrong_version = frame.clone();
+        wrong_version[4] = FRAME_VERSION + 1;
+        assert!(matches!(
+            codec.decode(&mut wrong_version),
+            Err(CodecError::UnsupportedVersion)
+        ));
+        assert!(wrong_version.is_empty());
+
+        let mut too_large = 1001u32.to_be_bytes().to_vec();
+        assert!(matches!(
+            codec.decode(&mut too_large),
+            Err(CodecError::FrameTooLarge)
+        ));
+        let long = SignedMessage::attach(&[0; 1000], falcon512::sign([0; 1000], &sk));
+        assert!(matches!(
+            codec.encode(&long, &mut vec![]),
+            Err(CodecError::FrameTooLarge)
+        ));
+
+        let mut truncated = frame[..frame.len() - 1].to_vec();
+        assert!(matches!(
+            codec.decode_eof(&mut truncated),
+            Err(CodecError::Truncated)
+        ));
+
+        let (sk1024, pk1024) = falcon1024::keygen(rng.gen());
+        let mut wrong_variant = vec![];
+        SignedMessageCodec::new(pk1024)
+            .encode(
+                &SignedMessage::attach(b"msg", falcon1024::sign(b"msg", &sk1024)),
+                &mut wrong_variant,
+            )
+            .unwrap();
+        assert!(matches!(
+            SignedMessageCodec::new(codec.public_key().clone()).decode(&mut wrong_variant),
+            Err(CodecError::Malformed(_))
+        ));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_tokio_codec() {
+        use bytes::BytesMut;
+        use tokio_util::codec::{Decoder, Encoder};
+
+        let (sk, pk) = falcon512::keygen(thread_rng().gen());
+        let mut codec = SignedMessageCodec::new(pk);
+        let record = SignedMessage::attach(b"msg", falcon512::sign(b"msg", &sk));
+
+        let mut wire = BytesMut::new();
+        Encoder::encode(&mut codec, &record, &mut wire).unwrap();
+        Encoder::encode(&mut codec, record.clone(), &mut wire).unwrap();
+        let mut vec_wire = vec![];
+        codec.encode(&record, &mut vec_wire).unwrap();
+        assert_eq!(&wire[..vec_wire.len()], vec_wire.as_slice());
+
+        let mut buffer = wire.split_to(10);
+        assert!(Decoder::decode(&mut codec, &mut buffer).unwrap().is_none());
+        buffer.unsplit(wire);
+        assert_eq!(
+            Decoder::decode(&mut codec, &mut buffer).unwrap(),
+            Some(record.clone())
+        );
+        buffer.truncate(buffer.len() - 1);
+        assert!(matches!(
+            Decoder::decode_eof(&mut codec, &mut buffer),
+            Err(CodecError::Truncated)
+        ));
+    }
+}
@@ -1,4 +1,5 @@
-use std::{
+use alloc::{vec, vec::Vec};
+use core::{
     f64::consts::PI,
     ops::{Add, Mul, MulAssign, Sub},
 };
@@ -6,6 +7,10 @@ use std::{
 use num::{One, Zero};
 use num_complex::Complex64;
 
+#[cfg(not(feature = "std"))]
+use crate::float::FloatExt;
+#[cfg(feature = "fixed-point")]
+use crate::fpr;
 use crate::inverse::Inverse;
 
 pub(crate) trait CyclotomicFourier
@@ -169,6 +174,14 @@ where
         let n_over_2 = f.len() / 2;
         let mut f0 = vec![Self::zero(); n_over_2];
         let mut f1 = vec![Self::zero(); n_over_2];
+        Self::split_fft_into(f, &mut f0, &mut f1, psi_inv_rev);
+        (f0, f1)
+    }
+
+    /// Like [`CyclotomicFourier::split_fft`], but writing the halves into
+    /// the given slices, which must have length `f.len() / 2`.
+    fn split_fft_into(f: &[Self], f0: &mut [Self], f1: &mut [Self], psi_inv_rev: &[Self]) {
+        let n_over_2 = f.len() / 2;
         let two_inv = (Self::one() + Self::one()).inverse_or_zero();
         for i in 0..n_over_2 {
             let two_i = i * 2;
@@ -176,19 +189,24 @@ where
             f0[i] = two_inv * (f[two_i] + f[two_i + 1]);
             f1[i] = two_zeta_inv * (f[two_i] - f[two_i + 1]);
         }
-        (f0, f1)
     }
 
     fn merge_fft(f0: &[Self], f1: &[Self], psi_rev: &[Self]) -> Vec<Self> {
-        let n_over_2 = f0.len();
-        let n = 2 * n_over_2;
+        let n = 2 * f0.len();
         let mut f = vec![Self::zero(); n];
+        Self::merge_fft_into(f0, f1, &mut f, psi_rev);
+        f
+    }
+
+    /// Like [`CyclotomicFourier::merge_fft`], but writing the result into
+    /// the given slice, which must have length `2 * f0.len()`.
+    fn merge_fft_into(f0: &[Self], f1: &[Self], f: &mut [Self], psi_rev: &[Self]) {
+        let n_over_2 = f0.len();
         for i in 0..n_over_2 {
             let two_i = i * 2;
             f[two_i] = f0[i] + psi_rev[n_over_2 + i] * f1[i];
             f[two_i + 1] = f0[i] - psi_rev[n_over_2 + i] * f1[i];
         }
-        f
     }
 }
 
@@ -223,6 +241,26 @@ impl CyclotomicFourier for Complex64 {
         Self::bitreverse_array(&mut array);
         array
     }
+
+    #[cfg(feature = "fixed-point")]
+    fn fft(a: &mut [Self], psi_rev: &[Self]) {
+        fpr::fft(a, psi_rev)
+    }
+
+    #[cfg(feature = "fixed-point")]
+    fn ifft(a: &mut [Self], psi_inv_rev: &[Self], ninv: Self) {
+        fpr::ifft(a, psi_inv_rev, ninv)
+    }
+
+    #[cfg(feature = "fixed-point")]
+    fn split_fft_into(f: &[Self], f0: &mut [Self], f1: &mut [Self], psi_inv_rev: &[Self]) {
+        fpr::split_fft_into(f, f0, f1, psi_inv_rev)
+    }
+
+    #[cfg(feature = "fixed-point")]
+    fn merge_fft_into(f0: &[Self], f1: &[Self], f: &mut [Self], psi_rev: &[Self]) {
+        fpr::merge_fft_into(f0, f1, f, psi_rev)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,94 @@
+//! Deterministic derivation of many key pairs from one master seed.
+//!
+//! A child is identified by a path of labels, e.g., `["fleet", "node-17"]`.
+//! Each label in turn is hashed together with the seed derived so far, so
+//! that the seed for a path prefix can be handed out to derive the subtree
+//! below it without revealing the master seed or any sibling subtree:
+//!
+//! ```
+//! use falcon_rust::{derivation::derive_seed, falcon512};
+//! # use rand::{thread_rng, Rng};
+//!
+//! let master_seed: [u8; 32] = thread_rng().gen();
+//! let fleet_seed = derive_seed(&master_seed, &["fleet"]);
+//! assert_eq!(
+//!     falcon512::derive_child(&fleet_seed, &["node-17"]),
+//!     falcon512::derive_child(&master_seed, &["fleet", "node-17"]),
+//! );
+//! ```
+//!
+//! The derived seed for a path is `seed_k` where `seed_0` is the master seed
+//! and `seed_i` is the first 32 bytes of
+//! `SHAKE256("falcon-rust child key derivation" || seed_{i-1} || len || label_i)`,
+//! with `len` the length of the `i`th label in bytes as a big-endian `u64`.
+//! The key pair is then [`keygen`](crate::falcon512::keygen) of that seed.
+
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+use crate::falcon::{self, PublicKey, SecretKey};
+
+/// Derive the seed for the child at the given path. The empty path yields
+/// the master seed itself.
+pub fn derive_seed(master_seed: &[u8; 32], path: &[&str]) -> [u8; 32] {
+    path.iter().fold(*master_seed, |seed, label| {
+        let mut hasher = Shake256::default();
+        hasher.update(b"falcon-rust child key derivation");
+        hasher.update(&seed);
+        hasher.update(&(label.len() as u64).to_be_bytes());
+        hasher.update(label.as_bytes());
+        let mut child_seed = [0u8; 32];
+        hasher.finalize_xof().read(&mut child_seed);
+        child_seed
+    })
+}
+
+/// Derive the key pair for the child at the given path.
+///
+/// # Panics
+///
+/// If the derived seed is weak, which happens with negligible probability;
+/// see [`is_weak_seed`](crate::falcon512::is_weak_seed).
+pub fn derive_child<const N: usize>(
+    master_seed: &[u8; 32],
+    path: &[&str],
+) -> (SecretKey<N>, PublicKey<N>) {
+    falcon::keygen(derive_seed(master_seed, path))
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon512;
+
+    use super::derive_seed;
+
+    #[test]
+    fn test_derive_child() {
+        let master_seed: [u8; 32] = thread_rng().gen();
+        assert_eq!(derive_seed(&master_seed, &[]), master_seed);
+
+        // labels are length-prefixed, so they cannot run into each other
+        let seeds = [
+            derive_seed(&master_seed, &["a"]),
+            derive_seed(&master_seed, &["b"]),
+            derive_seed(&master_seed, &["ab"]),
+            derive_seed(&master_seed, &["a", "b"]),
+            derive_seed(&master_seed, &["", "ab"]),
+            derive_seed(&[0u8; 32], &["a"]),
+        ];
+        for (i, seed) in seeds.iter().enumerate() {
+            assert!(seeds[i + 1..].iter().all(|other| other != seed));
+        }
+
+        let (sk, pk) = falcon512::derive_child(&master_seed, &["fleet", "node-17"]);
+        assert!(falcon512::consistent(&sk, &pk));
+        assert_eq!(
+            (sk, pk),
+            falcon512::keygen(derive_seed(&master_seed, &["fleet", "node-17"]))
+        );
+    }
+}
@@ -0,0 +1,382 @@
+//! Verification through trait objects, for applications that support several
+//! signature algorithms and select one at run time, and enums that hold keys
+//! and signatures of either Falcon variant, for wire formats that carry the
+//! variant in-band.
+//!
+//! ```
+//! use falcon_rust::{dynamic::AnyVerifier, falcon1024, falcon512};
+//! # use rand::{thread_rng, Rng};
+//!
+//! # let (sk, pk512) = falcon512::keygen(thread_rng().gen());
+//! # let (_, pk1024) = falcon1024::keygen(thread_rng().gen());
+//! let verifiers: Vec<Box<dyn AnyVerifier>> = vec![Box::new(pk512), Box::new(pk1024)];
+//! # let sig = falcon512::sign(b"message", &sk).to_bytes();
+//! let algorithms = verifiers
+//!     .iter()
+//!     .filter(|verifier| verifier.verify_bytes(b"message", &sig))
+//!     .map(|verifier| verifier.algorithm())
+//!     .collect::<Vec<_>>();
+//! # assert_eq!(algorithms, ["Falcon-512"]);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{
+    falcon::{self, FalconDeserializationError, PublicKey, SecretKey, Signature},
+    fingerprint::Fingerprint,
+    verifier_context::VerifierContext,
+};
+
+/// A verifier for some signature algorithm, usable as `dyn AnyVerifier`.
+///
+/// Signatures are passed in their encoded form, since their type depends on
+/// the algorithm.
+pub trait AnyVerifier: Send + Sync {
+    /// The name of the signature algorithm, e.g., `"Falcon-512"`.
+    fn algorithm(&self) -> &'static str;
+
+    /// Verify an encoded signature on a message. Signatures that cannot be
+    /// decoded, including those for a different algorithm, are rejected.
+    fn verify_bytes(&self, m: &[u8], sig: &[u8]) -> bool;
+}
+
+const fn algorithm_name(n: usize) -> &'static str {
+    match n {
+        512 => "Falcon-512",
+        _ => "Falcon-1024",
+    }
+}
+
+impl<const N: usize> AnyVerifier for PublicKey<N> {
+    fn algorithm(&self) -> &'static str {
+        algorithm_name(N)
+    }
+
+    fn verify_bytes(&self, m: &[u8], sig: &[u8]) -> bool {
+        Signature::<N>::from_bytes(sig).is_ok_and(|sig| falcon::verify(m, &sig, self))
+    }
+}
+
+impl<const N: usize> AnyVerifier for VerifierContext<N> {
+    fn algorithm(&self) -> &'static str {
+        algorithm_name(N)
+    }
+
+    fn verify_bytes(&self, m: &[u8], sig: &[u8]) -> bool {
+        Signature::<N>::from_bytes(sig).is_ok_and(|sig| self.verify(m, &sig))
+    }
+}
+
+/// A public key for either Falcon variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FalconPublicKey {
+    F512(PublicKey<512>),
+    F1024(PublicKey<1024>),
+}
+
+impl FalconPublicKey {
+    /// Deserialize a public key of either variant, which is inferred from
+    /// the encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        match PublicKey::from_bytes(bytes) {
+            Err(FalconDeserializationError::WrongVariant) => {
+                PublicKey::from_bytes(bytes).map(Self::F1024)
+            }
+            result => result.map(Self::F512),
+        }
+    }
+
+    /// Serialize the public key to a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    /// Borrow the encoding of the public key; see [`PublicKey::as_bytes`].
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::F512(pk) => pk.as_bytes(),
+            Self::F1024(pk) => pk.as_bytes(),
+        }
+    }
+
+    /// The name of the Falcon variant, as reported by
+    /// [`AnyVerifier::algorithm`].
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            Self::F512(_) => algorithm_name(512),
+            Self::F1024(_) => algorithm_name(1024),
+        }
+    }
+
+    /// The fingerprint of the key; see [`PublicKey::fingerprint`].
+    pub fn fingerprint(&self) -> Fingerprint {
+        match self {
+            Self::F512(pk) => pk.fingerprint(),
+            Self::F1024(pk) => pk.fingerprint(),
+        }
+    }
+
+    /// Verify a signature. Signatures for the other variant are rejected.
+    pub fn verify(&self, m: &[u8], sig: &FalconSignature) -> bool {
+        match (self, sig) {
+            (Self::F512(pk), FalconSignature::F512(sig)) => falcon::verify(m, sig, pk),
+            (Self::F1024(pk), FalconSignature::F1024(sig)) => falcon::verify(m, sig, pk),
+            _ => false,
+        }
+    }
+}
+
+impl AnyVerifier for FalconPublicKey {
+    fn algorithm(&self) -> &'static str {
+        self.algorithm()
+    }
+
+    fn verify_bytes(&self, m: &[u8], sig: &[u8]) -> bool {
+        FalconSignature::from_bytes(sig).is_ok_and(|sig| self.verify(m, &sig))
+    }
+}
+
+impl From<PublicKey<512>> for FalconPublicKey {
+    fn from(pk: PublicKey<512>) -> Self {
+        Self::F512(pk)
+    }
+}
+
+impl From<PublicKey<1024>> for FalconPublicKey {
+    fn from(pk: PublicKey<1024>) -> Self {
+        Self::F1024(pk)
+    }
+}
+
+impl TryFrom<&[u8]> for FalconPublicKey {
+    type Error = FalconDeserializationError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl AsRef<[u8]> for FalconPublicKey {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// A secret key for either Falcon variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FalconSecretKey {
+    F512(SecretKey<512>),
+    F1024(SecretKey<1024>),
+}
+
+impl FalconSecretKey {
+    /// Deserialize a secret key of either variant, which is inferred from
+    /// the encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        match SecretKey::from_bytes(bytes) {
+            Err(FalconDeserializationError::WrongVariant) => {
+                SecretKey::from_bytes(bytes).map(Self::F1024)
+            }
+            result => result.map(Self::F512),
+        }
+    }
+
+    /// Serialize the secret key to a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::F512(sk) => sk.to_bytes(),
+            Self::F1024(sk) => sk.to_bytes(),
+        }
+    }
+
+    /// The name of the Falcon variant, as reported by
+    /// [`AnyVerifier::algorithm`].
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            Self::F512(_) => algorithm_name(512),
+            Self::F1024(_) => algorithm_name(1024),
+        }
+    }
+
+    /// Recompute the matching public key; see [`SecretKey::public_key`].
+    pub fn public_key(&self) -> FalconPublicKey {
+        match self {
+            Self::F512(sk) => sk.public_key().into(),
+            Self::F1024(sk) => sk.public_key().into(),
+        }
+    }
+
+    /// Sign a message, producing a signature of the same variant.
+    #[cfg(feature = "std")]
+    pub fn sign(&self, m: &[u8]) -> FalconSignature {
+        match self {
+            Self::F512(sk) => falcon::sign(m, sk).into(),
+            Self::F1024(sk) => falcon::sign(m, sk).into(),
+        }
+    }
+}
+
+impl From<SecretKey<512>> for FalconSecretKey {
+    fn from(sk: SecretKey<512>) -> Self {
+        Self::F512(sk)
+    }
+}
+
+impl From<SecretKey<1024>> for FalconSecretKey {
+    fn from(sk: SecretKey<1024>) -> Self {
+        Self::F1024(sk)
+    }
+}
+
+impl TryFrom<&[u8]> for FalconSecretKey {
+    type Error = FalconDeserializationError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+/// A signature for either Falcon variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FalconSignature {
+    F512(Signature<512>),
+    F1024(Signature<1024>),
+}
+
+impl FalconSignature {
+    /// Deserialize a signature of either variant, which is inferred from the
+    /// encoding. Accepts the same encodings as [`Signature::from_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        match Signature::from_bytes(bytes) {
+            Err(FalconDeserializationError::WrongVariant) => {
+                Signature::from_bytes(bytes).map(Self::F1024)
+            }
+            result => result.map(Self::F512),
+        }
+    }
+
+    /// Serialize the signature to a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    /// Borrow the encoding of the signature; see [`Signature::as_bytes`].
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::F512(sig) => sig.as_bytes(),
+            Self::F1024(sig) => sig.as_bytes(),
+        }
+    }
+
+    /// The name of the Falcon variant, as reported by
+    /// [`AnyVerifier::algorithm`].
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            Self::F512(_) => algorithm_name(512),
+            Self::F1024(_) => algorithm_name(1024),
+        }
+    }
+}
+
+impl From<Signature<512>> for FalconSignature {
+    fn from(sig: Signature<512>) -> Self {
+        Self::F512(sig)
+    }
+}
+
+impl From<Signature<1024>> for FalconSignature {
+    fn from(sig: Signature<1024>) -> Self {
+        Self::F1024(sig)
+    }
+}
+
+impl TryFrom<&[u8]> for FalconSignature {
+    type Error = FalconDeserializationError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl AsRef<[u8]> for FalconSignature {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon::FalconDeserializationError, falcon1024, falcon512};
+
+    use super::{AnyVerifier, FalconPublicKey, FalconSecretKey, FalconSignature};
+
+    #[test]
+    fn test_any_verifier() {
+        let mut rng = thread_rng();
+        let msg = b"one of several algorithms";
+        let (sk512, pk512) = falcon512::keygen(rng.gen());
+        let (sk1024, pk1024) = falcon1024::keygen(rng.gen());
+        let verifiers: Vec<Box<dyn AnyVerifier>> = vec![
+            Box::new(pk512.clone()),
+            Box::new(falcon1024::VerifierContext::new(&pk1024)),
+        ];
+
+        let sig512 = FalconSignature::from(falcon512::sign(msg, &sk512));
+        let sig1024 = FalconSignature::from(falcon1024::sign(msg, &sk1024));
+        for (sig, expected) in [(&sig512, [true, false]), (&sig1024, [false, true])] {
+            let bytes = sig.as_bytes();
+            assert_eq!(FalconSignature::from_bytes(bytes).as_ref(), Ok(sig));
+            let accepted = verifiers
+                .iter()
+                .map(|verifier| verifier.verify_bytes(msg, bytes))
+                .collect::<Vec<_>>();
+            assert_eq!(accepted, expected);
+            let algorithm = verifiers[expected[1] as usize].algorithm();
+            assert_eq!(algorithm, sig.algorithm());
+        }
+
+        let reference = falcon512::sign(msg, &sk512).to_reference_bytes();
+        assert!(matches!(
+            FalconSignature::from_bytes(&reference),
+            Ok(FalconSignature::F512(_))
+        ));
+        assert!(!verifiers[0].verify_bytes(b"another message", &reference));
+        assert_eq!(
+            FalconSignature::from_bytes(&[]),
+            Err(FalconDeserializationError::CannotInferFalconVariant)
+        );
+    }
+
+    #[test]
+    fn test_variant_enums() {
+        let mut rng = thread_rng();
+        let msg = b"variant carried in-band";
+        let (sk512, pk512) = falcon512::keygen(rng.gen());
+        let (sk1024, pk1024) = falcon1024::keygen(rng.gen());
+        let keys = [
+            (FalconSecretKey::from(sk512), FalconPublicKey::from(pk512)),
+            (FalconSecretKey::from(sk1024), FalconPublicKey::from(pk1024)),
+        ];
+
+        let mut signatures = vec![];
+        for (sk, pk) in &keys {
+            let sk = FalconSecretKey::from_bytes(&sk.to_bytes()).unwrap();
+            let pk = FalconPublicKey::from_bytes(&pk.to_bytes()).unwrap();
+            assert_eq!(sk.algorithm(), pk.algorithm());
+            assert_eq!(sk.public_key().as_bytes(), pk.as_bytes());
+            assert_eq!(sk.public_key().fingerprint(), pk.fingerprint());
+            let sig = FalconSignature::from_bytes(&sk.sign(msg).to_bytes()).unwrap();
+            assert_eq!(sig.algorithm(), pk.algorithm());
+            assert!(pk.verify(msg, &sig));
+            assert!(!pk.verify(b"another message", &sig));
+            assert!(pk.verify_bytes(msg, sig.as_bytes()));
+            signatures.push(sig);
+        }
+
+        // signatures for the other variant are rejected
+        assert!(!keys[0].1.verify(msg, &signatures[1]));
+        assert!(!keys[1].1.verify(msg, &signatures[0]));
+    }
+}
@@ -0,0 +1,176 @@
+//! Public keys embedded at compile time.
+//!
+//! A [`PublicKey`] stores its polynomial on the heap and so cannot be built
+//! in a constant. An [`EmbeddedPublicKey`] holds the decoded coefficients in
+//! a fixed-size array instead; it is decoded and validated by a `const fn`,
+//! so a malformed key embedded in a `const` or `static` fails the build
+//! rather than the first verification.
+//!
+//! ```
+//! use falcon_rust::{falcon512, include_public_key};
+//!
+//! static TRUSTED_KEY: falcon512::EmbeddedPublicKey =
+//!     include_public_key!(falcon512, "../fixtures/pqclean-512.pk");
+//!
+//! let pk: falcon512::PublicKey = TRUSTED_KEY.public_key();
+//! # assert_eq!(pk.as_bytes(), include_bytes!("../fixtures/pqclean-512.pk"));
+//! ```
+
+use crate::{
+    falcon::{FalconDeserializationError, PublicKey},
+    falcon_field::{Felt, Q},
+    polynomial::Polynomial,
+};
+
+/// A public key that can be decoded in constant context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EmbeddedPublicKey<const N: usize> {
+    h: [u16; N],
+}
+
+impl<const N: usize> EmbeddedPublicKey<N> {
+    /// Deserialize the given slice of bytes into a public key.
+    ///
+    /// Unlike [`PublicKey::from_bytes`], this also rejects coefficients that
+    /// are not reduced modulo q.
+    pub const fn try_from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        let n = match bytes.len() {
+            897 => 512,
+            1793 => 1024,
+            _ => return Err(FalconDeserializationError::BadEncodingLength),
+        };
+        if n != N {
+            return Err(FalconDeserializationError::WrongVariant);
+        }
+        if bytes[0] >> 4 != 0 {
+            return Err(FalconDeserializationError::InvalidHeaderFormat);
+        }
+        if bytes[0] as u32 != N.ilog2() {
+            return Err(FalconDeserializationError::InvalidLogN);
+        }
+
+        // coefficients are packed as 14-bit integers, most significant bit
+        // first
+        let mut h = [0u16; N];
+        let mut i = 0;
+        while i < N {
+            let mut value = 0u16;
+            let mut bit = 14 * i;
+            while bit < 14 * (i + 1) {
+                let byte = bytes[1 + bit / 8];
+                value = (value << 1) | ((byte >> (7 - bit % 8)) & 1) as u16;
+                bit += 1;
+            }
+            if value as u32 >= Q {
+                return Err(FalconDeserializationError::BadFieldElementEncoding);
+            }
+            h[i] = value;
+            i += 1;
+        }
+
+        Ok(Self { h })
+    }
+
+    /// Deserialize the given slice of bytes into a public key, panicking if
+    /// it is malformed. In constant context the panic is a compile error:
+    ///
+    /// ```compile_fail
+    /// use falcon_rust::falcon512;
+    ///
+    /// const KEY: falcon512::EmbeddedPublicKey =
+    ///     falcon512::EmbeddedPublicKey::from_bytes(&[0u8; 897]);
+    /// ```
+    pub const fn from_bytes(bytes: &[u8]) -> Self {
+        match Self::try_from_bytes(bytes) {
+            Ok(key) => key,
+            Err(FalconDeserializationError::BadEncodingLength) => {
+                panic!("embedded public key has the wrong length")
+            }
+            Err(FalconDeserializationError::WrongVariant) => {
+                panic!("embedded public key is for the other Falcon variant")
+            }
+            Err(FalconDeserializationError::BadFieldElementEncoding) => {
+                panic!("embedded public key has a coefficient out of range")
+            }
+            Err(_) => panic!("embedded public key has an invalid header"),
+        }
+    }
+
+    /// Convert into a public key that can be used for verification.
+    pub fn public_key(&self) -> PublicKey<N> {
+        PublicKey::new(Polynomial::new(
+            self.h.iter().map(|&c| Felt::new(c as i16)).collect(),
+        ))
+    }
+}
+
+impl<const N: usize> From<&EmbeddedPublicKey<N>> for PublicKey<N> {
+    fn from(key: &EmbeddedPublicKey<N>) -> Self {
+        key.public_key()
+    }
+}
+
+/// Embed a public key file in the binary, validating it at compile time.
+///
+/// The first argument is the variant module (`falcon512` or `falcon1024`);
+/// the second is a path to the encoded key, resolved like
+/// [`include_bytes!`]. Expands to a constant
+/// [`EmbeddedPublicKey`](crate::embedded::EmbeddedPublicKey).
+#[macro_export]
+macro_rules! include_public_key {
+    ($variant:ident, $path:expr) => {{
+        const KEY: $crate::$variant::EmbeddedPublicKey =
+            $crate::$variant::EmbeddedPublicKey::from_bytes(include_bytes!($path));
+        KEY
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon::FalconDeserializationError, falcon1024, falcon512};
+
+    const PQCLEAN_512: falcon512::EmbeddedPublicKey =
+        include_public_key!(falcon512, "../fixtures/pqclean-512.pk");
+
+    #[test]
+    fn test_embedded_public_key() {
+        let bytes = include_bytes!("../fixtures/pqclean-512.pk");
+        assert_eq!(
+            PQCLEAN_512.public_key(),
+            falcon512::PublicKey::from_bytes(bytes).unwrap()
+        );
+
+        let (_, pk) = falcon512::keygen(thread_rng().gen());
+        let embedded = falcon512::EmbeddedPublicKey::from_bytes(pk.as_bytes());
+        assert_eq!(falcon512::PublicKey::from(&embedded), pk);
+    }
+
+    #[test]
+    fn test_embedded_public_key_errors() {
+        let mut bytes = include_bytes!("../fixtures/pqclean-512.pk").to_vec();
+        assert_eq!(
+            falcon1024::EmbeddedPublicKey::try_from_bytes(&bytes),
+            Err(FalconDeserializationError::WrongVariant)
+        );
+        assert_eq!(
+            falcon512::EmbeddedPublicKey::try_from_bytes(&bytes[1..]),
+            Err(FalconDeserializationError::BadEncodingLength)
+        );
+
+        bytes[0] = 0x0a;
+        assert_eq!(
+            falcon512::EmbeddedPublicKey::try_from_bytes(&bytes),
+            Err(FalconDeserializationError::InvalidLogN)
+        );
+
+        bytes[0] = 0x09;
+        bytes[1] = 0xff;
+        bytes[2] = 0xff;
+        assert_eq!(
+            falcon512::EmbeddedPublicKey::try_from_bytes(&bytes),
+            Err(FalconDeserializationError::BadFieldElementEncoding)
+        );
+    }
+}
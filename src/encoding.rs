@@ -1,9 +1,11 @@
+//! Compression and decompression routines for signatures.
+
+use alloc::{vec, vec::Vec};
+
 use bit_vec::BitVec;
 use itertools::Itertools;
 use num::Integer;
 
-/// Compression and decompression routines for signatures.
-
 /// This is a deprecated compress routine used now only for testing
 /// compatibility with the new, faster implementation (below).
 #[allow(dead_code)]
@@ -269,11 +271,78 @@ pub(crate) fn decompress(x: &[u8], n: usize) -> Option<Vec<i16>> {
     Some(result)
 }
 
+/// The number of bits per coefficient in the constant-time ("CT") encoding
+/// of the reference implementation.
+pub(crate) const CT_BITS: usize = 12;
+
+/// Encode v in the CT format of the reference implementation: every
+/// coefficient as a `CT_BITS`-bit two's complement integer, packed
+/// big-endian. Unlike the compressed encoding, the length depends only on
+/// the number of coefficients, and so does the running time.
+///
+/// Return None if some coefficient lies outside (-2^11, 2^11).
+pub(crate) fn encode_ct(v: &[i16]) -> Option<Vec<u8>> {
+    let bound = 1i16 << (CT_BITS - 1);
+    let mask = (1u32 << CT_BITS) - 1;
+    let mut bytes = Vec::with_capacity((v.len() * CT_BITS).div_ceil(8));
+    let mut acc = 0u32;
+    let mut acc_len = 0;
+    for &c in v {
+        if c <= -bound || c >= bound {
+            return None;
+        }
+        acc = (acc << CT_BITS) | (c as u32 & mask);
+        acc_len += CT_BITS;
+        while acc_len >= 8 {
+            acc_len -= 8;
+            bytes.push((acc >> acc_len) as u8);
+        }
+    }
+    if acc_len > 0 {
+        bytes.push((acc << (8 - acc_len)) as u8);
+    }
+    Some(bytes)
+}
+
+/// Decode n coefficients in the CT format. Like the reference
+/// implementation, reject -2^11, which `encode_ct` never produces, so that
+/// every vector has exactly one encoding.
+///
+/// Return None if the length is wrong, a coefficient is -2^11, or the
+/// padding bits are not zero.
+pub(crate) fn decode_ct(x: &[u8], n: usize) -> Option<Vec<i16>> {
+    if x.len() != (n * CT_BITS).div_ceil(8) {
+        return None;
+    }
+    let mask = (1u32 << CT_BITS) - 1;
+    let sign = 1u32 << (CT_BITS - 1);
+    let mut result = Vec::with_capacity(n);
+    let mut acc = 0u32;
+    let mut acc_len = 0;
+    for &byte in x {
+        acc = (acc << 8) | byte as u32;
+        acc_len += 8;
+        // CT_BITS > 8, so each byte completes at most one coefficient
+        if acc_len >= CT_BITS && result.len() < n {
+            acc_len -= CT_BITS;
+            let w = (acc >> acc_len) & mask;
+            if w == sign {
+                return None;
+            }
+            result.push(w as i16 - ((w & sign) << 1) as i16);
+        }
+    }
+    if acc & ((1 << acc_len) - 1) != 0 {
+        return None;
+    }
+    Some(result)
+}
+
 #[cfg(test)]
 mod test {
 
     use crate::{
-        encoding::{compress, compress_slow, decompress, decompress_slow},
+        encoding::{compress, compress_slow, decode_ct, decompress, decompress_slow, encode_ct},
         falcon_field::Q,
     };
     use bit_vec::BitVec;
@@ -316,6 +385,27 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_encode_decode_ct() {
+        for n in [512, 1024] {
+            let v = short_elements(n);
+            let encoded = encode_ct(&v).unwrap();
+            assert_eq!(encoded.len(), n * 12 / 8);
+            assert_eq!(decode_ct(&encoded, n).unwrap(), v);
+            assert!(decode_ct(&encoded[1..], n).is_none());
+        }
+
+        // two's complement, big-endian
+        assert_eq!(encode_ct(&[1, -1]).unwrap(), [0x00, 0x1f, 0xff]);
+        assert_eq!(encode_ct(&[2047, -2047]).unwrap(), [0x7f, 0xf8, 0x01]);
+        assert!(encode_ct(&[2048, 0]).is_none());
+        assert!(encode_ct(&[-2048, 0]).is_none());
+        assert!(decode_ct(&[0x80, 0x00, 0x00], 2).is_none());
+        // padding bits must be zero
+        assert_eq!(decode_ct(&[0x00, 0x10], 1).unwrap(), [1]);
+        assert!(decode_ct(&[0x00, 0x11], 1).is_none());
+    }
+
     #[test]
     fn compress_empty_vec_does_not_crash() {
         compress(&[], 0);
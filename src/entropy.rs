@@ -0,0 +1,26 @@
+//! The source of all randomness the crate draws on its own, as opposed to
+//! randomness derived from a caller-supplied seed.
+//!
+//! This is the operating system's randomness, extended by [`thread_rng`],
+//! which in the browser, with the `wasm` feature, is `crypto.getRandomValues`,
+//! unless building with `--cfg falcon_test_rng` routes it through the
+//! seedable PRNG of [`test_rng`](crate::test_rng).
+
+#[cfg(not(falcon_test_rng))]
+use rand::{rngs::ThreadRng, thread_rng};
+
+/// The random number generator for keys, salts, and sampling.
+#[cfg(not(falcon_test_rng))]
+pub(crate) fn rng() -> ThreadRng {
+    // According to the docs [1], `thread_rng` uses entropy supplied
+    // by the operating system and ChaCha12 to extend it. So it is
+    // cryptographically secure, afaict.
+    // [1]: https://rust-random.github.io/rand/rand/rngs/struct.ThreadRng.html
+    thread_rng()
+}
+
+/// The random number generator for keys, salts, and sampling.
+#[cfg(falcon_test_rng)]
+pub(crate) fn rng() -> crate::test_rng::TestRng {
+    crate::test_rng::TestRng
+}
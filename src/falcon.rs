@@ -1,15 +1,42 @@
+use alloc::{vec, vec::Vec};
+use core::{borrow::Borrow, cmp::Ordering, fmt};
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
 use bit_vec::BitVec;
+#[cfg(feature = "encrypted-keys")]
+use chacha20poly1305::{aead::AeadInPlace, KeyInit, XChaCha20Poly1305};
 use itertools::Itertools;
 use num_complex::{Complex, Complex64};
-use rand::{rngs::StdRng, thread_rng, Rng, RngCore, SeedableRng};
-
+#[cfg(feature = "std")]
+use rand::Rng;
+use rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Digest, Sha3_256, Sha3_512, Shake128, Shake256,
+};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+#[cfg(not(feature = "std"))]
+use crate::float::FloatExt;
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+use crate::neon::{norm_squared, norm_squared_i16, sub_mul};
+#[cfg(feature = "std")]
+use crate::{
+    cyclotomic_fourier::CyclotomicFourier, fast_fft::COMPLEX_BITREVERSED_POWERS_1024,
+    ffsampling::ffsampling_into, polynomial::hash_to_point_into, reference_prng::ReferencePrng,
+};
 use crate::{
-    encoding::{compress, decompress},
+    encoding::{compress, decode_ct, decompress, encode_ct, CT_BITS},
     falcon_field::{Felt, Q},
-    fast_fft::FastFft,
+    fast_fft::{felt_ntt_point, FastFft},
     ffsampling::{ffldl, ffsampling, gram, normalize_tree, LdlTree},
-    math::ntru_gen,
-    polynomial::{hash_to_point, Polynomial},
+    fingerprint::Fingerprint,
+    math::{ntru_gen_reference, ntru_gen_with_stats},
+    nonce::Nonce,
+    polynomial::{hash_to_point_vectored, zeroize_complex, Polynomial},
 };
 
 #[derive(Copy, Clone, Debug)]
@@ -27,7 +54,7 @@ pub enum FalconVariant {
 }
 
 impl FalconVariant {
-    const fn from_n(n: usize) -> Self {
+    pub(crate) const fn from_n(n: usize) -> Self {
         match n {
             512 => Self::Falcon512,
             1024 => Self::Falcon1024,
@@ -54,7 +81,7 @@ impl FalconVariant {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FalconDeserializationError {
     CannotDetermineFieldElementEncodingMethod,
     CannotInferFalconVariant,
@@ -63,9 +90,500 @@ pub enum FalconDeserializationError {
     BadEncodingLength,
     BadFieldElementEncoding,
     WrongVariant,
+    UnsupportedKeygenVersion,
+    UnsupportedFormatVersion,
+    DecryptionFailed,
 }
 
-#[derive(Debug, Clone)]
+/// Reasons why [`sign_with`] and the other fallible signing functions fail
+/// to produce a signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignError {
+    /// The context string is longer than 255 bytes.
+    ContextTooLong,
+    /// The digest given to [`sign_prehash`] is not as long as the output
+    /// of its hash function.
+    DigestLengthMismatch,
+    /// No candidate signature passed the norm and length checks within the
+    /// configured attempt limit.
+    AttemptLimitExceeded,
+    /// The signature did not verify against the public key passed to
+    /// [`sign_verified`], either because a fault corrupted the computation
+    /// or because the key pair does not match.
+    FaultDetected,
+}
+
+/// Reasons why a signature is rejected, reported by [`try_verify`] and the
+/// other `try_` verification functions, or why a key pair is, reported by
+/// [`SecretKey::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FalconError {
+    /// The signature or key could not be decoded.
+    MalformedEncoding(FalconDeserializationError),
+    /// The encoded signature is too short to hold its 40-byte nonce.
+    BadNonceLength,
+    /// The squared norm of the signature is not below the bound.
+    NormBoundExceeded,
+    /// `s1 + s2·h` differs from the hash of the message at this coefficient.
+    IndexMismatch(usize),
+    /// An index given to [`try_fverify`] is not less than N.
+    IndexOutOfRange(usize),
+    /// The secret basis does not satisfy the NTRU equation f G - g F = q.
+    NtruEquationUnsatisfied,
+    /// The public key is not g / f mod q for the secret key.
+    PublicKeyMismatch,
+}
+
+impl From<FalconDeserializationError> for FalconError {
+    fn from(error: FalconDeserializationError) -> Self {
+        Self::MalformedEncoding(error)
+    }
+}
+
+/// The quantities that decide whether a signature is valid, reported by
+/// [`verify_with_details`]: a signature is valid if and only if the squared
+/// norm of `(s1, s2)` is below the bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerificationDetails {
+    /// The squared norm of `(s1, s2)`.
+    pub norm_squared: i64,
+    /// The acceptance bound of the variant, which the squared norm must be
+    /// below.
+    pub bound: i64,
+}
+
+impl VerificationDetails {
+    /// Test whether the signature is valid.
+    pub fn is_valid(&self) -> bool {
+        self.norm_squared < self.bound
+    }
+
+    /// The squared norm as a fraction of the bound; valid signatures have a
+    /// ratio below 1.
+    pub fn ratio(&self) -> f64 {
+        self.norm_squared as f64 / self.bound as f64
+    }
+}
+
+/// A seed was refused because it is obviously not random, e.g., all zeros
+/// or a short pattern repeated. See [`is_weak_seed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeakSeedError;
+
+/// Test whether a seed is degenerate: all bytes equal, or a block of at
+/// most 16 bytes repeated. Such seeds are almost always placeholders or the
+/// result of a broken randomness source, and the keys or signatures derived
+/// from them would be predictable.
+///
+/// This only catches the most blatant mistakes. A seed that passes is not
+/// thereby known to be random.
+pub fn is_weak_seed(seed: &[u8; 32]) -> bool {
+    [1, 2, 4, 8, 16]
+        .into_iter()
+        .any(|period| seed.iter().skip(period).zip(seed).all(|(a, b)| a == b))
+}
+
+/// Counters describing the work done by key generation.
+///
+/// Key generation samples candidate pairs (f, g) until one passes all
+/// checks; every candidate is counted in `candidates` and every rejected one
+/// in exactly one of the other counters, so for a single successful key
+/// generation `candidates == rejected() + 1`. The counters accumulate, so
+/// the same struct can be passed to many key generations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct KeygenStats {
+    /// Candidates (f, g) sampled.
+    pub candidates: u64,
+    /// Candidates rejected because f is not invertible modulo q.
+    pub non_invertible: u64,
+    /// Candidates rejected because the Gram-Schmidt norm of the basis is
+    /// too large.
+    pub gram_schmidt_norm_failures: u64,
+    /// Candidates for which solving the NTRU equation failed, so that
+    /// sampling was retried.
+    pub ntru_solve_failures: u64,
+}
+
+impl KeygenStats {
+    /// The total number of rejected candidates.
+    pub fn rejected(&self) -> u64 {
+        self.non_invertible + self.gram_schmidt_norm_failures + self.ntru_solve_failures
+    }
+}
+
+/// Versions of the algorithm that expands a seed into a key pair.
+///
+/// A given version and seed reproduce the same key pair in every release of
+/// this crate. Changes to key generation that alter its output are released
+/// as a new version, and old versions remain supported.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeygenVersion {
+    /// NTRUGen with randomness drawn from ChaCha12 keyed with the seed.
+    V1,
+}
+
+impl KeygenVersion {
+    /// The version used by [`keygen`] and [`try_keygen`].
+    pub const CURRENT: Self = Self::V1;
+
+    /// The tag identifying this version in a serialized [`KeySeed`].
+    pub const fn to_byte(self) -> u8 {
+        match self {
+            Self::V1 => 1,
+        }
+    }
+
+    /// The version with the given tag, if it is known.
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Self::V1),
+            _ => None,
+        }
+    }
+}
+
+/// A secret key in its most compact serialized form: the seed it is
+/// generated from, tagged with the [`KeygenVersion`] that expands it.
+///
+/// The encoding is the version tag followed by the seed, 33 bytes in total.
+#[derive(Clone)]
+pub struct KeySeed {
+    version: KeygenVersion,
+    seed: [u8; 32],
+}
+
+impl KeySeed {
+    /// The length of the encoding produced by [`KeySeed::to_bytes`].
+    pub const BYTE_LEN: usize = 33;
+
+    /// Tag a seed with the current key generation version.
+    pub fn new(seed: [u8; 32]) -> Self {
+        Self::with_version(KeygenVersion::CURRENT, seed)
+    }
+
+    /// Tag a seed with the given key generation version.
+    pub fn with_version(version: KeygenVersion, seed: [u8; 32]) -> Self {
+        Self { version, seed }
+    }
+
+    /// Draw a fresh seed from the operating system's randomness.
+    #[cfg(feature = "std")]
+    pub fn generate() -> Self {
+        Self::new(crate::entropy::rng().gen())
+    }
+
+    /// The key generation version.
+    pub fn version(&self) -> KeygenVersion {
+        self.version
+    }
+
+    /// The seed.
+    pub fn seed(&self) -> &[u8; 32] {
+        &self.seed
+    }
+
+    /// Expand the seed into a key pair, or fail if the seed is weak.
+    pub fn keygen<const N: usize>(&self) -> Result<(SecretKey<N>, PublicKey<N>), WeakSeedError> {
+        try_keygen_versioned(self.version, self.seed)
+    }
+
+    /// Like [`KeySeed::keygen`], but also add the work done to `stats`.
+    pub fn keygen_with_stats<const N: usize>(
+        &self,
+        stats: &mut KeygenStats,
+    ) -> Result<(SecretKey<N>, PublicKey<N>), WeakSeedError> {
+        try_keygen_versioned_with_stats(self.version, self.seed, Some(stats))
+    }
+
+    /// Serialize the seed and its version.
+    pub fn to_bytes(&self) -> [u8; Self::BYTE_LEN] {
+        let mut bytes = [0u8; Self::BYTE_LEN];
+        bytes[0] = self.version.to_byte();
+        bytes[1..].copy_from_slice(&self.seed);
+        bytes
+    }
+
+    /// Deserialize a seed and its version.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        if bytes.len() != Self::BYTE_LEN {
+            return Err(FalconDeserializationError::BadEncodingLength);
+        }
+        let version = KeygenVersion::from_byte(bytes[0])
+            .ok_or(FalconDeserializationError::UnsupportedKeygenVersion)?;
+        Ok(Self::with_version(version, bytes[1..].try_into().unwrap()))
+    }
+}
+
+impl fmt::Debug for KeySeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeySeed")
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Compares the seeds in constant time; the version is public.
+impl ConstantTimeEq for KeySeed {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        Choice::from((self.version == other.version) as u8) & self.seed.ct_eq(&other.seed)
+    }
+}
+
+impl PartialEq for KeySeed {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for KeySeed {}
+
+/// Options for [`sign_with`], built up fluently:
+///
+/// ```
+/// # use falcon_rust::falcon512::{self, SigningOptions};
+/// # use rand::{thread_rng, Rng};
+/// # let (sk, pk) = falcon512::keygen(thread_rng().gen());
+/// let options = SigningOptions::new()
+///     .deterministic(true)
+///     .context(b"example")
+///     .attempt_limit(8);
+/// let sig = falcon512::sign_with(b"message", &sk, &options).unwrap();
+/// assert!(falcon512::verify_ctx(b"message", b"example", &sig, &pk));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SigningOptions<'a> {
+    deterministic: bool,
+    context: Option<&'a [u8]>,
+    attempt_limit: Option<usize>,
+    reference_randomness: Option<(&'a Nonce, &'a [u8])>,
+}
+
+impl<'a> SigningOptions<'a> {
+    /// The default options: randomized, without context and without an
+    /// attempt limit. Signing with these is equivalent to [`sign`].
+    pub const fn new() -> Self {
+        Self {
+            deterministic: false,
+            context: None,
+            attempt_limit: None,
+            reference_randomness: None,
+        }
+    }
+
+    /// Derive the salt and the sampler randomness from the secret key and
+    /// the message rather than from the system RNG, so that signing the same
+    /// message twice yields the same signature.
+    pub const fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Bind the signature to a context string of at most 255 bytes, framed
+    /// as in the pure mode of FIPS 206 (draft), like [`sign_ctx`]. Such
+    /// signatures only verify with [`verify_ctx`] and the same context.
+    /// Note that the empty context differs from no context.
+    pub const fn context(mut self, context: &'a [u8]) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Give up after this many candidate signatures have been rejected by
+    /// the norm and length checks. Without a limit, signing retries until it
+    /// succeeds, which happens after very few attempts on average.
+    pub const fn attempt_limit(mut self, attempt_limit: usize) -> Self {
+        self.attempt_limit = Some(attempt_limit);
+        self
+    }
+
+    /// Sign exactly as the reference implementation does, given the nonce
+    /// and the seed that it draws from `randombytes()`: the seed is absorbed
+    /// into SHAKE256, which keys the ChaCha20-based PRNG that drives the
+    /// Gaussian sampler, and signatures are limited to the length that the
+    /// reference `crypto_sign_signature()` produces. The resulting
+    /// signature is then byte-identical to the reference one, see
+    /// [`Signature::to_reference_bytes`], so known-answer tests of the C
+    /// code can be replayed.
+    ///
+    /// The reference implementation computes in floating point in a
+    /// different order, so in principle a rounding difference could tip a
+    /// sample the other way; this is far too unlikely to be observed.
+    ///
+    /// Takes precedence over [`SigningOptions::deterministic`]. Never reuse
+    /// a nonce and seed for different messages.
+    pub const fn reference_randomness(mut self, nonce: &'a Nonce, seed: &'a [u8]) -> Self {
+        self.reference_randomness = Some((nonce, seed));
+        self
+    }
+}
+
+/// Prefix the message parts with the domain separator of FIPS 206, which
+/// like FIPS 204 is a mode byte, 0 for pure and 1 for pre-hashed signing,
+/// followed by the length-prefixed context string. The header is written to
+/// `header`, which must outlive the result; `None` if the context is longer
+/// than 255 bytes.
+fn domain_separate<'a>(
+    mode: u8,
+    context: &'a [u8],
+    m: &[&'a [u8]],
+    header: &'a mut [u8; 2],
+) -> Option<Vec<&'a [u8]>> {
+    *header = [mode, u8::try_from(context.len()).ok()?];
+    Some([&[header.as_slice(), context], m].concat())
+}
+
+/// Hash functions for pre-hashed signing with [`sign_prehash`], i.e., those
+/// of the SHA-3 family approved for HashML-DSA in FIPS 204, which FIPS 206
+/// (draft) follows.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrehashAlgorithm {
+    Sha3_256,
+    Sha3_512,
+    /// SHAKE128 with 256 bits of output.
+    Shake128,
+    /// SHAKE256 with 512 bits of output.
+    Shake256,
+}
+
+impl PrehashAlgorithm {
+    /// The DER encoding of the object identifier of the hash function,
+    /// which is hashed along with the digest.
+    pub const fn oid(self) -> [u8; 11] {
+        let last = match self {
+            Self::Sha3_256 => 0x08,
+            Self::Sha3_512 => 0x0a,
+            Self::Shake128 => 0x0b,
+            Self::Shake256 => 0x0c,
+        };
+        [
+            0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, last,
+        ]
+    }
+
+    /// The length of the digest in bytes.
+    pub const fn digest_len(self) -> usize {
+        match self {
+            Self::Sha3_256 | Self::Shake128 => 32,
+            Self::Sha3_512 | Self::Shake256 => 64,
+        }
+    }
+
+    /// Hash a message, for when the digest is not computed elsewhere.
+    pub fn digest(self, m: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha3_256 => Sha3_256::digest(m).to_vec(),
+            Self::Sha3_512 => Sha3_512::digest(m).to_vec(),
+            Self::Shake128 => {
+                let mut digest = vec![0u8; self.digest_len()];
+                Shake128::digest_xof(m, &mut digest);
+                digest
+            }
+            Self::Shake256 => {
+                let mut digest = vec![0u8; self.digest_len()];
+                Shake256::digest_xof(m, &mut digest);
+                digest
+            }
+        }
+    }
+}
+
+/// Hash the nonce followed by the message to a point, the polynomial `c`
+/// that a signature with this nonce on the message must match, exactly as
+/// signing and verification compute it: SHAKE256 over `nonce || m`,
+/// squeezed in 16-bit big-endian words that are reduced modulo q after
+/// rejecting those of at least 5q. Coefficients are in `[0, q)`.
+pub fn hash_to_point<const N: usize>(m: &[u8], nonce: &Nonce) -> Polynomial<u16> {
+    hash_message(nonce.as_bytes(), &[m], N).map(|c| c.value() as u16)
+}
+
+/// Hash the salt followed by the message parts to a point.
+fn hash_message(r: &[u8; 40], m: &[&[u8]], n: usize) -> Polynomial<Felt> {
+    hash_to_point_vectored(&[&[r.as_slice()], m].concat(), n)
+}
+
+/// The hash of a message to a point, remembered for the first message it is
+/// computed for. Messages are told apart by their SHA3-256 digest, which is
+/// much cheaper than the SHAKE256 expansion it saves.
+///
+/// A cache is derived data: all caches compare equal and hash alike, so
+/// they do not affect the comparisons of the signatures holding them.
+///
+/// Without the `std` feature, nothing is remembered and every hash is
+/// computed afresh.
+#[derive(Clone, Default)]
+struct HashToPointCache(#[cfg(feature = "std")] OnceLock<([u8; 32], Polynomial<Felt>)>);
+
+impl HashToPointCache {
+    /// Like [`hash_message`], but served from the cache if `m` is the
+    /// message this cache was filled for, and filling it if it is empty.
+    #[cfg(feature = "std")]
+    fn hash_message(&self, r: &[u8; 40], m: &[&[u8]], n: usize) -> Polynomial<Felt> {
+        let mut hasher = Sha3_256::default();
+        for part in m {
+            Digest::update(&mut hasher, part);
+        }
+        let digest: [u8; 32] = hasher.finalize().into();
+        match self.0.get() {
+            Some((key, c)) if *key == digest => c.clone(),
+            Some(_) => hash_message(r, m, n),
+            None => self
+                .0
+                .get_or_init(|| (digest, hash_message(r, m, n)))
+                .1
+                .clone(),
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn hash_message(&self, r: &[u8; 40], m: &[&[u8]], n: usize) -> Polynomial<Felt> {
+        hash_message(r, m, n)
+    }
+
+    /// Fill the cache with the hash of `m`, unless it is already filled.
+    #[cfg(feature = "std")]
+    fn fill(&self, m: &[u8], c: &Polynomial<Felt>) {
+        let _ = self.0.set((Sha3_256::digest(m).into(), c.clone()));
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fill(&self, _m: &[u8], _c: &Polynomial<Felt>) {}
+}
+
+impl PartialEq for HashToPointCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for HashToPointCache {}
+
+impl PartialOrd for HashToPointCache {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HashToPointCache {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl core::hash::Hash for HashToPointCache {
+    fn hash<H: core::hash::Hasher>(&self, _state: &mut H) {}
+}
+
+impl fmt::Debug for HashToPointCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("HashToPointCache");
+        #[cfg(feature = "std")]
+        debug.field("filled", &self.0.get().is_some());
+        debug.finish()
+    }
+}
+
+#[derive(Clone)]
 pub struct SecretKey<const N: usize> {
     /// b0 = [[g, -f], [G, -F]]
     b0: [Polynomial<i16>; 4],
@@ -73,25 +591,65 @@ pub struct SecretKey<const N: usize> {
 }
 
 impl<const N: usize> SecretKey<N> {
+    /// The length of the encoding produced by [`SecretKey::to_bytes`].
+    pub const BYTE_LEN: usize =
+        1 + (Self::field_element_width(N, 0) + Self::field_element_width(N, 1) + 8) * N / 8;
+
     /// Generate a secret key using randomness supplied by the operating system.
+    #[cfg(feature = "std")]
     pub fn generate() -> Self {
-        // According to the docs [1], `thread_rng` uses entropy supplied
-        // by the operating system and ChaCha12 to extend it. So it is
-        // cryptographically secure, afaict.
-        // [1]: https://rust-random.github.io/rand/rand/rngs/struct.ThreadRng.html
-        Self::generate_from_seed(thread_rng().gen())
+        Self::generate_from_seed(crate::entropy::rng().gen())
     }
 
     /// Generate a secret key pseudorandomly by expanding a given seed.
+    ///
+    /// The seed is not checked; [`SecretKey::try_generate_from_seed`]
+    /// refuses weak seeds, see [`is_weak_seed`].
     pub fn generate_from_seed(seed: [u8; 32]) -> Self {
+        Self::from_b0(Self::gen_b0(KeygenVersion::CURRENT, seed, None))
+    }
+
+    /// Generate a secret key pseudorandomly by expanding a given seed, or
+    /// fail if the seed is weak.
+    pub fn try_generate_from_seed(seed: [u8; 32]) -> Result<Self, WeakSeedError> {
+        Self::try_generate_from_versioned_seed(KeygenVersion::CURRENT, seed)
+    }
+
+    /// Generate a secret key pseudorandomly by expanding a given seed with
+    /// the given version of the key generation algorithm, or fail if the
+    /// seed is weak.
+    pub fn try_generate_from_versioned_seed(
+        version: KeygenVersion,
+        seed: [u8; 32],
+    ) -> Result<Self, WeakSeedError> {
+        Self::try_generate_with_stats(version, seed, None)
+    }
+
+    fn try_generate_with_stats(
+        version: KeygenVersion,
+        seed: [u8; 32],
+        stats: Option<&mut KeygenStats>,
+    ) -> Result<Self, WeakSeedError> {
+        if is_weak_seed(&seed) {
+            return Err(WeakSeedError);
+        }
         // separate sk gen for testing purposes
-        let b0 = Self::gen_b0(seed);
-        Self::from_b0(b0)
+        let b0 = Self::gen_b0(version, seed, stats);
+        Ok(Self::from_b0(b0))
     }
 
-    pub(crate) fn gen_b0(seed: [u8; 32]) -> [Polynomial<i16>; 4] {
-        let mut rng: StdRng = SeedableRng::from_seed(seed);
-        let (f, g, capital_f, capital_g) = ntru_gen(N, &mut rng);
+    pub(crate) fn gen_b0(
+        version: KeygenVersion,
+        seed: [u8; 32],
+        stats: Option<&mut KeygenStats>,
+    ) -> [Polynomial<i16>; 4] {
+        let mut discarded = KeygenStats::default();
+        let stats = stats.unwrap_or(&mut discarded);
+        let (f, g, capital_f, capital_g) = match version {
+            // `StdRng` may change between releases of `rand`; `ChaCha12Rng`
+            // is what it was when this version was fixed
+            KeygenVersion::V1 => ntru_gen_with_stats(N, &mut ChaCha12Rng::from_seed(seed), stats),
+        };
         [g, -f, capital_g, -capital_f]
     }
 
@@ -108,8 +666,21 @@ impl<const N: usize> SecretKey<N> {
         SecretKey { b0, tree }
     }
 
+    /// Test whether the basis satisfies the NTRU equation f G - g F = q over
+    /// the integers, modulo X^n + 1. Every secret key produced by key
+    /// generation does; a key that was corrupted or assembled from
+    /// mismatched parts almost certainly does not.
+    fn satisfies_ntru_equation(&self) -> bool {
+        let [g, minus_f, capital_g, minus_capital_f] =
+            self.b0.clone().map(|p| p.map(|&c| c as i64));
+        // f G - g F = g (-F) - (-f) G
+        let lhs =
+            (g.karatsuba(&minus_capital_f) - minus_f.karatsuba(&capital_g)).reduce_by_cyclotomic(N);
+        lhs == Polynomial::constant(Q as i64)
+    }
+
     /// Determine how many bits to use for each field element of a given polynomial.
-    fn field_element_width(n: usize, polynomial_index: usize) -> usize {
+    const fn field_element_width(n: usize, polynomial_index: usize) -> usize {
         if polynomial_index == 2 {
             8
         } else {
@@ -145,6 +716,41 @@ impl<const N: usize> SecretKey<N> {
         Ok(Felt::new(uint))
     }
 
+    /// Copy out the secret polynomials f, g, F and G, e.g., to hand them to
+    /// another implementation. Everything else about a secret key, from its
+    /// `Debug` output to its encoding helpers, keeps them out of sight; the
+    /// conspicuous name marks the places that do not.
+    pub fn expose_secret(&self) -> SecretPolynomials {
+        let [g, minus_f, capital_g, minus_capital_f] = &self.b0;
+        SecretPolynomials {
+            f: minus_f.coefficients.iter().map(|c| -c).collect(),
+            g: g.coefficients.clone(),
+            capital_f: minus_capital_f.coefficients.iter().map(|c| -c).collect(),
+            capital_g: capital_g.coefficients.clone(),
+        }
+    }
+
+    /// Recompute the matching public key, `h = g * f^-1 mod q`, so that it
+    /// need not be stored alongside the secret key. Same as
+    /// [`PublicKey::from_secret_key`].
+    pub fn public_key(&self) -> PublicKey<N> {
+        PublicKey::from_secret_key(self)
+    }
+
+    /// Check that the secret key is internally consistent and that the
+    /// public key belongs to it, catching corrupted or mismatched key files
+    /// before they produce signatures that do not verify. See also
+    /// [`consistent`].
+    pub fn validate(&self, pk: &PublicKey<N>) -> Result<(), FalconError> {
+        if !self.satisfies_ntru_equation() {
+            return Err(FalconError::NtruEquationUnsatisfied);
+        }
+        if self.public_key() != *pk {
+            return Err(FalconError::PublicKeyMismatch);
+        }
+        Ok(())
+    }
+
     /// Serialize the secret key to a vector of bytes.
     pub fn to_bytes(&self) -> Vec<u8> {
         // header
@@ -273,127 +879,744 @@ impl<const N: usize> SecretKey<N> {
     }
 }
 
+/// Compares the bases coefficient by coefficient in constant time.
+impl<const N: usize> ConstantTimeEq for SecretKey<N> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.b0
+            .iter()
+            .zip(&other.b0)
+            .flat_map(|(own, other)| own.coefficients.iter().zip(&other.coefficients))
+            .fold(Choice::from(1), |equal, (a, b)| equal & a.ct_eq(b))
+    }
+}
+
+/// Secret keys compare in constant time, see [`ConstantTimeEq`].
 impl<const N: usize> PartialEq for SecretKey<N> {
     fn eq(&self, other: &Self) -> bool {
-        let own_f = &self.b0[1];
-        let own_g = &self.b0[0];
-        let own_capital_f = &self.b0[3];
-        let own_capital_g = &self.b0[2];
+        self.ct_eq(other).into()
+    }
+}
 
-        let other_f = &other.b0[1];
-        let other_g = &other.b0[0];
-        let other_capital_f = &other.b0[3];
-        let other_capital_g = &other.b0[2];
+impl<const N: usize> Eq for SecretKey<N> {}
 
-        own_f == other_f
-            && own_g == other_g
-            && own_capital_f == other_capital_f
-            && own_capital_g == other_capital_g
+/// The basis and the tree are overwritten with zeros when the key is
+/// dropped, so that they do not linger in freed memory.
+impl<const N: usize> Drop for SecretKey<N> {
+    fn drop(&mut self) {
+        for b in self.b0.iter_mut() {
+            b.zeroize();
+        }
+        self.tree.zeroize();
     }
 }
 
-impl<const N: usize> Eq for SecretKey<N> {}
+impl<const N: usize> ZeroizeOnDrop for SecretKey<N> {}
+
+/// Secret keys are redacted so that logging code cannot leak them. This
+/// prints nothing but the short fingerprint of the public key, which
+/// identifies the key without revealing it.
+impl<const N: usize> fmt::Debug for SecretKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fingerprint = self.public_key().fingerprint();
+        f.debug_struct("SecretKey")
+            .field("n", &N)
+            .field("fingerprint", &format_args!("{}", fingerprint.short()))
+            .finish_non_exhaustive()
+    }
+}
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct PublicKey<const N: usize> {
-    h: Polynomial<Felt>,
+/// The polynomials of the NTRU basis of a secret key, as returned by
+/// [`SecretKey::expose_secret`]. They are overwritten with zeros when
+/// dropped, and not printed by `Debug`.
+#[derive(Clone)]
+pub struct SecretPolynomials {
+    pub f: Vec<i16>,
+    pub g: Vec<i16>,
+    pub capital_f: Vec<i16>,
+    pub capital_g: Vec<i16>,
 }
 
-impl<const N: usize> PublicKey<N> {
-    /// Compute the public key that matches with this secret key.
-    pub fn from_secret_key(sk: &SecretKey<N>) -> Self {
-        let f = sk.b0[1].map(|&c| -Felt::new(c));
-        let f_ntt = f.fft();
-        let g = sk.b0[0].map(|&c| Felt::new(c));
-        let g_ntt = g.fft();
-        let h_ntt = g_ntt.hadamard_div(&f_ntt);
-        let h = h_ntt.ifft();
-        Self { h }
+impl Drop for SecretPolynomials {
+    fn drop(&mut self) {
+        self.f.zeroize();
+        self.g.zeroize();
+        self.capital_f.zeroize();
+        self.capital_g.zeroize();
     }
+}
 
-    /// Deserialize the given slice of bytes into a public key.
-    pub fn from_bytes(byte_array: &[u8]) -> Result<Self, FalconDeserializationError> {
-        let n: usize = match byte_array.len() {
-            897 => 512,
-            1793 => 1024,
-            _ => return Err(FalconDeserializationError::BadEncodingLength),
-        };
+impl ZeroizeOnDrop for SecretPolynomials {}
 
-        // match against variant generic type parameter
-        if n != N {
-            return Err(FalconDeserializationError::WrongVariant);
-        }
+impl fmt::Debug for SecretPolynomials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretPolynomials").finish_non_exhaustive()
+    }
+}
 
-        // parse header
-        let header = byte_array[0];
+/// A secret key together with the FFT of its basis, which signing would
+/// otherwise recompute for every signature. See [`sign_with_expanded_key`]
+/// and [`sign_with_scratch`].
+#[derive(Clone)]
+pub struct ExpandedSecretKey<const N: usize> {
+    sk: SecretKey<N>,
+    /// FFT(g), FFT(f), FFT(G), FFT(F)
+    b_fft: [Vec<Complex64>; 4],
+}
 
-        if header >> 4 != 0 {
-            return Err(FalconDeserializationError::InvalidHeaderFormat);
+impl<const N: usize> ExpandedSecretKey<N> {
+    /// Expand a secret key.
+    pub fn new(sk: &SecretKey<N>) -> Self {
+        let [g, minus_f, capital_g, minus_capital_f] = &sk.b0;
+        let b_fft = [
+            g.map(|&i| Complex64::new(i as f64, 0.0)),
+            minus_f.map(|&i| Complex64::new(-i as f64, 0.0)),
+            capital_g.map(|&i| Complex64::new(i as f64, 0.0)),
+            minus_capital_f.map(|&i| Complex64::new(-i as f64, 0.0)),
+        ]
+        .map(|b| b.fft().coefficients);
+        Self {
+            sk: sk.clone(),
+            b_fft,
         }
+    }
 
-        let l = n.ilog2();
-        if header != l as u8 {
-            return Err(FalconDeserializationError::InvalidLogN);
-        }
+    /// The secret key this was expanded from.
+    pub fn secret_key(&self) -> &SecretKey<N> {
+        &self.sk
+    }
 
-        // parse h
-        let bit_buffer = BitVec::from_bytes(&byte_array[1..]);
-        let h = Polynomial::new(
-            bit_buffer
-                .iter()
-                .chunks(14)
+    /// The length of the encoding produced by
+    /// [`ExpandedSecretKey::to_encrypted_bytes`].
+    #[cfg(feature = "encrypted-keys")]
+    pub const ENCRYPTED_BYTE_LEN: usize = 2 + 24 + Self::PLAINTEXT_LEN + 16;
+
+    /// The basis, followed by its FFT and the LDL tree, whose coefficients
+    /// are complex numbers of two `f64`s each.
+    #[cfg(feature = "encrypted-keys")]
+    const PLAINTEXT_LEN: usize = 4 * 2 * N + (4 * N + ldl_tree_len(N)) * 16;
+
+    /// Serialize the expanded secret key, encrypted and authenticated under
+    /// the given 32-byte key, so that it can be stored on disk and loaded at
+    /// startup without expanding the secret key again.
+    ///
+    /// The encoding is a header byte `0x60 | log2(N)`, a format version
+    /// byte, a random 24-byte nonce, the ciphertext, and a 16-byte tag.
+    /// The plaintext holds the basis and, bit for bit, its FFT and the LDL
+    /// tree, so a loaded key signs exactly like the original. It is
+    /// encrypted with XChaCha20-Poly1305, with the two header bytes as
+    /// associated data.
+    #[cfg(feature = "encrypted-keys")]
+    pub fn to_encrypted_bytes(&self, key: &[u8; 32]) -> Vec<u8> {
+        let mut plaintext = Zeroizing::new(Vec::with_capacity(Self::PLAINTEXT_LEN));
+        for b in &self.sk.b0 {
+            plaintext.extend(b.coefficients.iter().flat_map(|c| c.to_be_bytes()));
+        }
+        let mut values = self.b_fft.concat();
+        self.sk.tree.flatten(&mut values);
+        plaintext.extend(values.iter().flat_map(|c| {
+            [c.re.to_bits(), c.im.to_bits()]
                 .into_iter()
-                .map(|ch| {
-                    let mut int = 0;
-                    for b in ch {
-                        int = (int << 1) | (b as i16);
-                    }
-                    int
-                })
-                .map(Felt::new)
-                .collect_vec(),
-        );
-
-        Ok(PublicKey { h })
+                .flat_map(u64::to_be_bytes)
+        }));
+        zeroize_complex(&mut values);
+        debug_assert_eq!(plaintext.len(), Self::PLAINTEXT_LEN);
+
+        let header = [0x60 | N.ilog2() as u8, ENCRYPTED_FORMAT_VERSION];
+        let nonce: [u8; 24] = crate::entropy::rng().gen();
+        let tag = XChaCha20Poly1305::new(key.into())
+            .encrypt_in_place_detached(&nonce.into(), &header, &mut plaintext)
+            .expect("the plaintext is short enough");
+        let mut bytes = Vec::with_capacity(Self::ENCRYPTED_BYTE_LEN);
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&nonce);
+        bytes.extend_from_slice(&plaintext);
+        bytes.extend_from_slice(&tag);
+        bytes
     }
 
-    // Serialize the public key as a list of bytes.
-    pub fn to_bytes(&self) -> Vec<u8> {
-        let header = self.h.coefficients.len().ilog2() as u8;
-        let mut bit_buffer = BitVec::from_bytes(&[header]);
-
-        for hi in self.h.coefficients.iter() {
-            for i in (0..14).rev() {
-                bit_buffer.push(hi.value() & (1 << i) != 0);
+    /// Deserialize an expanded secret key encrypted with
+    /// [`ExpandedSecretKey::to_encrypted_bytes`] under the given key.
+    ///
+    /// Fails with [`FalconDeserializationError::DecryptionFailed`] if the
+    /// key is wrong or the encoding was truncated or tampered with.
+    #[cfg(feature = "encrypted-keys")]
+    pub fn from_encrypted_bytes(
+        bytes: &[u8],
+        key: &[u8; 32],
+    ) -> Result<Self, FalconDeserializationError> {
+        if bytes.len() < 2 {
+            return Err(FalconDeserializationError::BadEncodingLength);
+        }
+        if bytes[0] >> 4 != 6 {
+            return Err(FalconDeserializationError::InvalidHeaderFormat);
+        }
+        match bytes[0] & 15 {
+            9 | 10 if (bytes[0] & 15) as u32 != N.ilog2() => {
+                return Err(FalconDeserializationError::WrongVariant)
             }
+            9 | 10 => {}
+            _ => return Err(FalconDeserializationError::InvalidLogN),
+        }
+        if bytes[1] != ENCRYPTED_FORMAT_VERSION {
+            return Err(FalconDeserializationError::UnsupportedFormatVersion);
+        }
+        // a truncated or extended encoding fails authentication like any
+        // other tampering
+        if bytes.len() != Self::ENCRYPTED_BYTE_LEN {
+            return Err(FalconDeserializationError::DecryptionFailed);
         }
+        let (header, rest) = bytes.split_at(2);
+        let (nonce, rest) = rest.split_at(24);
+        let (ciphertext, tag) = rest.split_at(Self::PLAINTEXT_LEN);
+        let mut plaintext = Zeroizing::new(ciphertext.to_vec());
+        XChaCha20Poly1305::new(key.into())
+            .decrypt_in_place_detached(nonce.into(), header, &mut plaintext, tag.into())
+            .map_err(|_| FalconDeserializationError::DecryptionFailed)?;
+
+        let (b0_bytes, values_bytes) = plaintext.split_at(4 * 2 * N);
+        let b0 = b0_bytes
+            .chunks_exact(2 * N)
+            .map(|b| {
+                Polynomial::new(
+                    b.chunks_exact(2)
+                        .map(|c| i16::from_be_bytes([c[0], c[1]]))
+                        .collect(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        let mut values = values_bytes.chunks_exact(16).map(|c| {
+            Complex64::new(
+                f64::from_bits(u64::from_be_bytes(c[..8].try_into().unwrap())),
+                f64::from_bits(u64::from_be_bytes(c[8..].try_into().unwrap())),
+            )
+        });
+        let b_fft = [(); 4].map(|_| values.by_ref().take(N).collect::<Vec<_>>());
+        let tree = LdlTree::unflatten(N, &mut values).expect("the length was checked");
+        Ok(Self {
+            sk: SecretKey { b0, tree },
+            b_fft,
+        })
+    }
+}
 
-        bit_buffer.to_bytes()
+/// The format version of [`ExpandedSecretKey::to_encrypted_bytes`].
+#[cfg(feature = "encrypted-keys")]
+const ENCRYPTED_FORMAT_VERSION: u8 = 2;
+
+/// The number of coefficients in the LDL tree over polynomials of `n`
+/// coefficients.
+#[cfg(feature = "encrypted-keys")]
+const fn ldl_tree_len(n: usize) -> usize {
+    if n > 2 {
+        n + 2 * ldl_tree_len(n / 2)
+    } else {
+        n + 4
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Signature<const N: usize> {
-    r: [u8; 40],
-    s: Vec<u8>, //s2
+// the FFT is determined by the key
+impl<const N: usize> From<&SecretKey<N>> for ExpandedSecretKey<N> {
+    fn from(sk: &SecretKey<N>) -> Self {
+        Self::new(sk)
+    }
 }
 
-impl<const N: usize> Signature<N> {
-    /// Serialize the signature to a vector of bytes.
-    pub fn to_bytes(&self) -> Vec<u8> {
-        // header
-        let felt_encoding = 2; // standard (compressed)
-        let n = self.s.len();
-        let l = n.checked_ilog2().unwrap() as u8;
-        let header: u8 = (felt_encoding << 5)
-                        | (1 << 4) // fixed bit
+impl<const N: usize> ConstantTimeEq for ExpandedSecretKey<N> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.sk.ct_eq(&other.sk)
+    }
+}
+
+impl<const N: usize> PartialEq for ExpandedSecretKey<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<const N: usize> Eq for ExpandedSecretKey<N> {}
+
+/// The FFT of the basis is overwritten with zeros when the key is dropped;
+/// the secret key inside zeroizes itself.
+impl<const N: usize> Drop for ExpandedSecretKey<N> {
+    fn drop(&mut self) {
+        for b_fft in self.b_fft.iter_mut() {
+            zeroize_complex(b_fft);
+        }
+    }
+}
+
+impl<const N: usize> ZeroizeOnDrop for ExpandedSecretKey<N> {}
+
+/// Expanded secret keys are redacted like secret keys.
+impl<const N: usize> fmt::Debug for ExpandedSecretKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpandedSecretKey")
+            .field("n", &N)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Working memory for [`sign_with_scratch`]. Allocate it once per signing
+/// thread and reuse it for every signature.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct SignScratch<const N: usize> {
+    c: Vec<Felt>,
+    t0: Vec<Complex64>,
+    t1: Vec<Complex64>,
+    z0: Vec<Complex64>,
+    z1: Vec<Complex64>,
+    s0: Vec<Complex64>,
+    s1: Vec<Complex64>,
+    s2: Vec<i16>,
+    work: Vec<Complex64>,
+    psi_inv_rev: Vec<Complex64>,
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> SignScratch<N> {
+    /// Allocate the working memory for signing with keys of this variant.
+    pub fn new() -> Self {
+        let zeros = vec![Complex64::new(0.0, 0.0); N];
+        Self {
+            c: vec![Felt::new(0); N],
+            t0: zeros.clone(),
+            t1: zeros.clone(),
+            z0: zeros.clone(),
+            z1: zeros.clone(),
+            s0: zeros.clone(),
+            s1: zeros,
+            s2: vec![0; N],
+            work: vec![Complex64::new(0.0, 0.0); 4 * N],
+            psi_inv_rev: COMPLEX_BITREVERSED_POWERS_1024[..N]
+                .iter()
+                .map(|c| c.conj())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> Default for SignScratch<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The working memory holds values derived from the last secret key used
+/// with it, and is overwritten with zeros when dropped.
+#[cfg(feature = "std")]
+impl<const N: usize> Drop for SignScratch<N> {
+    fn drop(&mut self) {
+        for buffer in [
+            &mut self.t0,
+            &mut self.t1,
+            &mut self.z0,
+            &mut self.z1,
+            &mut self.s0,
+            &mut self.s1,
+            &mut self.work,
+        ] {
+            zeroize_complex(buffer);
+        }
+        self.s2.zeroize();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> ZeroizeOnDrop for SignScratch<N> {}
+
+#[cfg(feature = "std")]
+impl<const N: usize> fmt::Debug for SignScratch<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignScratch")
+            .field("n", &N)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PublicKey<const N: usize> {
+    pub(crate) h: Polynomial<Felt>,
+    /// The canonical encoding, computed once on construction.
+    encoding: Vec<u8>,
+}
+
+impl<const N: usize> PublicKey<N> {
+    /// The length of the encoding produced by [`PublicKey::to_bytes`].
+    pub const BYTE_LEN: usize = 1 + 14 * N / 8;
+
+    /// Compute the public key that matches with this secret key.
+    pub fn from_secret_key(sk: &SecretKey<N>) -> Self {
+        let f = sk.b0[1].map(|&c| -Felt::new(c));
+        let f_ntt = f.fft();
+        let g = sk.b0[0].map(|&c| Felt::new(c));
+        let g_ntt = g.fft();
+        let h_ntt = g_ntt.hadamard_div(&f_ntt);
+        let h = h_ntt.ifft();
+        Self::new(h)
+    }
+
+    pub(crate) fn new(h: Polynomial<Felt>) -> Self {
+        let encoding = Self::encode(&h);
+        Self { h, encoding }
+    }
+
+    /// The fingerprint of the key, SHAKE256 of its encoding, which
+    /// identifies it in a fraction of the space.
+    pub fn fingerprint(&self) -> Fingerprint {
+        let mut fingerprint = [0u8; Fingerprint::LEN];
+        Shake256::default()
+            .chain(&self.encoding)
+            .finalize_xof()
+            .read(&mut fingerprint);
+        Fingerprint::new(fingerprint)
+    }
+
+    /// The coefficients of the public polynomial `h`, in `[0, q)`.
+    pub fn coefficients(&self) -> Vec<u16> {
+        self.h
+            .coefficients
+            .iter()
+            .map(|c| c.value() as u16)
+            .collect()
+    }
+
+    /// The coefficients of `h` in centered form, in `[-(q-1)/2, (q-1)/2]`.
+    pub fn centered_coefficients(&self) -> Vec<i16> {
+        self.h
+            .coefficients
+            .iter()
+            .map(|c| c.balanced_value())
+            .collect()
+    }
+
+    /// The public polynomial `h` in NTT domain, as used by verification,
+    /// with entries in `[0, q)`. Entry `i` is `h(ψ^(2·rev(i) + 1))`, where
+    /// `rev` reverses the order of the `log2(N)` bits of its argument and `ψ`
+    /// is a fixed primitive `2N`-th root of unity modulo q.
+    pub fn ntt_coefficients(&self) -> Vec<u16> {
+        self.h
+            .fft()
+            .coefficients
+            .iter()
+            .map(|c| c.value() as u16)
+            .collect()
+    }
+
+    /// Deserialize the given slice of bytes into a public key.
+    pub fn from_bytes(byte_array: &[u8]) -> Result<Self, FalconDeserializationError> {
+        let n: usize = match byte_array.len() {
+            897 => 512,
+            1793 => 1024,
+            _ => return Err(FalconDeserializationError::BadEncodingLength),
+        };
+
+        // match against variant generic type parameter
+        if n != N {
+            return Err(FalconDeserializationError::WrongVariant);
+        }
+
+        // parse header
+        let header = byte_array[0];
+
+        if header >> 4 != 0 {
+            return Err(FalconDeserializationError::InvalidHeaderFormat);
+        }
+
+        let l = n.ilog2();
+        if header != l as u8 {
+            return Err(FalconDeserializationError::InvalidLogN);
+        }
+
+        // parse h
+        let bit_buffer = BitVec::from_bytes(&byte_array[1..]);
+        let h = Polynomial::new(
+            bit_buffer
+                .iter()
+                .chunks(14)
+                .into_iter()
+                .map(|ch| {
+                    let mut int = 0;
+                    for b in ch {
+                        int = (int << 1) | (b as i16);
+                    }
+                    int
+                })
+                .map(Felt::new)
+                .collect_vec(),
+        );
+
+        Ok(PublicKey::new(h))
+    }
+
+    /// Serialize the public key as a list of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    /// Borrow the canonical encoding of the public key, without allocating.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.encoding
+    }
+
+    fn encode(h: &Polynomial<Felt>) -> Vec<u8> {
+        let header = h.coefficients.len().ilog2() as u8;
+        let mut bit_buffer = BitVec::from_bytes(&[header]);
+
+        for hi in h.coefficients.iter() {
+            for i in (0..14).rev() {
+                bit_buffer.push(hi.value() & (1 << i) != 0);
+            }
+        }
+
+        bit_buffer.to_bytes()
+    }
+}
+
+/// Public keys are ordered like their canonical encodings, i.e., as
+/// produced by [`PublicKey::to_bytes`].
+impl<const N: usize> Ord for PublicKey<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // the encoding packs coefficients with a fixed width, most
+        // significant bit first, so byte order is coefficient order
+        self.h
+            .coefficients
+            .iter()
+            .map(|c| c.value())
+            .cmp(other.h.coefficients.iter().map(|c| c.value()))
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for PublicKey<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Compares the canonical encodings in constant time.
+impl<const N: usize> ConstantTimeEq for PublicKey<N> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.as_bytes().ct_eq(other.as_bytes())
+    }
+}
+
+impl<const N: usize> fmt::Debug for PublicKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PublicKey").field("h", &self.h).finish()
+    }
+}
+
+impl<const N: usize> PartialOrd for PublicKey<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Formats the public key as the hex encoding of [`PublicKey::to_bytes`].
+impl<const N: usize> fmt::Display for PublicKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl<const N: usize> fmt::LowerHex for PublicKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.as_bytes()))
+    }
+}
+
+/// Encodings of a [`Signature`], selected with
+/// [`Signature::to_bytes_with_format`]. [`Signature::from_bytes`] accepts
+/// all of them.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SignatureFormat {
+    /// The fixed-length encoding of [`Signature::to_bytes`].
+    #[default]
+    Standard,
+    /// The variable-length compressed encoding of the reference
+    /// implementation, as produced by [`Signature::to_reference_bytes`].
+    Compressed,
+    /// The padded encoding of the specification and of FN-DSA: the
+    /// compressed encoding with the compressed vector padded with zeros, so
+    /// that every signature is [`Signature::BYTE_LEN`] bytes long.
+    Padded,
+    /// The constant-time ("CT") encoding of the reference implementation,
+    /// with every coefficient of `s2` uncompressed in 12 bits, so that every
+    /// signature is [`Signature::CT_BYTE_LEN`] bytes long and can be decoded
+    /// without Golomb-Rice decompression.
+    Ct,
+}
+
+/// Signatures are ordered like their canonical encodings, i.e., as produced
+/// by [`Signature::to_bytes`].
+///
+/// A signature remembers the hash of the first message it is verified
+/// against, so verifying it again, or expanding it, skips the SHAKE256
+/// expansion. The cache takes no part in comparisons or hashing, so
+/// signatures are sound map keys even though Clippy's `mutable_key_type`
+/// lint cannot tell.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Signature<const N: usize> {
+    pub(crate) r: [u8; 40],
+    pub(crate) s: Vec<u8>, //s2
+    /// The canonical encoding, computed once on construction.
+    encoding: Vec<u8>,
+    hash_cache: HashToPointCache,
+}
+
+impl<const N: usize> Signature<N> {
+    /// The length of the encoding produced by [`Signature::to_bytes`], which
+    /// is also the maximum length that [`Signature::from_bytes`] accepts.
+    pub const BYTE_LEN: usize = FalconVariant::from_n(N).parameters().sig_bytelen;
+
+    /// The length of the CT encoding; see [`SignatureFormat::Ct`].
+    pub const CT_BYTE_LEN: usize = ct_byte_len(N);
+
+    pub(crate) fn new(r: [u8; 40], s: Vec<u8>) -> Self {
+        let encoding = Self::encode(&r, &s);
+        Self {
+            r,
+            s,
+            encoding,
+            hash_cache: HashToPointCache::default(),
+        }
+    }
+
+    /// Serialize the signature to a vector of bytes. The encoding always has
+    /// the fixed length [`Signature::BYTE_LEN`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    /// Serialize the signature in the given format, converting between the
+    /// formats when decoded with [`Signature::from_bytes`].
+    ///
+    /// Returns `None` only for [`SignatureFormat::Ct`], if `s2` is malformed
+    /// or has a coefficient too large for 12 bits. Neither happens for
+    /// signatures that verify.
+    pub fn to_bytes_with_format(&self, format: SignatureFormat) -> Option<Vec<u8>> {
+        let header = N.ilog2() as u8;
+        match format {
+            SignatureFormat::Standard => Some(self.to_bytes()),
+            SignatureFormat::Compressed => Some(self.to_reference_bytes()),
+            SignatureFormat::Padded => {
+                Some([&[0x30 | header], self.r.as_slice(), &self.s].concat())
+            }
+            SignatureFormat::Ct => {
+                let s = encode_ct(&self.s2()?)?;
+                Some([&[0x50 | header], self.r.as_slice(), &s].concat())
+            }
+        }
+    }
+
+    /// Deserialize a signature, accepting only the given format. Unlike
+    /// [`Signature::from_bytes`], this lets a protocol that fixes the format
+    /// reject the others, e.g., signatures that are not of constant size.
+    pub fn from_bytes_with_format(
+        bytes: &[u8],
+        format: SignatureFormat,
+    ) -> Result<Self, FalconDeserializationError> {
+        // the compressed and padded encodings share the header 0011nnnn, and
+        // the standard and CT encodings share 0101nnnn
+        let (header, length) = match format {
+            SignatureFormat::Standard => (5, None),
+            SignatureFormat::Compressed => (3, None),
+            SignatureFormat::Padded => (3, Some(Self::BYTE_LEN)),
+            SignatureFormat::Ct => (5, Some(Self::CT_BYTE_LEN)),
+        };
+        if bytes.first().map(|byte| byte >> 4) != Some(header) {
+            return Err(FalconDeserializationError::InvalidHeaderFormat);
+        }
+        let is_ct = bytes.len() == Self::CT_BYTE_LEN;
+        if length.is_some_and(|length| bytes.len() != length)
+            || (format == SignatureFormat::Standard && is_ct)
+        {
+            return Err(FalconDeserializationError::BadEncodingLength);
+        }
+        Self::from_bytes(bytes)
+    }
+
+    /// The salt that was hashed together with the message.
+    pub fn nonce(&self) -> Nonce {
+        Nonce::new(self.r)
+    }
+
+    /// Borrow the canonical encoding of the signature, without allocating.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.encoding
+    }
+
+    /// The coefficients of the short polynomial `s2`, as signed integers
+    /// like they are encoded; or `None` if the encoding is malformed, in
+    /// which case the signature never verifies.
+    pub fn s2(&self) -> Option<Vec<i16>> {
+        decompress(&self.s, N)
+    }
+
+    /// The coefficients of `s2` reduced modulo q, in `[0, q)`; or `None` if
+    /// the encoding is malformed.
+    pub fn s2_reduced(&self) -> Option<Vec<u16>> {
+        Some(
+            self.s2()?
+                .into_iter()
+                .map(|c| Felt::new(c).value() as u16)
+                .collect(),
+        )
+    }
+
+    /// Serialize the signature in the variable-length encoding of the
+    /// reference implementation, which is what PQClean and its bindings
+    /// accept, i.e., without the zero padding of [`Signature::to_bytes`].
+    pub fn to_reference_bytes(&self) -> Vec<u8> {
+        let header = 0x30 | N.ilog2() as u8;
+        [&[header], self.r.as_slice(), self.compressed_s()].concat()
+    }
+
+    /// The compressed vector without zero padding. Its last meaningful bit
+    /// is always a one, so stripping all trailing zero bytes loses nothing.
+    pub(crate) fn compressed_s(&self) -> &[u8] {
+        let length = self
+            .s
+            .iter()
+            .rposition(|&byte| byte != 0)
+            .map_or(0, |i| i + 1);
+        &self.s[..length]
+    }
+
+    fn encode(r: &[u8; 40], s: &[u8]) -> Vec<u8> {
+        // header
+        let felt_encoding = 2; // standard (compressed)
+        let n = s.len();
+        let l = n.checked_ilog2().unwrap() as u8;
+        let header: u8 = (felt_encoding << 5)
+                        | (1 << 4) // fixed bit
                         | l;
 
-        [vec![header], self.r.to_vec(), self.s.clone()].concat()
+        [&[header], r.as_slice(), s].concat()
     }
 
     /// Deserialize a signature from a slice of bytes.
+    ///
+    /// Besides the fixed-length encoding produced by [`Signature::to_bytes`],
+    /// this also accepts the variable-length compressed encoding produced by
+    /// the reference implementation, and every other [`SignatureFormat`].
     pub fn from_bytes(byte_vector: &[u8]) -> Result<Self, FalconDeserializationError> {
+        // reference implementation encoding; header is 0011nnnn
+        if byte_vector.first().is_some_and(|header| header >> 4 == 3) {
+            return Self::from_reference_bytes(byte_vector);
+        }
+
+        // CT encoding; header is 0101nnnn like ours, but the length differs
+        if byte_vector.first().is_some_and(|header| header >> 4 == 5)
+            && (byte_vector.len() == ct_byte_len(512) || byte_vector.len() == ct_byte_len(1024))
+        {
+            return Self::from_ct_bytes(byte_vector);
+        }
+
         // check signature length; infer variant
         let n = if byte_vector.len() == FalconVariant::Falcon512.parameters().sig_bytelen {
             512
@@ -431,445 +1654,2997 @@ impl<const N: usize> Signature<N> {
         }
 
         // tests pass; assemble object
-        Ok(Signature::<N> {
-            r: salt,
-            s: signature_vector.to_vec(),
-        })
+        Ok(Signature::new(salt, signature_vector.to_vec()))
     }
-}
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ExpandedSignature<const N: usize> {
-    r: [u8; 40],
-    s1: Polynomial<Felt>,
-    s2: Polynomial<Felt>,
-}
+    /// Deserialize a signature encoded by the reference implementation,
+    /// where the compressed vector is not padded to a fixed length.
+    fn from_reference_bytes(byte_vector: &[u8]) -> Result<Self, FalconDeserializationError> {
+        let sig_bytelen = FalconVariant::from_n(N).parameters().sig_bytelen;
 
-impl<const N: usize> ExpandedSignature<N> {
-    /// from Signature to ExpandedSignature
-    /// s2 is decompressed
-    /// s1 is recovered
-    pub fn from_signature(m: &[u8], sig: &Signature<N>, pk: &PublicKey<N>) -> Self {
-        let n = N;
+        // check log n
+        let header = byte_vector[0];
+        let logn = (header & 15) as usize;
+        let n = match logn {
+            9 => 512,
+            10 => 1024,
+            _ => return Err(FalconDeserializationError::InvalidLogN),
+        };
+
+        // match n against const type parameter
+        if n != N {
+            return Err(FalconDeserializationError::WrongVariant);
+        }
 
-        let r_cat_m = [sig.r.to_vec(), m.to_vec()].concat();
-        let c = hash_to_point(&r_cat_m, n);
+        // header, salt, and at least one byte of compressed signature
+        if byte_vector.len() <= 41 || byte_vector.len() > sig_bytelen {
+            return Err(FalconDeserializationError::BadEncodingLength);
+        }
 
-        let s2 = Polynomial::new(
-            decompress(&sig.s, n)
-                .unwrap()
-                .iter()
-                .map(|a| Felt::new(*a))
-                .collect_vec(),
-        ); //todo: improve error handling
+        // pad the compressed vector with zeros to the fixed length
+        let salt: [u8; 40] = byte_vector[1..=40].try_into().unwrap();
+        let mut s = byte_vector[41..].to_vec();
+        s.resize(sig_bytelen - 41, 0);
 
-        let s2_ntt = s2.fft();
-        let h_ntt = pk.h.fft();
-        let c_ntt = c.fft();
+        Ok(Signature::new(salt, s))
+    }
 
-        // s1 = c - s2 * pk.h;
-        let s1_ntt = c_ntt - s2_ntt.hadamard_mul(&h_ntt);
-        let s1 = s1_ntt.ifft();
+    /// Deserialize a signature in the CT encoding of the reference
+    /// implementation, recompressing `s2` for the internal representation.
+    fn from_ct_bytes(byte_vector: &[u8]) -> Result<Self, FalconDeserializationError> {
+        // check log n
+        let n = match byte_vector[0] & 15 {
+            9 => 512,
+            10 => 1024,
+            _ => return Err(FalconDeserializationError::InvalidLogN),
+        };
+        if n != N {
+            return Err(FalconDeserializationError::WrongVariant);
+        }
+        if byte_vector.len() != Self::CT_BYTE_LEN {
+            return Err(FalconDeserializationError::BadEncodingLength);
+        }
 
-        ExpandedSignature { r: sig.r, s1, s2 }
+        let salt: [u8; 40] = byte_vector[1..=40].try_into().unwrap();
+        let s2 = decode_ct(&byte_vector[41..], N)
+            .ok_or(FalconDeserializationError::BadFieldElementEncoding)?;
+        // signatures whose s2 does not compress to the fixed length are
+        // rejected, as they would be in the other encodings
+        let s = compress(&s2, Self::BYTE_LEN - 41)
+            .ok_or(FalconDeserializationError::BadEncodingLength)?;
+
+        Ok(Signature::new(salt, s))
     }
 }
 
-// Generate a key pair pseudorandomly by expanding a seed.
-pub fn keygen<const N: usize>(seed: [u8; 32]) -> (SecretKey<N>, PublicKey<N>) {
-    let sk = SecretKey::generate_from_seed(seed);
-    let pk = PublicKey::from_secret_key(&sk);
-    (sk, pk)
+/// The length of the CT encoding of a signature for ring degree n: header,
+/// salt, and `CT_BITS` per coefficient.
+const fn ct_byte_len(n: usize) -> usize {
+    1 + 40 + (n * CT_BITS).div_ceil(8)
 }
 
-/// Sign a message with the secret key.
-///
-/// Algorithm 10 of the specification [1, p.39].
+impl<const N: usize> AsRef<[u8]> for Signature<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Compares the canonical encodings in constant time.
+impl<const N: usize> ConstantTimeEq for Signature<N> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.as_bytes().ct_eq(other.as_bytes())
+    }
+}
+
+impl<const N: usize> fmt::Debug for Signature<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signature")
+            .field("r", &self.r)
+            .field("s", &self.s)
+            .finish()
+    }
+}
+
+/// Formats the signature as the hex encoding of [`Signature::to_bytes`].
+impl<const N: usize> fmt::Display for Signature<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl<const N: usize> fmt::LowerHex for Signature<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.as_bytes()))
+    }
+}
+
+/// A signature together with both halves of the short vector it encodes,
+/// which fast verification works on.
 ///
-/// [1]: https://falcon-sign.info/falcon.pdf
-pub fn sign<const N: usize>(m: &[u8], sk: &SecretKey<N>) -> Signature<N> {
-    let mut rng = thread_rng();
-    let mut r = [0u8; 40];
-    rng.fill_bytes(&mut r);
+/// The encoding is a header byte `0x40 | log2(N)`, followed by the nonce,
+/// then the coefficients of `s1` and then those of `s2`, each reduced
+/// modulo q as a big-endian `u16`. Expansions can thus be computed once,
+/// e.g. by a prover, and shipped to verifiers, which run [`fverify`] on the
+/// decoded signature without redoing [`ExpandedSignature::from_signature`].
+/// Decoding does not check that the two halves belong together; the
+/// verification functions do.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExpandedSignature<const N: usize> {
+    r: [u8; 40],
+    s1: Polynomial<Felt>,
+    s2: Polynomial<Felt>,
+    hash_cache: HashToPointCache,
+}
 
-    let params = FalconVariant::from_n(N).parameters();
-    let bound = params.sig_bound;
-    let n = params.n;
+impl<const N: usize> ExpandedSignature<N> {
+    /// The salt that was hashed together with the message.
+    pub fn nonce(&self) -> Nonce {
+        Nonce::new(self.r)
+    }
 
-    let r_cat_m = [r.to_vec(), m.to_vec()].concat();
+    /// The coefficients of `s1 = c - s2·h`, the short polynomial that
+    /// verification recovers from the hashed message `c` and the public key
+    /// `h`, in centered form, in `[-(q-1)/2, (q-1)/2]`.
+    pub fn s1(&self) -> Vec<i16> {
+        self.s1
+            .coefficients
+            .iter()
+            .map(|c| c.balanced_value())
+            .collect()
+    }
 
-    let c = hash_to_point(&r_cat_m, n);
-    let one_over_q = 1.0 / (Q as f64);
-    let c_over_q_fft = c
-        .map(|cc| Complex::new(one_over_q * cc.value() as f64, 0.0))
-        .fft();
+    /// The coefficients of `s1` reduced modulo q, in `[0, q)`.
+    pub fn s1_reduced(&self) -> Vec<u16> {
+        self.s1
+            .coefficients
+            .iter()
+            .map(|c| c.value() as u16)
+            .collect()
+    }
 
-    // B = [[FFT(g), -FFT(f)], [FFT(G), -FFT(F)]]
-    let capital_f_fft = sk.b0[3].map(|&i| Complex64::new(-i as f64, 0.0)).fft();
-    let f_fft = sk.b0[1].map(|&i| Complex64::new(-i as f64, 0.0)).fft();
-    let capital_g_fft = sk.b0[2].map(|&i| Complex64::new(i as f64, 0.0)).fft();
-    let g_fft = sk.b0[0].map(|&i| Complex64::new(i as f64, 0.0)).fft();
-    let t0 = c_over_q_fft.hadamard_mul(&capital_f_fft);
-    let t1 = -c_over_q_fft.hadamard_mul(&f_fft);
+    /// The coefficients of `s2` in centered form; the same as
+    /// [`Signature::s2`].
+    pub fn s2(&self) -> Vec<i16> {
+        self.s2
+            .coefficients
+            .iter()
+            .map(|c| c.balanced_value())
+            .collect()
+    }
 
-    let s = loop {
-        let mut seed = [0u8; 32];
-        rng.fill_bytes(&mut seed);
-        let bold_s = loop {
-            let z = ffsampling(&(t0.clone(), t1.clone()), &sk.tree, &params, &mut rng);
-            let t0_min_z0 = t0.clone() - z.0;
-            let t1_min_z1 = t1.clone() - z.1;
+    /// The coefficients of `s2` reduced modulo q, in `[0, q)`.
+    pub fn s2_reduced(&self) -> Vec<u16> {
+        self.s2
+            .coefficients
+            .iter()
+            .map(|c| c.value() as u16)
+            .collect()
+    }
 
-            // s = (t-z) * B
-            let s0 = t0_min_z0.hadamard_mul(&g_fft) + t1_min_z1.hadamard_mul(&capital_g_fft);
-            let s1 = t0_min_z0.hadamard_mul(&f_fft) + t1_min_z1.hadamard_mul(&capital_f_fft);
+    /// from Signature to ExpandedSignature
+    /// s2 is decompressed
+    /// s1 is recovered
+    ///
+    /// # Panics
+    ///
+    /// Panics if the encoding of `s2` is malformed; see [`Signature::s2`].
+    pub fn from_signature(m: impl AsRef<[u8]>, sig: &Signature<N>, pk: &PublicKey<N>) -> Self {
+        Self::from_signature_with_h_ntt(m.as_ref(), sig, &pk.h.fft())
+            .expect("malformed signature encoding")
+    }
 
-            // compute the norm of (s0||s1) and note that they are in FFT representation
-            let length_squared: f64 = (s0
-                .coefficients
-                .iter()
-                .map(|a| (a * a.conj()).re)
-                .sum::<f64>()
-                + s1.coefficients
-                    .iter()
-                    .map(|a| (a * a.conj()).re)
-                    .sum::<f64>())
-                / (n as f64);
+    /// Expand many signatures by the same signer, bringing the public key
+    /// into NTT domain only once. Equivalent to calling
+    /// [`ExpandedSignature::from_signature`] on each pair, except that a
+    /// malformed signature fails the batch with
+    /// [`FalconDeserializationError::BadFieldElementEncoding`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `msgs` and `sigs` differ in length.
+    pub fn batch_from_signatures<M: AsRef<[u8]>>(
+        msgs: &[M],
+        sigs: &[Signature<N>],
+        pk: &PublicKey<N>,
+    ) -> Result<Vec<Self>, FalconDeserializationError> {
+        assert_eq!(msgs.len(), sigs.len(), "one message per signature");
+        let h_ntt = pk.h.fft();
+        msgs.iter()
+            .zip(sigs)
+            .map(|(m, sig)| Self::from_signature_with_h_ntt(m.as_ref(), sig, &h_ntt))
+            .collect()
+    }
 
-            if length_squared > (bound as f64) {
-                continue;
-            }
+    /// Like [`ExpandedSignature::batch_from_signatures`], but on the rayon
+    /// thread pool.
+    #[cfg(feature = "rayon")]
+    pub fn par_batch_from_signatures<M: AsRef<[u8]> + Sync>(
+        msgs: &[M],
+        sigs: &[Signature<N>],
+        pk: &PublicKey<N>,
+    ) -> Result<Vec<Self>, FalconDeserializationError> {
+        use rayon::prelude::*;
+
+        assert_eq!(msgs.len(), sigs.len(), "one message per signature");
+        let h_ntt = pk.h.fft();
+        msgs.par_iter()
+            .zip(sigs)
+            .map(|(m, sig)| Self::from_signature_with_h_ntt(m.as_ref(), sig, &h_ntt))
+            .collect()
+    }
 
-            break [s0, s1];
-        };
-        let s2 = bold_s[1].ifft();
-        let maybe_s = compress(
-            &s2.coefficients
+    /// Like [`ExpandedSignature::from_signature`], but with the public key
+    /// given in NTT domain, and failing on a malformed encoding of `s2`.
+    fn from_signature_with_h_ntt(
+        m: &[u8],
+        sig: &Signature<N>,
+        h_ntt: &Polynomial<Felt>,
+    ) -> Result<Self, FalconDeserializationError> {
+        let n = N;
+
+        let s2 = Polynomial::new(
+            decompress(&sig.s, n)
+                .ok_or(FalconDeserializationError::BadFieldElementEncoding)?
                 .iter()
-                .map(|a| a.re.round() as i16)
+                .map(|a| Felt::new(*a))
                 .collect_vec(),
-            params.sig_bytelen - 41,
         );
 
-        match maybe_s {
-            Some(s) => {
-                break s;
-            }
-            None => {
-                continue;
-            }
+        let c = sig.hash_cache.hash_message(&sig.r, &[m], n);
+
+        let s2_ntt = s2.fft();
+        let c_ntt = c.fft();
+
+        // s1 = c - s2 * pk.h;
+        let s1_ntt = c_ntt - s2_ntt.hadamard_mul(h_ntt);
+        let s1 = s1_ntt.ifft();
+
+        let hash_cache = HashToPointCache::default();
+        hash_cache.fill(m, &c);
+        Ok(ExpandedSignature {
+            r: sig.r,
+            s1,
+            s2,
+            hash_cache,
+        })
+    }
+
+    /// The length of the encoding produced by [`ExpandedSignature::to_bytes`].
+    pub const BYTE_LEN: usize = 1 + 40 + 4 * N;
+
+    /// Serialize the expanded signature to a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = 0x40 | N.ilog2() as u8;
+        core::iter::once(header)
+            .chain(self.r)
+            .chain(
+                self.s1
+                    .coefficients
+                    .iter()
+                    .chain(self.s2.coefficients.iter())
+                    .flat_map(|c| (c.value() as u16).to_be_bytes()),
+            )
+            .collect()
+    }
+
+    /// Deserialize an expanded signature from a slice of bytes.
+    ///
+    /// That `s1` is consistent with `s2`, which depends on the message and
+    /// the public key, is only established by verification.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        let n = match bytes.len() {
+            2089 => 512,
+            4137 => 1024,
+            _ => return Err(FalconDeserializationError::BadEncodingLength),
         };
-    };
+        if n != N {
+            return Err(FalconDeserializationError::WrongVariant);
+        }
+        if bytes[0] >> 4 != 4 {
+            return Err(FalconDeserializationError::InvalidHeaderFormat);
+        }
+        if (bytes[0] & 15) as u32 != N.ilog2() {
+            return Err(FalconDeserializationError::InvalidLogN);
+        }
 
-    Signature { r, s }
+        let (r, coefficients) = bytes[1..].split_at(40);
+        let mut coefficients = coefficients.chunks_exact(2).map(|chunk| {
+            let value = u16::from_be_bytes([chunk[0], chunk[1]]);
+            if u32::from(value) < Q {
+                Ok(Felt::new(value as i16))
+            } else {
+                Err(FalconDeserializationError::BadFieldElementEncoding)
+            }
+        });
+        let s1 = coefficients
+            .by_ref()
+            .take(N)
+            .collect::<Result<Vec<_>, _>>()?;
+        let s2 = coefficients.collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            r: r.try_into().unwrap(),
+            s1: Polynomial::new(s1),
+            s2: Polynomial::new(s2),
+            hash_cache: HashToPointCache::default(),
+        })
+    }
 }
 
-/// Verify a signature. Algorithm 16 in the spec [1, p.45].
+/// A public key together with its NTT, which verification needs.
 ///
-/// [1]: https://falcon-sign.info/falcon.pdf
-pub fn verify<const N: usize>(m: &[u8], sig: &Signature<N>, pk: &PublicKey<N>) -> bool {
-    let n = N;
-    let params = FalconVariant::from_n(N).parameters();
-    let r_cat_m = [sig.r.to_vec(), m.to_vec()].concat();
-    let c = hash_to_point(&r_cat_m, n);
+/// Computing the NTT is a sizable part of the cost of a single verification.
+/// An expanded public key can be computed once, serialized, and shipped to
+/// any number of verifiers, which then skip both the decoding and the NTT.
+///
+/// The encoding is a header byte `0x70 | log2(N)`, followed by the
+/// coefficients of `h` and then those of its NTT, each as a big-endian `u16`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandedPublicKey<const N: usize> {
+    pk: PublicKey<N>,
+    h_ntt: Polynomial<Felt>,
+}
 
-    let s2 = match decompress(&sig.s, n) {
-        Some(success) => success,
-        None => {
-            println!("Failed to decompress signature");
-            return false;
+impl<const N: usize> ExpandedPublicKey<N> {
+    /// The length of the encoding produced by [`ExpandedPublicKey::to_bytes`].
+    pub const BYTE_LEN: usize = 1 + 4 * N;
+
+    /// Expand a public key.
+    pub fn new(pk: &PublicKey<N>) -> Self {
+        Self {
+            pk: pk.clone(),
+            h_ntt: pk.h.fft(),
         }
-    };
-    let s2_ntt = Polynomial::new(s2.iter().map(|a| Felt::new(*a)).collect_vec()).fft();
-    let h_ntt = pk.h.fft();
-    let c_ntt = c.fft();
+    }
 
-    // s1 = c - s2 * pk.h;
-    let s1_ntt = c_ntt - s2_ntt.hadamard_mul(&h_ntt);
-    let s1 = s1_ntt.ifft();
+    /// The public key this was expanded from.
+    pub fn public_key(&self) -> &PublicKey<N> {
+        &self.pk
+    }
 
-    let length_squared = s1
-        .coefficients
-        .iter()
-        .map(|i| i.balanced_value() as i64)
-        .map(|i| i * i)
-        .sum::<i64>()
-        + s2.iter().map(|&i| i as i64).map(|i| i * i).sum::<i64>();
-    length_squared < params.sig_bound
-}
+    pub(crate) fn h_ntt(&self) -> &Polynomial<Felt> {
+        &self.h_ntt
+    }
 
-/// Verify an expanded signature
-pub fn verify_expanded<const N: usize>(
-    m: &[u8],
-    sig: &ExpandedSignature<N>,
-    pk: &PublicKey<N>,
-) -> bool {
-    let n = N;
-    let params = FalconVariant::from_n(N).parameters();
+    /// Serialize the expanded public key to a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = 0x70 | N.ilog2() as u8;
+        core::iter::once(header)
+            .chain(
+                self.pk
+                    .h
+                    .coefficients
+                    .iter()
+                    .chain(self.h_ntt.coefficients.iter())
+                    .flat_map(|c| (c.value() as u16).to_be_bytes()),
+            )
+            .collect()
+    }
 
-    // norm check
-    let length_squared = sig
-        .s1
-        .coefficients
-        .iter()
-        .map(|i| i.balanced_value() as i64)
-        .map(|i| i * i)
-        .sum::<i64>()
-        + sig
-            .s2
-            .coefficients
-            .iter()
-            .map(|&i| i.balanced_value() as i64)
-            .map(|i| i * i)
-            .sum::<i64>();
+    /// Deserialize an expanded public key from a slice of bytes.
+    ///
+    /// The NTT is not recomputed to check it against the public key, since
+    /// that would defeat the purpose; the encoding should come from a trusted
+    /// source, or be checked with [`ExpandedPublicKey::is_consistent`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        let n = match bytes.len() {
+            2049 => 512,
+            4097 => 1024,
+            _ => return Err(FalconDeserializationError::BadEncodingLength),
+        };
+        if n != N {
+            return Err(FalconDeserializationError::WrongVariant);
+        }
+        if bytes[0] >> 4 != 7 {
+            return Err(FalconDeserializationError::InvalidHeaderFormat);
+        }
+        if (bytes[0] & 15) as u32 != N.ilog2() {
+            return Err(FalconDeserializationError::InvalidLogN);
+        }
 
-    if length_squared >= params.sig_bound {
-        return false;
+        let mut coefficients = bytes[1..].chunks_exact(2).map(|chunk| {
+            let value = u16::from_be_bytes([chunk[0], chunk[1]]);
+            if u32::from(value) < Q {
+                Ok(Felt::new(value as i16))
+            } else {
+                Err(FalconDeserializationError::BadFieldElementEncoding)
+            }
+        });
+        let h = coefficients
+            .by_ref()
+            .take(N)
+            .collect::<Result<Vec<_>, _>>()?;
+        let h_ntt = coefficients.collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            pk: PublicKey::new(Polynomial::new(h)),
+            h_ntt: Polynomial::new(h_ntt),
+        })
     }
 
-    let r_cat_m = [sig.r.to_vec(), m.to_vec()].concat();
-    let c = hash_to_point(&r_cat_m, n);
-    // s1 + s2 * pk.h = c
-    // do the full check
-    let s2_ntt = Polynomial::new(sig.s2.coefficients.clone()).fft();
-    let h_ntt = pk.h.fft();
+    /// Test whether the NTT matches the public key.
+    pub fn is_consistent(&self) -> bool {
+        self.pk.h.fft() == self.h_ntt
+    }
+}
 
-    // s1 = c - s2 * pk.h;
-    let should_be_s1 = c - s2_ntt.hadamard_mul(&h_ntt).ifft();
+impl<const N: usize> From<&PublicKey<N>> for ExpandedPublicKey<N> {
+    fn from(pk: &PublicKey<N>) -> Self {
+        Self::new(pk)
+    }
+}
 
-    sig.s1 == should_be_s1
+impl<const N: usize> From<PublicKey<N>> for ExpandedPublicKey<N> {
+    fn from(pk: PublicKey<N>) -> Self {
+        let h_ntt = pk.h.fft();
+        Self { pk, h_ntt }
+    }
 }
 
-/// Fast verify a signature
-pub fn fverify<const N: usize>(
-    m: &[u8],
-    sig: &ExpandedSignature<N>,
-    pk: &PublicKey<N>,
-    indices: &Vec<usize>,
-) -> bool {
-    let n = N;
-    let params = FalconVariant::from_n(N).parameters();
+macro_rules! impl_byte_conversions {
+    ($type:ident) => {
+        impl<const N: usize> TryFrom<&[u8]> for $type<N> {
+            type Error = FalconDeserializationError;
 
-    // norm check
-    let length_squared = sig
-        .s1
-        .coefficients
-        .iter()
-        .map(|i| i.balanced_value() as i64)
-        .map(|i| i * i)
-        .sum::<i64>()
-        + sig
-            .s2
-            .coefficients
-            .iter()
-            .map(|&i| i.balanced_value() as i64)
-            .map(|i| i * i)
-            .sum::<i64>();
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                Self::from_bytes(bytes)
+            }
+        }
 
-    if length_squared >= params.sig_bound {
-        return false;
-    }
+        impl<const N: usize> From<&$type<N>> for Vec<u8> {
+            fn from(value: &$type<N>) -> Self {
+                value.to_bytes()
+            }
+        }
+    };
+    ($type:ident, $n:literal) => {
+        impl TryFrom<[u8; $type::<$n>::BYTE_LEN]> for $type<$n> {
+            type Error = FalconDeserializationError;
 
-    let r_cat_m = [sig.r.to_vec(), m.to_vec()].concat();
-    let c = hash_to_point(&r_cat_m, n);
+            fn try_from(bytes: [u8; $type::<$n>::BYTE_LEN]) -> Result<Self, Self::Error> {
+                Self::from_bytes(&bytes)
+            }
+        }
 
-    // s1 + s2 * pk.h = c
-    // only check for selected indices
-    for &i in indices {
-        let should_be_ci = sig.s1.coefficients[i] + sig.s2.mul_coeff(&pk.h, i, n);
-        if should_be_ci != c.coefficients[i] {
-            return false;
+        impl TryFrom<&[u8; $type::<$n>::BYTE_LEN]> for $type<$n> {
+            type Error = FalconDeserializationError;
+
+            fn try_from(bytes: &[u8; $type::<$n>::BYTE_LEN]) -> Result<Self, Self::Error> {
+                Self::from_bytes(bytes)
+            }
         }
-    }
 
-    true
+        impl From<&$type<$n>> for [u8; $type::<$n>::BYTE_LEN] {
+            fn from(value: &$type<$n>) -> Self {
+                value
+                    .to_bytes()
+                    .try_into()
+                    .expect("canonical encodings have a fixed length")
+            }
+        }
+    };
 }
 
-/// Fast verify a signature
-/// Then if it passes, do a full verify
-pub fn fverify_fullverify<const N: usize>(
-    m: &[u8],
-    sig: &ExpandedSignature<N>,
-    pk: &PublicKey<N>,
-    indices: &Vec<usize>,
-) -> bool {
-    let n = N;
-    let params = FalconVariant::from_n(N).parameters();
+impl_byte_conversions!(SecretKey);
+impl_byte_conversions!(PublicKey);
+impl_byte_conversions!(Signature);
+impl_byte_conversions!(ExpandedPublicKey);
+impl_byte_conversions!(SecretKey, 512);
+impl_byte_conversions!(SecretKey, 1024);
+impl_byte_conversions!(PublicKey, 512);
+impl_byte_conversions!(PublicKey, 1024);
+impl_byte_conversions!(Signature, 512);
+impl_byte_conversions!(Signature, 1024);
+impl_byte_conversions!(ExpandedPublicKey, 512);
+impl_byte_conversions!(ExpandedPublicKey, 1024);
+impl_byte_conversions!(ExpandedSignature);
+impl_byte_conversions!(ExpandedSignature, 512);
+impl_byte_conversions!(ExpandedSignature, 1024);
+
+/// Keys and signatures serialize as their canonical encodings: as hex
+/// strings in human-readable formats and as byte strings otherwise.
+#[cfg(feature = "serde")]
+macro_rules! impl_serde {
+    ($type:ident, $name:literal) => {
+        impl<const N: usize> serde::Serialize for $type<N> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let bytes = self.to_bytes();
+                if serializer.is_human_readable() {
+                    serializer.serialize_str(&hex::encode(bytes))
+                } else {
+                    serializer.serialize_bytes(&bytes)
+                }
+            }
+        }
 
-    let length_squared = sig
-        .s1
-        .coefficients
-        .iter()
-        .map(|i| i.balanced_value() as i64)
+        impl<'de, const N: usize> serde::Deserialize<'de> for $type<N> {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let visitor = EncodingVisitor {
+                    name: $name,
+                    from_bytes: Self::from_bytes,
+                };
+                if deserializer.is_human_readable() {
+                    deserializer.deserialize_str(visitor)
+                } else {
+                    deserializer.deserialize_bytes(visitor)
+                }
+            }
+        }
+    };
+}
+
+/// Deserializes a canonical encoding, given as bytes, a sequence of bytes,
+/// or a hex string.
+#[cfg(feature = "serde")]
+struct EncodingVisitor<T> {
+    name: &'static str,
+    from_bytes: fn(&[u8]) -> Result<T, FalconDeserializationError>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::de::Visitor<'de> for EncodingVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the encoding of a {} or its hex encoding", self.name)
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<T, E> {
+        let bytes = hex::decode(v).map_err(E::custom)?;
+        self.visit_bytes(&bytes)
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<T, E> {
+        (self.from_bytes)(v).map_err(|error| E::custom(format!("{error:?}")))
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde!(SecretKey, "secret key");
+#[cfg(feature = "serde")]
+impl_serde!(PublicKey, "public key");
+#[cfg(feature = "serde")]
+impl_serde!(Signature, "signature");
+#[cfg(feature = "serde")]
+impl_serde!(ExpandedPublicKey, "expanded public key");
+#[cfg(feature = "serde")]
+impl_serde!(ExpandedSignature, "expanded signature");
+
+/// Generate a key pair pseudorandomly by expanding a seed.
+///
+/// The seed is not checked; [`try_keygen`] refuses weak seeds, see
+/// [`is_weak_seed`].
+pub fn keygen<const N: usize>(seed: [u8; 32]) -> (SecretKey<N>, PublicKey<N>) {
+    let sk = SecretKey::generate_from_seed(seed);
+    let pk = PublicKey::from_secret_key(&sk);
+    (sk, pk)
+}
+
+/// Generate a key pair from a seed drawn from the given random number
+/// generator, e.g., [`OsRng`](rand::rngs::OsRng) or a DRBG. Weak seeds are
+/// drawn again.
+pub fn keygen_with_rng<const N: usize, R: CryptoRng + RngCore>(
+    rng: &mut R,
+) -> (SecretKey<N>, PublicKey<N>) {
+    let mut seed = Zeroizing::new([0u8; 32]);
+    loop {
+        rng.fill_bytes(seed.as_mut());
+        if let Ok(keys) = try_keygen(*seed) {
+            return keys;
+        }
+    }
+}
+
+/// Generate a key pair pseudorandomly by expanding a seed, or fail if the
+/// seed is weak.
+pub fn try_keygen<const N: usize>(
+    seed: [u8; 32],
+) -> Result<(SecretKey<N>, PublicKey<N>), WeakSeedError> {
+    try_keygen_versioned(KeygenVersion::CURRENT, seed)
+}
+
+/// Generate a key pair pseudorandomly by expanding a seed with the given
+/// version of the key generation algorithm, or fail if the seed is weak.
+pub fn try_keygen_versioned<const N: usize>(
+    version: KeygenVersion,
+    seed: [u8; 32],
+) -> Result<(SecretKey<N>, PublicKey<N>), WeakSeedError> {
+    try_keygen_versioned_with_stats(version, seed, None)
+}
+
+/// Generate a key pair like [`try_keygen`], and add the work done by key
+/// generation to `stats`; see [`KeygenStats`].
+pub fn try_keygen_with_stats<const N: usize>(
+    seed: [u8; 32],
+    stats: &mut KeygenStats,
+) -> Result<(SecretKey<N>, PublicKey<N>), WeakSeedError> {
+    try_keygen_versioned_with_stats(KeygenVersion::CURRENT, seed, Some(stats))
+}
+
+/// Generate a key pair from a 48-byte seed exactly like
+/// `crypto_sign_keypair()` of the reference implementation does from the
+/// 48 bytes it draws from `randombytes()`: the seed is expanded with
+/// SHAKE256, which drives the sampling of f and g. The keys match those of
+/// the NIST known-answer tests bit for bit; the tests check this against the
+/// first record of the KAT files, bundled under `fixtures/`.
+///
+/// Unlike [`keygen`], this does not refuse weak seeds, as the reference
+/// implementation does not either.
+pub fn keygen_from_nist_seed<const N: usize>(seed: [u8; 48]) -> (SecretKey<N>, PublicKey<N>) {
+    let mut shake = Shake256::default();
+    shake.update(&seed);
+    let (f, g, capital_f, capital_g) = ntru_gen_reference(N, &mut shake.finalize_xof());
+    let sk = SecretKey::from_b0([g, -f, capital_g, -capital_f]);
+    let pk = PublicKey::from_secret_key(&sk);
+    (sk, pk)
+}
+
+fn try_keygen_versioned_with_stats<const N: usize>(
+    version: KeygenVersion,
+    seed: [u8; 32],
+    stats: Option<&mut KeygenStats>,
+) -> Result<(SecretKey<N>, PublicKey<N>), WeakSeedError> {
+    let sk = SecretKey::try_generate_with_stats(version, seed, stats)?;
+    let pk = PublicKey::from_secret_key(&sk);
+    Ok((sk, pk))
+}
+
+/// Test whether the public key belongs to the secret key, and whether the
+/// secret key is internally consistent. Use this to catch mismatched or
+/// corrupted key files before producing any signatures with them.
+pub fn consistent<const N: usize>(sk: &SecretKey<N>, pk: &PublicKey<N>) -> bool {
+    sk.validate(pk).is_ok()
+}
+
+/// Sign a message with the secret key.
+///
+/// Algorithm 10 of the specification [1, p.39].
+///
+/// [1]: https://falcon-sign.info/falcon.pdf
+#[cfg(feature = "std")]
+pub fn sign<const N: usize>(m: impl AsRef<[u8]>, sk: &SecretKey<N>) -> Signature<N> {
+    sign_with_rng(m.as_ref(), sk, &mut crate::entropy::rng())
+}
+
+/// Sign a message with the secret key, deriving the salt and all sampler
+/// randomness pseudorandomly from the given seed. The same (message, key,
+/// seed) triple always yields the same signature.
+///
+/// Never reuse a seed for different messages. The seed is not checked;
+/// [`try_sign_with_seed`] refuses weak seeds, see [`is_weak_seed`].
+pub fn sign_with_seed<const N: usize>(m: &[u8], sk: &SecretKey<N>, seed: [u8; 32]) -> Signature<N> {
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+    sign_with_rng(m, sk, &mut rng)
+}
+
+/// Like [`sign_with_seed`], but fail if the seed is weak.
+pub fn try_sign_with_seed<const N: usize>(
+    m: &[u8],
+    sk: &SecretKey<N>,
+    seed: [u8; 32],
+) -> Result<Signature<N>, WeakSeedError> {
+    if is_weak_seed(&seed) {
+        return Err(WeakSeedError);
+    }
+    Ok(sign_with_seed(m, sk, seed))
+}
+
+/// Sign a message with the secret key, as configured by the given options.
+#[cfg(feature = "std")]
+pub fn sign_with<const N: usize>(
+    m: &[u8],
+    sk: &SecretKey<N>,
+    options: &SigningOptions,
+) -> Result<Signature<N>, SignError> {
+    let mut header = [0u8; 2];
+    let m = match options.context {
+        None => vec![m],
+        Some(context) => {
+            domain_separate(0, context, &[m], &mut header).ok_or(SignError::ContextTooLong)?
+        }
+    };
+    let signature = if let Some((nonce, seed)) = options.reference_randomness {
+        try_sign_reference(&m, sk, nonce, seed, options.attempt_limit)
+    } else if options.deterministic {
+        let mut hasher = Shake256::default();
+        hasher.update(b"falcon-rust deterministic signing");
+        hasher.update(&Zeroizing::new(sk.to_bytes()));
+        for part in m.iter() {
+            hasher.update(part);
+        }
+        let mut seed = [0u8; 32];
+        hasher.finalize_xof().read(&mut seed);
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        try_sign_with_rng(&m, sk, &mut rng, options.attempt_limit)
+    } else {
+        try_sign_with_rng(&m, sk, &mut crate::entropy::rng(), options.attempt_limit)
+    };
+    signature.ok_or(SignError::AttemptLimitExceeded)
+}
+
+/// Sign a message bound to a context string of at most 255 bytes, as in the
+/// pure mode of FIPS 206 (draft): the message is hashed as
+/// `0 || len(ctx) || ctx || m`. The signature only verifies with
+/// [`verify_ctx`] and the same context, so that, e.g., signatures made in a
+/// staging environment never verify in production. [`sign_with`] frames a
+/// [`SigningOptions::context`] the same way.
+#[cfg(feature = "std")]
+pub fn sign_ctx<const N: usize>(
+    m: &[u8],
+    ctx: &[u8],
+    sk: &SecretKey<N>,
+) -> Result<Signature<N>, SignError> {
+    let mut header = [0u8; 2];
+    let m = domain_separate(0, ctx, &[m], &mut header).ok_or(SignError::ContextTooLong)?;
+    Ok(try_sign_with_rng(&m, sk, &mut crate::entropy::rng(), None)
+        .expect("signing without attempt limit cannot fail"))
+}
+
+/// Sign the digest of a message, as in the pre-hash mode of FIPS 206
+/// (draft): what is hashed to a point is `1 || len(ctx) || ctx || OID ||
+/// digest`, where OID identifies the hash function. A large message can
+/// thus be hashed once, elsewhere, and only its digest sent to the signer.
+///
+/// The signature only verifies with [`verify_prehash`], with the same
+/// hash function and context; never with [`verify`] on the message.
+#[cfg(feature = "std")]
+pub fn sign_prehash<const N: usize>(
+    ph: PrehashAlgorithm,
+    digest: &[u8],
+    ctx: &[u8],
+    sk: &SecretKey<N>,
+) -> Result<Signature<N>, SignError> {
+    if digest.len() != ph.digest_len() {
+        return Err(SignError::DigestLengthMismatch);
+    }
+    let oid = ph.oid();
+    let mut header = [0u8; 2];
+    let m =
+        domain_separate(1, ctx, &[&oid, digest], &mut header).ok_or(SignError::ContextTooLong)?;
+    Ok(try_sign_with_rng(&m, sk, &mut crate::entropy::rng(), None)
+        .expect("signing without attempt limit cannot fail"))
+}
+
+/// Sign a message, giving up once `max_attempts` candidate signatures have
+/// been rejected, for callers with a latency budget. On success, also
+/// return the number of candidates sampled, at most `max_attempts`.
+///
+/// Signing succeeds after very few attempts on average, and the number of
+/// attempts is geometrically distributed, so a limit of, e.g., 16 is all
+/// but never exceeded.
+#[cfg(feature = "std")]
+pub fn sign_bounded<const N: usize>(
+    m: &[u8],
+    sk: &SecretKey<N>,
+    max_attempts: usize,
+) -> Result<(Signature<N>, usize), SignError> {
+    let mut rng = crate::entropy::rng();
+    let mut r = [0u8; 40];
+    rng.fill_bytes(&mut r);
+    let c = hash_message(&r, &[m], N);
+    try_sign_hashed_counted(r, &c, sk, &mut rng, Some(max_attempts))
+        .ok_or(SignError::AttemptLimitExceeded)
+}
+
+/// Sign a message and verify the signature against the public key before
+/// returning it, as a countermeasure against fault attacks: a fault injected
+/// during sampling can produce a signature that leaks the secret key, and
+/// such a signature almost never verifies. This roughly adds the cost of a
+/// verification to every signature.
+#[cfg(feature = "std")]
+pub fn sign_verified<const N: usize>(
+    m: &[u8],
+    sk: &SecretKey<N>,
+    pk: &PublicKey<N>,
+) -> Result<Signature<N>, SignError> {
+    let sig = sign(m, sk);
+    if verify(m, &sig, pk) {
+        Ok(sig)
+    } else {
+        Err(SignError::FaultDetected)
+    }
+}
+
+/// Sign a message given as a sequence of parts, which are hashed in order as
+/// if concatenated. The signature verifies against the concatenation with
+/// [`verify`], and against the parts with [`verify_vectored`].
+#[cfg(feature = "std")]
+pub fn sign_vectored<const N: usize>(m: &[&[u8]], sk: &SecretKey<N>) -> Signature<N> {
+    try_sign_with_rng(m, sk, &mut crate::entropy::rng(), None)
+        .expect("signing without attempt limit cannot fail")
+}
+
+/// Sign a message with an expanded secret key, using the given working
+/// memory instead of allocating. Together, the two amortize all of the setup
+/// that [`sign`] repeats for every signature; the sampling loop runs without
+/// touching the heap, and only the returned signature is allocated.
+///
+/// Produces the same signatures as [`sign`], given the same randomness.
+#[cfg(feature = "std")]
+pub fn sign_with_scratch<const N: usize>(
+    m: &[u8],
+    esk: &ExpandedSecretKey<N>,
+    scratch: &mut SignScratch<N>,
+) -> Signature<N> {
+    sign_with_scratch_and_rng(m, esk, scratch, &mut crate::entropy::rng())
+}
+
+/// Sign a message with an expanded secret key. The ffLDL tree is cached in
+/// every [`SecretKey`]; the expanded key also caches the FFT of the basis, so
+/// that a server signing continuously under one key only pays for the
+/// sampling. Use [`sign_with_scratch`] to also reuse the working memory.
+#[cfg(feature = "std")]
+pub fn sign_with_expanded_key<const N: usize>(
+    m: &[u8],
+    esk: &ExpandedSecretKey<N>,
+) -> Signature<N> {
+    sign_with_scratch(m, esk, &mut SignScratch::new())
+}
+
+#[cfg(feature = "std")]
+fn sign_with_scratch_and_rng<const N: usize, R: RngCore>(
+    m: &[u8],
+    esk: &ExpandedSecretKey<N>,
+    scratch: &mut SignScratch<N>,
+    rng: &mut R,
+) -> Signature<N> {
+    let mut r = [0u8; 40];
+    rng.fill_bytes(&mut r);
+
+    let params = FalconVariant::from_n(N).parameters();
+    let bound = params.sig_bound;
+    let n = params.n;
+    let [g_fft, f_fft, capital_g_fft, capital_f_fft] = &esk.b_fft;
+    let SignScratch {
+        c,
+        t0,
+        t1,
+        z0,
+        z1,
+        s0,
+        s1,
+        s2,
+        work,
+        psi_inv_rev,
+    } = scratch;
+
+    hash_to_point_into(&[&r, m], c);
+    let one_over_q = 1.0 / (Q as f64);
+    let c_over_q_fft = &mut *s0;
+    for (c_over_q, c) in c_over_q_fft.iter_mut().zip(c.iter()) {
+        *c_over_q = Complex::new(one_over_q * c.value() as f64, 0.0);
+    }
+    Complex64::fft(c_over_q_fft, &COMPLEX_BITREVERSED_POWERS_1024);
+    for i in 0..n {
+        t0[i] = c_over_q_fft[i] * capital_f_fft[i];
+        t1[i] = -(c_over_q_fft[i] * f_fft[i]);
+    }
+
+    let s = loop {
+        // keep the randomness in step with `try_sign_with_rng`
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        loop {
+            ffsampling_into(
+                (t0, t1),
+                &esk.sk.tree,
+                &params,
+                psi_inv_rev,
+                rng,
+                (z0, z1),
+                work,
+            );
+
+            // s = (t-z) * B
+            for i in 0..n {
+                let t0_min_z0 = t0[i] + -z0[i];
+                let t1_min_z1 = t1[i] + -z1[i];
+                s0[i] = t0_min_z0 * g_fft[i] + t1_min_z1 * capital_g_fft[i];
+                s1[i] = t0_min_z0 * f_fft[i] + t1_min_z1 * capital_f_fft[i];
+            }
+
+            // compute the norm of (s0||s1) and note that they are in FFT representation
+            let length_squared: f64 = (s0.iter().map(|a| (a * a.conj()).re).sum::<f64>()
+                + s1.iter().map(|a| (a * a.conj()).re).sum::<f64>())
+                / (n as f64);
+
+            if length_squared <= (bound as f64) {
+                break;
+            }
+        }
+        Complex64::ifft(s1, psi_inv_rev, Complex64::new(1.0 / (n as f64), 0.0));
+        for (s2, s1) in s2.iter_mut().zip(s1.iter()) {
+            *s2 = s1.re.round() as i16;
+        }
+        if let Some(s) = compress(s2, params.sig_bytelen - 41) {
+            break s;
+        }
+    };
+
+    Signature::new(r, s)
+}
+
+/// Sign a message with the secret key, drawing the salt and all sampler
+/// randomness from the given random number generator. [`sign`] and
+/// [`sign_with_seed`] are shorthands for this with the thread-local
+/// generator and a seeded one.
+pub fn sign_with_rng<const N: usize, R: CryptoRng + RngCore>(
+    m: &[u8],
+    sk: &SecretKey<N>,
+    rng: &mut R,
+) -> Signature<N> {
+    try_sign_with_rng(&[m], sk, rng, None).expect("signing without attempt limit cannot fail")
+}
+
+/// Sign, giving up once `attempt_limit` candidates have been rejected.
+fn try_sign_with_rng<const N: usize, R: RngCore>(
+    m: &[&[u8]],
+    sk: &SecretKey<N>,
+    rng: &mut R,
+    attempt_limit: Option<usize>,
+) -> Option<Signature<N>> {
+    let mut r = [0u8; 40];
+    rng.fill_bytes(&mut r);
+    let c = hash_message(&r, m, N);
+    try_sign_hashed(r, &c, sk, rng, attempt_limit)
+}
+
+/// Sign given the salt `r` and the hash `c` of the salt and the message.
+pub(crate) fn try_sign_hashed<const N: usize, R: RngCore>(
+    r: [u8; 40],
+    c: &Polynomial<Felt>,
+    sk: &SecretKey<N>,
+    rng: &mut R,
+    attempt_limit: Option<usize>,
+) -> Option<Signature<N>> {
+    try_sign_hashed_counted(r, c, sk, rng, attempt_limit).map(|(sig, _)| sig)
+}
+
+/// Like [`try_sign_hashed`], but also return the number of candidates
+/// sampled.
+fn try_sign_hashed_counted<const N: usize, R: RngCore>(
+    r: [u8; 40],
+    c: &Polynomial<Felt>,
+    sk: &SecretKey<N>,
+    rng: &mut R,
+    attempt_limit: Option<usize>,
+) -> Option<(Signature<N>, usize)> {
+    let mut attempts = 0;
+    let params = FalconVariant::from_n(N).parameters();
+    let bound = params.sig_bound;
+    let n = params.n;
+
+    let one_over_q = 1.0 / (Q as f64);
+    let c_over_q_fft = c
+        .map(|cc| Complex::new(one_over_q * cc.value() as f64, 0.0))
+        .fft();
+
+    // B = [[FFT(g), -FFT(f)], [FFT(G), -FFT(F)]]
+    let capital_f_fft = secret_fft(&sk.b0[3], -1.0);
+    let f_fft = secret_fft(&sk.b0[1], -1.0);
+    let capital_g_fft = secret_fft(&sk.b0[2], 1.0);
+    let g_fft = secret_fft(&sk.b0[0], 1.0);
+    let t0 = Zeroizing::new(c_over_q_fft.hadamard_mul(&capital_f_fft));
+    let mut t1 = Zeroizing::new(c_over_q_fft.hadamard_mul(&f_fft));
+    for t in t1.coefficients.iter_mut() {
+        *t = -*t;
+    }
+
+    let s = loop {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        let bold_s = loop {
+            if attempt_limit.is_some_and(|limit| attempts >= limit) {
+                return None;
+            }
+            attempts += 1;
+
+            // t - z, computed in place of the sample z
+            let (t0_min_z0, t1_min_z1) = ffsampling((&t0, &t1), &sk.tree, &params, rng);
+            let (mut t0_min_z0, mut t1_min_z1) =
+                (Zeroizing::new(t0_min_z0), Zeroizing::new(t1_min_z1));
+            for (d, t) in t0_min_z0.coefficients.iter_mut().zip(&t0.coefficients) {
+                *d = t - *d;
+            }
+            for (d, t) in t1_min_z1.coefficients.iter_mut().zip(&t1.coefficients) {
+                *d = t - *d;
+            }
+
+            // s = (t-z) * B
+            let mut s0 = Zeroizing::new(Polynomial::new(vec![Complex64::new(0.0, 0.0); n]));
+            let mut s1 = Zeroizing::new(Polynomial::new(vec![Complex64::new(0.0, 0.0); n]));
+            for i in 0..n {
+                let (d0, d1) = (t0_min_z0.coefficients[i], t1_min_z1.coefficients[i]);
+                s0.coefficients[i] =
+                    d0 * g_fft.coefficients[i] + d1 * capital_g_fft.coefficients[i];
+                s1.coefficients[i] =
+                    d0 * f_fft.coefficients[i] + d1 * capital_f_fft.coefficients[i];
+            }
+
+            // compute the norm of (s0||s1) and note that they are in FFT representation
+            let length_squared: f64 = (s0
+                .coefficients
+                .iter()
+                .map(|a| (a * a.conj()).re)
+                .sum::<f64>()
+                + s1.coefficients
+                    .iter()
+                    .map(|a| (a * a.conj()).re)
+                    .sum::<f64>())
+                / (n as f64);
+
+            if length_squared > (bound as f64) {
+                continue;
+            }
+
+            break s1;
+        };
+        let mut s2 = bold_s;
+        s2.ifft_inplace();
+        let maybe_s = compress(
+            &s2.coefficients
+                .iter()
+                .map(|a| a.re.round() as i16)
+                .collect_vec(),
+            params.sig_bytelen - 41,
+        );
+
+        match maybe_s {
+            Some(s) => {
+                break s;
+            }
+            None => {
+                continue;
+            }
+        };
+    };
+
+    Some((Signature::new(r, s), attempts))
+}
+
+/// The FFT of a polynomial of the secret basis, scaled by `sign`, which is
+/// wiped when dropped.
+fn secret_fft(b: &Polynomial<i16>, sign: f64) -> Zeroizing<Polynomial<Complex64>> {
+    let mut fft = Zeroizing::new(b.map(|&i| Complex64::new(sign * i as f64, 0.0)));
+    fft.fft_inplace();
+    fft
+}
+
+/// Sign as the reference implementation's `do_sign()` does after drawing
+/// the nonce and seed, giving up once `attempt_limit` candidates have been
+/// rejected.
+#[cfg(feature = "std")]
+fn try_sign_reference<const N: usize>(
+    m: &[&[u8]],
+    sk: &SecretKey<N>,
+    nonce: &Nonce,
+    seed: &[u8],
+    attempt_limit: Option<usize>,
+) -> Option<Signature<N>> {
+    let params = FalconVariant::from_n(N).parameters();
+    let n = params.n;
+    let c = hash_message(nonce.as_bytes(), m, n);
+    let mut shake = Shake256::default();
+    shake.update(seed);
+    let mut shake = shake.finalize_xof();
+
+    // B = [[g, -f], [G, -F]], and t = (c, 0) * B^-1
+    let [b00, b01, b10, b11] = sk.b0.each_ref().map(|b| secret_fft(b, 1.0));
+    let one_over_q = 1.0 / (Q as f64);
+    let c_fft = c.map(|cc| Complex64::new(cc.value() as f64, 0.0)).fft();
+    let mut t0 = Zeroizing::new(c_fft.hadamard_mul(&b11));
+    let mut t1 = Zeroizing::new(c_fft.hadamard_mul(&b01));
+    for (t0, t1) in t0.coefficients.iter_mut().zip(t1.coefficients.iter_mut()) {
+        *t0 *= one_over_q;
+        *t1 *= -one_over_q;
+    }
+
+    for _ in 0..attempt_limit.unwrap_or(usize::MAX) {
+        // every attempt reseeds the sampler from the SHAKE256 stream
+        let mut prng = ReferencePrng::new(&mut shake);
+        let (z0, z1) = ffsampling((&t0, &t1), &sk.tree, &params, &mut prng);
+        let (z0, z1) = (Zeroizing::new(z0), Zeroizing::new(z1));
+
+        // z * B is the lattice point close to (c, 0); s is the difference
+        let v0 = (z0.hadamard_mul(&b00) + z1.hadamard_mul(&b10)).ifft();
+        let v1 = (z0.hadamard_mul(&b01) + z1.hadamard_mul(&b11)).ifft();
+        let s1 = c
+            .coefficients
+            .iter()
+            .zip(v0.coefficients.iter())
+            .map(|(c, v)| c.value() as i64 - v.re.round_ties_even() as i64);
+        let s2 = v1
+            .coefficients
+            .iter()
+            .map(|v| -v.re.round_ties_even() as i16)
+            .collect_vec();
+        let length_squared: i64 = s1.map(|a| a * a).sum::<i64>()
+            + s2.iter().map(|&a| (a as i64) * (a as i64)).sum::<i64>();
+        if length_squared > params.sig_bound {
+            continue;
+        }
+
+        // `crypto_sign_signature()` leaves room for the two length bytes
+        // of `crypto_sign()`
+        if let Some(mut s) = compress(&s2, params.sig_bytelen - 43) {
+            s.resize(params.sig_bytelen - 41, 0);
+            return Some(Signature::new(*nonce.as_bytes(), s));
+        }
+    }
+    None
+}
+
+/// Verify a signature. Algorithm 16 in the spec [1, p.45].
+///
+/// Verification uses integer arithmetic only: it hashes the message to a
+/// point, decompresses `s2`, and checks the norm of `(c - s2 h, s2)`, with
+/// the product taken by the NTT over Z_q. None of this touches floating
+/// point, so the result is bit-identical on every platform, including
+/// soft-float targets.
+///
+/// [1]: https://falcon-sign.info/falcon.pdf
+pub fn verify<const N: usize>(m: impl AsRef<[u8]>, sig: &Signature<N>, pk: &PublicKey<N>) -> bool {
+    verify_with_h_ntt(&[m.as_ref()], sig, &pk.h.fft())
+}
+
+/// Like [`verify`], but with an expanded public key, which saves bringing
+/// the public key into NTT domain. Use this to verify many signatures under
+/// the same key.
+pub fn verify_with_expanded_pk<const N: usize>(
+    m: &[u8],
+    sig: &Signature<N>,
+    epk: &ExpandedPublicKey<N>,
+) -> bool {
+    verify_with_h_ntt(&[m], sig, epk.h_ntt())
+}
+
+/// Like [`verify`], but report why the signature is rejected.
+pub fn try_verify<const N: usize>(
+    m: &[u8],
+    sig: &Signature<N>,
+    pk: &PublicKey<N>,
+) -> Result<(), FalconError> {
+    try_verify_with_h_ntt(&[m], sig, &pk.h.fft())
+}
+
+/// Verify a signature and report the squared norm of `(s1, s2)` along with
+/// the bound it must be below, e.g., to monitor how close signatures run to
+/// the bound or to debug another signer. Fails only if the signature cannot
+/// be decoded; whether it is valid is [`VerificationDetails::is_valid`].
+pub fn verify_with_details<const N: usize>(
+    m: &[u8],
+    sig: &Signature<N>,
+    pk: &PublicKey<N>,
+) -> Result<VerificationDetails, FalconError> {
+    let prepared = prepare_verification(&[m], sig)?;
+    Ok(VerificationDetails {
+        norm_squared: prepared.norm_squared(&pk.h.fft()),
+        bound: FalconVariant::from_n(N).parameters().sig_bound,
+    })
+}
+
+/// Like [`try_verify`], but with the signature given in its encoded form,
+/// so that decoding errors are reported too.
+pub fn try_verify_bytes<const N: usize>(
+    m: &[u8],
+    sig: &[u8],
+    pk: &PublicKey<N>,
+) -> Result<(), FalconError> {
+    // header and nonce
+    if sig.len() < 1 + Nonce::LEN {
+        return Err(FalconError::BadNonceLength);
+    }
+    try_verify(m, &Signature::from_bytes(sig)?, pk)
+}
+
+/// Verify a signature on a message given as a sequence of parts, which are
+/// hashed in order as if concatenated.
+pub fn verify_vectored<const N: usize>(m: &[&[u8]], sig: &Signature<N>, pk: &PublicKey<N>) -> bool {
+    verify_with_h_ntt(m, sig, &pk.h.fft())
+}
+
+/// Verify a signature made with [`sign_ctx`], or with [`sign_with`] and a
+/// [`SigningOptions::context`], under the given context.
+pub fn verify_ctx<const N: usize>(
+    m: &[u8],
+    ctx: &[u8],
+    sig: &Signature<N>,
+    pk: &PublicKey<N>,
+) -> bool {
+    let mut header = [0u8; 2];
+    match domain_separate(0, ctx, &[m], &mut header) {
+        Some(m) => verify_vectored(&m, sig, pk),
+        None => false,
+    }
+}
+
+/// Verify a signature made with [`sign_prehash`] on the given digest.
+pub fn verify_prehash<const N: usize>(
+    ph: PrehashAlgorithm,
+    digest: &[u8],
+    ctx: &[u8],
+    sig: &Signature<N>,
+    pk: &PublicKey<N>,
+) -> bool {
+    if digest.len() != ph.digest_len() {
+        return false;
+    }
+    let oid = ph.oid();
+    let mut header = [0u8; 2];
+    match domain_separate(1, ctx, &[&oid, digest], &mut header) {
+        Some(m) => verify_vectored(&m, sig, pk),
+        None => false,
+    }
+}
+
+/// Verify a signature given the hash `c` of its nonce and the message, as
+/// computed by [`hash_to_point`], instead of the message. Use this to hash a
+/// message once and verify it under several keys. Coefficients of `c` must
+/// be in `[0, q)`.
+pub fn verify_raw<const N: usize>(
+    c: &Polynomial<u16>,
+    sig: &Signature<N>,
+    pk: &PublicKey<N>,
+) -> bool {
+    if c.coefficients.len() != N || c.coefficients.iter().any(|&x| x as u32 >= Q) {
+        return false;
+    }
+    let c = c.map(|&x| Felt::new(x as i16));
+    prepare_hashed_verification(c, sig).is_ok_and(|prepared| {
+        prepared
+            .check(&pk.h.fft(), FalconVariant::from_n(N).parameters().sig_bound)
+            .is_ok()
+    })
+}
+
+/// Like [`verify`], but with the public key given in NTT domain.
+pub(crate) fn verify_with_h_ntt<const N: usize>(
+    m: &[&[u8]],
+    sig: &Signature<N>,
+    h_ntt: &Polynomial<Felt>,
+) -> bool {
+    try_verify_with_h_ntt(m, sig, h_ntt).is_ok()
+}
+
+/// Like [`try_verify`], but with the public key given in NTT domain.
+pub(crate) fn try_verify_with_h_ntt<const N: usize>(
+    m: &[&[u8]],
+    sig: &Signature<N>,
+    h_ntt: &Polynomial<Felt>,
+) -> Result<(), FalconError> {
+    prepare_verification(m, sig)?.check(h_ntt, FalconVariant::from_n(N).parameters().sig_bound)
+}
+
+/// The part of verifying a signature that does not depend on the public
+/// key: the hash of the message and `s2`, both in NTT domain, and the
+/// squared norm of `s2`.
+pub(crate) struct PreparedVerification {
+    c_ntt: Polynomial<Felt>,
+    s2_ntt: Polynomial<Felt>,
+    s2_length_squared: i64,
+}
+
+impl PreparedVerification {
+    /// Check the norm of `(s1, s2)` for the public key given in NTT domain.
+    pub(crate) fn check(
+        &self,
+        h_ntt: &Polynomial<Felt>,
+        sig_bound: i64,
+    ) -> Result<(), FalconError> {
+        if self.norm_squared(h_ntt) < sig_bound {
+            Ok(())
+        } else {
+            Err(FalconError::NormBoundExceeded)
+        }
+    }
+
+    /// The squared norm of `(s1, s2)` for the public key given in NTT
+    /// domain.
+    fn norm_squared(&self, h_ntt: &Polynomial<Felt>) -> i64 {
+        // s1 = c - s2 * pk.h;
+        let s1 = sub_mul(&self.c_ntt, &self.s2_ntt, h_ntt).ifft();
+        norm_squared(&s1.coefficients) + self.s2_length_squared
+    }
+}
+
+/// Compute `c - s2 * h` for polynomials in NTT domain.
+#[cfg(not(all(target_arch = "aarch64", target_feature = "neon")))]
+fn sub_mul(c: &Polynomial<Felt>, s2: &Polynomial<Felt>, h: &Polynomial<Felt>) -> Polynomial<Felt> {
+    c.clone() - s2.hadamard_mul(h)
+}
+
+/// The squared norm of the balanced representatives of the coefficients.
+#[cfg(not(all(target_arch = "aarch64", target_feature = "neon")))]
+fn norm_squared(a: &[Felt]) -> i64 {
+    a.iter()
+        .map(|i| i.balanced_value() as i64)
+        .map(|i| i * i)
+        .sum::<i64>()
+}
+
+/// The squared norm of a short vector.
+#[cfg(not(all(target_arch = "aarch64", target_feature = "neon")))]
+fn norm_squared_i16(a: &[i16]) -> i64 {
+    a.iter().map(|&i| i as i64).map(|i| i * i).sum::<i64>()
+}
+
+/// Hash the message and decompress the signature.
+fn prepare_verification<const N: usize>(
+    m: &[&[u8]],
+    sig: &Signature<N>,
+) -> Result<PreparedVerification, FalconError> {
+    prepare_hashed_verification(sig.hash_cache.hash_message(&sig.r, m, N), sig)
+}
+
+/// Like [`prepare_verification`], given the hash `c` of the salt and the
+/// message.
+pub(crate) fn prepare_hashed_verification<const N: usize>(
+    c: Polynomial<Felt>,
+    sig: &Signature<N>,
+) -> Result<PreparedVerification, FalconError> {
+    let s2 = decompress(&sig.s, N).ok_or(FalconError::MalformedEncoding(
+        FalconDeserializationError::BadFieldElementEncoding,
+    ))?;
+    let s2_ntt = Polynomial::new(s2.iter().map(|a| Felt::new(*a)).collect_vec()).fft();
+    let s2_length_squared = norm_squared_i16(&s2);
+    Ok(PreparedVerification {
+        c_ntt: c.fft(),
+        s2_ntt,
+        s2_length_squared,
+    })
+}
+
+/// Verify a signature against several candidate public keys, e.g., the old
+/// and the new key during a key rotation. Returns the index of the first key
+/// for which the signature is valid, if any.
+///
+/// The message is hashed and the signature decompressed only once, so this
+/// is cheaper than calling [`verify`] for every key.
+pub fn verify_any<const N: usize>(
+    m: &[u8],
+    sig: &Signature<N>,
+    pks: &[PublicKey<N>],
+) -> Option<usize> {
+    verify_any_with_h_ntt(&[m], sig, pks.iter().map(|pk| pk.h.fft()))
+}
+
+/// Like [`verify_any`], but with the public keys given in NTT domain.
+pub(crate) fn verify_any_with_h_ntt<const N: usize, H: Borrow<Polynomial<Felt>>>(
+    m: &[&[u8]],
+    sig: &Signature<N>,
+    h_ntts: impl IntoIterator<Item = H>,
+) -> Option<usize> {
+    let params = FalconVariant::from_n(N).parameters();
+    let prepared = prepare_verification(m, sig).ok()?;
+
+    h_ntts
+        .into_iter()
+        .position(|h_ntt| prepared.check(h_ntt.borrow(), params.sig_bound).is_ok())
+}
+
+/// Verify an expanded signature
+pub fn verify_expanded<const N: usize>(
+    m: &[u8],
+    sig: &ExpandedSignature<N>,
+    pk: &PublicKey<N>,
+) -> bool {
+    verify_expanded_with_h_ntt(m, sig, &pk.h.fft())
+}
+
+/// Like [`verify_expanded`], but report why the signature is rejected.
+pub fn try_verify_expanded<const N: usize>(
+    m: &[u8],
+    sig: &ExpandedSignature<N>,
+    pk: &PublicKey<N>,
+) -> Result<(), FalconError> {
+    try_verify_expanded_with_h_ntt(m, sig, &pk.h.fft())
+}
+
+/// Like [`verify_expanded`], but with the public key given in NTT domain.
+pub(crate) fn verify_expanded_with_h_ntt<const N: usize>(
+    m: &[u8],
+    sig: &ExpandedSignature<N>,
+    h_ntt: &Polynomial<Felt>,
+) -> bool {
+    try_verify_expanded_with_h_ntt(m, sig, h_ntt).is_ok()
+}
+
+/// Like [`try_verify_expanded`], but with the public key given in NTT
+/// domain.
+fn try_verify_expanded_with_h_ntt<const N: usize>(
+    m: &[u8],
+    sig: &ExpandedSignature<N>,
+    h_ntt: &Polynomial<Felt>,
+) -> Result<(), FalconError> {
+    let n = N;
+    let params = FalconVariant::from_n(N).parameters();
+
+    // norm check
+    let length_squared = sig
+        .s1
+        .coefficients
+        .iter()
+        .map(|i| i.balanced_value() as i64)
+        .map(|i| i * i)
+        .sum::<i64>()
+        + sig
+            .s2
+            .coefficients
+            .iter()
+            .map(|&i| i.balanced_value() as i64)
+            .map(|i| i * i)
+            .sum::<i64>();
+
+    if length_squared >= params.sig_bound {
+        return Err(FalconError::NormBoundExceeded);
+    }
+
+    let c = sig.hash_cache.hash_message(&sig.r, &[m], n);
+    // s1 + s2 * pk.h = c
+    // do the full check
+    let s2_ntt = Polynomial::new(sig.s2.coefficients.clone()).fft();
+
+    // s1 = c - s2 * pk.h;
+    let should_be_s1 = c - s2_ntt.hadamard_mul(h_ntt).ifft();
+
+    match sig
+        .s1
+        .coefficients
+        .iter()
+        .zip(&should_be_s1.coefficients)
+        .position(|(a, b)| a != b)
+    {
+        Some(i) => Err(FalconError::IndexMismatch(i)),
+        None => Ok(()),
+    }
+}
+
+/// Fast verify a signature
+///
+/// Verifiers that cannot store an [`ExpandedSignature`] per signature can
+/// check the compressed signature with [`fverify_direct`] instead.
+pub fn fverify<const N: usize>(
+    m: &[u8],
+    sig: &ExpandedSignature<N>,
+    pk: &PublicKey<N>,
+    indices: &[usize],
+) -> bool {
+    try_fverify(m, sig, pk, indices).is_ok()
+}
+
+/// Like [`fverify`], but report why the signature is rejected.
+pub fn try_fverify<const N: usize>(
+    m: &[u8],
+    sig: &ExpandedSignature<N>,
+    pk: &PublicKey<N>,
+    indices: &[usize],
+) -> Result<(), FalconError> {
+    let n = N;
+    let params = FalconVariant::from_n(N).parameters();
+
+    // norm check
+    let length_squared = sig
+        .s1
+        .coefficients
+        .iter()
+        .map(|i| i.balanced_value() as i64)
+        .map(|i| i * i)
+        .sum::<i64>()
+        + sig
+            .s2
+            .coefficients
+            .iter()
+            .map(|&i| i.balanced_value() as i64)
+            .map(|i| i * i)
+            .sum::<i64>();
+
+    if length_squared >= params.sig_bound {
+        return Err(FalconError::NormBoundExceeded);
+    }
+    if let Some(&i) = indices.iter().find(|&&i| i >= n) {
+        return Err(FalconError::IndexOutOfRange(i));
+    }
+
+    let c = sig.hash_cache.hash_message(&sig.r, &[m], n);
+
+    // s1 + s2 * pk.h = c
+    // only check for selected indices
+    for &i in indices {
+        let should_be_ci = sig.s1.coefficients[i] + sig.s2.mul_coeff(&pk.h, i, n);
+        if should_be_ci != c.coefficients[i] {
+            return Err(FalconError::IndexMismatch(i));
+        }
+    }
+
+    Ok(())
+}
+
+/// Like [`fverify`], but with the indices derived from the public key, the
+/// message and the signature with [`fiat_shamir_indices`] instead of chosen
+/// by the caller. Every verifier thus checks the same coefficients, and the
+/// check needs no interaction or randomness.
+///
+/// A forger can recompute the indices for every signature they try, so the
+/// number of indices alone must make passing the check unlikely; see
+/// [`fverify`].
+pub fn fverify_fs<const N: usize>(
+    m: &[u8],
+    sig: &ExpandedSignature<N>,
+    pk: &PublicKey<N>,
+    num_indices: usize,
+) -> bool {
+    fverify(m, sig, pk, &fiat_shamir_indices(m, sig, pk, num_indices))
+}
+
+/// Derive `num_indices` distinct coefficient indices, or all N if more are
+/// asked for, from a SHAKE256 transcript of the public key, the message and
+/// the signature. These are the indices that [`fverify_fs`] checks.
+pub fn fiat_shamir_indices<const N: usize>(
+    m: &[u8],
+    sig: &ExpandedSignature<N>,
+    pk: &PublicKey<N>,
+    num_indices: usize,
+) -> Vec<usize> {
+    let mut hasher = Shake256::default();
+    hasher.update(b"falcon-rust fverify indices");
+    hasher.update(pk.as_bytes());
+    hasher.update(&(m.len() as u64).to_le_bytes());
+    hasher.update(m);
+    hasher.update(&sig.to_bytes());
+    let mut reader = hasher.finalize_xof();
+
+    let num_indices = num_indices.min(N);
+    let mut chosen = vec![false; N];
+    let mut indices = Vec::with_capacity(num_indices);
+    while indices.len() < num_indices {
+        let mut bytes = [0u8; 2];
+        reader.read(&mut bytes);
+        // N is a power of two, so masking is unbiased
+        let index = u16::from_le_bytes(bytes) as usize & (N - 1);
+        if !chosen[index] {
+            chosen[index] = true;
+            indices.push(index);
+        }
+    }
+    indices
+}
+
+/// A heuristic estimate of the number of distinct, uniformly random
+/// indices [`fverify`] must check so that a forged expanded signature
+/// passes with probability about `target_false_accept`. It is not a bound:
+/// it only holds under the model below.
+///
+/// The estimate assumes that
+///
+/// - the forger has neither the secret key nor any other short basis of
+///   the lattice, and does not use lattice reduction to find one;
+/// - c - s2 * h behaves like a uniformly random polynomial mod q, whatever
+///   short s2 the forger picks;
+/// - the forger then matches c in as many coefficients as the norm bound
+///   allows by making the corresponding coefficients of s1 agree, and
+///   leaves all other coefficients mismatched;
+/// - the indices are uniform, independent of the forgery, and not known to
+///   the forger in advance.
+///
+/// Matching the m smallest of N uniform values costs about (q / 2N)^2 m^3 /
+/// 3 of the squared norm, so the model lets about m = (3 beta^2 (2N /
+/// q)^2)^(1/3) coefficients (about 89 of 512 and 180 of 1024) agree, and
+/// the estimate is the probability from [`fverify_false_accept_probability`]
+/// at the remaining N - m mismatches. A forger that breaks an assumption,
+/// e.g., by choosing short s1 and s2 with lattice reduction, may match more
+/// coefficients and pass more often. With [`fverify_fs`], the indices
+/// are known to the forger, who can retry until one attempt passes, so the
+/// target applies to a single attempt at best.
+///
+/// Returns 0 if the target is at least 1 and N if it is not positive.
+pub fn fverify_required_indices<const N: usize>(target_false_accept: f64) -> usize {
+    let params = FalconVariant::from_n(N).parameters();
+    let scale = 2.0 * N as f64 / Q as f64;
+    let matching = (3.0 * params.sig_bound as f64 * scale * scale).cbrt() as usize;
+    let mismatches = N.saturating_sub(matching).max(1);
+
+    if target_false_accept.is_nan() || target_false_accept <= 0.0 {
+        return N;
+    }
+    (0..=N)
+        .find(|&k| fverify_false_accept_probability::<N>(mismatches, k) <= target_false_accept)
+        .unwrap_or(N)
+}
+
+/// The probability that [`fverify`] with `num_indices` distinct, uniformly
+/// random indices accepts an expanded signature for which s1 + s2 * h
+/// differs from c in `mismatches` of the N coefficients, i.e., the
+/// probability that none of the indices hits a mismatch.
+pub fn fverify_false_accept_probability<const N: usize>(
+    mismatches: usize,
+    num_indices: usize,
+) -> f64 {
+    // (N - mismatches choose k) / (N choose k)
+    (0..num_indices.min(N))
+        .map(|j| N.saturating_sub(mismatches + j) as f64 / (N - j) as f64)
+        .product()
+}
+
+/// Fast verify a signature
+/// Then if it passes, do a full verify
+pub fn fverify_fullverify<const N: usize>(
+    m: &[u8],
+    sig: &ExpandedSignature<N>,
+    pk: &PublicKey<N>,
+    indices: &[usize],
+) -> bool {
+    fverify_fullverify_with_h_ntt(m, sig, pk, None, indices)
+}
+
+/// Like [`fverify_fullverify`], but optionally with the public key's NTT
+/// precomputed. If it is not supplied, it is computed only once the fast
+/// check passes.
+pub(crate) fn fverify_fullverify_with_h_ntt<const N: usize>(
+    m: &[u8],
+    sig: &ExpandedSignature<N>,
+    pk: &PublicKey<N>,
+    h_ntt: Option<&Polynomial<Felt>>,
+    indices: &[usize],
+) -> bool {
+    let n = N;
+    let params = FalconVariant::from_n(N).parameters();
+
+    let length_squared = sig
+        .s1
+        .coefficients
+        .iter()
+        .map(|i| i.balanced_value() as i64)
         .map(|i| i * i)
         .sum::<i64>()
         + sig
             .s2
             .coefficients
             .iter()
-            .map(|&i| i.balanced_value() as i64)
-            .map(|i| i * i)
-            .sum::<i64>();
+            .map(|&i| i.balanced_value() as i64)
+            .map(|i| i * i)
+            .sum::<i64>();
+
+    if length_squared >= params.sig_bound {
+        return false;
+    }
+
+    let c = sig.hash_cache.hash_message(&sig.r, &[m], n);
+
+    // s1 + s2 * pk.h = c
+    // only check for selected indices
+    for &i in indices {
+        let should_be_ci = sig.s1.coefficients[i] + sig.s2.mul_coeff(&pk.h, i, n);
+        if should_be_ci != c.coefficients[i] {
+            return false;
+        }
+    }
+
+    // s1 + s2 * pk.h = c
+    // do the full check
+    let s2_ntt = Polynomial::new(sig.s2.coefficients.clone()).fft();
+    let should_be_s1 = match h_ntt {
+        Some(h_ntt) => c - s2_ntt.hadamard_mul(h_ntt).ifft(),
+        None => c - s2_ntt.hadamard_mul(&pk.h.fft()).ifft(),
+    };
+
+    sig.s1 == should_be_s1
+}
+
+/// An expanded signature together with the hashed message, for checking
+/// the verification equation `s1 + s2·h = c` at single points.
+///
+/// The equation holds if and only if it holds at each of the N roots of
+/// X^N + 1, i.e., in every entry of the NTT. Instead of computing all
+/// entries with NTTs, [`LazyExpandedSignature::check`] evaluates the four
+/// polynomials at the one root of the entry with Horner's rule, in O(N)
+/// operations, so checking a few entries is cheap.
+///
+/// This is not a drop-in replacement for [`fverify`]. An index selects an
+/// entry of the NTT here, but a coefficient there, so the same indices
+/// test different things, and their soundness differs:
+///
+/// - A wrong `s1` is caught by [`fverify`] only at the coefficients where
+///   it is wrong. A forger who can match `c - s2·h` in most coefficients
+///   with short values of `s1` thus passes with fewer checks, which is
+///   what [`fverify_required_indices`] estimates.
+/// - Any error in the coefficients spreads over the entries of the NTT: a
+///   nonzero difference `d = s1 + s2·h - c` vanishes in an entry only where
+///   its root is a root of `d`. Making `d` vanish in chosen entries while
+///   keeping `s1` and `s2` short is a linear condition modulo q on short
+///   vectors, i.e., a lattice problem, not a matter of picking `s1`
+///   coefficient by coefficient. For an honest mistake, e.g., a single
+///   wrong coefficient, or an unrelated message, every or almost every
+///   entry mismatches.
+///
+/// For uniformly random indices, [`fverify_false_accept_probability`]
+/// gives the acceptance probability in terms of the number of mismatching
+/// entries of the NTT, but the estimate of [`fverify_required_indices`]
+/// does not apply.
+#[derive(Debug, Clone)]
+pub struct LazyExpandedSignature<'a, const N: usize> {
+    sig: &'a ExpandedSignature<N>,
+    pk: &'a PublicKey<N>,
+    c: Polynomial<Felt>,
+}
+
+impl<'a, const N: usize> LazyExpandedSignature<'a, N> {
+    /// Hash the message; nothing else is computed until asked for.
+    pub fn new(m: &[u8], sig: &'a ExpandedSignature<N>, pk: &'a PublicKey<N>) -> Self {
+        Self {
+            sig,
+            pk,
+            c: sig.hash_cache.hash_message(&sig.r, &[m], N),
+        }
+    }
+
+    /// Test whether entry `i` of the NTT of `s1 + s2·h` is that of `c`, by
+    /// evaluating the polynomials at the root `ψ^(2·rev(i) + 1)`; see
+    /// [`PublicKey::ntt_coefficients`].
+    ///
+    /// # Panics
+    ///
+    /// If `i` is not less than N.
+    pub fn check(&self, i: usize) -> bool {
+        assert!(i < N, "index out of range");
+        let x = felt_ntt_point(i, N);
+        self.sig.s1.evaluate(&x) + self.sig.s2.evaluate(&x) * self.pk.h.evaluate(&x)
+            == self.c.evaluate(&x)
+    }
+
+    /// Check the norm, and then that the equation holds in the entries of
+    /// the NTT selected by `indices`, with [`LazyExpandedSignature::check`].
+    /// Unlike the indices of [`fverify`], these are not coefficients; see
+    /// [`LazyExpandedSignature`]. Fails if an index is not less than N.
+    pub fn verify_entries(&self, indices: &[usize]) -> bool {
+        let params = FalconVariant::from_n(N).parameters();
+        let length_squared = self
+            .sig
+            .s1
+            .coefficients
+            .iter()
+            .chain(&self.sig.s2.coefficients)
+            .map(|i| i.balanced_value() as i64)
+            .map(|i| i * i)
+            .sum::<i64>();
+        length_squared < params.sig_bound
+            && indices.iter().all(|&i| i < N)
+            && indices.iter().all(|&i| self.check(i))
+    }
+}
+
+/// Research mode: check a signature against only the given coefficients of
+/// `s1 = c - s2·h`, each computed directly from the compressed signature in
+/// O(N) operations, without expanding the signature or transforming the
+/// public key.
+///
+/// The check is that `s2` decodes and that the norm of `s2` together with
+/// the selected coefficients of `s1` is within the bound. This is necessary
+/// for validity, so every valid signature passes. It is not sufficient,
+/// but for a signature not produced with the secret key the coefficients
+/// of `s1` are essentially uniform modulo q, and a few dozen of them exceed
+/// the bound with overwhelming probability. Duplicate indices count once.
+///
+/// # Panics
+///
+/// If an index is not less than N.
+pub fn sparse_verify<const N: usize>(
+    m: &[u8],
+    sig: &Signature<N>,
+    pk: &PublicKey<N>,
+    indices: &[usize],
+) -> bool {
+    let params = FalconVariant::from_n(N).parameters();
+    let s2 = match decompress(&sig.s, N) {
+        Some(s2) => Polynomial::new(s2.into_iter().map(Felt::new).collect_vec()),
+        None => return false,
+    };
+    let mut length_squared = s2
+        .coefficients
+        .iter()
+        .map(|i| i.balanced_value() as i64)
+        .map(|i| i * i)
+        .sum::<i64>();
+    if length_squared >= params.sig_bound {
+        return false;
+    }
+
+    let c = sig.hash_cache.hash_message(&sig.r, &[m], N);
+    for i in indices.iter().copied().sorted().dedup() {
+        let s1_i = c.coefficients[i] - s2.mul_coeff(&pk.h, i, N);
+        length_squared += (s1_i.balanced_value() as i64).pow(2);
+        if length_squared >= params.sig_bound {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Fast verify a signature given by its encoding, in any format that
+/// [`Signature::from_bytes`] accepts, without expanding it: decompress `s2`
+/// and compute only the coefficients of `s1 = c - s2·h` selected by
+/// `indices`, like [`sparse_verify`].
+///
+/// There is no stored `s1` to compare against, as there is for [`fverify`],
+/// so the check is the norm of `s2` together with the selected coefficients
+/// of `s1`. Every valid signature passes. For any other signature, the
+/// selected coefficients are essentially uniform modulo q, so it fails
+/// with overwhelming probability once a few dozen are selected, but may
+/// pass with a handful.
+///
+/// Fails if the encoding is malformed or an index is not less than N.
+pub fn fverify_direct<const N: usize>(
+    m: &[u8],
+    sig: &[u8],
+    pk: &PublicKey<N>,
+    indices: &[usize],
+) -> bool {
+    indices.iter().all(|&i| i < N)
+        && Signature::<N>::from_bytes(sig).is_ok_and(|sig| sparse_verify(m, &sig, pk, indices))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeSet, HashSet};
+
+    use itertools::Itertools;
+    use rand::{rngs::StdRng, thread_rng, Rng, RngCore, SeedableRng};
+    use subtle::ConstantTimeEq;
+
+    use crate::{
+        encoding::compress,
+        falcon::{
+            consistent, fverify, fverify_direct, is_weak_seed, keygen, keygen_with_rng, sign,
+            sign_bounded, sign_ctx, sign_prehash, sign_vectored, sign_verified, sign_with,
+            sign_with_expanded_key, sign_with_rng, sign_with_scratch, sign_with_scratch_and_rng,
+            sign_with_seed, sparse_verify, try_keygen, try_keygen_versioned, try_keygen_with_stats,
+            try_sign_with_rng, try_sign_with_seed, verify, verify_any, verify_ctx, verify_expanded,
+            verify_prehash, verify_raw, verify_vectored, verify_with_details, ExpandedSecretKey,
+            ExpandedSignature, FalconDeserializationError, FalconError, FalconVariant, KeySeed,
+            KeygenStats, KeygenVersion, LazyExpandedSignature, PrehashAlgorithm, SignError,
+            SignScratch, Signature, SignatureFormat, SigningOptions, WeakSeedError,
+        },
+        falcon_field::{Felt, Q},
+        fast_fft::{felt_ntt_point, FastFft},
+        nonce::Nonce,
+        polynomial::{hash_to_point, Polynomial},
+    };
+
+    use super::{PublicKey, SecretKey};
+
+    #[test]
+    fn test_operation_falcon_512() {
+        let mut rng = thread_rng();
+        let mut msg = [0u8; 5];
+        rng.fill_bytes(&mut msg);
+
+        println!("testing small scheme ...");
+        const N: usize = 512;
+        println!("-> keygen ...");
+        let (sk, pk) = keygen::<N>(rng.gen());
+        println!("-> sign ...");
+        let sig = sign::<N>(&msg, &sk);
+        println!("-> verify ...");
+        assert!(verify::<N>(&msg, &sig, &pk));
+        println!("-> ok.");
+        let expanded_sig = super::ExpandedSignature::from_signature(msg, &sig, &pk);
+
+        println!(
+            "Falcon-512 Signature Size (serialized): {} bytes",
+            sig.to_bytes().len()
+        );
+
+        let s1_coeffs: Vec<i16> = expanded_sig
+            .s1
+            .coefficients
+            .iter()
+            .map(|c| c.balanced_value())
+            .collect();
+        let s2_coeffs: Vec<i16> = expanded_sig
+            .s2
+            .coefficients
+            .iter()
+            .map(|c| c.balanced_value())
+            .collect();
+
+        let s1_compressed = crate::encoding::compress(&s1_coeffs, 4096).unwrap();
+        let s2_compressed = crate::encoding::compress(&s2_coeffs, 4096).unwrap();
+
+        let s1_len =
+            s1_compressed.len() - s1_compressed.iter().rev().take_while(|&&x| x == 0).count();
+        let s2_len =
+            s2_compressed.len() - s2_compressed.iter().rev().take_while(|&&x| x == 0).count();
+
+        println!(
+            "Falcon-512 Expanded Signature Size (compressed): {} bytes (s1: {}, s2: {}, salt: 40)",
+            s1_len + s2_len + 40,
+            s1_len,
+            s2_len
+        );
+
+        let indices: Vec<usize> = (0..N).step_by(10).collect();
+        assert!(super::fverify::<N>(&msg, &expanded_sig, &pk, &indices));
+        println!("-> fverify ok.");
+        assert!(super::fverify_fullverify::<N>(
+            &msg,
+            &expanded_sig,
+            &pk,
+            &indices
+        ));
+        println!("-> fverify_fullverify ok.");
+    }
+
+    #[test]
+    fn test_stalling_operation_falcon_1024() {
+        // let seed: [u8; 32] = [
+        //     119, 186, 1, 120, 0, 255, 165, 121, 56, 149, 105, 255, 53, 63, 192, 102, 231, 197, 233,
+        //     249, 212, 179, 1, 18, 33, 42, 137, 10, 172, 179, 168, 35,
+        // ];
+        let seed: [u8; 32] = thread_rng().gen();
+        println!("seed: {:2?}", seed);
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+        let mut msg = [0u8; 5];
+        rng.fill_bytes(&mut msg);
+        println!("testing big scheme ...");
+        const N: usize = 1024;
+        println!("-> keygen ...");
+        let (sk, pk) = keygen::<N>(rng.gen());
+        println!("-> sign ...");
+        let sig = sign::<N>(&msg, &sk);
+        println!("-> verify ...");
+        assert!(verify::<N>(&msg, &sig, &pk));
+        println!("-> ok.");
+
+        let expanded_sig = super::ExpandedSignature::from_signature(msg, &sig, &pk);
+        println!(
+            "Falcon-1024 Signature Size (serialized): {} bytes",
+            sig.to_bytes().len()
+        );
+
+        let s1_coeffs: Vec<i16> = expanded_sig
+            .s1
+            .coefficients
+            .iter()
+            .map(|c| c.balanced_value())
+            .collect();
+        let s2_coeffs: Vec<i16> = expanded_sig
+            .s2
+            .coefficients
+            .iter()
+            .map(|c| c.balanced_value())
+            .collect();
 
-    if length_squared >= params.sig_bound {
-        return false;
+        // Use a large buffer to ensure it fits (e.g., 2048*2 bytes since coeffs are small)
+        let s1_compressed = crate::encoding::compress(&s1_coeffs, 8192).unwrap();
+        let s2_compressed = crate::encoding::compress(&s2_coeffs, 8192).unwrap();
+
+        let s1_len =
+            s1_compressed.len() - s1_compressed.iter().rev().take_while(|&&x| x == 0).count();
+        let s2_len =
+            s2_compressed.len() - s2_compressed.iter().rev().take_while(|&&x| x == 0).count();
+
+        println!(
+            "Falcon-1024 Expanded Signature Size (compressed): {} bytes (s1: {}, s2: {}, salt: 40)",
+            s1_len + s2_len + 40,
+            s1_len,
+            s2_len
+        );
     }
 
-    let r_cat_m = [sig.r.to_vec(), m.to_vec()].concat();
-    let c = hash_to_point(&r_cat_m, n);
+    #[test]
+    fn test_sign_with_seed_is_deterministic() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let msg = b"deterministic";
+        let seed: [u8; 32] = rng.gen();
+
+        let sig = sign_with_seed::<512>(msg, &sk, seed);
+        assert!(verify::<512>(msg, &sig, &pk));
+        assert_eq!(sig, sign_with_seed::<512>(msg, &sk, seed));
+        assert_ne!(sig, sign_with_seed::<512>(msg, &sk, rng.gen()));
+    }
 
-    // s1 + s2 * pk.h = c
-    // only check for selected indices
-    for &i in indices {
-        let should_be_ci = sig.s1.coefficients[i] + sig.s2.mul_coeff(&pk.h, i, n);
-        if should_be_ci != c.coefficients[i] {
-            return false;
+    #[test]
+    fn test_with_rng() {
+        let seed: [u8; 32] = thread_rng().gen();
+        let (sk, pk) = keygen_with_rng::<512, _>(&mut StdRng::from_seed(seed));
+        assert_eq!(
+            (sk.clone(), pk.clone()),
+            keygen_with_rng(&mut StdRng::from_seed(seed))
+        );
+        assert_ne!(pk, keygen_with_rng::<512, _>(&mut thread_rng()).1);
+
+        let msg = b"injected randomness";
+        let sig = sign_with_rng(msg, &sk, &mut StdRng::from_seed(seed));
+        assert!(verify(msg, &sig, &pk));
+        assert_eq!(sig, sign_with_seed(msg, &sk, seed));
+        assert!(verify(
+            msg,
+            &sign_with_rng(msg, &sk, &mut thread_rng()),
+            &pk
+        ));
+    }
+
+    #[test]
+    fn test_sign_bounded() {
+        let (sk, pk) = keygen::<512>(thread_rng().gen());
+        let msg = b"within budget";
+        let (sig, attempts) = sign_bounded(msg, &sk, 1000).unwrap();
+        assert!(verify(msg, &sig, &pk));
+        assert!((1..=1000).contains(&attempts));
+        assert_eq!(
+            sign_bounded(msg, &sk, 0),
+            Err(SignError::AttemptLimitExceeded)
+        );
+        if let Ok((sig, attempts)) = sign_bounded(msg, &sk, 1) {
+            assert_eq!(attempts, 1);
+            assert!(verify(msg, &sig, &pk));
         }
     }
 
-    // s1 + s2 * pk.h = c
-    // do the full check
-    let s2_ntt = Polynomial::new(sig.s2.coefficients.clone()).fft();
-    let h_ntt = pk.h.fft();
+    #[test]
+    fn test_sign_verified() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let msg = b"double-checked";
+        let sig = sign_verified(msg, &sk, &pk).unwrap();
+        assert!(verify(msg, &sig, &pk));
 
-    // s1 = c - s2 * pk.h;
-    let should_be_s1 = c - s2_ntt.hadamard_mul(&h_ntt).ifft();
+        let (_, other_pk) = keygen::<512>(rng.gen());
+        assert_eq!(
+            sign_verified(msg, &sk, &other_pk),
+            Err(SignError::FaultDetected)
+        );
+    }
 
-    sig.s1 == should_be_s1
-}
+    #[test]
+    fn test_verify_with_details() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let msg = b"telemetry";
+        let sig = sign(msg, &sk);
+        let details = verify_with_details(msg, &sig, &pk).unwrap();
+        assert!(details.is_valid());
+        assert_eq!(details.bound, 34034726);
+        assert!(details.norm_squared > 0 && details.ratio() < 1.0);
+
+        let details = verify_with_details(b"another message", &sig, &pk).unwrap();
+        assert!(!details.is_valid());
+        assert!(details.ratio() > 1.0);
+
+        let (sk, pk) = keygen::<1024>(rng.gen());
+        let details = verify_with_details(msg, &sign(msg, &sk), &pk).unwrap();
+        assert!(details.is_valid());
+        assert_eq!(details.bound, 70265242);
+    }
 
-#[cfg(test)]
-mod test {
-    use itertools::Itertools;
-    use rand::{rngs::StdRng, thread_rng, Rng, RngCore, SeedableRng};
+    #[test]
+    fn test_public_hash_to_point() {
+        let (sk, _) = keygen::<512>(thread_rng().gen());
+        let msg = b"challenge";
+        let sig = sign(msg, &sk);
+        let c = super::hash_to_point::<512>(msg, &sig.nonce());
+        let expected = hash_to_point(&[&sig.r, msg.as_slice()].concat(), 512);
+        assert_eq!(c, expected.map(|x| x.value() as u16));
+        assert!(c.coefficients.iter().all(|&x| (x as u32) < Q));
+
+        let c = super::hash_to_point::<1024>(msg, &sig.nonce());
+        let expected = hash_to_point(&[&sig.r, msg.as_slice()].concat(), 1024);
+        assert_eq!(c, expected.map(|x| x.value() as u16));
+    }
 
-    use crate::{
-        encoding::compress,
-        falcon::{keygen, sign, verify, FalconVariant, Signature},
-        falcon_field::Felt,
-        polynomial::{hash_to_point, Polynomial},
-    };
+    #[test]
+    fn test_verify_raw() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let (_, other_pk) = keygen::<512>(rng.gen());
+        let msg = b"hashed once";
+        let sig = sign(msg, &sk);
+        let c = super::hash_to_point::<512>(msg, &sig.nonce());
+        assert!(verify_raw(&c, &sig, &pk));
+        assert!(!verify_raw(&c, &sig, &other_pk));
+
+        let other_c = super::hash_to_point::<512>(b"another message", &sig.nonce());
+        assert!(!verify_raw(&other_c, &sig, &pk));
+        let mut out_of_range = c.clone();
+        out_of_range.coefficients[0] += Q as u16;
+        assert!(!verify_raw(&out_of_range, &sig, &pk));
+        let short = Polynomial::new(c.coefficients[..256].to_vec());
+        assert!(!verify_raw(&short, &sig, &pk));
+    }
+
+    #[test]
+    fn test_signing_options() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let msg = b"options";
+
+        let sig = sign_with::<512>(msg, &sk, &SigningOptions::new()).unwrap();
+        assert!(verify(msg, &sig, &pk));
+
+        let deterministic = SigningOptions::new().deterministic(true);
+        let sig = sign_with::<512>(msg, &sk, &deterministic).unwrap();
+        assert!(verify(msg, &sig, &pk));
+        assert_eq!(sig, sign_with::<512>(msg, &sk, &deterministic).unwrap());
+        assert_ne!(
+            sig,
+            sign_with::<512>(b"other", &sk, &deterministic).unwrap()
+        );
+
+        let with_context = deterministic.context(b"context");
+        let sig = sign_with::<512>(msg, &sk, &with_context).unwrap();
+        assert!(verify_ctx(msg, b"context", &sig, &pk));
+        assert!(!verify_ctx(msg, b"other", &sig, &pk));
+        assert!(!verify_ctx(msg, b"", &sig, &pk));
+        assert!(!verify(msg, &sig, &pk));
+        // the same framing as sign_ctx
+        assert!(verify_vectored(&[&[0, 7], b"context", msg], &sig, &pk));
+        let sig = sign_with::<512>(msg, &sk, &SigningOptions::new().context(b"")).unwrap();
+        assert!(verify_ctx(msg, b"", &sig, &pk));
+        assert!(!verify(msg, &sig, &pk));
+
+        let long_context = [0u8; 256];
+        assert_eq!(
+            sign_with::<512>(msg, &sk, &SigningOptions::new().context(&long_context)),
+            Err(SignError::ContextTooLong)
+        );
+        assert_eq!(
+            sign_with::<512>(msg, &sk, &SigningOptions::new().attempt_limit(0)),
+            Err(SignError::AttemptLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn test_message_lengths() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let large = (0..4 << 20).map(|_| rng.gen()).collect::<Vec<u8>>();
+
+        let sig = sign([], &sk);
+        assert!(verify(b"", &sig, &pk));
+        assert!(!verify([0], &sig, &pk));
+        let expanded = ExpandedSignature::from_signature("", &sig, &pk);
+        assert!(verify_expanded(b"", &expanded, &pk));
+
+        let sig = sign(&large, &sk);
+        assert!(verify(&large, &sig, &pk));
+        assert!(!verify(&large[1..], &sig, &pk));
+        let expanded = ExpandedSignature::from_signature(&large, &sig, &pk);
+        assert!(verify_expanded(&large, &expanded, &pk));
+
+        // owned and borrowed messages of any type
+        let sig = sign(String::from("message"), &sk);
+        assert!(verify("message", &sig, &pk));
+        assert!(verify(*b"message", &sig, &pk));
+    }
+
+    #[test]
+    fn test_sign_ctx() {
+        let (sk, pk) = keygen::<512>(thread_rng().gen());
+        let msg = b"deploy";
+        let sig = sign_ctx(msg, b"production", &sk).unwrap();
+        assert!(verify_ctx(msg, b"production", &sig, &pk));
+        assert!(!verify_ctx(msg, b"staging", &sig, &pk));
+        assert!(!verify_ctx(b"other", b"production", &sig, &pk));
+        assert!(!verify(msg, &sig, &pk));
+        assert!(verify_vectored(&[&[0, 10], b"production", msg], &sig, &pk));
+
+        let sig = sign_ctx(msg, b"", &sk).unwrap();
+        assert!(verify_ctx(msg, b"", &sig, &pk));
+        assert!(!verify(msg, &sig, &pk));
+
+        let long_context = [0u8; 256];
+        assert_eq!(
+            sign_ctx(msg, &long_context, &sk),
+            Err(SignError::ContextTooLong)
+        );
+        assert!(!verify_ctx(msg, &long_context, &sig, &pk));
+    }
+
+    #[test]
+    fn test_sign_prehash() {
+        let (sk, pk) = keygen::<512>(thread_rng().gen());
+        let msg = b"a large file";
+        for ph in [
+            PrehashAlgorithm::Sha3_256,
+            PrehashAlgorithm::Sha3_512,
+            PrehashAlgorithm::Shake128,
+            PrehashAlgorithm::Shake256,
+        ] {
+            let digest = ph.digest(msg);
+            assert_eq!(digest.len(), ph.digest_len());
+            let sig = sign_prehash(ph, &digest, b"ctx", &sk).unwrap();
+            assert!(verify_prehash(ph, &digest, b"ctx", &sig, &pk));
+            assert!(!verify_prehash(ph, &digest, b"", &sig, &pk));
+            assert!(!verify_prehash(ph, &ph.digest(b"other"), b"ctx", &sig, &pk));
+            assert!(!verify_ctx(&digest, b"ctx", &sig, &pk));
+            assert!(!verify(msg, &sig, &pk));
+            let oid = ph.oid();
+            assert!(verify_vectored(
+                &[&[1, 3], b"ctx", &oid, &digest],
+                &sig,
+                &pk
+            ));
+        }
+
+        // the hash function is bound into the signature
+        let digest = PrehashAlgorithm::Sha3_512.digest(msg);
+        let sig = sign_prehash(PrehashAlgorithm::Sha3_512, &digest, b"", &sk).unwrap();
+        assert!(!verify_prehash(
+            PrehashAlgorithm::Shake256,
+            &digest,
+            b"",
+            &sig,
+            &pk
+        ));
+        assert_eq!(
+            sign_prehash(PrehashAlgorithm::Sha3_512, &digest[..32], b"", &sk),
+            Err(SignError::DigestLengthMismatch)
+        );
+        assert_eq!(
+            PrehashAlgorithm::Sha3_512.oid(),
+            hex::decode("060960864801650304020a").unwrap().as_slice()
+        );
+    }
+    #[test]
+    fn test_byte_conversions() {
+        let (sk, pk) = keygen::<512>(thread_rng().gen());
+        let sig = sign::<512>(b"conversions", &sk);
+
+        let sk_bytes: [u8; 1281] = (&sk).into();
+        let pk_bytes: [u8; 897] = (&pk).into();
+        let sig_bytes: [u8; 666] = (&sig).into();
+        assert_eq!(SecretKey::<512>::try_from(sk_bytes).unwrap(), sk);
+        assert_eq!(PublicKey::<512>::try_from(&pk_bytes).unwrap(), pk);
+        assert_eq!(Signature::<512>::try_from(sig_bytes).unwrap(), sig);
+
+        assert_eq!(pk.as_bytes(), pk_bytes);
+        assert_eq!(sig.as_ref(), sig_bytes);
+
+        let pk_vec: Vec<u8> = (&pk).into();
+        assert_eq!(PublicKey::<512>::try_from(pk_vec.as_slice()).unwrap(), pk);
+        assert_eq!(
+            PublicKey::<1024>::try_from(pk_vec.as_slice()),
+            Err(FalconDeserializationError::WrongVariant)
+        );
+        assert_eq!(
+            Signature::<512>::try_from(&sig_bytes[..40]),
+            Err(FalconDeserializationError::CannotInferFalconVariant)
+        );
+    }
+
+    #[test]
+    fn test_vectored_messages() {
+        let (sk, pk) = keygen::<512>(thread_rng().gen());
+        let header = b"header";
+        let payload = b"payload";
+        let concatenated = [header.as_slice(), payload].concat();
+
+        let sig = sign_vectored::<512>(&[header, payload], &sk);
+        assert!(verify_vectored(&[header, payload], &sig, &pk));
+        assert!(verify_vectored(
+            &[&concatenated[..3], &concatenated[3..]],
+            &sig,
+            &pk
+        ));
+        assert!(verify(&concatenated, &sig, &pk));
+        assert!(!verify_vectored(&[payload, header], &sig, &pk));
+
+        let sig = sign::<512>(&concatenated, &sk);
+        assert!(verify_vectored(&[header, payload], &sig, &pk));
+        assert!(verify_vectored(&[], &sign::<512>(b"", &sk), &pk));
+    }
+
+    #[test]
+    fn test_byte_lengths() {
+        assert_eq!(PublicKey::<512>::BYTE_LEN, 897);
+        assert_eq!(PublicKey::<1024>::BYTE_LEN, 1793);
+        assert_eq!(SecretKey::<512>::BYTE_LEN, 1281);
+        assert_eq!(SecretKey::<1024>::BYTE_LEN, 2305);
+        assert_eq!(Signature::<512>::BYTE_LEN, 666);
+        assert_eq!(Signature::<1024>::BYTE_LEN, 1280);
+
+        let (sk, pk) = keygen::<512>(thread_rng().gen());
+        let sig = sign::<512>(b"lengths", &sk);
+        assert_eq!(sk.to_bytes().len(), SecretKey::<512>::BYTE_LEN);
+        assert_eq!(pk.as_bytes().len(), PublicKey::<512>::BYTE_LEN);
+        assert_eq!(sig.as_bytes().len(), Signature::<512>::BYTE_LEN);
+    }
+
+    #[test]
+    fn test_weak_seeds() {
+        let mut seed = [0u8; 32];
+        assert!(is_weak_seed(&seed));
+        assert!(is_weak_seed(&[0xff; 32]));
+        seed[..16].copy_from_slice(b"0123456789abcdef");
+        seed[16..].copy_from_slice(b"0123456789abcdef");
+        assert!(is_weak_seed(&seed));
+        seed[31] = b'x';
+        assert!(!is_weak_seed(&seed));
+
+        assert_eq!(try_keygen::<512>([7u8; 32]).err(), Some(WeakSeedError));
+        assert_eq!(
+            SecretKey::<512>::try_generate_from_seed([0u8; 32]).err(),
+            Some(WeakSeedError)
+        );
+        // the infallible functions do not check the seed
+        let (sk, pk) = keygen::<512>([0u8; 32]);
+        assert_eq!(sk, SecretKey::generate_from_seed([0u8; 32]));
+        let sig = sign_with_seed(b"unchecked", &sk, [0u8; 32]);
+        assert!(verify(b"unchecked", &sig, &pk));
+
+        let mut rng = thread_rng();
+        let (sk, pk) = try_keygen::<512>(rng.gen()).unwrap();
+        assert_eq!(
+            try_sign_with_seed(b"weak", &sk, [0u8; 32]),
+            Err(WeakSeedError)
+        );
+        let sig = try_sign_with_seed(b"strong", &sk, rng.gen()).unwrap();
+        assert!(verify(b"strong", &sig, &pk));
+    }
+
+    #[test]
+    fn test_sign_with_scratch() {
+        fn check<const N: usize>() {
+            let mut rng = thread_rng();
+            let (sk, pk) = keygen::<N>(rng.gen());
+            let esk = ExpandedSecretKey::new(&sk);
+            let mut scratch = SignScratch::new();
+            for _ in 0..3 {
+                let msg: [u8; 5] = rng.gen();
+                let sig = sign_with_scratch(&msg, &esk, &mut scratch);
+                assert!(verify(msg, &sig, &pk));
+
+                // same randomness, same signature
+                let seed: [u8; 32] = rng.gen();
+                let expected =
+                    try_sign_with_rng(&[&msg], &sk, &mut StdRng::from_seed(seed), None).unwrap();
+                let sig = sign_with_scratch_and_rng(
+                    &msg,
+                    &esk,
+                    &mut scratch,
+                    &mut StdRng::from_seed(seed),
+                );
+                assert_eq!(sig, expected);
+            }
+        }
+        check::<512>();
+        check::<1024>();
+    }
+
+    #[test]
+    fn test_secret_key_public_key() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        assert_eq!(sk.public_key(), pk);
+        let sk = SecretKey::<512>::from_bytes(&sk.to_bytes()).unwrap();
+        assert_eq!(sk.public_key(), pk);
+        let (sk, pk) = keygen::<1024>(rng.gen());
+        assert_eq!(sk.public_key(), pk);
+    }
+
+    #[test]
+    fn test_sign_with_expanded_key() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let esk = ExpandedSecretKey::from(&sk);
+        assert_eq!(esk, ExpandedSecretKey::new(&sk));
+        for _ in 0..3 {
+            let msg: [u8; 5] = rng.gen();
+            let sig = sign_with_expanded_key(&msg, &esk);
+            assert!(verify(msg, &sig, &pk));
+        }
+    }
+
+    #[cfg(feature = "encrypted-keys")]
+    #[test]
+    fn test_encrypted_expanded_secret_key() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let esk = ExpandedSecretKey::new(&sk);
+        let key: [u8; 32] = rng.gen();
+
+        let bytes = esk.to_encrypted_bytes(&key);
+        assert_eq!(bytes.len(), ExpandedSecretKey::<512>::ENCRYPTED_BYTE_LEN);
+        assert_ne!(bytes, esk.to_encrypted_bytes(&key));
+        let loaded = ExpandedSecretKey::<512>::from_encrypted_bytes(&bytes, &key).unwrap();
+        assert_eq!(loaded, esk);
+        assert_eq!(loaded.b_fft, esk.b_fft);
+
+        // the loaded key signs exactly like the original
+        let seed: [u8; 32] = rng.gen();
+        let mut scratch = SignScratch::new();
+        let expected =
+            sign_with_scratch_and_rng(b"msg", &esk, &mut scratch, &mut StdRng::from_seed(seed));
+        let sig =
+            sign_with_scratch_and_rng(b"msg", &loaded, &mut scratch, &mut StdRng::from_seed(seed));
+        assert_eq!(sig, expected);
+        assert!(verify(b"msg", &sig, &pk));
+        assert_eq!(
+            try_sign_with_rng(
+                &[b"msg"],
+                loaded.secret_key(),
+                &mut StdRng::from_seed(seed),
+                None
+            ),
+            try_sign_with_rng(&[b"msg"], &sk, &mut StdRng::from_seed(seed), None)
+        );
+
+        let mut wrong_key = key;
+        wrong_key[0] ^= 1;
+        assert_eq!(
+            ExpandedSecretKey::<512>::from_encrypted_bytes(&bytes, &wrong_key),
+            Err(FalconDeserializationError::DecryptionFailed)
+        );
+        let mut tampered = bytes.clone();
+        tampered[100] ^= 1;
+        assert_eq!(
+            ExpandedSecretKey::<512>::from_encrypted_bytes(&tampered, &key),
+            Err(FalconDeserializationError::DecryptionFailed)
+        );
+        let mut tampered = bytes.clone();
+        tampered[1] = 1;
+        assert_eq!(
+            ExpandedSecretKey::<512>::from_encrypted_bytes(&tampered, &key),
+            Err(FalconDeserializationError::UnsupportedFormatVersion)
+        );
+        assert_eq!(
+            ExpandedSecretKey::<1024>::from_encrypted_bytes(&bytes, &key),
+            Err(FalconDeserializationError::WrongVariant)
+        );
+        assert_eq!(
+            ExpandedSecretKey::<512>::from_encrypted_bytes(&bytes[..1], &key),
+            Err(FalconDeserializationError::BadEncodingLength)
+        );
+    }
+
+    #[cfg(feature = "encrypted-keys")]
+    #[test]
+    fn test_encrypted_expanded_secret_key_tampering() {
+        let mut rng = thread_rng();
+        let (sk, _) = keygen::<512>(rng.gen());
+        let key: [u8; 32] = rng.gen();
+        let bytes = ExpandedSecretKey::new(&sk).to_encrypted_bytes(&key);
+
+        // truncated anywhere after the header
+        for len in [2, 26, bytes.len() / 2, bytes.len() - 16, bytes.len() - 1] {
+            assert_eq!(
+                ExpandedSecretKey::<512>::from_encrypted_bytes(&bytes[..len], &key),
+                Err(FalconDeserializationError::DecryptionFailed)
+            );
+        }
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            ExpandedSecretKey::<512>::from_encrypted_bytes(&extended, &key),
+            Err(FalconDeserializationError::DecryptionFailed)
+        );
+
+        // a single bit flipped in the nonce, the ciphertext or the tag
+        for i in [
+            2,
+            25,
+            26,
+            bytes.len() / 2,
+            bytes.len() - 17,
+            bytes.len() - 1,
+        ] {
+            let mut tampered = bytes.clone();
+            tampered[i] ^= 1 << rng.gen_range(0..8);
+            assert_eq!(
+                ExpandedSecretKey::<512>::from_encrypted_bytes(&tampered, &key),
+                Err(FalconDeserializationError::DecryptionFailed)
+            );
+        }
+    }
+
+    #[test]
+    fn test_public_key_coefficients() {
+        let (_, pk) = keygen::<512>(thread_rng().gen());
+        let coefficients = pk.coefficients();
+        let centered = pk.centered_coefficients();
+        assert_eq!(coefficients.len(), 512);
+        for (&c, &centered) in coefficients.iter().zip(centered.iter()) {
+            assert!(u32::from(c) < Q);
+            assert!(centered.unsigned_abs() as u32 <= (Q - 1) / 2);
+            assert_eq!((i32::from(centered) - i32::from(c)).rem_euclid(Q as i32), 0);
+        }
+
+        let h_ntt = Polynomial::new(
+            pk.ntt_coefficients()
+                .iter()
+                .map(|&c| Felt::new(c as i16))
+                .collect(),
+        );
+        assert_eq!(h_ntt.ifft(), pk.h);
+        assert_eq!(PublicKey::new(h_ntt.ifft()), pk);
+    }
+
+    #[test]
+    fn test_fixed_point_signatures() {
+        use sha3::{Digest, Sha3_256};
+
+        // Golden values, which signing must reproduce bit for bit both with
+        // and without the `fixed-point` feature.
+        fn digest<const N: usize>() -> String {
+            let (sk, pk) = try_keygen::<N>(Sha3_256::digest(b"key").into()).unwrap();
+            let mut hasher = Sha3_256::new();
+            for i in 0..16u8 {
+                let msg = [i; 3];
+                let seed = Sha3_256::digest(msg).into();
+                let sig = try_sign_with_seed(&msg, &sk, seed).unwrap();
+                assert!(verify(msg, &sig, &pk));
+                hasher.update(sig.to_bytes());
+            }
+            hex::encode(hasher.finalize())
+        }
+        assert_eq!(
+            digest::<512>(),
+            "0992408129b0aab18254a4911d78e8daa351191b0ffdc922ec5b3769ea0c1f5f"
+        );
+        assert_eq!(
+            digest::<1024>(),
+            "4f36f418a0ad473abe03552d809dcfe51a7632675e80a0963f04482223feef32"
+        );
+    }
+
+    #[test]
+    fn test_keygen_versions() {
+        use sha3::{Digest, Sha3_256};
+
+        // Golden values: if these change, key generation has changed and
+        // needs a new `KeygenVersion` instead.
+        let seed: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let digest = |bytes: &[u8]| hex::encode(Sha3_256::digest(bytes));
+        let (sk, pk) = try_keygen_versioned::<512>(KeygenVersion::V1, seed).unwrap();
+        assert_eq!(
+            digest(&sk.to_bytes()),
+            "213e22d338957083c83ba150c8357e25bb96ba56b0a1dfaa74144982ea14ef52"
+        );
+        assert_eq!(
+            digest(pk.as_bytes()),
+            "550ad44fba3fd937fc39fd5b659bfb725c40637225930a08e8cb8808efecff27"
+        );
+        let (sk, pk) = try_keygen_versioned::<1024>(KeygenVersion::V1, seed).unwrap();
+        assert_eq!(
+            digest(&sk.to_bytes()),
+            "96cf7f1f9a202a00363507f857fcf385128531a9032e0c8f19decca58fd3186a"
+        );
+        assert_eq!(
+            digest(pk.as_bytes()),
+            "9cc0e0a112a9b3d4169717cbeae7aa806ecddbe3f47047a6e6135ddd5000fd8d"
+        );
+
+        let key_seed = KeySeed::new(seed);
+        assert_eq!(key_seed.version(), KeygenVersion::CURRENT);
+        let bytes = key_seed.to_bytes();
+        assert_eq!(bytes[0], 1);
+        assert_eq!(KeySeed::from_bytes(&bytes), Ok(key_seed.clone()));
+        assert_eq!(key_seed.keygen::<512>(), Ok(keygen::<512>(seed)));
+        assert_eq!(format!("{key_seed:?}"), "KeySeed { version: V1, .. }");
+
+        let mut bytes = bytes.to_vec();
+        bytes[0] = 0;
+        assert_eq!(
+            KeySeed::from_bytes(&bytes),
+            Err(FalconDeserializationError::UnsupportedKeygenVersion)
+        );
+        assert_eq!(
+            KeySeed::from_bytes(&bytes[1..]),
+            Err(FalconDeserializationError::BadEncodingLength)
+        );
+    }
+
+    #[test]
+    fn test_keygen_stats() {
+        let mut rng = thread_rng();
+        let seed: [u8; 32] = rng.gen();
+        let mut stats = KeygenStats::default();
+        let (sk, pk) = try_keygen_with_stats::<512>(seed, &mut stats).unwrap();
+        assert_eq!((sk, pk), keygen::<512>(seed));
+        assert!(stats.candidates >= 1);
+        assert_eq!(stats.candidates, stats.rejected() + 1);
+
+        // counters accumulate
+        let first = stats;
+        let key_seed = KeySeed::new(rng.gen());
+        key_seed.keygen_with_stats::<512>(&mut stats).unwrap();
+        assert!(stats.candidates > first.candidates);
+        assert_eq!(stats.candidates, stats.rejected() + 2);
+
+        assert_eq!(
+            try_keygen_with_stats::<512>([7u8; 32], &mut stats),
+            Err(WeakSeedError)
+        );
+        assert_eq!(stats.candidates, stats.rejected() + 2);
+    }
+
+    #[test]
+    fn test_reference_randomness() {
+        use std::collections::HashMap;
+
+        use crate::records::parse_records;
+
+        let text = include_str!("../fixtures/reference-signatures.txt");
+        let records = parse_records(
+            text,
+            |line| line,
+            |fields, _| {
+                Ok(fields
+                    .iter()
+                    .map(|field| (field.key, field.value))
+                    .collect::<HashMap<_, _>>())
+            },
+        )
+        .unwrap();
+        assert_eq!(records.len(), 4);
+        for record in records {
+            let field = |key: &str| record[key];
+            let bytes = |key: &str| hex::decode(field(key)).unwrap();
+            let (msg, seed, expected) = (bytes("msg"), bytes("seed"), bytes("sig"));
+            let nonce = Nonce::from_bytes(&bytes("nonce")).unwrap();
+            let options = SigningOptions::new().reference_randomness(&nonce, &seed);
+            let sig = match field("variant") {
+                "512" => {
+                    let sk = SecretKey::<512>::from_bytes(&bytes("sk")).unwrap();
+                    let sig = sign_with(&msg, &sk, &options).unwrap();
+                    assert!(verify(&msg, &sig, &PublicKey::from_secret_key(&sk)));
+                    sig.to_reference_bytes()
+                }
+                _ => {
+                    let sk = SecretKey::<1024>::from_bytes(&bytes("sk")).unwrap();
+                    sign_with(&msg, &sk, &options).unwrap().to_reference_bytes()
+                }
+            };
+            assert_eq!(sig, expected);
+        }
+    }
 
-    use super::{PublicKey, SecretKey};
+    #[test]
+    fn test_signature_formats() {
+        fn check<const N: usize>() {
+            let (sk, _) = keygen::<N>(thread_rng().gen());
+            let sig = sign(b"formats", &sk);
+            let reference = sig.to_reference_bytes();
+            let padded = sig.to_bytes_with_format(SignatureFormat::Padded).unwrap();
+            assert_eq!(padded.len(), Signature::<N>::BYTE_LEN);
+            assert_eq!(padded[0], 0x30 | N.ilog2() as u8);
+            assert_eq!(&padded[..reference.len()], reference);
+            assert!(padded[reference.len()..].iter().all(|&b| b == 0));
+
+            let ct = sig.to_bytes_with_format(SignatureFormat::Ct).unwrap();
+            assert_eq!(ct.len(), Signature::<N>::CT_BYTE_LEN);
+            assert_eq!(ct[0], 0x50 | N.ilog2() as u8);
+
+            for format in [
+                SignatureFormat::Standard,
+                SignatureFormat::Compressed,
+                SignatureFormat::Padded,
+                SignatureFormat::Ct,
+            ] {
+                let bytes = sig.to_bytes_with_format(format).unwrap();
+                assert_eq!(Signature::<N>::from_bytes(&bytes).unwrap(), sig);
+                assert_eq!(
+                    Signature::<N>::from_bytes_with_format(&bytes, format).unwrap(),
+                    sig
+                );
+            }
+            assert_eq!(
+                Signature::<N>::from_bytes_with_format(&sig.to_bytes(), SignatureFormat::Padded),
+                Err(FalconDeserializationError::InvalidHeaderFormat)
+            );
+            assert_eq!(
+                Signature::<N>::from_bytes_with_format(&padded, SignatureFormat::Standard),
+                Err(FalconDeserializationError::InvalidHeaderFormat)
+            );
+            assert_eq!(
+                Signature::<N>::from_bytes_with_format(&ct, SignatureFormat::Standard),
+                Err(FalconDeserializationError::BadEncodingLength)
+            );
+            assert_eq!(
+                Signature::<N>::from_bytes_with_format(&sig.to_bytes(), SignatureFormat::Ct),
+                Err(FalconDeserializationError::BadEncodingLength)
+            );
+            if reference.len() < padded.len() {
+                assert_eq!(
+                    Signature::<N>::from_bytes_with_format(&reference, SignatureFormat::Padded),
+                    Err(FalconDeserializationError::BadEncodingLength)
+                );
+            }
+        }
+        check::<512>();
+        check::<1024>();
+    }
 
     #[test]
-    fn test_operation_falcon_512() {
+    fn test_hash_to_point_cache() {
         let mut rng = thread_rng();
-        let mut msg = [0u8; 5];
-        rng.fill_bytes(&mut msg);
-
-        println!("testing small scheme ...");
-        const N: usize = 512;
-        println!("-> keygen ...");
-        let (sk, pk) = keygen::<N>(rng.gen());
-        println!("-> sign ...");
-        let sig = sign::<N>(&msg, &sk);
-        println!("-> verify ...");
-        assert!(verify::<N>(&msg, &sig, &pk));
-        println!("-> ok.");
-        let expanded_sig = super::ExpandedSignature::from_signature(&msg, &sig, &pk);
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let msg = b"gossiped";
+        let sig = sign(msg, &sk);
+        let fresh = Signature::<512>::from_bytes(&sig.to_bytes()).unwrap();
+        assert!(fresh.hash_cache.0.get().is_none());
+
+        assert!(verify(msg, &fresh, &pk));
+        let (_, c) = fresh.hash_cache.0.get().unwrap();
+        assert_eq!(*c, hash_to_point(&[&sig.r, msg.as_slice()].concat(), 512));
+        assert_eq!(fresh, sig);
+
+        // other messages are hashed afresh and do not evict the first one
+        assert!(!verify(b"other", &fresh, &pk));
+        assert!(verify_vectored(&[b"gossip", b"ed"], &fresh, &pk));
+        assert!(verify(msg, &fresh, &pk));
+
+        let expanded = ExpandedSignature::from_signature(msg, &fresh, &pk);
+        assert!(expanded.hash_cache.0.get().is_some());
+        assert!(verify_expanded(msg, &expanded, &pk));
+        assert!(!verify_expanded(b"other", &expanded, &pk));
+        assert_eq!(expanded, ExpandedSignature::from_signature(msg, &sig, &pk));
+    }
 
-        println!(
-            "Falcon-512 Signature Size (serialized): {} bytes",
-            sig.to_bytes().len()
-        );
+    #[test]
+    fn test_signature_coefficients() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let msg = b"circuit witness";
+        let sig = sign(msg, &sk);
+        let expanded = ExpandedSignature::from_signature(msg, &sig, &pk);
+
+        let s2 = sig.s2().unwrap();
+        assert_eq!(s2, expanded.s2());
+        assert_eq!(sig.s2_reduced().unwrap(), expanded.s2_reduced());
+        for (&centered, &reduced) in s2.iter().zip(sig.s2_reduced().unwrap().iter()) {
+            assert_eq!((centered as i32).rem_euclid(Q as i32), reduced as i32);
+        }
+        let s1 = expanded.s1();
+        for (&centered, &reduced) in s1.iter().zip(expanded.s1_reduced().iter()) {
+            assert_eq!((centered as i32).rem_euclid(Q as i32), reduced as i32);
+        }
 
-        let s1_coeffs: Vec<i16> = expanded_sig
-            .s1
-            .coefficients
-            .iter()
-            .map(|c| c.balanced_value())
-            .collect();
-        let s2_coeffs: Vec<i16> = expanded_sig
-            .s2
-            .coefficients
+        // s1 + s2 * h = c
+        let c = hash_to_point(&[&sig.r, msg.as_slice()].concat(), 512);
+        let s1 = Polynomial::new(s1.iter().map(|&x| Felt::new(x)).collect_vec());
+        let s2 = Polynomial::new(s2.iter().map(|&x| Felt::new(x)).collect_vec());
+        assert_eq!((s1 + (s2 * pk.h.clone()).reduce_by_cyclotomic(512)), c);
+        let norm_squared = expanded
+            .s1()
             .iter()
-            .map(|c| c.balanced_value())
-            .collect();
+            .chain(expanded.s2().iter())
+            .map(|&x| x as i64 * x as i64)
+            .sum::<i64>();
+        assert!(norm_squared < FalconVariant::Falcon512.parameters().sig_bound);
 
-        let s1_compressed = crate::encoding::compress(&s1_coeffs, 4096).unwrap();
-        let s2_compressed = crate::encoding::compress(&s2_coeffs, 4096).unwrap();
+        let malformed = Signature::<512>::new(sig.r, vec![0xff; sig.s.len()]);
+        assert_eq!(malformed.s2(), None);
+        assert_eq!(malformed.s2_reduced(), None);
+    }
 
-        let s1_len =
-            s1_compressed.len() - s1_compressed.iter().rev().take_while(|&&x| x == 0).count();
-        let s2_len =
-            s2_compressed.len() - s2_compressed.iter().rev().take_while(|&&x| x == 0).count();
+    #[test]
+    fn test_sparse_verify() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let (other_sk, _) = keygen::<512>(rng.gen());
+        let msg = b"sparse";
+        let sig = sign(msg, &sk);
+        let forged = sign(msg, &other_sk);
+
+        let all = (0..512).collect_vec();
+        let mut indices = (0..512).step_by(16).collect_vec();
+        indices.extend([3, 3, 511]);
+        assert!(sparse_verify(msg, &sig, &pk, &all));
+        assert!(sparse_verify(msg, &sig, &pk, &indices));
+        assert!(sparse_verify(msg, &sig, &pk, &[]));
+        assert!(!sparse_verify(msg, &forged, &pk, &indices));
+        assert!(!sparse_verify(b"other", &sig, &pk, &indices));
+
+        // with all coefficients, this is the norm check of `verify`
+        assert!(!sparse_verify(msg, &forged, &pk, &all));
+
+        let malformed = Signature::<512>::new(sig.r, vec![0xff; sig.s.len()]);
+        assert!(!sparse_verify(msg, &malformed, &pk, &indices));
+
+        let (sk, pk) = keygen::<1024>(rng.gen());
+        let sig = sign(msg, &sk);
+        let indices = (0..1024).step_by(32).collect_vec();
+        assert!(sparse_verify(msg, &sig, &pk, &indices));
+        assert!(!sparse_verify(b"other", &sig, &pk, &indices));
+    }
 
-        println!(
-            "Falcon-512 Expanded Signature Size (compressed): {} bytes (s1: {}, s2: {}, salt: 40)",
-            s1_len + s2_len + 40,
-            s1_len,
-            s2_len
-        );
+    #[test]
+    fn test_fverify_direct() {
+        fn check<const N: usize>() {
+            let mut rng = thread_rng();
+            let (sk, pk) = keygen::<N>(rng.gen());
+            let (other_sk, _) = keygen::<N>(rng.gen());
+            let msg = b"direct";
+            let sig = sign(msg, &sk);
+            let mut tampered_nonce = sig.to_bytes();
+            tampered_nonce[1] ^= 1;
+            let tampered_nonce = Signature::<N>::from_bytes(&tampered_nonce).unwrap();
+
+            let some = (0..N).step_by(N / 32).collect_vec();
+            let all = (0..N).collect_vec();
+            for indices in [&some, &all] {
+                // both accept the valid signature
+                let expanded = ExpandedSignature::from_signature(msg, &sig, &pk);
+                assert!(fverify(msg, &expanded, &pk, indices));
+                assert!(fverify_direct(msg, sig.as_bytes(), &pk, indices));
+                assert!(fverify_direct(msg, &sig.to_reference_bytes(), &pk, indices));
+
+                // and both reject a wrong message, a wrong signer and a
+                // tampered nonce
+                assert!(!fverify(b"other", &expanded, &pk, indices));
+                assert!(!fverify_direct(b"other", sig.as_bytes(), &pk, indices));
+                for forged in [sign(msg, &other_sk), tampered_nonce.clone()] {
+                    let expanded = ExpandedSignature::from_signature(msg, &forged, &pk);
+                    assert!(!fverify(msg, &expanded, &pk, indices));
+                    assert!(!fverify_direct(msg, forged.as_bytes(), &pk, indices));
+                }
+            }
 
-        let indices: Vec<usize> = (0..N).step_by(10).collect();
-        assert!(super::fverify::<N>(&msg, &expanded_sig, &pk, &indices));
-        println!("-> fverify ok.");
-        assert!(super::fverify_fullverify::<N>(
-            &msg,
-            &expanded_sig,
-            &pk,
-            &indices
-        ));
-        println!("-> fverify_fullverify ok.");
+            assert!(fverify_direct(msg, sig.as_bytes(), &pk, &[]));
+            assert!(!fverify_direct(msg, sig.as_bytes(), &pk, &[N]));
+            assert!(!fverify_direct(msg, &sig.as_bytes()[1..], &pk, &some));
+            let malformed = Signature::<N>::new(sig.r, vec![0xff; sig.s.len()]);
+            assert!(!fverify_direct(msg, malformed.as_bytes(), &pk, &some));
+        }
+
+        check::<512>();
+        check::<1024>();
     }
 
     #[test]
-    fn test_stalling_operation_falcon_1024() {
-        // let seed: [u8; 32] = [
-        //     119, 186, 1, 120, 0, 255, 165, 121, 56, 149, 105, 255, 53, 63, 192, 102, 231, 197, 233,
-        //     249, 212, 179, 1, 18, 33, 42, 137, 10, 172, 179, 168, 35,
-        // ];
-        let seed: [u8; 32] = thread_rng().gen();
-        println!("seed: {:2?}", seed);
-        let mut rng: StdRng = SeedableRng::from_seed(seed);
+    fn test_consistent() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let (other_sk, other_pk) = keygen::<512>(rng.gen());
+        assert!(consistent(&sk, &pk));
+        assert!(consistent(&other_sk, &other_pk));
+        assert!(!consistent(&sk, &other_pk));
+        assert!(!consistent(&other_sk, &pk));
+
+        // corrupt F, which the public key does not depend on
+        let mut bytes = sk.to_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let corrupted = SecretKey::<512>::from_bytes(&bytes).unwrap();
+        assert_eq!(PublicKey::from_secret_key(&corrupted), pk);
+        assert!(!consistent(&corrupted, &pk));
+    }
 
-        let mut msg = [0u8; 5];
-        rng.fill_bytes(&mut msg);
-        println!("testing big scheme ...");
-        const N: usize = 1024;
-        println!("-> keygen ...");
-        let (sk, pk) = keygen::<N>(rng.gen());
-        println!("-> sign ...");
-        let sig = sign::<N>(&msg, &sk);
-        println!("-> verify ...");
-        assert!(verify::<N>(&msg, &sig, &pk));
-        println!("-> ok.");
+    #[test]
+    fn test_validate() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let (_, other_pk) = keygen::<512>(rng.gen());
+        assert_eq!(sk.validate(&pk), Ok(()));
+        assert_eq!(sk.validate(&other_pk), Err(FalconError::PublicKeyMismatch));
+
+        let mut bytes = sk.to_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let corrupted = SecretKey::<512>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            corrupted.validate(&pk),
+            Err(FalconError::NtruEquationUnsatisfied)
+        );
+    }
 
-        let expanded_sig = super::ExpandedSignature::from_signature(&msg, &sig, &pk);
-        println!(
-            "Falcon-1024 Signature Size (serialized): {} bytes",
-            sig.to_bytes().len()
+    #[test]
+    fn test_constant_time_eq() {
+        let mut rng = thread_rng();
+        let seed = rng.gen();
+        let (sk, pk) = keygen::<512>(seed);
+        let (other_sk, other_pk) = keygen::<512>(rng.gen());
+        assert!(bool::from(sk.ct_eq(&keygen::<512>(seed).0)));
+        assert!(!bool::from(sk.ct_eq(&other_sk)));
+        assert!(bool::from(pk.ct_eq(&pk.clone())));
+        assert!(!bool::from(pk.ct_eq(&other_pk)));
+        assert_eq!(sk, SecretKey::from_bytes(&sk.to_bytes()).unwrap());
+        assert_ne!(sk, other_sk);
+
+        // keys that differ in a single coefficient
+        let mut bytes = sk.to_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let corrupted = SecretKey::<512>::from_bytes(&bytes).unwrap();
+        assert!(!bool::from(sk.ct_eq(&corrupted)));
+        assert_ne!(
+            ExpandedSecretKey::new(&sk),
+            ExpandedSecretKey::new(&corrupted)
         );
 
-        let s1_coeffs: Vec<i16> = expanded_sig
-            .s1
-            .coefficients
+        let sig = sign(b"msg", &sk);
+        assert!(bool::from(sig.ct_eq(&sig.clone())));
+        assert!(!bool::from(sig.ct_eq(&sign(b"msg", &sk))));
+
+        let key_seed = KeySeed::new(seed);
+        assert_eq!(key_seed, KeySeed::new(seed));
+        assert_ne!(key_seed, KeySeed::new(rng.gen()));
+    }
+
+    #[test]
+    fn test_expose_secret() {
+        let (sk, _) = keygen::<512>(thread_rng().gen());
+        let polynomials = sk.expose_secret();
+        assert_eq!(format!("{polynomials:?}"), "SecretPolynomials { .. }");
+        assert_eq!(polynomials.g, sk.b0[0].coefficients);
+        assert_eq!(polynomials.capital_g, sk.b0[2].coefficients);
+        assert!(polynomials
+            .f
             .iter()
-            .map(|c| c.balanced_value())
-            .collect();
-        let s2_coeffs: Vec<i16> = expanded_sig
-            .s2
-            .coefficients
+            .zip(&sk.b0[1].coefficients)
+            .all(|(f, minus_f)| *f == -minus_f));
+        assert!(polynomials
+            .capital_f
             .iter()
-            .map(|c| c.balanced_value())
-            .collect();
+            .zip(&sk.b0[3].coefficients)
+            .all(|(f, minus_f)| *f == -minus_f));
+        assert_eq!(polynomials.f.len(), 512);
+    }
 
-        // Use a large buffer to ensure it fits (e.g., 2048*2 bytes since coeffs are small)
-        let s1_compressed = crate::encoding::compress(&s1_coeffs, 8192).unwrap();
-        let s2_compressed = crate::encoding::compress(&s2_coeffs, 8192).unwrap();
+    #[test]
+    fn test_verify_any() {
+        let mut rng = thread_rng();
+        let (old_sk, old_pk) = keygen::<512>(rng.gen());
+        let (new_sk, new_pk) = keygen::<512>(rng.gen());
+        let (other_sk, _) = keygen::<512>(rng.gen());
+        let pks = [old_pk, new_pk];
+        let msg = b"rotation";
+
+        assert_eq!(verify_any(msg, &sign(msg, &old_sk), &pks), Some(0));
+        assert_eq!(verify_any(msg, &sign(msg, &new_sk), &pks), Some(1));
+        assert_eq!(verify_any(msg, &sign(msg, &other_sk), &pks), None);
+        assert_eq!(verify_any(b"other", &sign(msg, &new_sk), &pks), None);
+        assert_eq!(verify_any(msg, &sign(msg, &new_sk), &[]), None);
+    }
 
-        let s1_len =
-            s1_compressed.len() - s1_compressed.iter().rev().take_while(|&&x| x == 0).count();
-        let s2_len =
-            s2_compressed.len() - s2_compressed.iter().rev().take_while(|&&x| x == 0).count();
+    #[test]
+    fn test_formatting() {
+        let (sk, pk) = keygen::<512>(thread_rng().gen());
+        let sig = sign::<512>(b"formatting", &sk);
 
-        println!(
-            "Falcon-1024 Expanded Signature Size (compressed): {} bytes (s1: {}, s2: {}, salt: 40)",
-            s1_len + s2_len + 40,
-            s1_len,
-            s2_len
+        let debug = format!("{:?}", sk);
+        let fingerprint = pk.fingerprint().short();
+        assert_eq!(
+            debug,
+            format!("SecretKey {{ n: 512, fingerprint: {fingerprint}, .. }}")
         );
+
+        assert_eq!(format!("{}", pk), hex::encode(pk.to_bytes()));
+        assert_eq!(format!("{:x}", pk), hex::encode(pk.to_bytes()));
+        assert_eq!(format!("{}", sig), hex::encode(sig.to_bytes()));
+        assert_eq!(format!("{:x}", sig), hex::encode(sig.to_bytes()));
+    }
+
+    #[test]
+    fn test_hash_and_ord() {
+        let mut rng = thread_rng();
+        let (sk0, pk0) = keygen::<512>(rng.gen());
+        let (_, pk1) = keygen::<512>(rng.gen());
+        let msg = b"hash and ord";
+        let sig0 = sign::<512>(msg, &sk0);
+        let sig1 = sign::<512>(msg, &sk0);
+
+        let signatures = HashSet::from([sig0.clone(), sig1.clone(), sig0.clone()]);
+        assert_eq!(signatures.len(), 2);
+        let expanded = HashSet::from([
+            ExpandedSignature::from_signature(msg, &sig0, &pk0),
+            ExpandedSignature::from_signature(msg, &sig0, &pk0),
+        ]);
+        assert_eq!(expanded.len(), 1);
+
+        assert_eq!(pk0.cmp(&pk1), pk0.to_bytes().cmp(&pk1.to_bytes()),);
+        assert_eq!(sig0.cmp(&sig1), sig0.to_bytes().cmp(&sig1.to_bytes()),);
+        let keys = BTreeSet::from([pk1.clone(), pk0.clone(), pk1.clone()]);
+        assert_eq!(keys.len(), 2);
     }
 
     #[test]
@@ -1019,14 +4794,14 @@ mod test {
             214, -42, 110, -324, -34, 158, -102, -4, -61, -117, -134, -310, -99, 79, -308, -306,
             -199, -126, -190, 27, -43, 120, 94, 340, -435, -99, 167, 210, -70, -84, 199,
         ];
-        let sig = Signature {
-            r: nonce.try_into().unwrap(),
-            s: compress(
+        let sig = Signature::new(
+            nonce.try_into().unwrap(),
+            compress(
                 &expected_signature_vector,
                 (FalconVariant::from_n(512).parameters().sig_bytelen - 41) * 8,
             )
             .unwrap(),
-        };
+        );
 
         // We can't recreate this signature because we do not have the seed that generated
         // it.
@@ -1364,14 +5139,14 @@ mod test {
             -258, 55, -130, 190, -133, -34, 121, -293, -124, -130, -98, 20, -56, -9, 21, -266, -12,
             -59,
         ];
-        let sig = Signature::<1024> {
-            r: nonce.try_into().unwrap(),
-            s: compress(
+        let sig = Signature::<1024>::new(
+            nonce.try_into().unwrap(),
+            compress(
                 &signature_vector,
                 (FalconVariant::Falcon1024.parameters().sig_bytelen - 41) * 8,
             )
             .unwrap(),
-        };
+        );
 
         let pk = PublicKey::from_secret_key(&sk);
         assert!(verify::<1024>(&data, &sig, &pk));
@@ -1486,14 +5261,14 @@ mod test {
         let n = 1024;
         let sigvec = signature_vector(n);
         let nonce = [0u8; 40];
-        let original_signature = Signature::<1024> {
-            r: nonce,
-            s: compress(
+        let original_signature = Signature::<1024>::new(
+            nonce,
+            compress(
                 &sigvec,
                 FalconVariant::Falcon1024.parameters().sig_bytelen - 41,
             )
             .unwrap(),
-        };
+        );
 
         let serialized = original_signature.to_bytes();
         assert_eq!(
@@ -1507,14 +5282,14 @@ mod test {
         let n = 512;
         let sigvec = signature_vector(n);
         let nonce = [0u8; 40];
-        let original_signature = Signature::<512> {
-            r: nonce,
-            s: compress(
+        let original_signature = Signature::<512>::new(
+            nonce,
+            compress(
                 &sigvec,
                 FalconVariant::Falcon512.parameters().sig_bytelen - 41,
             )
             .unwrap(),
-        };
+        );
 
         let serialized = original_signature.to_bytes();
         assert_eq!(
@@ -1533,14 +5308,14 @@ mod test {
         let n = 512;
         let sigvec = signature_vector(n);
         let nonce = [0u8; 40];
-        let original_signature = Signature::<512> {
-            r: nonce,
-            s: compress(
+        let original_signature = Signature::<512>::new(
+            nonce,
+            compress(
                 &sigvec,
                 FalconVariant::Falcon512.parameters().sig_bytelen - 41,
             )
             .unwrap(),
-        };
+        );
         let mut serialized = original_signature.to_bytes();
 
         // try every byte of header
@@ -1754,4 +5529,422 @@ mod test {
                 .collect_vec()
         );
     }
+
+    #[test]
+    fn test_expanded_signature_bytes() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let sig = sign(b"msg", &sk);
+        let expanded = ExpandedSignature::from_signature(b"msg", &sig, &pk);
+
+        let bytes = expanded.to_bytes();
+        assert_eq!(bytes.len(), ExpandedSignature::<512>::BYTE_LEN);
+        let decoded = ExpandedSignature::<512>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, expanded);
+        assert!(verify_expanded(b"msg", &decoded, &pk));
+        assert!(fverify(b"msg", &decoded, &pk, &[0, 17, 511]));
+        assert!(!fverify(b"other", &decoded, &pk, &(0..16).collect_vec()));
+        assert_eq!(
+            ExpandedSignature::<1024>::from_bytes(&bytes),
+            Err(FalconDeserializationError::WrongVariant)
+        );
+
+        let mut bad_header = bytes.clone();
+        bad_header[0] = 0x39;
+        assert_eq!(
+            ExpandedSignature::<512>::from_bytes(&bad_header),
+            Err(FalconDeserializationError::InvalidHeaderFormat)
+        );
+        let mut unreduced = bytes;
+        unreduced[41..43].copy_from_slice(&(Q as u16).to_be_bytes());
+        assert_eq!(
+            ExpandedSignature::<512>::from_bytes(&unreduced),
+            Err(FalconDeserializationError::BadFieldElementEncoding)
+        );
+    }
+
+    #[test]
+    fn test_lazy_expanded_signature() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let msg = b"lazily expanded";
+        let sig = ExpandedSignature::from_signature(msg, &sign(msg, &sk), &pk);
+        let lazy = LazyExpandedSignature::new(msg, &sig, &pk);
+        assert!((0..512).all(|i| lazy.check(i)));
+        assert!(lazy.verify_entries(&[0, 3, 100, 511]));
+        assert!(!lazy.verify_entries(&[512]));
+
+        // the evaluation points are those of the NTT
+        let s1_ntt = sig.s1.fft();
+        for i in [0, 1, 255, 511] {
+            assert_eq!(
+                sig.s1.evaluate(&felt_ntt_point(i, 512)),
+                s1_ntt.coefficients[i]
+            );
+        }
+        let s2 = Polynomial::new(
+            (0..1024)
+                .map(|_| Felt::new(rng.gen_range(-50..50)))
+                .collect(),
+        );
+        let s2_ntt = s2.fft();
+        for i in [0, 1, 700, 1023] {
+            assert_eq!(
+                s2.evaluate(&felt_ntt_point(i, 1024)),
+                s2_ntt.coefficients[i]
+            );
+        }
+
+        // for another message, the equation fails at almost every point
+        let lazy = LazyExpandedSignature::new(b"other", &sig, &pk);
+        assert!((0..512).filter(|&i| lazy.check(i)).count() < 8);
+        assert!(!lazy.verify_entries(&[0, 1, 2, 3]));
+
+        // so does a signature with a tampered s1
+        let mut tampered = sig.clone();
+        tampered.s1.coefficients[7] += Felt::new(1);
+        let lazy = LazyExpandedSignature::new(msg, &tampered, &pk);
+        assert!((0..512).all(|i| !lazy.check(i)));
+        // whereas fverify notices it only at that coefficient
+        assert!(!lazy.verify_entries(&[0, 3, 100, 511]));
+        assert!(fverify(msg, &tampered, &pk, &[0, 3, 100, 511]));
+        assert!(!fverify(msg, &tampered, &pk, &[7]));
+
+        let (sk, pk) = keygen::<1024>(rng.gen());
+        let sig = ExpandedSignature::from_signature(msg, &sign(msg, &sk), &pk);
+        assert!(LazyExpandedSignature::new(msg, &sig, &pk).verify_entries(&[5, 1000]));
+    }
+
+    #[test]
+    fn test_batch_from_signatures() {
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let msgs = (0..8).map(|i| vec![i as u8; i + 1]).collect_vec();
+        let sigs = msgs.iter().map(|m| sign(m, &sk)).collect_vec();
+        let expanded = ExpandedSignature::batch_from_signatures(&msgs, &sigs, &pk).unwrap();
+        assert_eq!(expanded.len(), 8);
+        for ((m, sig), expanded) in msgs.iter().zip(&sigs).zip(&expanded) {
+            assert_eq!(expanded, &ExpandedSignature::from_signature(m, sig, &pk));
+            assert!(verify_expanded(m, expanded, &pk));
+        }
+        #[cfg(feature = "rayon")]
+        assert_eq!(
+            ExpandedSignature::par_batch_from_signatures(&msgs, &sigs, &pk),
+            Ok(expanded)
+        );
+        assert_eq!(
+            ExpandedSignature::<512>::batch_from_signatures::<&[u8]>(&[], &[], &pk),
+            Ok(vec![])
+        );
+
+        // a malformed s2 fails the batch instead of panicking
+        let mut sigs = sigs;
+        let mut corrupt = sigs[3].to_bytes();
+        corrupt[41..].fill(0);
+        sigs[3] = Signature::from_bytes(&corrupt).unwrap();
+        assert_eq!(sigs[3].s2(), None);
+        assert_eq!(
+            ExpandedSignature::batch_from_signatures(&msgs, &sigs, &pk),
+            Err(FalconDeserializationError::BadFieldElementEncoding)
+        );
+        #[cfg(feature = "rayon")]
+        assert_eq!(
+            ExpandedSignature::par_batch_from_signatures(&msgs, &sigs, &pk),
+            Err(FalconDeserializationError::BadFieldElementEncoding)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use super::ExpandedPublicKey;
+
+        fn round_trip<T>(value: &T)
+        where
+            T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+        {
+            let json = serde_json::to_string(value).unwrap();
+            assert_eq!(&serde_json::from_str::<T>(&json).unwrap(), value);
+
+            // ciborium reads byte strings up to the length of its scratch
+            // buffer, which by default is too short for expanded keys
+            let mut cbor = vec![];
+            ciborium::into_writer(value, &mut cbor).unwrap();
+            let decoded: T =
+                ciborium::from_reader_with_buffer(cbor.as_slice(), &mut [0; 8192]).unwrap();
+            assert_eq!(&decoded, value);
+        }
+
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<1024>(rng.gen());
+        let sig = sign(b"msg", &sk);
+        round_trip(&sk);
+        round_trip(&pk);
+        round_trip(&sig);
+        round_trip(&ExpandedPublicKey::new(&pk));
+        round_trip(&ExpandedSignature::from_signature(b"msg", &sig, &pk));
+
+        assert_eq!(
+            serde_json::to_string(&pk).unwrap(),
+            format!("\"{}\"", hex::encode(pk.to_bytes()))
+        );
+        let json = serde_json::to_string(&sig).unwrap();
+        assert!(serde_json::from_str::<Signature<512>>(&json).is_err());
+    }
+
+    #[test]
+    fn test_zeroize() {
+        use zeroize::{Zeroize, ZeroizeOnDrop};
+
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<SecretKey<512>>();
+        assert_zeroize_on_drop::<ExpandedSecretKey<1024>>();
+        assert_zeroize_on_drop::<SignScratch<512>>();
+
+        let sk = SecretKey::<512>::generate();
+        let mut tree = sk.tree.clone();
+        tree.zeroize();
+        let mut values = vec![];
+        tree.flatten(&mut values);
+        // the polynomials are emptied and the leaves cleared
+        assert_eq!(values.len(), 2 * 512);
+        assert!(values.iter().all(|v| v.re == 0.0 && v.im == 0.0));
+
+        let mut b0 = sk.b0.clone();
+        for b in b0.iter_mut() {
+            b.zeroize();
+            assert!(b.coefficients.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_try_verify() {
+        use super::{try_fverify, try_verify, try_verify_bytes, try_verify_expanded, FalconError};
+
+        let (sk, pk) = keygen::<512>(thread_rng().gen());
+        let sig = sign(b"msg", &sk);
+        assert_eq!(try_verify(b"msg", &sig, &pk), Ok(()));
+        assert_eq!(
+            try_verify(b"other", &sig, &pk),
+            Err(FalconError::NormBoundExceeded)
+        );
+
+        let bytes = sig.to_bytes();
+        assert_eq!(try_verify_bytes(b"msg", &bytes, &pk), Ok(()));
+        assert_eq!(
+            try_verify_bytes(b"msg", &bytes[..30], &pk),
+            Err(FalconError::BadNonceLength)
+        );
+        assert_eq!(
+            try_verify_bytes(b"msg", &bytes[..100], &pk),
+            Err(FalconError::MalformedEncoding(
+                FalconDeserializationError::CannotInferFalconVariant
+            ))
+        );
+
+        let expanded = ExpandedSignature::from_signature(b"msg", &sig, &pk);
+        assert_eq!(try_verify_expanded(b"msg", &expanded, &pk), Ok(()));
+        assert!(matches!(
+            try_verify_expanded(b"other", &expanded, &pk),
+            Err(FalconError::IndexMismatch(_))
+        ));
+        assert_eq!(try_fverify(b"msg", &expanded, &pk, &[0, 511]), Ok(()));
+        assert_eq!(
+            try_fverify(b"msg", &expanded, &pk, &[0, 512]),
+            Err(FalconError::IndexOutOfRange(512))
+        );
+        assert!(matches!(
+            try_fverify(b"other", &expanded, &pk, &(0..8).collect_vec()),
+            Err(FalconError::IndexMismatch(_))
+        ));
+    }
+
+    /// The key generation of PQClean, from the seed that
+    /// `crypto_sign_keypair()` draws from `randombytes()`. Returns f, g and
+    /// F.
+    fn reference_keygen(seed: &[u8; 48], logn: u32) -> [Vec<i8>; 3] {
+        #[repr(C)]
+        struct Shake256IncCtx {
+            ctx: [u64; 26],
+        }
+        type Keygen = unsafe extern "C" fn(
+            rng: *mut Shake256IncCtx,
+            f: *mut i8,
+            g: *mut i8,
+            capital_f: *mut i8,
+            capital_g: *mut i8,
+            h: *mut u16,
+            logn: u32,
+            tmp: *mut u8,
+        );
+        extern "C" {
+            fn shake256_inc_init(state: *mut Shake256IncCtx);
+            fn shake256_inc_absorb(state: *mut Shake256IncCtx, input: *const u8, inlen: usize);
+            fn shake256_inc_finalize(state: *mut Shake256IncCtx);
+            fn shake256_inc_ctx_release(state: *mut Shake256IncCtx);
+            fn PQCLEAN_FALCON512_CLEAN_keygen(
+                rng: *mut Shake256IncCtx,
+                f: *mut i8,
+                g: *mut i8,
+                capital_f: *mut i8,
+                capital_g: *mut i8,
+                h: *mut u16,
+                logn: u32,
+                tmp: *mut u8,
+            );
+            fn PQCLEAN_FALCON1024_CLEAN_keygen(
+                rng: *mut Shake256IncCtx,
+                f: *mut i8,
+                g: *mut i8,
+                capital_f: *mut i8,
+                capital_g: *mut i8,
+                h: *mut u16,
+                logn: u32,
+                tmp: *mut u8,
+            );
+        }
+        // make sure the C code is linked
+        let _ = pqcrypto_falcon::falcon512::public_key_bytes();
+
+        let n = 1 << logn;
+        let keygen: Keygen = match logn {
+            9 => PQCLEAN_FALCON512_CLEAN_keygen,
+            _ => PQCLEAN_FALCON1024_CLEAN_keygen,
+        };
+        let [mut f, mut g, mut capital_f, mut capital_g] = [(); 4].map(|_| vec![0i8; n]);
+        let mut h = vec![0u16; n];
+        // FALCON_KEYGEN_TEMP_10, aligned for floating-point values
+        let mut tmp = vec![0u64; 28672 / 8];
+        let mut state = Shake256IncCtx { ctx: [0; 26] };
+        unsafe {
+            shake256_inc_init(&mut state);
+            shake256_inc_absorb(&mut state, seed.as_ptr(), seed.len());
+            shake256_inc_finalize(&mut state);
+            keygen(
+                &mut state,
+                f.as_mut_ptr(),
+                g.as_mut_ptr(),
+                capital_f.as_mut_ptr(),
+                capital_g.as_mut_ptr(),
+                h.as_mut_ptr(),
+                logn,
+                tmp.as_mut_ptr().cast(),
+            );
+            shake256_inc_ctx_release(&mut state);
+        }
+        [f, g, capital_f]
+    }
+
+    #[test]
+    fn test_keygen_from_nist_seed() {
+        use super::keygen_from_nist_seed;
+
+        fn check<const N: usize>(seed: [u8; 48]) {
+            let (sk, pk) = keygen_from_nist_seed::<N>(seed);
+            let [f, g, capital_f] = reference_keygen(&seed, N.ilog2());
+            let to_i8 = |p: &Polynomial<i16>| p.coefficients.iter().map(|&c| c as i8).collect_vec();
+            assert_eq!(to_i8(&-sk.b0[1].clone()), f);
+            assert_eq!(to_i8(&sk.b0[0]), g);
+            assert_eq!(to_i8(&-sk.b0[3].clone()), capital_f);
+            assert!(consistent(&sk, &pk));
+            assert_eq!(keygen_from_nist_seed::<N>(seed).1, pk);
+        }
+
+        let mut rng = thread_rng();
+        for _ in 0..3 {
+            check::<512>(std::array::from_fn(|_| rng.gen()));
+        }
+        check::<1024>(std::array::from_fn(|_| rng.gen()));
+
+        // count = 0 of the NIST KAT files, for which the reference
+        // implementation draws this seed from the DRBG
+        let seed: [u8; 48] = hex::decode(
+            "7C9935A0B07694AA0C6D10E4DB6B1ADD2FD81A25CCB148032DCD739936737F2D\
+             B505D7CFAD1B497499323C8686325E47",
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+        let field = |text: &str, key: &str| {
+            let line = text.lines().find_map(|line| line.strip_prefix(key));
+            hex::decode(line.unwrap()).unwrap()
+        };
+        let kat = include_str!("../fixtures/falcon512-KAT-count0.rsp");
+        let (sk, pk) = keygen_from_nist_seed::<512>(seed);
+        assert_eq!(sk.to_bytes(), field(kat, "sk = "));
+        assert_eq!(pk.to_bytes(), field(kat, "pk = "));
+        let kat = include_str!("../fixtures/falcon1024-KAT-count0.rsp");
+        let (sk, pk) = keygen_from_nist_seed::<1024>(seed);
+        assert_eq!(sk.to_bytes(), field(kat, "sk = "));
+        assert_eq!(pk.to_bytes(), field(kat, "pk = "));
+    }
+
+    #[test]
+    fn test_verify_with_expanded_pk() {
+        use super::{verify_with_expanded_pk, ExpandedPublicKey};
+
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let (_, other_pk) = keygen::<512>(rng.gen());
+        let epk = ExpandedPublicKey::from(&pk);
+        assert_eq!(epk, ExpandedPublicKey::new(&pk));
+        assert_eq!(ExpandedPublicKey::from(pk.clone()), epk);
+        assert!(epk.is_consistent());
+
+        for i in 0..5u8 {
+            let msg = [i; 16];
+            let sig = sign(msg, &sk);
+            assert!(verify_with_expanded_pk(&msg, &sig, &epk));
+            assert!(!verify_with_expanded_pk(&[i + 1; 16], &sig, &epk));
+            assert!(!verify_with_expanded_pk(
+                &msg,
+                &sig,
+                &ExpandedPublicKey::from(&other_pk)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_fverify_fs() {
+        use super::{fiat_shamir_indices, fverify_fs};
+
+        let mut rng = thread_rng();
+        let (sk, pk) = keygen::<512>(rng.gen());
+        let sig = sign(b"msg", &sk);
+        let expanded = ExpandedSignature::from_signature(b"msg", &sig, &pk);
+
+        let indices = fiat_shamir_indices(b"msg", &expanded, &pk, 16);
+        assert_eq!(indices.len(), 16);
+        assert!(indices.iter().all(|&i| i < 512));
+        assert_eq!(indices.iter().collect::<HashSet<_>>().len(), 16);
+        assert_eq!(fiat_shamir_indices(b"msg", &expanded, &pk, 16), indices);
+        assert_ne!(fiat_shamir_indices(b"other", &expanded, &pk, 16), indices);
+        assert_eq!(fiat_shamir_indices(b"msg", &expanded, &pk, 1000).len(), 512);
+
+        assert!(fverify_fs(b"msg", &expanded, &pk, 16));
+        assert!(!fverify_fs(b"other", &expanded, &pk, 16));
+    }
+
+    #[test]
+    fn test_fverify_required_indices() {
+        use super::{fverify_false_accept_probability, fverify_required_indices};
+
+        assert_eq!(fverify_false_accept_probability::<512>(0, 8), 1.0);
+        assert_eq!(fverify_false_accept_probability::<512>(1, 1), 511.0 / 512.0);
+        assert_eq!(fverify_false_accept_probability::<512>(1, 512), 0.0);
+        assert_eq!(
+            fverify_false_accept_probability::<512>(256, 2),
+            0.5 * 255.0 / 511.0
+        );
+
+        assert_eq!(fverify_required_indices::<512>(1.0), 0);
+        assert_eq!(fverify_required_indices::<512>(0.0), 512);
+        for target in [1e-3, 2f64.powi(-64), 2f64.powi(-128)] {
+            let k = fverify_required_indices::<512>(target);
+            let m = fverify_required_indices::<1024>(target);
+            assert!(0 < k && k < 512 && 0 < m && m < 1024);
+            assert!(k < fverify_required_indices::<512>(target / 16.0));
+        }
+        assert!(fverify_required_indices::<512>(2f64.powi(-64)) > 8);
+    }
 }
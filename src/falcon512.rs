@@ -1,35 +1,337 @@
-use crate::falcon::{self};
+use alloc::vec::Vec;
 
+use rand::{CryptoRng, RngCore};
+
+#[cfg(feature = "std")]
+use crate::batch;
+use crate::{
+    derivation, embedded,
+    falcon::{self},
+    generic, hasher,
+    polynomial::Polynomial,
+    shared, signed_message, verifier_context,
+};
+
+pub use crate::{
+    falcon::{
+        is_weak_seed, FalconDeserializationError, FalconError, KeySeed, KeygenStats, KeygenVersion,
+        PrehashAlgorithm, SecretPolynomials, SignError, SignatureFormat, SigningOptions,
+        VerificationDetails, WeakSeedError,
+    },
+    fingerprint::Fingerprint,
+    nonce::Nonce,
+};
+
+pub type Falcon = generic::Falcon<512>;
 pub type SecretKey = falcon::SecretKey<512>;
 pub type PublicKey = falcon::PublicKey<512>;
 pub type Signature = falcon::Signature<512>;
 pub type ExpandedSignature = falcon::ExpandedSignature<512>;
+pub type ExpandedPublicKey = falcon::ExpandedPublicKey<512>;
+pub type ExpandedSecretKey = falcon::ExpandedSecretKey<512>;
+pub type LazyExpandedSignature<'a> = falcon::LazyExpandedSignature<'a, 512>;
+#[cfg(feature = "std")]
+pub type SignScratch = falcon::SignScratch<512>;
+pub type EmbeddedPublicKey = embedded::EmbeddedPublicKey<512>;
+pub type VerifierContext = verifier_context::VerifierContext<512>;
+pub type SharedSignature = shared::SharedSignature<512>;
+pub type SharedExpandedSignature = shared::SharedExpandedSignature<512>;
+pub type SignedMessage = signed_message::SignedMessage<512>;
+pub type Hasher = hasher::Hasher<512>;
+
+/// The length of an encoded public key in bytes.
+pub const PK_LEN: usize = PublicKey::BYTE_LEN;
+/// The length of an encoded secret key in bytes.
+pub const SK_LEN: usize = SecretKey::BYTE_LEN;
+/// The length of a signature as encoded by [`Signature::to_bytes`].
+pub const SIG_LEN: usize = Signature::BYTE_LEN;
+/// The maximum length of an encoded signature. Signatures produced by the
+/// reference implementation can be shorter.
+pub const SIG_MAX_LEN: usize = Signature::BYTE_LEN;
+/// The length of an expanded signature as encoded by
+/// [`ExpandedSignature::to_bytes`].
+pub const EXPANDED_SIG_LEN: usize = ExpandedSignature::BYTE_LEN;
+/// The length of the nonce in bytes.
+pub const NONCE_LEN: usize = Nonce::LEN;
 
 pub fn keygen(seed: [u8; 32]) -> (SecretKey, PublicKey) {
     falcon::keygen(seed)
 }
 
-pub fn sign(msg: &[u8], sk: &SecretKey) -> Signature {
+pub fn keygen_with_rng<R: CryptoRng + RngCore>(rng: &mut R) -> (SecretKey, PublicKey) {
+    falcon::keygen_with_rng(rng)
+}
+
+pub fn try_keygen(seed: [u8; 32]) -> Result<(SecretKey, PublicKey), WeakSeedError> {
+    falcon::try_keygen(seed)
+}
+
+pub fn try_keygen_versioned(
+    version: KeygenVersion,
+    seed: [u8; 32],
+) -> Result<(SecretKey, PublicKey), WeakSeedError> {
+    falcon::try_keygen_versioned(version, seed)
+}
+
+pub fn try_keygen_with_stats(
+    seed: [u8; 32],
+    stats: &mut KeygenStats,
+) -> Result<(SecretKey, PublicKey), WeakSeedError> {
+    falcon::try_keygen_with_stats(seed, stats)
+}
+
+pub fn keygen_from_nist_seed(seed: [u8; 48]) -> (SecretKey, PublicKey) {
+    falcon::keygen_from_nist_seed(seed)
+}
+
+pub fn consistent(sk: &SecretKey, pk: &PublicKey) -> bool {
+    falcon::consistent(sk, pk)
+}
+
+pub fn derive_child(master_seed: &[u8; 32], path: &[&str]) -> (SecretKey, PublicKey) {
+    derivation::derive_child(master_seed, path)
+}
+
+#[cfg(feature = "bip39")]
+pub fn keygen_from_mnemonic(
+    mnemonic: &crate::mnemonic::Mnemonic,
+    passphrase: &str,
+) -> (SecretKey, PublicKey) {
+    crate::mnemonic::keygen_from_mnemonic(mnemonic, passphrase)
+}
+
+#[cfg(feature = "std")]
+pub fn sign(msg: impl AsRef<[u8]>, sk: &SecretKey) -> Signature {
     falcon::sign(msg, sk)
 }
 
-pub fn verify(msg: &[u8], sig: &Signature, pk: &PublicKey) -> bool {
+pub fn sign_with_seed(msg: &[u8], sk: &SecretKey, seed: [u8; 32]) -> Signature {
+    falcon::sign_with_seed(msg, sk, seed)
+}
+
+pub fn sign_with_rng<R: CryptoRng + RngCore>(msg: &[u8], sk: &SecretKey, rng: &mut R) -> Signature {
+    falcon::sign_with_rng(msg, sk, rng)
+}
+
+pub fn try_sign_with_seed(
+    msg: &[u8],
+    sk: &SecretKey,
+    seed: [u8; 32],
+) -> Result<Signature, WeakSeedError> {
+    falcon::try_sign_with_seed(msg, sk, seed)
+}
+
+#[cfg(feature = "std")]
+pub fn sign_with_scratch(
+    msg: &[u8],
+    esk: &ExpandedSecretKey,
+    scratch: &mut SignScratch,
+) -> Signature {
+    falcon::sign_with_scratch(msg, esk, scratch)
+}
+
+#[cfg(feature = "std")]
+pub fn sign_with_expanded_key(msg: &[u8], esk: &ExpandedSecretKey) -> Signature {
+    falcon::sign_with_expanded_key(msg, esk)
+}
+
+#[cfg(feature = "std")]
+pub fn sign_attached(msg: &[u8], sk: &SecretKey) -> SignedMessage {
+    signed_message::sign_attached(msg, sk)
+}
+
+pub fn open(sm: &[u8], pk: &PublicKey) -> Option<Vec<u8>> {
+    signed_message::open(sm, pk)
+}
+
+#[cfg(feature = "std")]
+pub fn sign_bounded(
+    msg: &[u8],
+    sk: &SecretKey,
+    max_attempts: usize,
+) -> Result<(Signature, usize), SignError> {
+    falcon::sign_bounded(msg, sk, max_attempts)
+}
+
+#[cfg(feature = "std")]
+pub fn sign_verified(msg: &[u8], sk: &SecretKey, pk: &PublicKey) -> Result<Signature, SignError> {
+    falcon::sign_verified(msg, sk, pk)
+}
+
+#[cfg(feature = "std")]
+pub fn sign_vectored(msg: &[&[u8]], sk: &SecretKey) -> Signature {
+    falcon::sign_vectored(msg, sk)
+}
+
+#[cfg(feature = "std")]
+pub fn sign_with(
+    msg: &[u8],
+    sk: &SecretKey,
+    options: &SigningOptions,
+) -> Result<Signature, SignError> {
+    falcon::sign_with(msg, sk, options)
+}
+
+#[cfg(feature = "std")]
+pub fn sign_ctx(msg: &[u8], ctx: &[u8], sk: &SecretKey) -> Result<Signature, SignError> {
+    falcon::sign_ctx(msg, ctx, sk)
+}
+
+#[cfg(feature = "std")]
+pub fn sign_prehash(
+    ph: PrehashAlgorithm,
+    digest: &[u8],
+    ctx: &[u8],
+    sk: &SecretKey,
+) -> Result<Signature, SignError> {
+    falcon::sign_prehash(ph, digest, ctx, sk)
+}
+
+pub fn verify(msg: impl AsRef<[u8]>, sig: &Signature, pk: &PublicKey) -> bool {
     falcon::verify(msg, sig, pk)
 }
 
+pub fn verify_with_expanded_pk(msg: &[u8], sig: &Signature, epk: &ExpandedPublicKey) -> bool {
+    falcon::verify_with_expanded_pk(msg, sig, epk)
+}
+
+pub fn try_verify(msg: &[u8], sig: &Signature, pk: &PublicKey) -> Result<(), FalconError> {
+    falcon::try_verify(msg, sig, pk)
+}
+
+pub fn hash_to_point(msg: &[u8], nonce: &Nonce) -> Polynomial<u16> {
+    falcon::hash_to_point::<512>(msg, nonce)
+}
+
+pub fn verify_raw(c: &Polynomial<u16>, sig: &Signature, pk: &PublicKey) -> bool {
+    falcon::verify_raw(c, sig, pk)
+}
+
+pub fn verify_with_details(
+    msg: &[u8],
+    sig: &Signature,
+    pk: &PublicKey,
+) -> Result<VerificationDetails, FalconError> {
+    falcon::verify_with_details(msg, sig, pk)
+}
+
+pub fn try_verify_bytes(msg: &[u8], sig: &[u8], pk: &PublicKey) -> Result<(), FalconError> {
+    falcon::try_verify_bytes(msg, sig, pk)
+}
+
+#[cfg(feature = "std")]
+pub fn verify_many<M: AsRef<[u8]> + Sync>(items: &[(M, Signature, PublicKey)]) -> Vec<bool> {
+    batch::verify_many(items)
+}
+
+#[cfg(feature = "std")]
+pub fn verify_batch(msgs: &[&[u8]], sigs: &[Signature], pk: &PublicKey) -> Vec<bool> {
+    batch::verify_batch(msgs, sigs, pk)
+}
+
+#[cfg(feature = "std")]
+pub fn verify_batch_multi(msgs: &[&[u8]], sigs: &[Signature], pks: &[PublicKey]) -> Vec<bool> {
+    batch::verify_batch_multi(msgs, sigs, pks)
+}
+
+#[cfg(feature = "std")]
+pub fn verify_batch_until<M: AsRef<[u8]>>(
+    items: &[(M, ExpandedSignature)],
+    pk: &PublicKey,
+    deadline: std::time::Instant,
+) -> Vec<batch::Verdict> {
+    batch::verify_batch_until(items, pk, deadline)
+}
+
+#[cfg(feature = "std")]
+pub fn dedup<M: AsRef<[u8]>>(
+    records: impl IntoIterator<Item = (M, Signature)>,
+) -> impl Iterator<Item = (M, Signature)> {
+    batch::dedup(records)
+}
+
+pub fn verify_any(msg: &[u8], sig: &Signature, pks: &[PublicKey]) -> Option<usize> {
+    falcon::verify_any(msg, sig, pks)
+}
+
+pub fn verify_vectored(msg: &[&[u8]], sig: &Signature, pk: &PublicKey) -> bool {
+    falcon::verify_vectored(msg, sig, pk)
+}
+
+pub fn verify_ctx(msg: &[u8], ctx: &[u8], sig: &Signature, pk: &PublicKey) -> bool {
+    falcon::verify_ctx(msg, ctx, sig, pk)
+}
+
+pub fn verify_prehash(
+    ph: PrehashAlgorithm,
+    digest: &[u8],
+    ctx: &[u8],
+    sig: &Signature,
+    pk: &PublicKey,
+) -> bool {
+    falcon::verify_prehash(ph, digest, ctx, sig, pk)
+}
+
 pub fn verify_expanded(msg: &[u8], sig: &ExpandedSignature, pk: &PublicKey) -> bool {
     falcon::verify_expanded(msg, sig, pk)
 }
 
-pub fn fverify(msg: &[u8], sig: &ExpandedSignature, pk: &PublicKey, indices: &Vec<usize>) -> bool {
+pub fn try_verify_expanded(
+    msg: &[u8],
+    sig: &ExpandedSignature,
+    pk: &PublicKey,
+) -> Result<(), FalconError> {
+    falcon::try_verify_expanded(msg, sig, pk)
+}
+
+pub fn fverify(msg: &[u8], sig: &ExpandedSignature, pk: &PublicKey, indices: &[usize]) -> bool {
     falcon::fverify(msg, sig, pk, indices)
 }
 
+pub fn try_fverify(
+    msg: &[u8],
+    sig: &ExpandedSignature,
+    pk: &PublicKey,
+    indices: &[usize],
+) -> Result<(), FalconError> {
+    falcon::try_fverify(msg, sig, pk, indices)
+}
+
+pub fn fverify_fs(msg: &[u8], sig: &ExpandedSignature, pk: &PublicKey, num_indices: usize) -> bool {
+    falcon::fverify_fs(msg, sig, pk, num_indices)
+}
+
+pub fn fverify_required_indices(target_false_accept: f64) -> usize {
+    falcon::fverify_required_indices::<512>(target_false_accept)
+}
+
 pub fn fverify_fullverify(
     msg: &[u8],
     sig: &ExpandedSignature,
     pk: &PublicKey,
-    indices: &Vec<usize>,
+    indices: &[usize],
 ) -> bool {
     falcon::fverify_fullverify(msg, sig, pk, indices)
 }
+
+#[cfg(feature = "rayon")]
+pub fn par_verify<M: AsRef<[u8]> + Sync>(items: &[(M, Signature)], pk: &PublicKey) -> Vec<usize> {
+    batch::par_verify(items, pk)
+}
+
+#[cfg(feature = "rayon")]
+pub fn par_fverify_fullverify<M: AsRef<[u8]> + Sync>(
+    items: &[(M, ExpandedSignature)],
+    pk: &PublicKey,
+    indices: &[usize],
+) -> Vec<usize> {
+    batch::par_fverify_fullverify(items, pk, indices)
+}
+
+pub fn sparse_verify(msg: &[u8], sig: &Signature, pk: &PublicKey, indices: &[usize]) -> bool {
+    falcon::sparse_verify(msg, sig, pk, indices)
+}
+
+pub fn fverify_direct(msg: &[u8], sig: &[u8], pk: &PublicKey, indices: &[usize]) -> bool {
+    falcon::fverify_direct(msg, sig, pk, indices)
+}
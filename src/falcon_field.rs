@@ -1,5 +1,5 @@
-use std::fmt::Display;
-use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+use core::fmt::Display;
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use rand_distr::{
     num_traits::{One, Zero},
@@ -12,7 +12,8 @@ use crate::inverse::Inverse;
 /// q is the integer modulus which is used in Falcon.
 pub(crate) const Q: u32 = 12 * 1024 + 1;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
 pub(crate) struct Felt(u32);
 
 impl Felt {
@@ -124,7 +125,7 @@ impl Distribution<Felt> for Standard {
 }
 
 impl Display for Felt {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_fmt(format_args!("{}", self.value()))
     }
 }
@@ -1,9 +1,11 @@
-use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use itertools::Itertools;
 use num::{One, Zero};
 use num_complex::Complex64;
 
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+use crate::avx2;
 use crate::{
     cyclotomic_fourier::CyclotomicFourier, falcon_field::Felt, polynomial::Polynomial,
     u32_field::U32Field,
@@ -32,7 +34,7 @@ pub trait FastFft: Sized + Clone {
 }
 
 #[allow(clippy::approx_constant)]
-const COMPLEX_BITREVERSED_POWERS_1024: [Complex64; 1024] = [
+pub(crate) const COMPLEX_BITREVERSED_POWERS_1024: [Complex64; 1024] = [
     Complex64::new(1.0, 0.0),
     Complex64::new(0.00000000000000006123233995736766, 1.0),
     Complex64::new(0.7071067811865476, 0.7071067811865475),
@@ -1062,10 +1064,18 @@ const COMPLEX_BITREVERSED_POWERS_1024: [Complex64; 1024] = [
 impl FastFft for Polynomial<Complex64> {
     type Field = Complex64;
     fn fft_inplace(&mut self) {
+        #[cfg(all(feature = "std", target_arch = "x86_64", not(feature = "fixed-point")))]
+        if avx2::is_available() {
+            return avx2::fft(&mut self.coefficients, &COMPLEX_BITREVERSED_POWERS_1024);
+        }
         Complex64::fft(&mut self.coefficients, &COMPLEX_BITREVERSED_POWERS_1024);
     }
 
     fn ifft_inplace(&mut self) {
+        #[cfg(all(feature = "std", target_arch = "x86_64", not(feature = "fixed-point")))]
+        if avx2::is_available() {
+            return avx2::ifft(&mut self.coefficients, &COMPLEX_BITREVERSED_POWERS_1024);
+        }
         let n = self.coefficients.len();
         let psi_inv_rev = &COMPLEX_BITREVERSED_POWERS_1024
             .iter()
@@ -1098,7 +1108,7 @@ impl FastFft for Polynomial<Complex64> {
     }
 }
 
-const FELT_BITREVERSED_POWERS_1024: [Felt; 1024] = [
+pub(crate) const FELT_BITREVERSED_POWERS_1024: [Felt; 1024] = [
     Felt::new(1),
     Felt::new(1479),
     Felt::new(8246),
@@ -2125,7 +2135,7 @@ const FELT_BITREVERSED_POWERS_1024: [Felt; 1024] = [
     Felt::new(8239),
 ];
 
-const FELT_BITREVERSED_POWERS_INVERSE_1024: [Felt; 1024] = [
+pub(crate) const FELT_BITREVERSED_POWERS_INVERSE_1024: [Felt; 1024] = [
     Felt::new(1),
     Felt::new(10810),
     Felt::new(7143),
@@ -3167,6 +3177,10 @@ impl FastFft for Polynomial<Felt> {
     type Field = Felt;
 
     fn fft_inplace(&mut self) {
+        #[cfg(all(feature = "std", target_arch = "x86_64"))]
+        if avx2::is_available() {
+            return avx2::ntt(&mut self.coefficients);
+        }
         Felt::fft(&mut self.coefficients, &FELT_BITREVERSED_POWERS_1024);
     }
 
@@ -3186,6 +3200,10 @@ impl FastFft for Polynomial<Felt> {
             1024 => FELT_NINV_1024,
             _ => panic!("vector length is not power of 2 or larger than 1024"),
         };
+        #[cfg(all(feature = "std", target_arch = "x86_64"))]
+        if avx2::is_available() {
+            return avx2::intt(&mut self.coefficients, ninv);
+        }
         Felt::ifft(
             &mut self.coefficients,
             &FELT_BITREVERSED_POWERS_INVERSE_1024,
@@ -3210,6 +3228,25 @@ impl FastFft for Polynomial<Felt> {
     }
 }
 
+/// The root of X^n + 1 at which entry `i` of the NTT of a polynomial with
+/// `n` coefficients evaluates it, namely `ψ^(2·rev(i) + 1)`, where `rev`
+/// reverses the `log2(n)` bits of its argument and `ψ` is the primitive
+/// 2n-th root of unity whose powers the NTT uses.
+pub(crate) fn felt_ntt_point(i: usize, n: usize) -> Felt {
+    // entry 512 of the table is the primitive 2048-th root of unity itself
+    let mut base = FELT_BITREVERSED_POWERS_1024[512];
+    let mut exponent = (2 * Felt::bitreverse_index(i, n) + 1) * (1024 / n);
+    let mut point = Felt::one();
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            point *= base;
+        }
+        base *= base;
+        exponent >>= 1;
+    }
+    point
+}
+
 const U32_FIELD_PSI_REV_1024: [U32Field; 1024] = [
     U32Field(1),
     U32Field(655882981),
@@ -0,0 +1,332 @@
+//! A C interface with the names, signatures and encodings of PQClean's
+//! `falcon-512/clean` and `falcon-1024/clean`, so that C and C++ code built
+//! against PQClean can link against this crate instead:
+//!
+//! ```sh
+//! cargo rustc --lib --release --features cdylib --crate-type cdylib
+//! ```
+//!
+//! Every function returns 0 on success and -1 on failure. As in PQClean,
+//! the buffers passed to a function may overlap, except that `sm` must not
+//! overlap `sk` in `crypto_sign`, and output buffers must be large enough:
+//! keys take exactly the `CRYPTO_*KEYBYTES`, and signatures and signed
+//! messages at most `CRYPTO_BYTES` more than the message.
+//!
+//! Detached signatures are in the variable-length encoding of
+//! [`Signature::to_reference_bytes`], and signed messages in that of
+//! [`SignedMessage`](signed_message::SignedMessage).
+//!
+//! Since the names are PQClean's, a binary should not link both. With
+//! `pqcrypto-falcon` in the same build, e.g. with the `pqcrypto` feature,
+//! the linker takes these definitions for both and the interoperability
+//! checks no longer compare against the reference implementation.
+
+use core::{ptr, slice};
+
+use rand::Rng;
+
+use crate::{
+    entropy,
+    falcon::{self, PublicKey, SecretKey, Signature},
+    signed_message,
+};
+
+pub const PQCLEAN_FALCON512_CLEAN_CRYPTO_SECRETKEYBYTES: usize = SecretKey::<512>::BYTE_LEN;
+pub const PQCLEAN_FALCON512_CLEAN_CRYPTO_PUBLICKEYBYTES: usize = PublicKey::<512>::BYTE_LEN;
+pub const PQCLEAN_FALCON512_CLEAN_CRYPTO_BYTES: usize = Signature::<512>::BYTE_LEN;
+pub const PQCLEAN_FALCON1024_CLEAN_CRYPTO_SECRETKEYBYTES: usize = SecretKey::<1024>::BYTE_LEN;
+pub const PQCLEAN_FALCON1024_CLEAN_CRYPTO_PUBLICKEYBYTES: usize = PublicKey::<1024>::BYTE_LEN;
+pub const PQCLEAN_FALCON1024_CLEAN_CRYPTO_BYTES: usize = Signature::<1024>::BYTE_LEN;
+
+/// Borrow `len` bytes at `data`, which may be null if `len` is zero.
+unsafe fn input<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    }
+}
+
+/// Copy `bytes` to `out`, which may overlap the inputs they were computed
+/// from, and store their length in `out_len` if given.
+unsafe fn output(bytes: &[u8], out: *mut u8, out_len: *mut usize) {
+    ptr::copy(bytes.as_ptr(), out, bytes.len());
+    if !out_len.is_null() {
+        *out_len = bytes.len();
+    }
+}
+
+fn status(success: bool) -> i32 {
+    if success {
+        0
+    } else {
+        -1
+    }
+}
+
+unsafe fn keypair<const N: usize>(pk: *mut u8, sk: *mut u8) -> i32 {
+    let (secret_key, public_key) = falcon::keygen::<N>(entropy::rng().gen());
+    output(public_key.as_bytes(), pk, ptr::null_mut());
+    output(&secret_key.to_bytes(), sk, ptr::null_mut());
+    0
+}
+
+unsafe fn signature<const N: usize>(
+    sig: *mut u8,
+    siglen: *mut usize,
+    m: *const u8,
+    mlen: usize,
+    sk: *const u8,
+) -> i32 {
+    let Ok(secret_key) = SecretKey::<N>::from_bytes(input(sk, SecretKey::<N>::BYTE_LEN)) else {
+        return -1;
+    };
+    let bytes = falcon::sign(input(m, mlen), &secret_key).to_reference_bytes();
+    output(&bytes, sig, siglen);
+    0
+}
+
+unsafe fn verify<const N: usize>(
+    sig: *const u8,
+    siglen: usize,
+    m: *const u8,
+    mlen: usize,
+    pk: *const u8,
+) -> i32 {
+    let (Ok(signature), Ok(public_key)) = (
+        Signature::<N>::from_bytes(input(sig, siglen)),
+        PublicKey::<N>::from_bytes(input(pk, PublicKey::<N>::BYTE_LEN)),
+    ) else {
+        return -1;
+    };
+    status(falcon::verify(input(m, mlen), &signature, &public_key))
+}
+
+unsafe fn sign<const N: usize>(
+    sm: *mut u8,
+    smlen: *mut usize,
+    m: *const u8,
+    mlen: usize,
+    sk: *const u8,
+) -> i32 {
+    let Ok(secret_key) = SecretKey::<N>::from_bytes(input(sk, SecretKey::<N>::BYTE_LEN)) else {
+        return -1;
+    };
+    let bytes = signed_message::sign_attached(input(m, mlen), &secret_key).to_bytes();
+    output(&bytes, sm, smlen);
+    0
+}
+
+unsafe fn open<const N: usize>(
+    m: *mut u8,
+    mlen: *mut usize,
+    sm: *const u8,
+    smlen: usize,
+    pk: *const u8,
+) -> i32 {
+    let Ok(public_key) = PublicKey::<N>::from_bytes(input(pk, PublicKey::<N>::BYTE_LEN)) else {
+        return -1;
+    };
+    match signed_message::open(input(sm, smlen), &public_key) {
+        Some(message) => {
+            output(&message, m, mlen);
+            0
+        }
+        None => -1,
+    }
+}
+
+macro_rules! pqclean_api {
+    ($n:literal, $keypair:ident, $signature:ident, $verify:ident, $sign:ident, $open:ident) => {
+        /// Generate a key pair, writing the encoded keys to `pk` and `sk`.
+        ///
+        /// # Safety
+        ///
+        /// `pk` and `sk` must be valid for writes of the key lengths.
+        #[no_mangle]
+        pub unsafe extern "C" fn $keypair(pk: *mut u8, sk: *mut u8) -> i32 {
+            keypair::<$n>(pk, sk)
+        }
+
+        /// Sign `m`, writing the signature to `sig` and its length to
+        /// `siglen`.
+        ///
+        /// # Safety
+        ///
+        /// `m` must be valid for reads of `mlen` bytes, `sk` for reads of
+        /// the secret key length, `sig` for writes of `CRYPTO_BYTES`, and
+        /// `siglen` for a write.
+        #[no_mangle]
+        pub unsafe extern "C" fn $signature(
+            sig: *mut u8,
+            siglen: *mut usize,
+            m: *const u8,
+            mlen: usize,
+            sk: *const u8,
+        ) -> i32 {
+            signature::<$n>(sig, siglen, m, mlen, sk)
+        }
+
+        /// Verify the signature `sig` on `m`.
+        ///
+        /// # Safety
+        ///
+        /// `sig` and `m` must be valid for reads of `siglen` and `mlen`
+        /// bytes, and `pk` for reads of the public key length.
+        #[no_mangle]
+        pub unsafe extern "C" fn $verify(
+            sig: *const u8,
+            siglen: usize,
+            m: *const u8,
+            mlen: usize,
+            pk: *const u8,
+        ) -> i32 {
+            verify::<$n>(sig, siglen, m, mlen, pk)
+        }
+
+        /// Sign `m`, writing the signed message to `sm` and its length to
+        /// `smlen`.
+        ///
+        /// # Safety
+        ///
+        /// `m` must be valid for reads of `mlen` bytes, `sk` for reads of
+        /// the secret key length, `sm` for writes of `mlen + CRYPTO_BYTES`,
+        /// and `smlen` for a write.
+        #[no_mangle]
+        pub unsafe extern "C" fn $sign(
+            sm: *mut u8,
+            smlen: *mut usize,
+            m: *const u8,
+            mlen: usize,
+            sk: *const u8,
+        ) -> i32 {
+            sign::<$n>(sm, smlen, m, mlen, sk)
+        }
+
+        /// Verify the signed message `sm`, and on success write the message
+        /// to `m` and its length to `mlen`.
+        ///
+        /// # Safety
+        ///
+        /// `sm` must be valid for reads of `smlen` bytes, `pk` for reads of
+        /// the public key length, `m` for writes of `smlen` bytes, and
+        /// `mlen` for a write.
+        #[no_mangle]
+        pub unsafe extern "C" fn $open(
+            m: *mut u8,
+            mlen: *mut usize,
+            sm: *const u8,
+            smlen: usize,
+            pk: *const u8,
+        ) -> i32 {
+            open::<$n>(m, mlen, sm, smlen, pk)
+        }
+    };
+}
+
+pqclean_api!(
+    512,
+    PQCLEAN_FALCON512_CLEAN_crypto_sign_keypair,
+    PQCLEAN_FALCON512_CLEAN_crypto_sign_signature,
+    PQCLEAN_FALCON512_CLEAN_crypto_sign_verify,
+    PQCLEAN_FALCON512_CLEAN_crypto_sign,
+    PQCLEAN_FALCON512_CLEAN_crypto_sign_open
+);
+
+pqclean_api!(
+    1024,
+    PQCLEAN_FALCON1024_CLEAN_crypto_sign_keypair,
+    PQCLEAN_FALCON1024_CLEAN_crypto_sign_signature,
+    PQCLEAN_FALCON1024_CLEAN_crypto_sign_verify,
+    PQCLEAN_FALCON1024_CLEAN_crypto_sign,
+    PQCLEAN_FALCON1024_CLEAN_crypto_sign_open
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pqclean_api() {
+        let mut pk = [0u8; PQCLEAN_FALCON512_CLEAN_CRYPTO_PUBLICKEYBYTES];
+        let mut sk = [0u8; PQCLEAN_FALCON512_CLEAN_CRYPTO_SECRETKEYBYTES];
+        let m = b"message";
+        let mut sig = [0u8; PQCLEAN_FALCON512_CLEAN_CRYPTO_BYTES];
+        let mut siglen = 0;
+        let mut sm = [0u8; 7 + PQCLEAN_FALCON512_CLEAN_CRYPTO_BYTES];
+        let mut smlen = 0;
+        let mut opened = [0u8; 7 + PQCLEAN_FALCON512_CLEAN_CRYPTO_BYTES];
+        let mut openedlen = 0;
+        // SAFETY: the buffers have the lengths the functions require.
+        unsafe {
+            assert_eq!(
+                PQCLEAN_FALCON512_CLEAN_crypto_sign_keypair(pk.as_mut_ptr(), sk.as_mut_ptr()),
+                0
+            );
+            assert_eq!(
+                PQCLEAN_FALCON512_CLEAN_crypto_sign_signature(
+                    sig.as_mut_ptr(),
+                    &mut siglen,
+                    m.as_ptr(),
+                    m.len(),
+                    sk.as_ptr()
+                ),
+                0
+            );
+            assert_eq!(sig[0], 0x39);
+            assert_eq!(
+                PQCLEAN_FALCON512_CLEAN_crypto_sign_verify(
+                    sig.as_ptr(),
+                    siglen,
+                    m.as_ptr(),
+                    m.len(),
+                    pk.as_ptr()
+                ),
+                0
+            );
+            assert_eq!(
+                PQCLEAN_FALCON512_CLEAN_crypto_sign_verify(
+                    sig.as_ptr(),
+                    siglen,
+                    m.as_ptr(),
+                    m.len() - 1,
+                    pk.as_ptr()
+                ),
+                -1
+            );
+
+            assert_eq!(
+                PQCLEAN_FALCON512_CLEAN_crypto_sign(
+                    sm.as_mut_ptr(),
+                    &mut smlen,
+                    m.as_ptr(),
+                    m.len(),
+                    sk.as_ptr()
+                ),
+                0
+            );
+            assert_eq!(
+                PQCLEAN_FALCON512_CLEAN_crypto_sign_open(
+                    opened.as_mut_ptr(),
+                    &mut openedlen,
+                    sm.as_ptr(),
+                    smlen,
+                    pk.as_ptr()
+                ),
+                0
+            );
+            assert_eq!(&opened[..openedlen], m);
+            sm[smlen - 1] ^= 1;
+            assert_eq!(
+                PQCLEAN_FALCON512_CLEAN_crypto_sign_open(
+                    opened.as_mut_ptr(),
+                    &mut openedlen,
+                    sm.as_ptr(),
+                    smlen,
+                    pk.as_ptr()
+                ),
+                -1
+            );
+        }
+    }
+}
@@ -1,8 +1,21 @@
+#[cfg(any(feature = "encrypted-keys", test))]
+use alloc::vec::Vec;
+use alloc::{boxed::Box, vec};
+
 use num_complex::{Complex, Complex64};
+#[cfg(feature = "std")]
 use rand::RngCore;
 use rand_distr::num_traits::{One, Zero};
+use zeroize::Zeroize;
 
-use crate::{falcon, fast_fft::FastFft, polynomial::Polynomial, samplerz::sampler_z};
+#[cfg(not(feature = "std"))]
+use crate::float::FloatExt;
+#[cfg(feature = "std")]
+use crate::{
+    cyclotomic_fourier::CyclotomicFourier, fast_fft::COMPLEX_BITREVERSED_POWERS_1024,
+    samplerz::sampler_z,
+};
+use crate::{falcon, fast_fft::FastFft, polynomial::Polynomial, samplerz::SamplerZ};
 
 /// Computes the Gram matrix. The argument must be a 2x2 matrix
 /// whose elements are equal-length vectors of complex numbers,
@@ -52,6 +65,63 @@ pub(crate) enum LdlTree {
     Leaf([Complex64; 2]),
 }
 
+impl LdlTree {
+    /// Append the coefficients of the tree, in pre-order, to `values`. The
+    /// shape of the tree is determined by its size and is not recorded.
+    #[cfg(any(feature = "encrypted-keys", test))]
+    pub(crate) fn flatten(&self, values: &mut Vec<Complex64>) {
+        match self {
+            LdlTree::Branch(ell, left, right) => {
+                values.extend_from_slice(&ell.coefficients);
+                left.flatten(values);
+                right.flatten(values);
+            }
+            LdlTree::Leaf(vector) => values.extend_from_slice(vector),
+        }
+    }
+
+    /// Rebuild a tree over polynomials of `n` coefficients from the values
+    /// produced by [`LdlTree::flatten`]. Returns `None` if there are too few.
+    #[cfg(feature = "encrypted-keys")]
+    pub(crate) fn unflatten(
+        n: usize,
+        values: &mut impl Iterator<Item = Complex64>,
+    ) -> Option<Self> {
+        let ell = Polynomial::new(values.by_ref().take(n).collect());
+        if ell.coefficients.len() != n {
+            return None;
+        }
+        let (left, right) = if n > 2 {
+            (
+                Self::unflatten(n / 2, values)?,
+                Self::unflatten(n / 2, values)?,
+            )
+        } else {
+            let mut leaf = || Some(LdlTree::Leaf([values.next()?, values.next()?]));
+            (leaf()?, leaf()?)
+        };
+        Some(LdlTree::Branch(ell, Box::new(left), Box::new(right)))
+    }
+}
+
+impl Zeroize for LdlTree {
+    fn zeroize(&mut self) {
+        match self {
+            LdlTree::Branch(ell, left, right) => {
+                ell.zeroize();
+                left.zeroize();
+                right.zeroize();
+            }
+            LdlTree::Leaf(vector) => {
+                for value in vector {
+                    value.re.zeroize();
+                    value.im.zeroize();
+                }
+            }
+        }
+    }
+}
+
 /// Compute the LDL Tree of G. Corresponds to Algorithm 9 of the
 /// specification [1, p.37]. The argument is a 2x2 matrix of
 /// polynomials, given in FFT form.
@@ -92,30 +162,46 @@ pub(crate) fn normalize_tree(tree: &mut LdlTree, sigma: f64) {
 /// Sample short polynomials using a Falcon tree. Algorithm 11 from the spec [1, p.40].
 ///
 /// [1]: https://falcon-sign.info/falcon.pdf
-pub(crate) fn ffsampling(
-    t: &(Polynomial<Complex64>, Polynomial<Complex64>),
+pub(crate) fn ffsampling<S: SamplerZ + ?Sized>(
+    t: (&Polynomial<Complex64>, &Polynomial<Complex64>),
     tree: &LdlTree,
     parameters: &falcon::FalconParameters,
-    rng: &mut dyn RngCore,
+    sampler: &mut S,
 ) -> (Polynomial<Complex64>, Polynomial<Complex64>) {
     match tree {
         LdlTree::Branch(ell, left, right) => {
-            let bold_t1 = t.1.split_fft();
-            let bold_z1 = ffsampling(&bold_t1, right, parameters, rng);
+            let mut bold_t1 = t.1.split_fft();
+            let mut bold_z1 = ffsampling((&bold_t1.0, &bold_t1.1), right, parameters, sampler);
             let z1 = Polynomial::<Complex64>::merge_fft(&bold_z1.0, &bold_z1.1);
 
             // t0' = t0  + (t1 - z1) * l
-            let t0_prime = t.0.clone() + (t.1.clone() - z1.clone()).hadamard_mul(ell);
+            let mut t1_min_z1 = t.1.clone() - z1.clone();
+            let mut t0_prime = t.0.clone() + t1_min_z1.hadamard_mul(ell);
 
-            let bold_t0 = t0_prime.split_fft();
-            let bold_z0 = ffsampling(&bold_t0, left, parameters, rng);
+            let mut bold_t0 = t0_prime.split_fft();
+            let mut bold_z0 = ffsampling((&bold_t0.0, &bold_t0.1), left, parameters, sampler);
             let z0 = Polynomial::<Complex64>::merge_fft(&bold_z0.0, &bold_z0.1);
 
+            // the intermediate samples reveal as much as the result
+            for temporary in [
+                &mut bold_t1.0,
+                &mut bold_t1.1,
+                &mut bold_z1.0,
+                &mut bold_z1.1,
+                &mut t1_min_z1,
+                &mut t0_prime,
+                &mut bold_t0.0,
+                &mut bold_t0.1,
+                &mut bold_z0.0,
+                &mut bold_z0.1,
+            ] {
+                temporary.zeroize();
+            }
             (z0, z1)
         }
         LdlTree::Leaf(value) => {
-            let z0 = sampler_z(t.0.coefficients[0].re, value[0].re, parameters.sigmin, rng);
-            let z1 = sampler_z(t.1.coefficients[0].re, value[0].re, parameters.sigmin, rng);
+            let z0 = sampler.sample_z(t.0.coefficients[0].re, value[0].re, parameters.sigmin);
+            let z1 = sampler.sample_z(t.1.coefficients[0].re, value[0].re, parameters.sigmin);
             (
                 Polynomial::new(vec![Complex64::new(z0 as f64, 0.0)]),
                 Polynomial::new(vec![Complex64::new(z1 as f64, 0.0)]),
@@ -124,6 +210,64 @@ pub(crate) fn ffsampling(
     }
 }
 
+/// Like [`ffsampling`], but without allocating: the sample is written into
+/// `z`, and `work` must hold at least `4 * t.0.len()` elements. The
+/// arithmetic and the use of randomness are exactly those of
+/// [`ffsampling`], so both produce the same sample from the same `rng`.
+///
+/// `psi_inv_rev` holds the conjugates of the bitreversed powers of the
+/// primitive root of unity, at least as many as `t.0` has coefficients.
+#[cfg(feature = "std")]
+pub(crate) fn ffsampling_into(
+    t: (&[Complex64], &[Complex64]),
+    tree: &LdlTree,
+    parameters: &falcon::FalconParameters,
+    psi_inv_rev: &[Complex64],
+    rng: &mut dyn RngCore,
+    z: (&mut [Complex64], &mut [Complex64]),
+    work: &mut [Complex64],
+) {
+    match tree {
+        LdlTree::Branch(ell, left, right) => {
+            let n = t.0.len();
+            let (a, rest) = work.split_at_mut(n);
+            let (b, rest) = rest.split_at_mut(n);
+            {
+                let (a0, a1) = a.split_at_mut(n / 2);
+                let (b0, b1) = b.split_at_mut(n / 2);
+                Complex64::split_fft_into(t.1, a0, a1, psi_inv_rev);
+                ffsampling_into(
+                    (a0, a1),
+                    right,
+                    parameters,
+                    psi_inv_rev,
+                    rng,
+                    (b0, b1),
+                    rest,
+                );
+                Complex64::merge_fft_into(b0, b1, z.1, &COMPLEX_BITREVERSED_POWERS_1024);
+            }
+
+            // t0' = t0  + (t1 - z1) * l
+            for (i, t0_prime) in a.iter_mut().enumerate() {
+                *t0_prime = t.0[i] + (t.1[i] + -z.1[i]) * ell.coefficients[i];
+            }
+
+            let (b0, b1) = b.split_at_mut(n / 2);
+            Complex64::split_fft_into(a, b0, b1, psi_inv_rev);
+            let (a0, a1) = a.split_at_mut(n / 2);
+            ffsampling_into((b0, b1), left, parameters, psi_inv_rev, rng, (a0, a1), rest);
+            Complex64::merge_fft_into(a0, a1, z.0, &COMPLEX_BITREVERSED_POWERS_1024);
+        }
+        LdlTree::Leaf(value) => {
+            let z0 = sampler_z(t.0[0].re, value[0].re, parameters.sigmin, rng);
+            let z1 = sampler_z(t.1[0].re, value[0].re, parameters.sigmin, rng);
+            z.0[0] = Complex64::new(z0 as f64, 0.0);
+            z.1[0] = Complex64::new(z1 as f64, 0.0);
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use itertools::Itertools;
@@ -0,0 +1,130 @@
+//! Short identifiers of public keys.
+
+use alloc::string::String;
+use core::fmt;
+
+use crate::falcon::FalconDeserializationError;
+
+/// A 32-byte identifier of a public key: the first 32 bytes of SHAKE256
+/// over its encoding, as returned by
+/// [`PublicKey::fingerprint`](crate::falcon512::PublicKey::fingerprint).
+/// It stands in for the key, which is 897 or 1793 bytes long, in logs, key
+/// rings, and revocation lists.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint([u8; Fingerprint::LEN]);
+
+impl Fingerprint {
+    /// The length of a fingerprint in bytes.
+    pub const LEN: usize = 32;
+
+    /// The number of bytes that [`Fingerprint::short`] shows.
+    pub const SHORT_LEN: usize = 8;
+
+    /// Wrap the given bytes.
+    pub const fn new(bytes: [u8; Fingerprint::LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Read a fingerprint from a slice, which must be exactly
+    /// [`Fingerprint::LEN`] bytes long.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        bytes
+            .try_into()
+            .map(Self)
+            .map_err(|_| FalconDeserializationError::BadEncodingLength)
+    }
+
+    /// Borrow the bytes of the fingerprint.
+    pub const fn as_bytes(&self) -> &[u8; Fingerprint::LEN] {
+        &self.0
+    }
+
+    /// The hex encoding of the first [`Fingerprint::SHORT_LEN`] bytes, for
+    /// humans to tell keys apart.
+    pub fn short(&self) -> String {
+        hex::encode(&self.0[..Self::SHORT_LEN])
+    }
+}
+
+impl From<[u8; Fingerprint::LEN]> for Fingerprint {
+    fn from(bytes: [u8; Fingerprint::LEN]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Fingerprint> for [u8; Fingerprint::LEN] {
+    fn from(fingerprint: Fingerprint) -> Self {
+        fingerprint.0
+    }
+}
+
+impl TryFrom<&[u8]> for Fingerprint {
+    type Error = FalconDeserializationError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl AsRef<[u8]> for Fingerprint {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fingerprint({})", hex::encode(self.0))
+    }
+}
+
+/// Formats the fingerprint as hex.
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl fmt::LowerHex for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+    use sha3::{
+        digest::{ExtendableOutput, Update},
+        Shake256,
+    };
+
+    use crate::{falcon::FalconDeserializationError, falcon1024, falcon512};
+
+    use super::Fingerprint;
+
+    #[test]
+    fn test_fingerprint() {
+        let (_, pk) = falcon512::keygen(thread_rng().gen());
+        let fingerprint = pk.fingerprint();
+        let mut expected = [0u8; 64];
+        Shake256::default()
+            .chain(pk.as_bytes())
+            .finalize_xof_into(&mut expected);
+        assert_eq!(fingerprint.as_bytes(), &expected[..Fingerprint::LEN]);
+
+        let (_, other_pk) = falcon512::keygen(thread_rng().gen());
+        assert_ne!(other_pk.fingerprint(), fingerprint);
+        let (_, pk1024) = falcon1024::keygen(thread_rng().gen());
+        assert_ne!(pk1024.fingerprint(), fingerprint);
+
+        assert_eq!(format!("{fingerprint}"), hex::encode(fingerprint));
+        assert_eq!(fingerprint.short().len(), 2 * Fingerprint::SHORT_LEN);
+        assert!(format!("{fingerprint}").starts_with(&fingerprint.short()));
+        assert_eq!(Fingerprint::try_from(fingerprint.as_ref()), Ok(fingerprint));
+        assert_eq!(
+            Fingerprint::from_bytes(&[0u8; 31]),
+            Err(FalconDeserializationError::BadEncodingLength)
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Interoperability fixtures.
+//!
+//! This module bundles a small corpus of key pairs and signatures that were
+//! produced by other Falcon implementations -- the reference C code as
+//! packaged by PQClean, and the upstream `falcon-rust` crate -- together
+//! with a loader for it. The tests in this module decode and verify every
+//! record, so they serve as executable proof of wire compatibility.
+
+use crate::records::{parse_records, Field};
+
+/// The corpus shipped with the crate; see `fixtures/interop.txt`.
+const INTEROP_CORPUS: &str = include_str!("../fixtures/interop.txt");
+
+/// One record of an interoperability corpus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InteropFixture {
+    /// The implementation that produced this record.
+    pub source: String,
+    /// The ring degree: 512 or 1024.
+    pub variant: usize,
+    /// The message that was signed.
+    pub message: Vec<u8>,
+    /// The encoded public key.
+    pub public_key: Vec<u8>,
+    /// The encoded secret key.
+    pub secret_key: Vec<u8>,
+    /// The encoded detached signature.
+    pub signature: Vec<u8>,
+}
+
+/// Reasons why a corpus of fixtures fails to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixtureParseError {
+    /// The line (1-based) is neither blank, a comment, nor `key = value`.
+    MalformedLine(usize),
+    /// The line (1-based) has a key that is not part of the format.
+    UnknownKey(usize),
+    /// The line (1-based) has a value that is not valid hex.
+    InvalidHex(usize),
+    /// The line (1-based) has a variant other than 512 or 1024.
+    InvalidVariant(usize),
+    /// A record ending on the given line (1-based) lacks the named field.
+    MissingField(usize, &'static str),
+}
+
+/// Load the interoperability corpus bundled with the crate.
+pub fn interop_corpus() -> Vec<InteropFixture> {
+    parse_fixtures(INTEROP_CORPUS).expect("bundled interop corpus is well-formed")
+}
+
+/// Parse a corpus of fixtures.
+///
+/// Records are blocks of `key = value` lines separated by blank lines. Lines
+/// starting with `#` are comments. Every record must define `source`,
+/// `variant`, `msg`, `pk`, `sk` and `sig`; the last four are hex-encoded.
+pub fn parse_fixtures(text: &str) -> Result<Vec<InteropFixture>, FixtureParseError> {
+    #[derive(Default)]
+    struct Partial {
+        source: Option<String>,
+        variant: Option<usize>,
+        message: Option<Vec<u8>>,
+        public_key: Option<Vec<u8>>,
+        secret_key: Option<Vec<u8>>,
+        signature: Option<Vec<u8>>,
+    }
+
+    impl Partial {
+        fn finish(self, line: usize) -> Result<InteropFixture, FixtureParseError> {
+            let missing = |field| FixtureParseError::MissingField(line, field);
+            Ok(InteropFixture {
+                source: self.source.ok_or_else(|| missing("source"))?,
+                variant: self.variant.ok_or_else(|| missing("variant"))?,
+                message: self.message.ok_or_else(|| missing("msg"))?,
+                public_key: self.public_key.ok_or_else(|| missing("pk"))?,
+                secret_key: self.secret_key.ok_or_else(|| missing("sk"))?,
+                signature: self.signature.ok_or_else(|| missing("sig"))?,
+            })
+        }
+    }
+
+    parse_records(text, FixtureParseError::MalformedLine, |fields, line| {
+        let mut current = Partial::default();
+        for &Field { line, key, value } in fields {
+            let decode = |v: &str| hex::decode(v).map_err(|_| FixtureParseError::InvalidHex(line));
+            match key {
+                "source" => current.source = Some(value.to_string()),
+                "variant" => match value {
+                    "512" => current.variant = Some(512),
+                    "1024" => current.variant = Some(1024),
+                    _ => return Err(FixtureParseError::InvalidVariant(line)),
+                },
+                "msg" => current.message = Some(decode(value)?),
+                "pk" => current.public_key = Some(decode(value)?),
+                "sk" => current.secret_key = Some(decode(value)?),
+                "sig" => current.signature = Some(decode(value)?),
+                _ => return Err(FixtureParseError::UnknownKey(line)),
+            }
+        }
+        current.finish(line)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{falcon1024, falcon512};
+
+    use super::{interop_corpus, parse_fixtures, FixtureParseError, InteropFixture};
+
+    fn check_fixture_512(fixture: &InteropFixture) {
+        let pk = falcon512::PublicKey::from_bytes(&fixture.public_key).unwrap();
+        let sk = falcon512::SecretKey::from_bytes(&fixture.secret_key).unwrap();
+        let sig = falcon512::Signature::from_bytes(&fixture.signature).unwrap();
+
+        assert_eq!(pk.as_bytes(), fixture.public_key);
+        assert_eq!(sk.to_bytes(), fixture.secret_key);
+        assert_eq!(falcon512::PublicKey::from_secret_key(&sk), pk);
+        assert!(falcon512::consistent(&sk, &pk));
+        assert!(falcon512::verify(&fixture.message, &sig, &pk));
+
+        let mut tampered = fixture.message.clone();
+        tampered.push(0);
+        assert!(!falcon512::verify(&tampered, &sig, &pk));
+    }
+
+    fn check_fixture_1024(fixture: &InteropFixture) {
+        let pk = falcon1024::PublicKey::from_bytes(&fixture.public_key).unwrap();
+        let sk = falcon1024::SecretKey::from_bytes(&fixture.secret_key).unwrap();
+        let sig = falcon1024::Signature::from_bytes(&fixture.signature).unwrap();
+
+        assert_eq!(pk.as_bytes(), fixture.public_key);
+        assert_eq!(sk.to_bytes(), fixture.secret_key);
+        assert_eq!(falcon1024::PublicKey::from_secret_key(&sk), pk);
+        assert!(falcon1024::consistent(&sk, &pk));
+        assert!(falcon1024::verify(&fixture.message, &sig, &pk));
+
+        let mut tampered = fixture.message.clone();
+        tampered.push(0);
+        assert!(!falcon1024::verify(&tampered, &sig, &pk));
+    }
+
+    #[test]
+    fn test_interop_corpus() {
+        let corpus = interop_corpus();
+        assert!(corpus.iter().any(|f| f.source == "pqclean"));
+        assert!(corpus.iter().any(|f| f.source == "falcon-rust-0.1.2"));
+
+        for fixture in corpus.iter() {
+            match fixture.variant {
+                512 => check_fixture_512(fixture),
+                1024 => check_fixture_1024(fixture),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_sign_with_foreign_secret_key() {
+        let fixture = interop_corpus()
+            .into_iter()
+            .find(|f| f.source == "pqclean" && f.variant == 512)
+            .unwrap();
+        let pk = falcon512::PublicKey::from_bytes(&fixture.public_key).unwrap();
+        let sk = falcon512::SecretKey::from_bytes(&fixture.secret_key).unwrap();
+
+        let msg = b"signed with a key from the reference implementation";
+        let sig = falcon512::sign(msg, &sk);
+        assert!(falcon512::verify(msg, &sig, &pk));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_fixtures("# only a comment\n\n"), Ok(vec![]));
+        assert_eq!(
+            parse_fixtures("source pqclean\n"),
+            Err(FixtureParseError::MalformedLine(1))
+        );
+        assert_eq!(
+            parse_fixtures("source = x\nnonce = 00\n"),
+            Err(FixtureParseError::UnknownKey(2))
+        );
+        assert_eq!(
+            parse_fixtures("msg = 0g\n"),
+            Err(FixtureParseError::InvalidHex(1))
+        );
+        assert_eq!(
+            parse_fixtures("variant = 256\n"),
+            Err(FixtureParseError::InvalidVariant(1))
+        );
+        assert_eq!(
+            parse_fixtures("source = x\nvariant = 512\n\n"),
+            Err(FixtureParseError::MissingField(3, "msg"))
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! The floating-point functions of `std` that `core` lacks, computed by
+//! [`libm`] when the `std` feature is disabled. Import [`FloatExt`] under
+//! `#[cfg(not(feature = "std"))]` so that calls such as `x.sqrt()` resolve
+//! to the inherent methods of `std` when they exist.
+//!
+//! All but `sin`, `cos`, and `cbrt` are exact in both, so signing and
+//! verification do not depend on the feature.
+//!
+//! [`libm`]: https://crates.io/crates/libm
+
+pub(crate) trait FloatExt {
+    fn cbrt(self) -> Self;
+    fn cos(self) -> Self;
+    #[cfg(not(feature = "fixed-point"))]
+    fn floor(self) -> Self;
+    fn round(self) -> Self;
+    fn sin(self) -> Self;
+    fn sqrt(self) -> Self;
+}
+
+impl FloatExt for f64 {
+    fn cbrt(self) -> Self {
+        libm::cbrt(self)
+    }
+
+    fn cos(self) -> Self {
+        libm::cos(self)
+    }
+
+    #[cfg(not(feature = "fixed-point"))]
+    fn floor(self) -> Self {
+        libm::floor(self)
+    }
+
+    fn round(self) -> Self {
+        libm::round(self)
+    }
+
+    fn sin(self) -> Self {
+        libm::sin(self)
+    }
+
+    fn sqrt(self) -> Self {
+        libm::sqrt(self)
+    }
+}
@@ -0,0 +1,558 @@
+//! Floating-point arithmetic emulated on integers, after `fpr.c` of the
+//! reference implementation. Values are IEEE-754 binary64 bit patterns, and
+//! addition, multiplication and division round to nearest-even exactly as
+//! the hardware does, so results match `f64` bit for bit. Subnormals,
+//! infinities and NaNs are not supported; subnormal results flush to zero.
+//!
+//! Unlike the soft-float routines the compiler falls back on for targets
+//! without an FPU, these run without branches or memory accesses that
+//! depend on the operands, which matters when the operands are derived from
+//! the secret key.
+
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+use num_complex::Complex64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Fpr(u64);
+
+impl Fpr {
+    pub(crate) const fn from_f64(x: f64) -> Self {
+        Self(x.to_bits())
+    }
+
+    pub(crate) const fn to_f64(self) -> f64 {
+        f64::from_bits(self.0)
+    }
+
+    pub(crate) fn from_i64(i: i64) -> Self {
+        scaled(i, 0)
+    }
+
+    /// Round towards minus infinity. The value must fit an `i64`.
+    pub(crate) fn floor(self) -> i64 {
+        let x = self.0;
+        let e = ((x >> 52) & 0x7FF) as i32;
+        let t = x >> 63;
+        // the absolute value, scaled into [2^62, 2^63)
+        let xi = (((x << 10) | (1 << 62)) & ((1 << 63) - 1)) as i64;
+        let xi = (xi ^ (t as i64).wrapping_neg()).wrapping_add(t as i64);
+        let cc = 1085 - e;
+        // an arithmetic shift rounds towards minus infinity on both sides
+        let mut xi = irsh(xi, (cc & 63) as u32);
+        // if the true shift count was 64 or more, the result is 0 or -1
+        xi ^= (xi ^ (t as i64).wrapping_neg()) & ((((63 - cc) as u32) >> 31) as i64).wrapping_neg();
+        // and it is 0 for either zero
+        xi & ((((e + 0x7FF) >> 11) as i64).wrapping_neg())
+    }
+}
+
+impl From<f64> for Fpr {
+    fn from(x: f64) -> Self {
+        Self::from_f64(x)
+    }
+}
+
+impl Add for Fpr {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        add(self.0, rhs.0)
+    }
+}
+
+impl Sub for Fpr {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        add(self.0, rhs.0 ^ (1 << 63))
+    }
+}
+
+impl Neg for Fpr {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0 ^ (1 << 63))
+    }
+}
+
+impl Mul for Fpr {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        mul(self.0, rhs.0)
+    }
+}
+
+impl Div for Fpr {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        div(self.0, rhs.0)
+    }
+}
+
+/// `x >> n` for `n < 64`.
+fn ursh(x: u64, n: u32) -> u64 {
+    let x = x ^ ((x ^ (x >> 32)) & ((n >> 5) as u64).wrapping_neg());
+    x >> (n & 31)
+}
+
+/// `x >> n` for `n < 64`, arithmetically.
+fn irsh(x: i64, n: u32) -> i64 {
+    let x = x ^ ((x ^ (x >> 32)) & ((n >> 5) as i64).wrapping_neg());
+    x >> (n & 31)
+}
+
+/// `x << n` for `n < 64`.
+fn ulsh(x: u64, n: u32) -> u64 {
+    let x = x ^ ((x ^ (x << 32)) & ((n >> 5) as u64).wrapping_neg());
+    x << (n & 31)
+}
+
+/// Shift `m` left until its top bit is set, adjusting the exponent `e` so
+/// that `m 2^e` is unchanged. Zero stays zero.
+fn normalize(mut m: u64, mut e: i32) -> (u64, i32) {
+    e -= 63;
+    for shift in [32, 16, 8, 4, 2, 1] {
+        let nt = (m >> (64 - shift)) as u32;
+        let nt = (nt | nt.wrapping_neg()) >> 31;
+        m ^= (m ^ (m << shift)) & (nt as u64).wrapping_sub(1);
+        e += (nt * shift) as i32;
+    }
+    (m, e)
+}
+
+/// Pack `(-1)^s m 2^e`, with `m` in [2^54, 2^55) or zero and its lowest bit
+/// sticky, rounding to nearest-even.
+fn pack(s: u64, e: i32, m: u64) -> Fpr {
+    // below the normal range, flush to zero
+    let e = e + 1076;
+    let m = m & (((e as u32) >> 31) as u64).wrapping_sub(1);
+    // a zero mantissa gets a zero exponent, keeping the sign
+    let e = e & ((m >> 54) as i32).wrapping_neg();
+    // the top bit of m carries into the exponent
+    let x = ((s << 63) | (m >> 2)).wrapping_add(((e as u32) as u64) << 52);
+    // round up if the low three bits are 011, 110 or 111
+    Fpr(x + ((0xC8u32 >> (m as u32 & 7)) & 1) as u64)
+}
+
+/// `i 2^sc`.
+fn scaled(i: i64, sc: i32) -> Fpr {
+    let s = (i as u64) >> 63;
+    let i = (i ^ (s as i64).wrapping_neg()).wrapping_add(s as i64);
+    let (mut m, mut e) = normalize(i as u64, 9 + sc);
+    m |= ((m as u32 & 0x1FF) + 0x1FF) as u64;
+    m >>= 9;
+    // if i = 0, the above is wrong
+    let t = ((i | i.wrapping_neg()) as u64) >> 63;
+    m &= t.wrapping_neg();
+    e &= (t as i32).wrapping_neg();
+    pack(s, e, m)
+}
+
+fn add(x: u64, y: u64) -> Fpr {
+    // Swap so that x has the larger absolute value. Then the result has the
+    // sign of x, except that x + (-x) must be +0; for equal absolute values,
+    // swapping whenever x is negative takes care of that.
+    let mask = (1u64 << 63) - 1;
+    let za = (x & mask).wrapping_sub(y & mask);
+    let cs = (za >> 63) as u32 | ((1 - (za.wrapping_neg() >> 63) as u32) & (x >> 63) as u32);
+    let m = (x ^ y) & (cs as u64).wrapping_neg();
+    let (x, y) = (x ^ m, y ^ m);
+
+    // mantissas scaled into [2^55, 2^56), or zero
+    let mut ex = (x >> 52) as i32;
+    let sx = ex >> 11;
+    ex &= 0x7FF;
+    let m = (((ex + 0x7FF) >> 11) as u64) << 52;
+    let mut xu = ((x & ((1 << 52) - 1)) | m) << 3;
+    ex -= 1078;
+    let mut ey = (y >> 52) as i32;
+    let sy = ey >> 11;
+    ey &= 0x7FF;
+    let m = (((ey + 0x7FF) >> 11) as u64) << 52;
+    let mut yu = ((y & ((1 << 52) - 1)) | m) << 3;
+    ey -= 1078;
+
+    // align y, clamping it to zero if it is shifted out entirely, and
+    // keeping the lowest bit sticky
+    let mut cc = ex - ey;
+    yu &= ((((cc - 60) as u32) >> 31) as u64).wrapping_neg();
+    cc &= 63;
+    let m = ulsh(1, cc as u32) - 1;
+    yu |= (yu & m) + m;
+    yu = ursh(yu, cc as u32);
+
+    xu = xu.wrapping_add(yu.wrapping_sub((yu << 1) & ((sx ^ sy) as u64).wrapping_neg()));
+
+    let (mut xu, mut ex) = normalize(xu, ex);
+    xu |= ((xu as u32 & 0x1FF) + 0x1FF) as u64;
+    xu >>= 9;
+    ex += 9;
+    pack(sx as u64, ex, xu)
+}
+
+fn mul(x: u64, y: u64) -> Fpr {
+    let xu = (x & ((1 << 52) - 1)) | (1 << 52);
+    let yu = (y & ((1 << 52) - 1)) | (1 << 52);
+
+    // multiply the 53-bit mantissas in 25-bit limbs, so that the low limbs
+    // only matter for the sticky bit
+    let x0 = xu as u32 & 0x01FF_FFFF;
+    let x1 = (xu >> 25) as u32;
+    let y0 = yu as u32 & 0x01FF_FFFF;
+    let y1 = (yu >> 25) as u32;
+    let w = x0 as u64 * y0 as u64;
+    let z0 = w as u32 & 0x01FF_FFFF;
+    let mut z1 = (w >> 25) as u32;
+    let w = x0 as u64 * y1 as u64;
+    z1 += w as u32 & 0x01FF_FFFF;
+    let mut z2 = (w >> 25) as u32;
+    let w = x1 as u64 * y0 as u64;
+    z1 += w as u32 & 0x01FF_FFFF;
+    z2 += (w >> 25) as u32;
+    let mut zu = x1 as u64 * y1 as u64;
+    z2 += z1 >> 25;
+    z1 &= 0x01FF_FFFF;
+    zu += z2 as u64;
+    zu |= (((z0 | z1) + 0x01FF_FFFF) >> 25) as u64;
+
+    // bring the product from [2^54, 2^56) into [2^54, 2^55)
+    let zv = (zu >> 1) | (zu & 1);
+    let w = zu >> 55;
+    zu ^= (zu ^ zv) & w.wrapping_neg();
+
+    let ex = ((x >> 52) & 0x7FF) as i32;
+    let ey = ((y >> 52) & 0x7FF) as i32;
+    let e = ex + ey - 2100 + w as i32;
+    let s = (x ^ y) >> 63;
+
+    // either operand zero
+    let d = ((ex + 0x7FF) & (ey + 0x7FF)) >> 11;
+    zu &= (d as u64).wrapping_neg();
+    pack(s, e, zu)
+}
+
+/// Divide; the divisor must not be zero.
+fn div(x: u64, y: u64) -> Fpr {
+    let mut xu = (x & ((1 << 52) - 1)) | (1 << 52);
+    let yu = (y & ((1 << 52) - 1)) | (1 << 52);
+
+    // 55 bits of quotient, one at a time
+    let mut q = 0u64;
+    for _ in 0..55 {
+        let b = (xu.wrapping_sub(yu) >> 63).wrapping_sub(1);
+        xu -= b & yu;
+        q |= b & 1;
+        xu <<= 1;
+        q <<= 1;
+    }
+    // the extra bit is sticky
+    q |= (xu | xu.wrapping_neg()) >> 63;
+
+    // bring the quotient from [2^54, 2^56) into [2^54, 2^55)
+    let q2 = (q >> 1) | (q & 1);
+    let w = q >> 55;
+    q ^= (q ^ q2) & w.wrapping_neg();
+
+    let ex = ((x >> 52) & 0x7FF) as i32;
+    let ey = ((y >> 52) & 0x7FF) as i32;
+    let mut e = ex - ey - 55 + w as i32;
+    let s = (x ^ y) >> 63;
+
+    // zero dividend
+    let d = (ex + 0x7FF) >> 11;
+    e &= d.wrapping_neg();
+    q &= (d as u64).wrapping_neg();
+    pack(s, e, q)
+}
+
+/// A complex number with emulated parts, computed on as [`Complex64`] is.
+#[derive(Clone, Copy)]
+struct FprComplex {
+    re: Fpr,
+    im: Fpr,
+}
+
+impl From<Complex64> for FprComplex {
+    fn from(c: Complex64) -> Self {
+        Self {
+            re: c.re.into(),
+            im: c.im.into(),
+        }
+    }
+}
+
+impl From<FprComplex> for Complex64 {
+    fn from(c: FprComplex) -> Self {
+        Complex64::new(c.re.to_f64(), c.im.to_f64())
+    }
+}
+
+impl Add for FprComplex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            re: self.re + rhs.re,
+            im: self.im + rhs.im,
+        }
+    }
+}
+
+impl Sub for FprComplex {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            re: self.re - rhs.re,
+            im: self.im - rhs.im,
+        }
+    }
+}
+
+impl Mul for FprComplex {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            re: self.re * rhs.re - self.im * rhs.im,
+            im: self.re * rhs.im + self.im * rhs.re,
+        }
+    }
+}
+
+/// The arithmetic the transforms below run on: [`FprComplex`], or, for
+/// comparison, [`Complex64`] itself.
+trait ComplexArithmetic:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + From<Complex64>
+    + Into<Complex64>
+{
+}
+
+impl<T> ComplexArithmetic for T where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + From<Complex64>
+        + Into<Complex64>
+{
+}
+
+/// See [`CyclotomicFourier::fft`](crate::cyclotomic_fourier::CyclotomicFourier::fft).
+pub(crate) fn fft(a: &mut [Complex64], psi_rev: &[Complex64]) {
+    fft_with::<FprComplex>(a, psi_rev)
+}
+
+/// See [`CyclotomicFourier::ifft`](crate::cyclotomic_fourier::CyclotomicFourier::ifft).
+pub(crate) fn ifft(a: &mut [Complex64], psi_inv_rev: &[Complex64], ninv: Complex64) {
+    ifft_with::<FprComplex>(a, psi_inv_rev, ninv)
+}
+
+/// See [`CyclotomicFourier::split_fft_into`](crate::cyclotomic_fourier::CyclotomicFourier::split_fft_into).
+pub(crate) fn split_fft_into(
+    f: &[Complex64],
+    f0: &mut [Complex64],
+    f1: &mut [Complex64],
+    psi_inv_rev: &[Complex64],
+) {
+    split_fft_into_with::<FprComplex>(f, f0, f1, psi_inv_rev)
+}
+
+/// See [`CyclotomicFourier::merge_fft_into`](crate::cyclotomic_fourier::CyclotomicFourier::merge_fft_into).
+pub(crate) fn merge_fft_into(
+    f0: &[Complex64],
+    f1: &[Complex64],
+    f: &mut [Complex64],
+    psi_rev: &[Complex64],
+) {
+    merge_fft_into_with::<FprComplex>(f0, f1, f, psi_rev)
+}
+
+fn fft_with<T: ComplexArithmetic>(a: &mut [Complex64], psi_rev: &[Complex64]) {
+    let n = a.len();
+    let mut t = n;
+    let mut m = 1;
+    while m < n {
+        t >>= 1;
+        for i in 0..m {
+            let j1 = 2 * i * t;
+            let s = T::from(psi_rev[m + i]);
+            for j in j1..j1 + t {
+                let u = T::from(a[j]);
+                let v = T::from(a[j + t]) * s;
+                a[j] = (u + v).into();
+                a[j + t] = (u - v).into();
+            }
+        }
+        m <<= 1;
+    }
+}
+
+fn ifft_with<T: ComplexArithmetic>(
+    a: &mut [Complex64],
+    psi_inv_rev: &[Complex64],
+    ninv: Complex64,
+) {
+    let n = a.len();
+    let mut t = 1;
+    let mut m = n;
+    while m > 1 {
+        let h = m / 2;
+        let mut j1 = 0;
+        for i in 0..h {
+            let s = T::from(psi_inv_rev[h + i]);
+            for j in j1..j1 + t {
+                let u = T::from(a[j]);
+                let v = T::from(a[j + t]);
+                a[j] = (u + v).into();
+                a[j + t] = ((u - v) * s).into();
+            }
+            j1 += 2 * t;
+        }
+        t <<= 1;
+        m >>= 1;
+    }
+    let ninv = T::from(ninv);
+    for ai in a.iter_mut() {
+        *ai = (T::from(*ai) * ninv).into();
+    }
+}
+
+fn split_fft_into_with<T: ComplexArithmetic>(
+    f: &[Complex64],
+    f0: &mut [Complex64],
+    f1: &mut [Complex64],
+    psi_inv_rev: &[Complex64],
+) {
+    // the inverse of 2 as `Inverse` computes it, with a negative zero
+    // imaginary part
+    let two_inv = T::from(Complex64::new(0.5, -0.0));
+    let n_over_2 = f.len() / 2;
+    for i in 0..n_over_2 {
+        let two_i = i * 2;
+        let two_zeta_inv = two_inv * psi_inv_rev[n_over_2 + i].into();
+        let (a, b) = (T::from(f[two_i]), T::from(f[two_i + 1]));
+        f0[i] = (two_inv * (a + b)).into();
+        f1[i] = (two_zeta_inv * (a - b)).into();
+    }
+}
+
+fn merge_fft_into_with<T: ComplexArithmetic>(
+    f0: &[Complex64],
+    f1: &[Complex64],
+    f: &mut [Complex64],
+    psi_rev: &[Complex64],
+) {
+    let n_over_2 = f0.len();
+    for i in 0..n_over_2 {
+        let two_i = i * 2;
+        let a = T::from(f0[i]);
+        let b = T::from(psi_rev[n_over_2 + i]) * f1[i].into();
+        f[two_i] = (a + b).into();
+        f[two_i + 1] = (a - b).into();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_complex::Complex64;
+    use rand::{thread_rng, Rng};
+
+    use super::{fft_with, ifft_with, merge_fft_into_with, split_fft_into_with, Fpr, FprComplex};
+    use crate::fast_fft::COMPLEX_BITREVERSED_POWERS_1024;
+
+    /// A random normal double with an exponent in a range where sums,
+    /// products and quotients stay normal.
+    fn random_f64<R: Rng>(rng: &mut R) -> f64 {
+        let x = rng.gen_range(1.0..2.0) * f64::powi(2.0, rng.gen_range(-60..60));
+        match rng.gen_range(0..8) {
+            0 => 0.0,
+            1 => -x,
+            _ if rng.gen() => x,
+            _ => -x.trunc(),
+        }
+    }
+
+    #[test]
+    fn test_fpr_matches_f64() {
+        let mut rng = thread_rng();
+        for _ in 0..100_000 {
+            let (x, y) = (random_f64(&mut rng), random_f64(&mut rng));
+            let (a, b) = (Fpr::from(x), Fpr::from(y));
+            assert_eq!((a + b).to_f64().to_bits(), (x + y).to_bits(), "{x} + {y}");
+            assert_eq!((a - b).to_f64().to_bits(), (x - y).to_bits(), "{x} - {y}");
+            assert_eq!((a * b).to_f64().to_bits(), (x * y).to_bits(), "{x} * {y}");
+            if y != 0.0 {
+                assert_eq!((a / b).to_f64().to_bits(), (x / y).to_bits(), "{x} / {y}");
+            }
+            assert_eq!(a.floor(), x.floor() as i64, "floor({x})");
+
+            let i = rng.gen::<i64>() >> rng.gen_range(0..64);
+            assert_eq!(Fpr::from_i64(i).to_f64().to_bits(), (i as f64).to_bits());
+        }
+        assert_eq!(Fpr::from(-0.0).floor(), 0);
+        assert_eq!(Fpr::from(-0.5).floor(), -1);
+    }
+
+    #[test]
+    fn test_fpr_fft_matches_f64() {
+        let mut rng = thread_rng();
+        for logn in 1..=10 {
+            let n = 1 << logn;
+            let a = (0..n)
+                .map(|_| Complex64::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0)))
+                .collect::<Vec<_>>();
+            let psi_rev = &COMPLEX_BITREVERSED_POWERS_1024[..n];
+            let psi_inv_rev = psi_rev.iter().map(|c| c.conj()).collect::<Vec<_>>();
+            let ninv = Complex64::new(1.0 / (n as f64), 0.0);
+            let bits = |a: &[Complex64]| {
+                a.iter()
+                    .map(|c| (c.re.to_bits(), c.im.to_bits()))
+                    .collect::<Vec<_>>()
+            };
+
+            let (mut expected, mut emulated) = (a.clone(), a.clone());
+            fft_with::<Complex64>(&mut expected, psi_rev);
+            fft_with::<FprComplex>(&mut emulated, psi_rev);
+            assert_eq!(bits(&emulated), bits(&expected));
+
+            ifft_with::<Complex64>(&mut expected, &psi_inv_rev, ninv);
+            ifft_with::<FprComplex>(&mut emulated, &psi_inv_rev, ninv);
+            assert_eq!(bits(&emulated), bits(&expected));
+
+            let mut expected = (
+                vec![Complex64::default(); n / 2],
+                vec![Complex64::default(); n / 2],
+            );
+            let mut emulated = expected.clone();
+            split_fft_into_with::<Complex64>(&a, &mut expected.0, &mut expected.1, &psi_inv_rev);
+            split_fft_into_with::<FprComplex>(&a, &mut emulated.0, &mut emulated.1, &psi_inv_rev);
+            assert_eq!(bits(&emulated.0), bits(&expected.0));
+            assert_eq!(bits(&emulated.1), bits(&expected.1));
+
+            let mut expected_merged = vec![Complex64::default(); n];
+            let mut emulated_merged = expected_merged.clone();
+            merge_fft_into_with::<Complex64>(
+                &expected.0,
+                &expected.1,
+                &mut expected_merged,
+                psi_rev,
+            );
+            merge_fft_into_with::<FprComplex>(
+                &expected.0,
+                &expected.1,
+                &mut emulated_merged,
+                psi_rev,
+            );
+            assert_eq!(bits(&emulated_merged), bits(&expected_merged));
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! The API generic over the ring degree, so that code can be written once
+//! and instantiated for either parameter set:
+//!
+//! ```
+//! use falcon_rust::generic::{Falcon, PublicKey, Signature};
+//! # use rand::{thread_rng, Rng};
+//!
+//! fn accept<const N: usize>(msg: &[u8], sig: &Signature<N>, pk: &PublicKey<N>) -> bool {
+//!     Falcon::<N>::verify(msg, sig, pk)
+//! }
+//!
+//! let (sk, pk) = Falcon::<512>::keygen(thread_rng().gen());
+//! assert!(accept(b"msg", &Falcon::sign(b"msg", &sk), &pk));
+//! let (sk, pk) = Falcon::<1024>::keygen(thread_rng().gen());
+//! assert!(accept(b"msg", &Falcon::sign(b"msg", &sk), &pk));
+//! ```
+//!
+//! The types of [`falcon512`](crate::falcon512) and
+//! [`falcon1024`](crate::falcon1024) are aliases for the instantiations of
+//! the types here, and [`falcon512::Falcon`](crate::falcon512::Falcon) is
+//! `Falcon<512>`. The degree N must be 512 or 1024; the functions panic
+//! for any other.
+
+#[cfg(feature = "std")]
+pub use crate::falcon::SignScratch;
+pub use crate::falcon::{
+    ExpandedPublicKey, ExpandedSecretKey, ExpandedSignature, FalconDeserializationError,
+    FalconError, LazyExpandedSignature, PrehashAlgorithm, PublicKey, SecretKey, SecretPolynomials,
+    SignError, Signature, SignatureFormat, SigningOptions, VerificationDetails, WeakSeedError,
+};
+pub use crate::{hasher::Hasher, signed_message::SignedMessage};
+
+use alloc::vec::Vec;
+
+use rand::{CryptoRng, RngCore};
+
+#[cfg(feature = "std")]
+use crate::batch;
+use crate::{falcon, nonce::Nonce, polynomial::Polynomial, signed_message};
+
+/// The Falcon signature scheme with ring degree N, as a namespace for the
+/// functions of [`falcon512`](crate::falcon512) and
+/// [`falcon1024`](crate::falcon1024).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Falcon<const N: usize>;
+
+impl<const N: usize> Falcon<N> {
+    /// The length of an encoded public key in bytes.
+    pub const PK_LEN: usize = PublicKey::<N>::BYTE_LEN;
+    /// The length of an encoded secret key in bytes.
+    pub const SK_LEN: usize = SecretKey::<N>::BYTE_LEN;
+    /// The length of a signature as encoded by [`Signature::to_bytes`].
+    pub const SIG_LEN: usize = Signature::<N>::BYTE_LEN;
+    /// The maximum length of an encoded signature. Signatures produced by
+    /// the reference implementation can be shorter.
+    pub const SIG_MAX_LEN: usize = Signature::<N>::BYTE_LEN;
+    /// The length of the nonce in bytes.
+    pub const NONCE_LEN: usize = Nonce::LEN;
+
+    /// See [`falcon512::keygen`](crate::falcon512::keygen).
+    pub fn keygen(seed: [u8; 32]) -> (SecretKey<N>, PublicKey<N>) {
+        falcon::keygen(seed)
+    }
+
+    /// See [`falcon512::keygen_with_rng`](crate::falcon512::keygen_with_rng).
+    pub fn keygen_with_rng<R: CryptoRng + RngCore>(rng: &mut R) -> (SecretKey<N>, PublicKey<N>) {
+        falcon::keygen_with_rng(rng)
+    }
+
+    /// See [`falcon512::try_keygen`](crate::falcon512::try_keygen).
+    pub fn try_keygen(seed: [u8; 32]) -> Result<(SecretKey<N>, PublicKey<N>), WeakSeedError> {
+        falcon::try_keygen(seed)
+    }
+
+    /// See [`falcon512::keygen_from_nist_seed`](crate::falcon512::keygen_from_nist_seed).
+    pub fn keygen_from_nist_seed(seed: [u8; 48]) -> (SecretKey<N>, PublicKey<N>) {
+        falcon::keygen_from_nist_seed(seed)
+    }
+
+    /// See [`falcon512::consistent`](crate::falcon512::consistent).
+    pub fn consistent(sk: &SecretKey<N>, pk: &PublicKey<N>) -> bool {
+        falcon::consistent(sk, pk)
+    }
+
+    /// See [`falcon512::sign`](crate::falcon512::sign).
+    #[cfg(feature = "std")]
+    pub fn sign(msg: impl AsRef<[u8]>, sk: &SecretKey<N>) -> Signature<N> {
+        falcon::sign(msg, sk)
+    }
+
+    /// See [`falcon512::sign_with_seed`](crate::falcon512::sign_with_seed).
+    pub fn sign_with_seed(msg: &[u8], sk: &SecretKey<N>, seed: [u8; 32]) -> Signature<N> {
+        falcon::sign_with_seed(msg, sk, seed)
+    }
+
+    /// See [`falcon512::sign_with_rng`](crate::falcon512::sign_with_rng).
+    pub fn sign_with_rng<R: CryptoRng + RngCore>(
+        msg: &[u8],
+        sk: &SecretKey<N>,
+        rng: &mut R,
+    ) -> Signature<N> {
+        falcon::sign_with_rng(msg, sk, rng)
+    }
+
+    /// See [`falcon512::sign_with`](crate::falcon512::sign_with).
+    #[cfg(feature = "std")]
+    pub fn sign_with(
+        msg: &[u8],
+        sk: &SecretKey<N>,
+        options: &SigningOptions,
+    ) -> Result<Signature<N>, SignError> {
+        falcon::sign_with(msg, sk, options)
+    }
+
+    /// See [`falcon512::sign_bounded`](crate::falcon512::sign_bounded).
+    #[cfg(feature = "std")]
+    pub fn sign_bounded(
+        msg: &[u8],
+        sk: &SecretKey<N>,
+        max_attempts: usize,
+    ) -> Result<(Signature<N>, usize), SignError> {
+        falcon::sign_bounded(msg, sk, max_attempts)
+    }
+
+    /// See [`falcon512::sign_verified`](crate::falcon512::sign_verified).
+    #[cfg(feature = "std")]
+    pub fn sign_verified(
+        msg: &[u8],
+        sk: &SecretKey<N>,
+        pk: &PublicKey<N>,
+    ) -> Result<Signature<N>, SignError> {
+        falcon::sign_verified(msg, sk, pk)
+    }
+
+    /// See [`falcon512::sign_ctx`](crate::falcon512::sign_ctx).
+    #[cfg(feature = "std")]
+    pub fn sign_ctx(msg: &[u8], ctx: &[u8], sk: &SecretKey<N>) -> Result<Signature<N>, SignError> {
+        falcon::sign_ctx(msg, ctx, sk)
+    }
+
+    /// See [`falcon512::sign_prehash`](crate::falcon512::sign_prehash).
+    #[cfg(feature = "std")]
+    pub fn sign_prehash(
+        ph: PrehashAlgorithm,
+        digest: &[u8],
+        ctx: &[u8],
+        sk: &SecretKey<N>,
+    ) -> Result<Signature<N>, SignError> {
+        falcon::sign_prehash(ph, digest, ctx, sk)
+    }
+
+    /// See [`falcon512::sign_with_scratch`](crate::falcon512::sign_with_scratch).
+    #[cfg(feature = "std")]
+    pub fn sign_with_scratch(
+        msg: &[u8],
+        esk: &ExpandedSecretKey<N>,
+        scratch: &mut SignScratch<N>,
+    ) -> Signature<N> {
+        falcon::sign_with_scratch(msg, esk, scratch)
+    }
+
+    /// See [`falcon512::sign_attached`](crate::falcon512::sign_attached).
+    #[cfg(feature = "std")]
+    pub fn sign_attached(msg: &[u8], sk: &SecretKey<N>) -> SignedMessage<N> {
+        signed_message::sign_attached(msg, sk)
+    }
+
+    /// See [`falcon512::open`](crate::falcon512::open).
+    pub fn open(sm: &[u8], pk: &PublicKey<N>) -> Option<Vec<u8>> {
+        signed_message::open(sm, pk)
+    }
+
+    /// See [`falcon512::sign_with_expanded_key`](crate::falcon512::sign_with_expanded_key).
+    #[cfg(feature = "std")]
+    pub fn sign_with_expanded_key(msg: &[u8], esk: &ExpandedSecretKey<N>) -> Signature<N> {
+        falcon::sign_with_expanded_key(msg, esk)
+    }
+
+    /// See [`falcon512::verify`](crate::falcon512::verify).
+    pub fn verify(msg: impl AsRef<[u8]>, sig: &Signature<N>, pk: &PublicKey<N>) -> bool {
+        falcon::verify(msg, sig, pk)
+    }
+
+    /// See [`falcon512::verify_with_expanded_pk`](crate::falcon512::verify_with_expanded_pk).
+    pub fn verify_with_expanded_pk(
+        msg: &[u8],
+        sig: &Signature<N>,
+        epk: &ExpandedPublicKey<N>,
+    ) -> bool {
+        falcon::verify_with_expanded_pk(msg, sig, epk)
+    }
+
+    /// See [`falcon512::try_verify`](crate::falcon512::try_verify).
+    pub fn try_verify(
+        msg: &[u8],
+        sig: &Signature<N>,
+        pk: &PublicKey<N>,
+    ) -> Result<(), FalconError> {
+        falcon::try_verify(msg, sig, pk)
+    }
+
+    /// See [`falcon512::hash_to_point`](crate::falcon512::hash_to_point).
+    pub fn hash_to_point(msg: &[u8], nonce: &Nonce) -> Polynomial<u16> {
+        falcon::hash_to_point::<N>(msg, nonce)
+    }
+
+    /// See [`falcon512::verify_raw`](crate::falcon512::verify_raw).
+    pub fn verify_raw(c: &Polynomial<u16>, sig: &Signature<N>, pk: &PublicKey<N>) -> bool {
+        falcon::verify_raw(c, sig, pk)
+    }
+
+    /// See [`falcon512::verify_with_details`](crate::falcon512::verify_with_details).
+    pub fn verify_with_details(
+        msg: &[u8],
+        sig: &Signature<N>,
+        pk: &PublicKey<N>,
+    ) -> Result<VerificationDetails, FalconError> {
+        falcon::verify_with_details(msg, sig, pk)
+    }
+
+    /// See [`falcon512::verify_ctx`](crate::falcon512::verify_ctx).
+    pub fn verify_ctx(msg: &[u8], ctx: &[u8], sig: &Signature<N>, pk: &PublicKey<N>) -> bool {
+        falcon::verify_ctx(msg, ctx, sig, pk)
+    }
+
+    /// See [`falcon512::verify_prehash`](crate::falcon512::verify_prehash).
+    pub fn verify_prehash(
+        ph: PrehashAlgorithm,
+        digest: &[u8],
+        ctx: &[u8],
+        sig: &Signature<N>,
+        pk: &PublicKey<N>,
+    ) -> bool {
+        falcon::verify_prehash(ph, digest, ctx, sig, pk)
+    }
+
+    /// See [`falcon512::verify_any`](crate::falcon512::verify_any).
+    pub fn verify_any(msg: &[u8], sig: &Signature<N>, pks: &[PublicKey<N>]) -> Option<usize> {
+        falcon::verify_any(msg, sig, pks)
+    }
+
+    /// See [`falcon512::verify_batch`](crate::falcon512::verify_batch).
+    #[cfg(feature = "std")]
+    pub fn verify_batch(msgs: &[&[u8]], sigs: &[Signature<N>], pk: &PublicKey<N>) -> Vec<bool> {
+        batch::verify_batch(msgs, sigs, pk)
+    }
+
+    /// See [`falcon512::verify_expanded`](crate::falcon512::verify_expanded).
+    pub fn verify_expanded(msg: &[u8], sig: &ExpandedSignature<N>, pk: &PublicKey<N>) -> bool {
+        falcon::verify_expanded(msg, sig, pk)
+    }
+
+    /// See [`falcon512::fverify`](crate::falcon512::fverify).
+    pub fn fverify(
+        msg: &[u8],
+        sig: &ExpandedSignature<N>,
+        pk: &PublicKey<N>,
+        indices: &[usize],
+    ) -> bool {
+        falcon::fverify(msg, sig, pk, indices)
+    }
+
+    /// See [`falcon512::fverify_fs`](crate::falcon512::fverify_fs).
+    pub fn fverify_fs(
+        msg: &[u8],
+        sig: &ExpandedSignature<N>,
+        pk: &PublicKey<N>,
+        num_indices: usize,
+    ) -> bool {
+        falcon::fverify_fs(msg, sig, pk, num_indices)
+    }
+
+    /// See [`falcon512::fverify_required_indices`](crate::falcon512::fverify_required_indices).
+    pub fn fverify_required_indices(target_false_accept: f64) -> usize {
+        falcon::fverify_required_indices::<N>(target_false_accept)
+    }
+
+    /// See [`falcon512::fverify_fullverify`](crate::falcon512::fverify_fullverify).
+    pub fn fverify_fullverify(
+        msg: &[u8],
+        sig: &ExpandedSignature<N>,
+        pk: &PublicKey<N>,
+        indices: &[usize],
+    ) -> bool {
+        falcon::fverify_fullverify(msg, sig, pk, indices)
+    }
+
+    /// See [`falcon512::fverify_direct`](crate::falcon512::fverify_direct).
+    pub fn fverify_direct(msg: &[u8], sig: &[u8], pk: &PublicKey<N>, indices: &[usize]) -> bool {
+        falcon::fverify_direct(msg, sig, pk, indices)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon1024, falcon512};
+
+    use super::{Falcon, PublicKey, SecretKey, Signature};
+
+    fn round_trip<const N: usize>() {
+        let (sk, pk) = Falcon::<N>::keygen(thread_rng().gen());
+        let sig = Falcon::sign(b"msg", &sk);
+        assert!(Falcon::verify(b"msg", &sig, &pk));
+        assert!(!Falcon::verify(b"other", &sig, &pk));
+        assert!(Falcon::consistent(&sk, &pk));
+
+        assert_eq!(sk.to_bytes().len(), Falcon::<N>::SK_LEN);
+        assert_eq!(pk.to_bytes().len(), Falcon::<N>::PK_LEN);
+        assert_eq!(sig.to_bytes().len(), Falcon::<N>::SIG_LEN);
+        assert_eq!(SecretKey::<N>::from_bytes(&sk.to_bytes()).unwrap(), sk);
+        assert_eq!(PublicKey::<N>::from_bytes(&pk.to_bytes()).unwrap(), pk);
+        assert_eq!(Signature::<N>::from_bytes(&sig.to_bytes()).unwrap(), sig);
+
+        // buffers sized by the constants, parsed with `TryFrom`
+        assert!(sig.to_reference_bytes().len() <= Falcon::<N>::SIG_MAX_LEN);
+        let mut buffer = vec![0u8; Falcon::<N>::PK_LEN + Falcon::<N>::SIG_MAX_LEN];
+        buffer[..Falcon::<N>::PK_LEN].copy_from_slice(pk.as_ref());
+        buffer[Falcon::<N>::PK_LEN..].copy_from_slice(sig.as_ref());
+        let (pk_bytes, sig_bytes) = buffer.split_at(Falcon::<N>::PK_LEN);
+        assert_eq!(PublicKey::<N>::try_from(pk_bytes).unwrap(), pk);
+        assert_eq!(Signature::<N>::try_from(sig_bytes).unwrap(), sig);
+        let sk_bytes = sk.to_bytes();
+        assert_eq!(SecretKey::<N>::try_from(sk_bytes.as_slice()).unwrap(), sk);
+        assert!(SecretKey::<N>::try_from(&sk_bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn test_generic() {
+        round_trip::<512>();
+        round_trip::<1024>();
+
+        // the per-variant modules are aliases
+        let (sk, pk) = falcon512::keygen(thread_rng().gen());
+        let sig: falcon512::Signature = falcon512::Falcon::sign(b"msg", &sk);
+        assert!(falcon512::verify(b"msg", &sig, &pk));
+        assert_eq!(falcon1024::Falcon::SIG_LEN, falcon1024::SIG_LEN);
+        assert_eq!(falcon512::Falcon::SIG_MAX_LEN, falcon512::SIG_MAX_LEN);
+        assert_eq!(falcon1024::Falcon::SIG_MAX_LEN, falcon1024::SIG_MAX_LEN);
+    }
+}
@@ -0,0 +1,167 @@
+//! Signing and verifying messages that are not in memory all at once.
+//!
+//! Falcon hashes the salt followed by the message, so the salt must be known
+//! before the first byte of the message: a [`Hasher`] for signing draws a
+//! fresh salt when created, and one for verifying takes the salt of the
+//! signature.
+//!
+//! ```
+//! # use falcon_rust::falcon512::{self, Hasher};
+//! # use rand::{thread_rng, Rng};
+//! # let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//! let mut hasher = Hasher::new();
+//! hasher.update(b"a multi-gigabyte ");
+//! hasher.update(b"payload");
+//! let sig = hasher.finalize_sign(&sk);
+//!
+//! let mut hasher = Hasher::for_signature(&sig);
+//! hasher.update(b"a multi-gigabyte payload");
+//! assert!(hasher.finalize_verify(&sig, &pk));
+//! assert!(falcon512::verify(b"a multi-gigabyte payload", &sig, &pk));
+//! ```
+
+use alloc::vec;
+
+#[cfg(feature = "std")]
+use rand::RngCore;
+use sha3::{digest::Update, Shake256};
+
+#[cfg(feature = "std")]
+use crate::{entropy, falcon::SecretKey};
+use crate::{
+    falcon::{self, FalconVariant, PublicKey, Signature},
+    falcon_field::Felt,
+    fast_fft::FastFft,
+    nonce::Nonce,
+    polynomial::{squeeze_point_into, Polynomial},
+};
+
+/// The hash of a salt and a message that is fed in pieces, to be finished
+/// into a signature or the verification of one.
+#[derive(Clone)]
+pub struct Hasher<const N: usize> {
+    r: [u8; 40],
+    shake: Shake256,
+}
+
+impl<const N: usize> Hasher<N> {
+    /// Start hashing a message to sign, under a fresh random salt.
+    #[cfg(feature = "std")]
+    pub fn new() -> Self {
+        let mut r = [0u8; 40];
+        entropy::rng().fill_bytes(&mut r);
+        Self::with_salt(r)
+    }
+
+    /// Start hashing a message to verify the given signature on.
+    pub fn for_signature(sig: &Signature<N>) -> Self {
+        Self::with_salt(sig.r)
+    }
+
+    fn with_salt(r: [u8; 40]) -> Self {
+        let mut shake = Shake256::default();
+        shake.update(&r);
+        Self { r, shake }
+    }
+
+    /// The salt that is hashed in front of the message.
+    pub fn nonce(&self) -> Nonce {
+        Nonce::new(self.r)
+    }
+
+    /// Append a piece of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.shake.update(data);
+    }
+
+    fn finalize(self) -> ([u8; 40], Polynomial<Felt>) {
+        let mut coefficients = vec![Felt::new(0); N];
+        squeeze_point_into(self.shake, &mut coefficients);
+        (self.r, Polynomial::new(coefficients))
+    }
+
+    /// Sign the message hashed so far. The signature verifies against the
+    /// concatenation of the pieces like any other, e.g., with
+    /// [`falcon512::verify`](crate::falcon512::verify).
+    #[cfg(feature = "std")]
+    pub fn finalize_sign(self, sk: &SecretKey<N>) -> Signature<N> {
+        let (r, c) = self.finalize();
+        falcon::try_sign_hashed(r, &c, sk, &mut entropy::rng(), None)
+            .expect("signing without attempt limit cannot fail")
+    }
+
+    /// Verify a signature on the message hashed so far. Fails if the hasher
+    /// was not created for this signature, i.e., with another salt.
+    pub fn finalize_verify(self, sig: &Signature<N>, pk: &PublicKey<N>) -> bool {
+        if self.r != sig.r {
+            return false;
+        }
+        let (_, c) = self.finalize();
+        falcon::prepare_hashed_verification(c, sig).is_ok_and(|prepared| {
+            prepared
+                .check(&pk.h.fft(), FalconVariant::from_n(N).parameters().sig_bound)
+                .is_ok()
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> Default for Hasher<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Feeds the message with, e.g., [`std::io::copy`].
+#[cfg(feature = "std")]
+impl<const N: usize> std::io::Write for Hasher<N> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon::{keygen, sign, verify};
+
+    use super::Hasher;
+
+    #[test]
+    fn test_hasher() {
+        fn check<const N: usize>() {
+            let mut rng = thread_rng();
+            let (sk, pk) = keygen::<N>(rng.gen());
+            let msg = (0..10_000).map(|_| rng.gen()).collect::<Vec<u8>>();
+
+            let mut hasher = Hasher::<N>::new();
+            for chunk in msg.chunks(999) {
+                hasher.update(chunk);
+            }
+            let nonce = hasher.nonce();
+            let sig = hasher.finalize_sign(&sk);
+            assert_eq!(sig.nonce(), nonce);
+            assert!(verify(&msg, &sig, &pk));
+
+            let sig = sign(&msg, &sk);
+            let mut hasher = Hasher::for_signature(&sig);
+            std::io::copy(&mut msg.as_slice(), &mut hasher).unwrap();
+            assert!(hasher.clone().finalize_verify(&sig, &pk));
+            hasher.update(b"trailing");
+            assert!(!hasher.finalize_verify(&sig, &pk));
+
+            // a hasher with another salt
+            let mut hasher = Hasher::new();
+            hasher.update(&msg);
+            assert!(!hasher.finalize_verify(&sig, &pk));
+        }
+        check::<512>();
+        check::<1024>();
+    }
+}
@@ -0,0 +1,217 @@
+//! Composite signatures of Falcon-512 and Ed25519, which remain secure as
+//! long as either of the two is, for deployments that adopt post-quantum
+//! signatures without giving up classical assurance:
+//!
+//! ```
+//! use falcon_rust::hybrid::HybridKeypair;
+//!
+//! let keypair = HybridKeypair::generate();
+//! let pk = keypair.public_key();
+//! let sig = keypair.sign(b"message");
+//! assert!(pk.verify(b"message", &sig));
+//! ```
+//!
+//! The construction follows the composite signatures of the IETF LAMPS
+//! draft: both components sign the same message representative
+//!
+//! ```text
+//! M' = "CompositeAlgorithmSignatures2025" || label || 0x00 || SHA-512(M)
+//! ```
+//!
+//! where the zero byte is the length of the empty context and `label` is
+//! [`LABEL`], and a composite signature verifies only if both components
+//! do. The draft does not define a Falcon combination yet, so the label is
+//! experimental. Public keys and signatures are encoded as the Falcon
+//! encoding followed by the Ed25519 one, as in the draft.
+
+use alloc::vec::Vec;
+
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use rand::Rng;
+use sha2::{Digest, Sha512};
+
+use crate::{
+    entropy,
+    falcon::{self, FalconDeserializationError, PublicKey, SecretKey, Signature},
+};
+
+const PREFIX: &[u8] = b"CompositeAlgorithmSignatures2025";
+
+/// The experimental label of the Falcon-512 and Ed25519 combination.
+pub const LABEL: &[u8] = b"COMPSIG-Falcon512-Ed25519-SHA512";
+
+fn message_representative(m: &[u8]) -> Vec<u8> {
+    [PREFIX, LABEL, &[0], &Sha512::digest(m)].concat()
+}
+
+/// A Falcon-512 and an Ed25519 secret key that sign together.
+pub struct HybridKeypair {
+    falcon_sk: SecretKey<512>,
+    falcon_pk: PublicKey<512>,
+    ed25519: SigningKey,
+}
+
+/// The public half of a [`HybridKeypair`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HybridPublicKey {
+    falcon: PublicKey<512>,
+    ed25519: VerifyingKey,
+}
+
+/// A Falcon-512 and an Ed25519 signature on the same message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HybridSignature {
+    falcon: Signature<512>,
+    ed25519: ed25519_dalek::Signature,
+}
+
+impl HybridKeypair {
+    /// Generate both key pairs from fresh randomness.
+    pub fn generate() -> Self {
+        let mut rng = entropy::rng();
+        let (falcon_sk, falcon_pk) = falcon::keygen(rng.gen());
+        Self {
+            falcon_sk,
+            falcon_pk,
+            ed25519: SigningKey::from_bytes(&rng.gen()),
+        }
+    }
+
+    /// Combine existing secret keys.
+    pub fn from_parts(falcon: SecretKey<512>, ed25519: SigningKey) -> Self {
+        Self {
+            falcon_pk: falcon.public_key(),
+            falcon_sk: falcon,
+            ed25519,
+        }
+    }
+
+    /// The public keys of both components.
+    pub fn public_key(&self) -> HybridPublicKey {
+        HybridPublicKey {
+            falcon: self.falcon_pk.clone(),
+            ed25519: self.ed25519.verifying_key(),
+        }
+    }
+
+    /// Sign the message with both keys.
+    pub fn sign(&self, m: impl AsRef<[u8]>) -> HybridSignature {
+        let representative = message_representative(m.as_ref());
+        HybridSignature {
+            falcon: falcon::sign(&representative, &self.falcon_sk),
+            ed25519: self.ed25519.sign(&representative),
+        }
+    }
+}
+
+impl HybridPublicKey {
+    pub const BYTE_LEN: usize = PublicKey::<512>::BYTE_LEN + PUBLIC_KEY_LENGTH;
+
+    pub fn new(falcon: PublicKey<512>, ed25519: VerifyingKey) -> Self {
+        Self { falcon, ed25519 }
+    }
+
+    pub fn falcon(&self) -> &PublicKey<512> {
+        &self.falcon
+    }
+
+    pub fn ed25519(&self) -> &VerifyingKey {
+        &self.ed25519
+    }
+
+    /// Verify a composite signature, which is valid only if both of its
+    /// components are.
+    pub fn verify(&self, m: impl AsRef<[u8]>, sig: &HybridSignature) -> bool {
+        let representative = message_representative(m.as_ref());
+        falcon::verify(&representative, &sig.falcon, &self.falcon)
+            && self
+                .ed25519
+                .verify_strict(&representative, &sig.ed25519)
+                .is_ok()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.falcon.as_bytes(), self.ed25519.as_bytes()].concat()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        if bytes.len() != Self::BYTE_LEN {
+            return Err(FalconDeserializationError::BadEncodingLength);
+        }
+        let (falcon, ed25519) = bytes.split_at(PublicKey::<512>::BYTE_LEN);
+        Ok(Self {
+            falcon: PublicKey::from_bytes(falcon)?,
+            ed25519: VerifyingKey::try_from(ed25519)
+                .map_err(|_| FalconDeserializationError::BadFieldElementEncoding)?,
+        })
+    }
+}
+
+impl HybridSignature {
+    pub fn falcon(&self) -> &Signature<512> {
+        &self.falcon
+    }
+
+    pub fn ed25519(&self) -> &ed25519_dalek::Signature {
+        &self.ed25519
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.falcon.to_bytes().as_slice(), &self.ed25519.to_bytes()].concat()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        let Some(split) = bytes.len().checked_sub(SIGNATURE_LENGTH) else {
+            return Err(FalconDeserializationError::BadEncodingLength);
+        };
+        let (falcon, ed25519) = bytes.split_at(split);
+        Ok(Self {
+            falcon: Signature::from_bytes(falcon)?,
+            ed25519: ed25519_dalek::Signature::from_slice(ed25519)
+                .map_err(|_| FalconDeserializationError::BadEncodingLength)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ed25519_dalek::{Signer, SigningKey};
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon::{keygen, sign};
+
+    use super::{message_representative, HybridKeypair, HybridPublicKey, HybridSignature};
+
+    #[test]
+    fn test_hybrid() {
+        let keypair = HybridKeypair::generate();
+        let pk = keypair.public_key();
+        let sig = keypair.sign(b"message");
+        assert!(pk.verify(b"message", &sig));
+        assert!(!pk.verify(b"other message", &sig));
+
+        let pk = HybridPublicKey::from_bytes(&pk.to_bytes()).unwrap();
+        assert_eq!(pk.to_bytes().len(), HybridPublicKey::BYTE_LEN);
+        let sig = HybridSignature::from_bytes(&sig.to_bytes()).unwrap();
+        assert!(pk.verify(b"message", &sig));
+        assert!(HybridPublicKey::from_bytes(&pk.to_bytes()[1..]).is_err());
+        assert!(HybridSignature::from_bytes(&sig.to_bytes()[..60]).is_err());
+
+        // a composite signature with either component replaced fails
+        let (falcon_sk, _) = keygen::<512>(thread_rng().gen());
+        let other = SigningKey::from_bytes(&thread_rng().gen());
+        let representative = message_representative(b"message");
+        let mut forged = sig.clone();
+        forged.falcon = sign(&representative, &falcon_sk);
+        assert!(!pk.verify(b"message", &forged));
+        let mut forged = sig.clone();
+        forged.ed25519 = other.sign(&representative);
+        assert!(!pk.verify(b"message", &forged));
+
+        let keypair = HybridKeypair::from_parts(falcon_sk, other);
+        assert!(!pk.verify(b"message", &keypair.sign(b"message")));
+        assert!(keypair
+            .public_key()
+            .verify(b"message", &keypair.sign(b"message")));
+    }
+}
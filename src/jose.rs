@@ -0,0 +1,142 @@
+//! JSON Web Signatures ([RFC 7515]) with Falcon, in the compact
+//! serialization, e.g., for experimenting with post-quantum JWTs:
+//!
+//! ```
+//! use falcon_rust::{falcon512, jose};
+//! # use rand::{thread_rng, Rng};
+//!
+//! let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//! let token = jose::sign(br#"{"sub":"alice"}"#, &sk);
+//! assert!(token.starts_with("eyJhbGciOiJGQUxDT041MTIifQ."));
+//! assert_eq!(jose::verify(&token, &pk).unwrap(), br#"{"sub":"alice"}"#);
+//! ```
+//!
+//! A token is `header.payload.signature`, each part in unpadded base64url,
+//! where the header is the JSON object `{"alg":"FALCON512"}` and the
+//! signature, in the encoding of [`Signature::to_bytes`], is on the ASCII
+//! string `header.payload`. JOSE has no algorithm names for Falcon yet;
+//! until they are registered, tokens carry the experimental values
+//! [`FALCON512_ALG`] and [`FALCON1024_ALG`].
+//!
+//! [RFC 7515]: https://www.rfc-editor.org/rfc/rfc7515
+
+use alloc::{format, string::String, vec::Vec};
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use serde_json::Value;
+
+use crate::falcon::{self, PublicKey, SecretKey, Signature};
+
+/// The experimental JWS algorithm name of Falcon-512.
+pub const FALCON512_ALG: &str = "FALCON512";
+
+/// The experimental JWS algorithm name of Falcon-1024.
+pub const FALCON1024_ALG: &str = "FALCON1024";
+
+/// The JWS algorithm name of Falcon-N, as found in the `alg` header
+/// parameter.
+pub const fn algorithm<const N: usize>() -> &'static str {
+    match N {
+        512 => FALCON512_ALG,
+        1024 => FALCON1024_ALG,
+        _ => unreachable!(),
+    }
+}
+
+/// Sign the payload into a token in the compact serialization.
+pub fn sign<const N: usize>(payload: &[u8], sk: &SecretKey<N>) -> String {
+    let header = format!(r#"{{"alg":"{}"}}"#, algorithm::<N>());
+    let signing_input = format!(
+        "{}.{}",
+        Base64UrlUnpadded::encode_string(header.as_bytes()),
+        Base64UrlUnpadded::encode_string(payload)
+    );
+    let sig = falcon::sign(&signing_input, sk);
+    format!(
+        "{}.{}",
+        signing_input,
+        Base64UrlUnpadded::encode_string(&sig.to_bytes())
+    )
+}
+
+/// Verify a token in the compact serialization and return its payload if
+/// it is valid. Fails unless the header names Falcon-N as the algorithm
+/// and has no critical extensions, which are not supported.
+pub fn verify<const N: usize>(token: &str, pk: &PublicKey<N>) -> Option<Vec<u8>> {
+    let (signing_input, signature) = token.rsplit_once('.')?;
+    let (header, payload) = signing_input.split_once('.')?;
+
+    let header = Base64UrlUnpadded::decode_vec(header).ok()?;
+    let Value::Object(header) = serde_json::from_slice(&header).ok()? else {
+        return None;
+    };
+    if header.get("alg")?.as_str()? != algorithm::<N>() || header.contains_key("crit") {
+        return None;
+    }
+
+    let signature = Base64UrlUnpadded::decode_vec(signature).ok()?;
+    let signature = Signature::<N>::from_bytes(&signature).ok()?;
+    if !falcon::verify(signing_input, &signature, pk) {
+        return None;
+    }
+    Base64UrlUnpadded::decode_vec(payload).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use base64ct::{Base64UrlUnpadded, Encoding};
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon::{keygen, sign as falcon_sign};
+
+    use super::{sign, verify};
+
+    #[test]
+    fn test_jws() {
+        let (sk, pk) = keygen::<512>(thread_rng().gen());
+        let token = sign(b"payload", &sk);
+        assert_eq!(verify(&token, &pk).unwrap(), b"payload");
+        let parts = token.split('.').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(
+            Base64UrlUnpadded::decode_vec(parts[0]).unwrap(),
+            br#"{"alg":"FALCON512"}"#
+        );
+        assert_eq!(verify(&sign(b"", &sk), &pk).unwrap(), b"");
+
+        let (_, other_pk) = keygen::<512>(thread_rng().gen());
+        assert!(verify(&token, &other_pk).is_none());
+        let (sk1024, pk1024) = keygen::<1024>(thread_rng().gen());
+        assert!(verify(&token, &pk1024).is_none());
+        let token1024 = sign(b"payload", &sk1024);
+        assert_eq!(verify(&token1024, &pk1024).unwrap(), b"payload");
+        assert!(verify(&token1024, &pk).is_none());
+
+        let other_payload = Base64UrlUnpadded::encode_string(b"other payload");
+        let tampered = [parts[0], &other_payload, parts[2]].join(".");
+        assert!(verify(&tampered, &pk).is_none());
+        assert!(verify(&parts[..2].join("."), &pk).is_none());
+        assert!(verify("", &pk).is_none());
+
+        // validly signed, but with a header that must be rejected
+        for header in [
+            r#"{"alg":"EdDSA"}"#,
+            r#"{"alg":"FALCON512","crit":["exp"],"exp":1}"#,
+            r#"["FALCON512"]"#,
+            r#"{"typ":"JWT"}"#,
+        ] {
+            let signing_input = format!(
+                "{}.{}",
+                Base64UrlUnpadded::encode_string(header.as_bytes()),
+                parts[1]
+            );
+            let sig = falcon_sign(&signing_input, &sk).to_bytes();
+            let token = format!(
+                "{}.{}",
+                signing_input,
+                Base64UrlUnpadded::encode_string(&sig)
+            );
+            assert!(verify(&token, &pk).is_none());
+        }
+    }
+}
@@ -0,0 +1,505 @@
+//! Known-answer tests in the format of the NIST submission package.
+//!
+//! The submission package ships `falcon512-KAT.rsp` and `falcon1024-KAT.rsp`,
+//! which list for each of a hundred DRBG seeds the key pair and the signed
+//! message that the reference implementation produces. This module parses
+//! such files and checks a record against this crate: key generation from the
+//! seed the reference implementation drew from the DRBG must reproduce the
+//! recorded key pair, the recorded signature must verify, and signing the
+//! message with the randomness drawn next must reproduce the recorded
+//! signature byte for byte.
+//!
+//! Only the first record of each KAT file is bundled with the crate, under
+//! `fixtures/`, and always checked by the tests of this module. To check all
+//! of them, point the `FALCON_KAT_DIR` environment variable at the directory
+//! holding the files and run the tests of this module.
+
+use aes::{
+    cipher::{BlockEncrypt, KeyInit},
+    Aes256,
+};
+
+use crate::{
+    falcon::{
+        self, FalconDeserializationError, PublicKey, SecretKey, SignError, Signature,
+        SigningOptions,
+    },
+    nonce::Nonce,
+    records::{parse_records, Field},
+};
+
+/// The AES-256 CTR DRBG of the NIST PQC test harness (`rng.c`), which is
+/// what `randombytes()` draws from when the KAT files are generated.
+pub struct NistDrbg {
+    key: [u8; 32],
+    v: [u8; 16],
+}
+
+impl NistDrbg {
+    /// Seed the generator with 48 bytes of entropy and no personalization
+    /// string, like `randombytes_init(entropy, NULL, 256)`.
+    pub fn new(entropy: &[u8; 48]) -> Self {
+        let mut drbg = Self {
+            key: [0; 32],
+            v: [0; 16],
+        };
+        drbg.update(Some(entropy));
+        drbg
+    }
+
+    /// Fill `dest` with the next bytes, like `randombytes(dest, dest.len())`.
+    /// Every call advances the state, so two calls do not produce the same
+    /// bytes as one call of the combined length.
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(16) {
+            let block = self.next_block();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+        self.update(None);
+    }
+
+    fn next_block(&mut self) -> [u8; 16] {
+        // V is a big-endian counter
+        for byte in self.v.iter_mut().rev() {
+            *byte = byte.wrapping_add(1);
+            if *byte != 0 {
+                break;
+            }
+        }
+        let mut block = self.v.into();
+        Aes256::new(&self.key.into()).encrypt_block(&mut block);
+        block.into()
+    }
+
+    fn update(&mut self, provided_data: Option<&[u8; 48]>) {
+        let mut temp = [0u8; 48];
+        for chunk in temp.chunks_exact_mut(16) {
+            chunk.copy_from_slice(&self.next_block());
+        }
+        if let Some(data) = provided_data {
+            for (t, d) in temp.iter_mut().zip(data) {
+                *t ^= d;
+            }
+        }
+        self.key.copy_from_slice(&temp[..32]);
+        self.v.copy_from_slice(&temp[32..]);
+    }
+}
+
+/// One record of a KAT file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KatRecord {
+    /// The position of the record in the file.
+    pub count: usize,
+    /// The seed of the DRBG from which all randomness of the record is
+    /// drawn.
+    pub seed: [u8; 48],
+    /// The message that was signed.
+    pub message: Vec<u8>,
+    /// The encoded public key.
+    pub public_key: Vec<u8>,
+    /// The encoded secret key.
+    pub secret_key: Vec<u8>,
+    /// The output of the reference `crypto_sign()`; see
+    /// [`to_signed_message`].
+    pub signed_message: Vec<u8>,
+}
+
+/// Reasons why a KAT file fails to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KatParseError {
+    /// The line (1-based) is neither blank, a comment, nor `key = value`.
+    MalformedLine(usize),
+    /// The line (1-based) has a key that is not part of the format.
+    UnknownKey(usize),
+    /// The line (1-based) has a value that is not valid hex.
+    InvalidHex(usize),
+    /// The line (1-based) has a value that is not a decimal number.
+    InvalidNumber(usize),
+    /// The line (1-based) has a seed that is not 48 bytes long.
+    InvalidSeed(usize),
+    /// The line (1-based) has a value whose length disagrees with the
+    /// `mlen` or `smlen` of its record.
+    LengthMismatch(usize),
+    /// A record ending on the given line (1-based) lacks the named field.
+    MissingField(usize, &'static str),
+}
+
+/// Parse a KAT file.
+///
+/// Records are blocks of `key = value` lines separated by blank lines, with
+/// the keys `count`, `seed`, `mlen`, `msg`, `pk`, `sk`, `smlen` and `sm`.
+/// Lines starting with `#` are comments.
+pub fn parse_rsp(text: &str) -> Result<Vec<KatRecord>, KatParseError> {
+    #[derive(Default)]
+    struct Partial {
+        count: Option<usize>,
+        seed: Option<[u8; 48]>,
+        message_length: Option<usize>,
+        message: Option<Vec<u8>>,
+        public_key: Option<Vec<u8>>,
+        secret_key: Option<Vec<u8>>,
+        signed_message_length: Option<usize>,
+        signed_message: Option<Vec<u8>>,
+    }
+
+    impl Partial {
+        fn finish(self, line: usize) -> Result<KatRecord, KatParseError> {
+            let missing = |field| KatParseError::MissingField(line, field);
+            let message = self.message.ok_or_else(|| missing("msg"))?;
+            let signed_message = self.signed_message.ok_or_else(|| missing("sm"))?;
+            if self.message_length.ok_or_else(|| missing("mlen"))? != message.len()
+                || self.signed_message_length.ok_or_else(|| missing("smlen"))?
+                    != signed_message.len()
+            {
+                return Err(KatParseError::LengthMismatch(line));
+            }
+            Ok(KatRecord {
+                count: self.count.ok_or_else(|| missing("count"))?,
+                seed: self.seed.ok_or_else(|| missing("seed"))?,
+                message,
+                public_key: self.public_key.ok_or_else(|| missing("pk"))?,
+                secret_key: self.secret_key.ok_or_else(|| missing("sk"))?,
+                signed_message,
+            })
+        }
+    }
+
+    parse_records(text, KatParseError::MalformedLine, |fields, line| {
+        let mut current = Partial::default();
+        for &Field { line, key, value } in fields {
+            let decode = |v: &str| hex::decode(v).map_err(|_| KatParseError::InvalidHex(line));
+            let number = |v: &str| {
+                v.parse::<usize>()
+                    .map_err(|_| KatParseError::InvalidNumber(line))
+            };
+            match key {
+                "count" => current.count = Some(number(value)?),
+                "seed" => {
+                    let seed = decode(value)?
+                        .try_into()
+                        .map_err(|_| KatParseError::InvalidSeed(line))?;
+                    current.seed = Some(seed);
+                }
+                "mlen" => current.message_length = Some(number(value)?),
+                "msg" => current.message = Some(decode(value)?),
+                "pk" => current.public_key = Some(decode(value)?),
+                "sk" => current.secret_key = Some(decode(value)?),
+                "smlen" => current.signed_message_length = Some(number(value)?),
+                "sm" => current.signed_message = Some(decode(value)?),
+                _ => return Err(KatParseError::UnknownKey(line)),
+            }
+        }
+        current.finish(line)
+    })
+}
+
+/// Encode a message and its signature like the reference `crypto_sign()`:
+/// the length of the encoded signature as two big-endian bytes, the nonce,
+/// the message, and a header byte followed by the compressed signature.
+pub fn to_signed_message<const N: usize>(m: &[u8], sig: &Signature<N>) -> Vec<u8> {
+    let compressed = sig.compressed_s();
+    let header = 0x20 | N.ilog2() as u8;
+    let length = (compressed.len() as u16 + 1).to_be_bytes();
+    [
+        length.as_slice(),
+        sig.nonce().as_bytes(),
+        m,
+        &[header],
+        compressed,
+    ]
+    .concat()
+}
+
+/// Split the output of the reference `crypto_sign()` into the message and
+/// its signature, or return `None` if it is malformed. The signature is not
+/// verified.
+pub fn from_signed_message<const N: usize>(sm: &[u8]) -> Option<(&[u8], Signature<N>)> {
+    let (length, rest) = sm.split_first_chunk::<2>()?;
+    let (nonce, rest) = rest.split_first_chunk::<{ Nonce::LEN }>()?;
+    let length = u16::from_be_bytes(*length) as usize;
+    let split = rest.len().checked_sub(length)?;
+    let (m, esig) = rest.split_at(split);
+    let (&header, compressed) = esig.split_first()?;
+    if header != 0x20 | N.ilog2() as u8 {
+        return None;
+    }
+    let reference = [&[0x30 | N.ilog2() as u8], nonce.as_slice(), compressed].concat();
+    Some((m, Signature::from_bytes(&reference).ok()?))
+}
+
+/// Reasons why a KAT record does not match this implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KatError {
+    /// The public or the secret key fails to decode.
+    MalformedKey(FalconDeserializationError),
+    /// The public key does not follow from the secret key.
+    PublicKeyMismatch,
+    /// Key generation from the recorded randomness produced another key
+    /// pair.
+    KeygenMismatch,
+    /// The signed message fails to decode, or holds another message.
+    MalformedSignedMessage,
+    /// The recorded signature does not verify.
+    VerificationFailed,
+    /// Signing with the recorded randomness failed.
+    SigningFailed(SignError),
+    /// Signing with the recorded randomness produced another signature.
+    SignatureMismatch,
+}
+
+impl From<FalconDeserializationError> for KatError {
+    fn from(error: FalconDeserializationError) -> Self {
+        Self::MalformedKey(error)
+    }
+}
+
+/// Check a KAT record for the given degree against this implementation.
+pub fn check_record<const N: usize>(record: &KatRecord) -> Result<(), KatError> {
+    let sk = SecretKey::<N>::from_bytes(&record.secret_key)?;
+    let pk = PublicKey::<N>::from_bytes(&record.public_key)?;
+    if PublicKey::from_secret_key(&sk) != pk {
+        return Err(KatError::PublicKeyMismatch);
+    }
+
+    // crypto_sign_keypair() draws the key generation seed, then
+    // crypto_sign() draws the nonce and the sampler seed
+    let mut drbg = NistDrbg::new(&record.seed);
+    let mut keygen_seed = [0u8; 48];
+    drbg.fill_bytes(&mut keygen_seed);
+    let (generated_sk, generated_pk) = falcon::keygen_from_nist_seed::<N>(keygen_seed);
+    if generated_sk.to_bytes() != record.secret_key || generated_pk != pk {
+        return Err(KatError::KeygenMismatch);
+    }
+
+    let (m, sig) = from_signed_message::<N>(&record.signed_message)
+        .filter(|(m, _)| *m == record.message)
+        .ok_or(KatError::MalformedSignedMessage)?;
+    if !falcon::verify(m, &sig, &pk) {
+        return Err(KatError::VerificationFailed);
+    }
+
+    let mut nonce = [0u8; Nonce::LEN];
+    drbg.fill_bytes(&mut nonce);
+    let mut seed = [0u8; 48];
+    drbg.fill_bytes(&mut seed);
+
+    let nonce = Nonce::new(nonce);
+    let options = SigningOptions::new().reference_randomness(&nonce, &seed);
+    let sig = falcon::sign_with(&record.message, &sk, &options).map_err(KatError::SigningFailed)?;
+    if to_signed_message(&record.message, &sig) != record.signed_message {
+        return Err(KatError::SignatureMismatch);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, path::Path};
+
+    use crate::{
+        falcon::{self, SigningOptions},
+        falcon512,
+        nonce::Nonce,
+    };
+
+    use super::{
+        check_record, from_signed_message, parse_rsp, to_signed_message, KatError, KatParseError,
+        KatRecord, NistDrbg,
+    };
+
+    /// The DRBG with which the NIST harness generates the seeds and messages
+    /// of all KAT files.
+    fn harness_drbg() -> NistDrbg {
+        NistDrbg::new(&std::array::from_fn(|i| i as u8))
+    }
+
+    #[test]
+    fn test_drbg() {
+        let mut drbg = harness_drbg();
+        let mut seed = [0u8; 48];
+        drbg.fill_bytes(&mut seed);
+        let mut msg = [0u8; 33];
+        drbg.fill_bytes(&mut msg);
+
+        // count = 0 in every KAT file of the NIST PQC process
+        assert_eq!(
+            hex::encode_upper(seed),
+            "061550234D158C5EC95595FE04EF7A25767F2E24CC2BC479D09D86DC9ABCFDE7\
+             056A8C266F9EF97ED08541DBD2E1FFA1"
+        );
+        assert_eq!(
+            hex::encode_upper(msg),
+            "D81C4D8D734FCBFBEADE3D3F8A039FAA2A2C9957E835AD55B22E75BF57BB556AC8"
+        );
+    }
+
+    /// Produce a record the way the NIST harness does.
+    fn make_record(count: usize, seed: [u8; 48], message: Vec<u8>) -> KatRecord {
+        let mut drbg = NistDrbg::new(&seed);
+        let mut keygen_seed = [0u8; 48];
+        drbg.fill_bytes(&mut keygen_seed);
+        let (sk, pk) = falcon512::keygen_from_nist_seed(keygen_seed);
+        let mut nonce = [0u8; Nonce::LEN];
+        drbg.fill_bytes(&mut nonce);
+        let mut sign_seed = [0u8; 48];
+        drbg.fill_bytes(&mut sign_seed);
+
+        let nonce = Nonce::new(nonce);
+        let options = SigningOptions::new().reference_randomness(&nonce, &sign_seed);
+        let sig = falcon::sign_with(&message, &sk, &options).unwrap();
+        KatRecord {
+            count,
+            seed,
+            signed_message: to_signed_message(&message, &sig),
+            message,
+            public_key: pk.to_bytes(),
+            secret_key: sk.to_bytes(),
+        }
+    }
+
+    fn to_rsp(records: &[KatRecord]) -> String {
+        let mut text = "# Falcon-512\n\n".to_string();
+        for record in records {
+            text += &format!(
+                "count = {}\nseed = {}\nmlen = {}\nmsg = {}\npk = {}\nsk = {}\nsmlen = {}\nsm = {}\n\n",
+                record.count,
+                hex::encode_upper(record.seed),
+                record.message.len(),
+                hex::encode_upper(&record.message),
+                hex::encode_upper(&record.public_key),
+                hex::encode_upper(&record.secret_key),
+                record.signed_message.len(),
+                hex::encode_upper(&record.signed_message),
+            );
+        }
+        text
+    }
+
+    #[test]
+    fn test_check_record() {
+        let mut drbg = harness_drbg();
+        let records = (0..2)
+            .map(|count| {
+                let mut seed = [0u8; 48];
+                drbg.fill_bytes(&mut seed);
+                let mut message = vec![0u8; 33 * (count + 1)];
+                drbg.fill_bytes(&mut message);
+                make_record(count, seed, message)
+            })
+            .collect::<Vec<_>>();
+
+        let parsed = parse_rsp(&to_rsp(&records)).unwrap();
+        assert_eq!(parsed, records);
+        for record in &parsed {
+            assert_eq!(check_record::<512>(record), Ok(()));
+            let (m, _) = from_signed_message::<512>(&record.signed_message).unwrap();
+            assert_eq!(m, record.message);
+        }
+        assert!(check_record::<1024>(&records[0]).is_err());
+
+        let mut tampered = records[0].clone();
+        tampered.seed[0] ^= 1;
+        assert_eq!(
+            check_record::<512>(&tampered),
+            Err(KatError::KeygenMismatch)
+        );
+        let mut tampered = records[0].clone();
+        let sk = falcon512::SecretKey::from_bytes(&tampered.secret_key).unwrap();
+        let sig = falcon512::sign(&tampered.message, &sk);
+        tampered.signed_message = to_signed_message(&tampered.message, &sig);
+        assert_eq!(
+            check_record::<512>(&tampered),
+            Err(KatError::SignatureMismatch)
+        );
+        let mut tampered = records[0].clone();
+        tampered.signed_message[2] ^= 1;
+        assert_eq!(
+            check_record::<512>(&tampered),
+            Err(KatError::VerificationFailed)
+        );
+        let mut tampered = records[0].clone();
+        tampered.public_key = records[1].public_key.clone();
+        assert_eq!(
+            check_record::<512>(&tampered),
+            Err(KatError::PublicKeyMismatch)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_rsp("# Falcon-512\n\n"), Ok(vec![]));
+        assert_eq!(parse_rsp("count 0\n"), Err(KatParseError::MalformedLine(1)));
+        assert_eq!(
+            parse_rsp("count = 0\nsig = 00\n"),
+            Err(KatParseError::UnknownKey(2))
+        );
+        assert_eq!(
+            parse_rsp("count = x\n"),
+            Err(KatParseError::InvalidNumber(1))
+        );
+        assert_eq!(parse_rsp("seed = 00\n"), Err(KatParseError::InvalidSeed(1)));
+        assert_eq!(
+            parse_rsp("count = 0\nmlen = 2\nmsg = 00\nsmlen = 0\nsm =\n\n"),
+            Err(KatParseError::LengthMismatch(6))
+        );
+        assert_eq!(
+            parse_rsp("count = 0\n\n"),
+            Err(KatParseError::MissingField(2, "msg"))
+        );
+    }
+
+    /// The first record of each official KAT file, which are checked even
+    /// without the files.
+    const EMBEDDED_KATS: [(&str, usize, &str); 2] = [
+        (
+            include_str!("../fixtures/falcon512-KAT-count0.rsp"),
+            512,
+            // `nistkat-sha256` of Falcon-512 in PQClean's META.yml
+            "da27fe8a462de7307ddf1f9b00072a457d9c5b14e838c148fbe2662094b9a2ca",
+        ),
+        (
+            include_str!("../fixtures/falcon1024-KAT-count0.rsp"),
+            1024,
+            "e699d88eb214fef30597385f40814baeb84ac505d5f05f5c257b0726fc4530b8",
+        ),
+    ];
+
+    fn check(n: usize, record: &KatRecord) -> Result<(), KatError> {
+        match n {
+            512 => check_record::<512>(record),
+            _ => check_record::<1024>(record),
+        }
+    }
+
+    /// Check the first record of the official KAT files, and all of them if
+    /// `FALCON_KAT_DIR` points at them.
+    #[test]
+    fn test_official_kats() {
+        use sha2::{Digest, Sha256};
+
+        for (text, n, digest) in EMBEDDED_KATS {
+            // the published digest covers the record, from `count` to `sm`
+            let record_text = &text[text.find("\ncount = 0").unwrap() + 1..];
+            assert_eq!(hex::encode(Sha256::digest(record_text)), digest);
+
+            let records = parse_rsp(text).unwrap();
+            assert_eq!(records.len(), 1);
+            assert_eq!(check(n, &records[0]), Ok(()), "Falcon-{n}, count = 0");
+        }
+
+        let Ok(dir) = env::var("FALCON_KAT_DIR") else {
+            eprintln!("FALCON_KAT_DIR is not set; checked only the first records");
+            return;
+        };
+        for (file, n) in [("falcon512-KAT.rsp", 512), ("falcon1024-KAT.rsp", 1024)] {
+            let text = fs::read_to_string(Path::new(&dir).join(file)).unwrap();
+            let records = parse_rsp(&text).unwrap();
+            assert!(!records.is_empty());
+            for record in &records {
+                assert_eq!(check(n, record), Ok(()), "{file}, count = {}", record.count);
+            }
+        }
+    }
+}
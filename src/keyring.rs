@@ -0,0 +1,277 @@
+//! A key ring that looks up public keys by fingerprint, for verifiers that
+//! accept signatures from many signers and receive the key id alongside
+//! each signature.
+//!
+//! ```
+//! use falcon_rust::{falcon512, keyring::{KeyRing, KeyRingError}};
+//! # use rand::{thread_rng, Rng};
+//! # let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//!
+//! let mut ring = KeyRing::expanded();
+//! let key_id = ring.insert(pk);
+//!
+//! let sig = falcon512::sign(b"hello", &sk);
+//! assert_eq!(ring.verify(&key_id, b"hello", &sig), Ok(()));
+//!
+//! // a ring is exported as the concatenated public keys
+//! let ring = KeyRing::<512>::import(&ring.export())?;
+//! assert_eq!(ring.verify(&key_id, b"hello", &sig), Ok(()));
+//! # Ok::<(), falcon_rust::falcon512::FalconDeserializationError>(())
+//! ```
+
+use crate::{
+    falcon::{FalconDeserializationError, PublicKey, Signature},
+    fingerprint::Fingerprint,
+    registry::{KeyRecord, KeyRegistry, RegistryError},
+};
+
+/// Reasons why a verification by key id fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyRingError {
+    /// No key with the fingerprint is in the ring.
+    UnknownKey,
+    /// The signature is invalid under the key.
+    InvalidSignature,
+}
+
+/// Public keys by [`Fingerprint`].
+///
+/// The ring is a [`KeyRegistry`] that registers every key under its
+/// fingerprint, as its only epoch, valid from time zero on and never
+/// expiring. A ring created with [`KeyRing::expanded`] stores every key
+/// together with its [`VerifierContext`](crate::verifier_context::VerifierContext),
+/// which takes more memory but saves the NTT of the key on every
+/// verification.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyRing<const N: usize> {
+    registry: KeyRegistry<N>,
+}
+
+impl<const N: usize> KeyRing<N> {
+    /// Create an empty ring.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty ring that pre-expands its keys.
+    pub fn expanded() -> Self {
+        Self {
+            registry: KeyRegistry::expanded(),
+        }
+    }
+
+    /// Test whether the ring pre-expands its keys.
+    pub fn is_expanded(&self) -> bool {
+        self.registry.is_expanded()
+    }
+
+    /// The registry that holds the keys, each under the hex encoding of its
+    /// fingerprint.
+    pub fn registry(&self) -> &KeyRegistry<N> {
+        &self.registry
+    }
+
+    /// Add a key and return its fingerprint. Adding a key that is already in
+    /// the ring has no effect.
+    pub fn insert(&mut self, pk: PublicKey<N>) -> Fingerprint {
+        let fingerprint = pk.fingerprint();
+        // a key that is already in the ring is a duplicate epoch, and is
+        // rejected without changing the ring
+        let _ = self
+            .registry
+            .register(&fingerprint.to_string(), KeyRecord::new(0, pk, 0));
+        fingerprint
+    }
+
+    /// Remove the key with the given fingerprint and return it, if it was in
+    /// the ring.
+    pub fn remove(&mut self, key_id: &Fingerprint) -> Option<PublicKey<N>> {
+        self.registry
+            .remove(&key_id.to_string())
+            .pop()
+            .map(|record| record.public_key)
+    }
+
+    /// The key with the given fingerprint.
+    pub fn get(&self, key_id: &Fingerprint) -> Option<&PublicKey<N>> {
+        self.registry
+            .keys(&key_id.to_string())
+            .first()
+            .map(|record| &record.public_key)
+    }
+
+    /// Test whether the key with the given fingerprint is in the ring.
+    pub fn contains(&self, key_id: &Fingerprint) -> bool {
+        self.get(key_id).is_some()
+    }
+
+    /// The fingerprints of the keys, in increasing order.
+    pub fn fingerprints(&self) -> impl Iterator<Item = Fingerprint> + '_ {
+        self.public_keys().map(PublicKey::fingerprint)
+    }
+
+    /// The keys, in increasing order of their fingerprints.
+    pub fn public_keys(&self) -> impl Iterator<Item = &PublicKey<N>> {
+        // the hex encodings sort like the fingerprints
+        let mut key_ids = self.registry.key_ids().collect::<Vec<_>>();
+        key_ids.sort_unstable();
+        key_ids
+            .into_iter()
+            .flat_map(|key_id| self.registry.keys(key_id))
+            .map(|record| &record.public_key)
+    }
+
+    /// The number of keys.
+    pub fn len(&self) -> usize {
+        self.registry.len()
+    }
+
+    /// Test whether the ring is empty.
+    pub fn is_empty(&self) -> bool {
+        self.registry.is_empty()
+    }
+
+    /// Verify a signature under the key with the given fingerprint.
+    pub fn verify(
+        &self,
+        key_id: &Fingerprint,
+        m: &[u8],
+        sig: &Signature<N>,
+    ) -> Result<(), KeyRingError> {
+        // the keys never expire and are never revoked, so any time will do
+        match self
+            .registry
+            .verify_registered(&key_id.to_string(), m, sig, 0)
+        {
+            Ok(_) => Ok(()),
+            Err(RegistryError::UnknownKey) => Err(KeyRingError::UnknownKey),
+            Err(_) => Err(KeyRingError::InvalidSignature),
+        }
+    }
+
+    /// Serialize the keys as the concatenation of their encodings, in
+    /// increasing order of their fingerprints.
+    pub fn export(&self) -> Vec<u8> {
+        self.public_keys()
+            .flat_map(|pk| pk.as_bytes().iter().copied())
+            .collect()
+    }
+
+    /// Deserialize a ring from the output of [`KeyRing::export`].
+    pub fn import(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        let mut ring = Self::new();
+        ring.import_into(bytes)?;
+        Ok(ring)
+    }
+
+    /// Add the keys in the output of [`KeyRing::export`] to the ring and
+    /// return how many were new. Nothing is added unless all keys decode.
+    pub fn import_into(&mut self, bytes: &[u8]) -> Result<usize, FalconDeserializationError> {
+        if bytes.len() % PublicKey::<N>::BYTE_LEN != 0 {
+            return Err(FalconDeserializationError::BadEncodingLength);
+        }
+        let keys = bytes
+            .chunks_exact(PublicKey::<N>::BYTE_LEN)
+            .map(PublicKey::from_bytes)
+            .collect::<Result<Vec<_>, _>>()?;
+        let len = self.len();
+        self.extend(keys);
+        Ok(self.len() - len)
+    }
+}
+
+impl<const N: usize> Extend<PublicKey<N>> for KeyRing<N> {
+    fn extend<I: IntoIterator<Item = PublicKey<N>>>(&mut self, keys: I) {
+        for pk in keys {
+            self.insert(pk);
+        }
+    }
+}
+
+impl<const N: usize> FromIterator<PublicKey<N>> for KeyRing<N> {
+    fn from_iter<I: IntoIterator<Item = PublicKey<N>>>(keys: I) -> Self {
+        let mut ring = Self::new();
+        ring.extend(keys);
+        ring
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon::FalconDeserializationError, falcon1024, falcon512};
+
+    use super::{KeyRing, KeyRingError};
+
+    #[test]
+    fn test_keyring() {
+        let mut rng = thread_rng();
+        let keys = (0..3)
+            .map(|_| falcon512::keygen(rng.gen()))
+            .collect::<Vec<_>>();
+        let msg = b"signed by a tenant";
+
+        for mut ring in [KeyRing::new(), KeyRing::expanded()] {
+            let key_ids = keys
+                .iter()
+                .map(|(_, pk)| ring.insert(pk.clone()))
+                .collect::<Vec<_>>();
+            assert_eq!(ring.insert(keys[0].1.clone()), key_ids[0]);
+            assert_eq!(ring.len(), 3);
+            assert_eq!(ring.get(&key_ids[1]), Some(&keys[1].1));
+            let mut sorted = key_ids.clone();
+            sorted.sort();
+            assert!(ring.fingerprints().eq(sorted));
+            assert_eq!(ring.registry().is_expanded(), ring.is_expanded());
+
+            let sig = falcon512::sign(msg, &keys[0].0);
+            assert_eq!(ring.verify(&key_ids[0], msg, &sig), Ok(()));
+            assert_eq!(
+                ring.verify(&key_ids[1], msg, &sig),
+                Err(KeyRingError::InvalidSignature)
+            );
+            assert_eq!(
+                ring.verify(&key_ids[0], b"another message", &sig),
+                Err(KeyRingError::InvalidSignature)
+            );
+
+            let exported = ring.export();
+            assert_eq!(exported.len(), 3 * falcon512::PublicKey::BYTE_LEN);
+            let imported = KeyRing::import(&exported).unwrap();
+            assert!(!imported.is_expanded());
+            assert!(imported.public_keys().eq(ring.public_keys()));
+            assert_eq!(imported.verify(&key_ids[0], msg, &sig), Ok(()));
+
+            assert_eq!(ring.remove(&key_ids[0]), Some(keys[0].1.clone()));
+            assert!(!ring.contains(&key_ids[0]));
+            assert_eq!(
+                ring.verify(&key_ids[0], msg, &sig),
+                Err(KeyRingError::UnknownKey)
+            );
+            assert_eq!(ring.import_into(&exported), Ok(1));
+            assert_eq!(ring.verify(&key_ids[0], msg, &sig), Ok(()));
+        }
+
+        let exported = keys
+            .into_iter()
+            .map(|(_, pk)| pk)
+            .collect::<KeyRing<512>>()
+            .export();
+        let mut ring = KeyRing::<512>::new();
+        assert_eq!(
+            ring.import_into(&exported[1..]),
+            Err(FalconDeserializationError::BadEncodingLength)
+        );
+        assert!(ring.is_empty());
+        let (_, pk1024) = falcon1024::keygen(rng.gen());
+        assert_eq!(
+            KeyRing::<512>::import(pk1024.as_bytes()),
+            Err(FalconDeserializationError::BadEncodingLength)
+        );
+        assert_eq!(
+            KeyRing::<1024>::import(pk1024.as_bytes()).map(|ring| ring.len()),
+            Ok(1)
+        );
+    }
+}
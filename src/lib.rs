@@ -54,18 +54,125 @@
 //! falcon512::PublicKey::from_bytes(&pk_buffer);
 //! falcon512::Signature::from_bytes(&sig_buffer);
 //! ```
+//!
+//! The most commonly used items can be imported at once with
+//! `use falcon_rust::prelude::*;`.
+//!
+//! # `no_std`
+//!
+//! The crate is `no_std` when the default `std` feature is disabled, and
+//! needs only an allocator with the `alloc` feature:
+//!
+//! ```toml
+//! falcon-rust = { version = "0.1.2", default-features = false, features = ["alloc"] }
+//! ```
+//!
+//! Keys, signatures, their encodings, key generation from a seed, signing
+//! with a seed, and all of verification remain available. Whatever draws
+//! on the operating system's randomness, threads, clocks, or files, such as
+//! [`falcon512::sign`] and [`batch`], needs `std`.
+//!
+//! On targets without an FPU, such as Cortex-M0 to M3, the compiler lowers
+//! `f64` to soft-float routines whose timing depends on the operands. The
+//! `fixed-point` feature runs the FFT and the Gaussian sampler of signing on
+//! floating-point arithmetic emulated with integers in constant time
+//! instead, as the reference implementation does. Signatures are the same
+//! bit for bit, which the tests check against golden values with and
+//! without the feature.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "alloc"))]
+compile_error!("falcon-rust needs the `alloc` feature, or `std` which implies it");
+
+extern crate alloc;
 
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+pub(crate) mod avx2;
+#[cfg(feature = "std")]
+pub mod backend;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod codec;
+#[cfg(feature = "cose")]
+pub mod cose;
 pub(crate) mod cyclotomic_fourier;
+pub mod derivation;
+pub mod dynamic;
+pub mod embedded;
 pub(crate) mod encoding;
+#[cfg(feature = "std")]
+pub(crate) mod entropy;
 pub(crate) mod falcon;
 pub mod falcon1024;
 pub mod falcon512;
 pub(crate) mod falcon_field;
 pub(crate) mod fast_fft;
+#[cfg(feature = "cdylib")]
+pub mod ffi;
 pub(crate) mod ffsampling;
+pub mod fingerprint;
+#[cfg(feature = "std")]
+pub mod fixtures;
+#[cfg(not(feature = "std"))]
+pub(crate) mod float;
+#[cfg(feature = "fixed-point")]
+pub(crate) mod fpr;
+pub mod generic;
+pub mod hasher;
+#[cfg(feature = "hybrid")]
+pub mod hybrid;
 pub(crate) mod inverse;
+#[cfg(feature = "jose")]
+pub mod jose;
+#[cfg(feature = "kat")]
+pub mod kat;
+#[cfg(feature = "std")]
+pub mod keyring;
+#[cfg(feature = "std")]
+pub mod manifest;
 pub mod math; // pub for benching
+pub mod migration;
+#[cfg(feature = "bip39")]
+pub mod mnemonic;
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+pub(crate) mod neon;
+pub mod nonce;
+#[cfg(feature = "pkcs8")]
+pub mod pkcs8_traits;
 pub mod polynomial; // pub for benching
+#[cfg(feature = "pqcrypto")]
+pub mod pqcrypto_interop;
+pub mod prelude;
+#[cfg(feature = "std")]
+pub mod queue;
+#[cfg(feature = "std")]
+pub mod receipt;
+#[cfg(any(feature = "std", test))]
+pub(crate) mod records;
+#[cfg(feature = "std")]
+pub(crate) mod reference_prng;
+#[cfg(feature = "std")]
+pub mod registry;
 pub(crate) mod samplerz;
+pub mod shared;
+#[cfg(feature = "signature")]
+pub mod signature_traits;
+pub mod signed_message;
+#[cfg(feature = "tokio")]
+pub mod signing_actor;
+#[cfg(feature = "rayon")]
+pub mod stream;
+#[cfg(all(feature = "std", falcon_test_rng))]
+pub mod test_rng;
+#[cfg(feature = "std")]
 pub mod test_utils;
 pub(crate) mod u32_field;
+#[cfg(feature = "upstream")]
+pub mod upstream_interop;
+pub mod verifier_context;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "x509")]
+pub mod x509;
@@ -0,0 +1,488 @@
+//! Signed manifests of file sets, e.g., release artifacts.
+//!
+//! A [`Manifest`] maps paths to SHAKE256 digests of the files' contents. Its
+//! canonical encoding is a line of text per file, sorted by path, in the
+//! style of `sha256sum`:
+//!
+//! ```text
+//! falcon-rust manifest v1
+//! <64 hex digits>  <path>
+//! ...
+//! ```
+//!
+//! Signing the manifest signs the encoding; verifying a [`SignedManifest`]
+//! against a directory checks the signature and then reports, per file,
+//! whether it is intact.
+//!
+//! ```
+//! use falcon_rust::{falcon512, manifest::{FileStatus, Manifest}};
+//! # use rand::{thread_rng, Rng};
+//! # let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//!
+//! let mut manifest = Manifest::new();
+//! manifest.insert("bin/tool", b"release build")?;
+//! manifest.insert("README.md", b"read me")?;
+//! let signed = manifest.sign(&sk);
+//!
+//! let report = signed.verify_with(&pk, |path| match path {
+//!     "bin/tool" => Ok(b"tampered build".to_vec()),
+//!     _ => Ok(b"read me".to_vec()),
+//! })?;
+//! assert_eq!(report.tampered().collect::<Vec<_>>(), [("bin/tool", FileStatus::Modified)]);
+//! # Ok::<(), falcon_rust::manifest::ManifestError>(())
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Component, Path, MAIN_SEPARATOR},
+};
+
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+use crate::falcon::{self, PublicKey, SecretKey, Signature};
+
+/// The first line of every encoded manifest.
+const HEADER: &str = "falcon-rust manifest v1";
+
+/// Reasons why building, decoding, or verifying a manifest fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestError {
+    /// A path is empty, contains a line break, or does not stay below the
+    /// root of the file set: it is absolute, has a drive or UNC prefix, or
+    /// has a `..` component.
+    InvalidPath,
+    /// The encoding is not a canonical manifest.
+    Malformed,
+    /// The signature on the manifest is invalid.
+    InvalidSignature,
+}
+
+/// The state of one file listed in a manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileStatus {
+    /// The contents match the digest.
+    Intact,
+    /// The contents do not match the digest.
+    Modified,
+    /// The file does not exist.
+    Missing,
+    /// The file exists but could not be read.
+    Unreadable,
+}
+
+/// A map from paths to digests of file contents.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Manifest {
+    entries: BTreeMap<String, [u8; 32]>,
+}
+
+/// The SHAKE256 digest of a file's contents, as listed in a manifest.
+pub fn digest(contents: &[u8]) -> [u8; 32] {
+    let mut hasher = Shake256::default();
+    hasher.update(contents);
+    let mut digest = [0u8; 32];
+    hasher.finalize_xof().read(&mut digest);
+    digest
+}
+
+/// Check that a path can be listed, and that joining it to a root cannot
+/// leave the root on any platform, so that a manifest signed on one
+/// platform cannot name files outside the root on another.
+fn check_path(path: &str) -> Result<(), ManifestError> {
+    let mut components = path.split(['/', '\\']);
+    let first = components.next().unwrap_or_default();
+    // `C:`, `C:x`, and the like
+    let is_drive = first.len() >= 2
+        && first.as_bytes()[0].is_ascii_alphabetic()
+        && first.as_bytes()[1] == b':';
+    if path.is_empty()
+        || path.contains(['\n', '\r'])
+        // rooted, which includes UNC paths like `\\server\share`
+        || first.is_empty()
+        || is_drive
+        || first == ".."
+        || components.any(|component| component == "..")
+        || Path::new(path).components().any(|component| {
+            matches!(
+                component,
+                Component::Prefix(_) | Component::RootDir | Component::ParentDir
+            )
+        })
+    {
+        Err(ManifestError::InvalidPath)
+    } else {
+        Ok(())
+    }
+}
+
+impl Manifest {
+    /// Create an empty manifest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// List the given files, read from paths relative to `root`. Paths are
+    /// recorded with `/` as the separator, whatever the platform. Paths that
+    /// a manifest cannot list, see [`ManifestError::InvalidPath`], are
+    /// rejected before anything is read.
+    pub fn from_files<P: AsRef<Path>>(root: &Path, paths: &[P]) -> io::Result<Self> {
+        let mut manifest = Self::new();
+        for path in paths {
+            let name = path
+                .as_ref()
+                .to_str()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path is not UTF-8"))?
+                .replace(MAIN_SEPARATOR, "/");
+            check_path(&name)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid path"))?;
+            let contents = fs::read(root.join(path))?;
+            manifest.entries.insert(name, digest(&contents));
+        }
+        Ok(manifest)
+    }
+
+    /// List a file with the given contents, replacing any previous entry for
+    /// the same path.
+    pub fn insert(&mut self, path: &str, contents: &[u8]) -> Result<(), ManifestError> {
+        self.insert_digest(path, digest(contents))
+    }
+
+    /// List a file by its digest, replacing any previous entry for the same
+    /// path.
+    pub fn insert_digest(&mut self, path: &str, digest: [u8; 32]) -> Result<(), ManifestError> {
+        check_path(path)?;
+        self.entries.insert(path.to_string(), digest);
+        Ok(())
+    }
+
+    /// The listed paths and their digests, sorted by path.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &[u8; 32])> {
+        self.entries
+            .iter()
+            .map(|(path, digest)| (path.as_str(), digest))
+    }
+
+    /// The number of listed files.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Test whether no file is listed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Serialize to the canonical encoding, which is what gets signed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoding = format!("{HEADER}\n");
+        for (path, digest) in &self.entries {
+            encoding += &format!("{}  {}\n", hex::encode(digest), path);
+        }
+        encoding.into_bytes()
+    }
+
+    /// Deserialize from the canonical encoding. Encodings that are not
+    /// canonical, e.g., with unsorted or duplicate paths, are rejected, so
+    /// that every manifest has exactly one encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ManifestError> {
+        let text = std::str::from_utf8(bytes).map_err(|_| ManifestError::Malformed)?;
+        let mut lines = text
+            .strip_suffix('\n')
+            .ok_or(ManifestError::Malformed)?
+            .split('\n');
+        if lines.next() != Some(HEADER) {
+            return Err(ManifestError::Malformed);
+        }
+
+        let mut manifest = Self::new();
+        let mut previous: Option<&str> = None;
+        for line in lines {
+            let (digest, path) = line.split_once("  ").ok_or(ManifestError::Malformed)?;
+            let digest = hex::decode(digest)
+                .ok()
+                .filter(|_| digest.bytes().all(|b| !b.is_ascii_uppercase()))
+                .and_then(|digest| digest.try_into().ok())
+                .ok_or(ManifestError::Malformed)?;
+            if previous.is_some_and(|previous| previous >= path) {
+                return Err(ManifestError::Malformed);
+            }
+            check_path(path).map_err(|_| ManifestError::Malformed)?;
+            manifest.entries.insert(path.to_string(), digest);
+            previous = Some(path);
+        }
+        Ok(manifest)
+    }
+
+    /// Sign the canonical encoding of the manifest.
+    pub fn sign<const N: usize>(&self, sk: &SecretKey<N>) -> SignedManifest<N> {
+        let signature = falcon::sign(self.to_bytes(), sk);
+        SignedManifest {
+            manifest: self.clone(),
+            signature,
+        }
+    }
+}
+
+/// The outcome of checking files against a signed manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestReport {
+    /// The status of every listed file, sorted by path.
+    pub files: Vec<(String, FileStatus)>,
+}
+
+impl ManifestReport {
+    /// Test whether every listed file is intact.
+    pub fn is_intact(&self) -> bool {
+        self.files
+            .iter()
+            .all(|(_, status)| *status == FileStatus::Intact)
+    }
+
+    /// The files that are not intact.
+    pub fn tampered(&self) -> impl Iterator<Item = (&str, FileStatus)> {
+        self.files
+            .iter()
+            .filter(|(_, status)| *status != FileStatus::Intact)
+            .map(|(path, status)| (path.as_str(), *status))
+    }
+}
+
+/// A manifest together with its signature.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SignedManifest<const N: usize> {
+    manifest: Manifest,
+    signature: Signature<N>,
+}
+
+impl<const N: usize> SignedManifest<N> {
+    /// Pair a manifest with a detached signature on its encoding.
+    pub fn new(manifest: Manifest, signature: Signature<N>) -> Self {
+        Self {
+            manifest,
+            signature,
+        }
+    }
+
+    /// The manifest. Note that it has not been verified.
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// The detached signature on the manifest's encoding.
+    pub fn signature(&self) -> &Signature<N> {
+        &self.signature
+    }
+
+    /// Verify the signature on the manifest, without checking any files.
+    pub fn verify(&self, pk: &PublicKey<N>) -> bool {
+        falcon::verify(self.manifest.to_bytes(), &self.signature, pk)
+    }
+
+    /// Verify the signature, then check every listed file, whose contents
+    /// `read` looks up by path.
+    pub fn verify_with(
+        &self,
+        pk: &PublicKey<N>,
+        mut read: impl FnMut(&str) -> io::Result<Vec<u8>>,
+    ) -> Result<ManifestReport, ManifestError> {
+        if !self.verify(pk) {
+            return Err(ManifestError::InvalidSignature);
+        }
+        let files = self
+            .manifest
+            .entries()
+            .map(|(path, expected)| {
+                let status = match read(path) {
+                    Ok(contents) if digest(&contents) == *expected => FileStatus::Intact,
+                    Ok(_) => FileStatus::Modified,
+                    Err(error) if error.kind() == io::ErrorKind::NotFound => FileStatus::Missing,
+                    Err(_) => FileStatus::Unreadable,
+                };
+                (path.to_string(), status)
+            })
+            .collect();
+        Ok(ManifestReport { files })
+    }
+
+    /// Verify the signature, then check every listed file, read from paths
+    /// relative to `root`.
+    pub fn verify_files(
+        &self,
+        pk: &PublicKey<N>,
+        root: &Path,
+    ) -> Result<ManifestReport, ManifestError> {
+        self.verify_with(pk, |path| fs::read(root.join(path)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon512;
+
+    use super::{FileStatus, Manifest, ManifestError, SignedManifest};
+
+    #[test]
+    fn test_manifest_encoding() {
+        let mut manifest = Manifest::new();
+        manifest.insert("b.txt", b"b").unwrap();
+        manifest.insert("a/c.txt", b"c").unwrap();
+        assert_eq!(
+            manifest.insert("bad\npath", b""),
+            Err(ManifestError::InvalidPath)
+        );
+        assert_eq!(manifest.insert("", b""), Err(ManifestError::InvalidPath));
+        assert_eq!(manifest.len(), 2);
+
+        let bytes = manifest.to_bytes();
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "falcon-rust manifest v1");
+        assert!(lines[1].ends_with("  a/c.txt"));
+        assert!(lines[2].ends_with("  b.txt"));
+        assert_eq!(Manifest::from_bytes(&bytes), Ok(manifest));
+
+        // unsorted, uppercase hex, missing final newline
+        let swapped = format!("{}\n{}\n{}\n", lines[0], lines[2], lines[1]);
+        assert_eq!(
+            Manifest::from_bytes(swapped.as_bytes()),
+            Err(ManifestError::Malformed)
+        );
+        let uppercase = text
+            .to_uppercase()
+            .replace("FALCON-RUST MANIFEST V1", lines[0]);
+        assert_eq!(
+            Manifest::from_bytes(uppercase.as_bytes()),
+            Err(ManifestError::Malformed)
+        );
+        assert_eq!(
+            Manifest::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ManifestError::Malformed)
+        );
+        assert_eq!(
+            Manifest::from_bytes(b"falcon-rust manifest v1\n"),
+            Ok(Manifest::new())
+        );
+    }
+
+    /// Check that a path is rejected when listing files and when decoding.
+    fn assert_rejected(path: &str) {
+        assert_eq!(
+            Manifest::new().insert(path, b""),
+            Err(ManifestError::InvalidPath),
+            "{path}"
+        );
+        let encoded = format!("falcon-rust manifest v1\n{}  {path}\n", "00".repeat(32));
+        assert_eq!(
+            Manifest::from_bytes(encoded.as_bytes()),
+            Err(ManifestError::Malformed),
+            "{path}"
+        );
+    }
+
+    #[test]
+    fn test_absolute_paths() {
+        for path in ["/etc/passwd", "/", "\\Windows\\System32"] {
+            assert_rejected(path);
+        }
+        let error = Manifest::from_files(&std::env::temp_dir(), &["/etc/passwd"]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_prefixed_paths() {
+        for path in [
+            "C:/Windows/System32",
+            "C:\\Windows",
+            "c:relative",
+            "a:b",
+            "\\\\server\\share\\file",
+            "//server/share/file",
+        ] {
+            assert_rejected(path);
+        }
+        // names that merely contain a colon are fine
+        let mut manifest = Manifest::new();
+        for path in ["ab:c", "1:/x", "a/c:d"] {
+            manifest.insert(path, b"").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_parent_paths() {
+        for path in [
+            "..",
+            "../../x",
+            "a/../../x",
+            "a/..",
+            "..\\x",
+            "a\\..\\..\\x",
+        ] {
+            assert_rejected(path);
+        }
+        // names that merely contain dots are fine
+        let mut manifest = Manifest::new();
+        for path in ["..a", "a..", "a/..b/c", "./a", "a/./b"] {
+            manifest.insert(path, b"").unwrap();
+        }
+        let root = std::env::temp_dir().join("falcon-manifest-parent");
+        let error = Manifest::from_files(&root, &["../etc/passwd"]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_signed_manifest() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let (_, other_pk) = falcon512::keygen(rng.gen());
+
+        let root = std::env::temp_dir().join(format!("falcon-manifest-{}", rng.gen::<u64>()));
+        fs::create_dir_all(root.join("bin")).unwrap();
+        for name in ["bin/tool", "README.md", "LICENSE", "CHANGELOG"] {
+            fs::write(root.join(name), name).unwrap();
+        }
+
+        let manifest =
+            Manifest::from_files(&root, &["bin/tool", "README.md", "LICENSE", "CHANGELOG"])
+                .unwrap();
+        let signed = manifest.sign(&sk);
+        assert!(signed.verify(&pk));
+        assert!(signed.verify_files(&pk, &root).unwrap().is_intact());
+
+        // ship the manifest and the signature separately
+        let received = SignedManifest::new(
+            Manifest::from_bytes(&signed.manifest().to_bytes()).unwrap(),
+            falcon512::Signature::from_bytes(&signed.signature().to_bytes()).unwrap(),
+        );
+        assert_eq!(received, signed);
+        assert_eq!(
+            received.verify_files(&other_pk, &root),
+            Err(ManifestError::InvalidSignature)
+        );
+
+        fs::write(root.join("bin/tool"), "backdoored").unwrap();
+        fs::remove_file(root.join("LICENSE")).unwrap();
+        let report = received.verify_files(&pk, &root).unwrap();
+        assert!(!report.is_intact());
+        assert_eq!(
+            report.tampered().collect::<Vec<_>>(),
+            [
+                ("LICENSE", FileStatus::Missing),
+                ("bin/tool", FileStatus::Modified)
+            ]
+        );
+
+        let mut forged = manifest.clone();
+        forged.insert("bin/tool", b"backdoored").unwrap();
+        assert!(!SignedManifest::new(forged, signed.signature().clone()).verify(&pk));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,12 +1,18 @@
-use std::vec::IntoIter;
+use alloc::{format, string::String, vec, vec::Vec};
+
+use alloc::vec::IntoIter;
 
 use itertools::Itertools;
 use num::{BigInt, FromPrimitive, One, Zero};
 use num_complex::Complex64;
 use rand::RngCore;
+use sha3::digest::XofReader;
 
+#[cfg(not(feature = "std"))]
+use crate::float::FloatExt;
 use crate::{
     cyclotomic_fourier::CyclotomicFourier,
+    falcon::KeygenStats,
     falcon_field::{Felt, Q},
     fast_fft::FastFft,
     inverse::Inverse,
@@ -402,19 +408,37 @@ pub fn ntru_gen(
     Polynomial<i16>,
     Polynomial<i16>,
     Polynomial<i16>,
+) {
+    ntru_gen_with_stats(n, rng, &mut KeygenStats::default())
+}
+
+/// Like [`ntru_gen`] but also count, in `stats`, the candidates (f, g) that
+/// were sampled and the reasons for which they were rejected.
+pub(crate) fn ntru_gen_with_stats(
+    n: usize,
+    rng: &mut dyn RngCore,
+    stats: &mut KeygenStats,
+) -> (
+    Polynomial<i16>,
+    Polynomial<i16>,
+    Polynomial<i16>,
+    Polynomial<i16>,
 ) {
     // let mut rng: StdRng = SeedableRng::from_seed(seed);
 
     loop {
         let f = gen_poly(n, rng);
         let g = gen_poly(n, rng);
+        stats.candidates += 1;
 
         let f_ntt = f.map(|&i| Felt::new(i)).fft();
         if f_ntt.coefficients.iter().any(|e| e.is_zero()) {
+            stats.non_invertible += 1;
             continue;
         }
         let gamma = gram_schmidt_norm_squared(&f, &g);
         if gamma > 1.3689f64 * (Q as f64) {
+            stats.gram_schmidt_norm_failures += 1;
             continue;
         }
 
@@ -428,7 +452,147 @@ pub fn ntru_gen(
                 capital_g.map(|&i| i as i16),
             );
         }
+        stats.ntru_solve_failures += 1;
+    }
+}
+
+/// Sample f, g, F, G like the key generation of the reference
+/// implementation (`keygen.c`), drawing all randomness from `rng`, so that
+/// the same SHAKE256 stream yields the same key.
+///
+/// Our NTRU solver finds the same F and G as the reference one. Candidates
+/// (f, g) for which the reference solver fails but ours succeeds would lead
+/// to different keys; no such candidate is known.
+pub(crate) fn ntru_gen_reference(
+    n: usize,
+    rng: &mut impl XofReader,
+) -> (
+    Polynomial<i16>,
+    Polynomial<i16>,
+    Polynomial<i16>,
+    Polynomial<i16>,
+) {
+    // max_fg_bits and max_FG_bits of the reference implementation
+    let fg_limit = if n == 1024 { 1 << 4 } else { 1 << 5 };
+    let capital_fg_limit = (1 << 7) - 1;
+
+    loop {
+        let f = gen_poly_reference(n, rng);
+        let g = gen_poly_reference(n, rng);
+
+        if f.coefficients
+            .iter()
+            .chain(&g.coefficients)
+            .any(|c| c.abs() >= fg_limit)
+        {
+            continue;
+        }
+        if gram_schmidt_norm_squared(&f, &g) > 1.3689f64 * (Q as f64) {
+            continue;
+        }
+        let f_ntt = f.map(|&i| Felt::new(i)).fft();
+        if f_ntt.coefficients.iter().any(|e| e.is_zero()) {
+            continue;
+        }
+
+        if let Some((capital_f, capital_g)) =
+            ntru_solve_entrypoint(f.map(|&i| i as i32), g.map(|&i| i as i32))
+        {
+            if capital_f
+                .coefficients
+                .iter()
+                .chain(&capital_g.coefficients)
+                .any(|c| c.abs() > capital_fg_limit)
+            {
+                continue;
+            }
+            return (
+                f,
+                g,
+                capital_f.map(|&i| i as i16),
+                capital_g.map(|&i| i as i16),
+            );
+        }
+    }
+}
+
+/// The distribution of one coefficient for n = 1024, scaled by 2^63:
+/// element 0 is P(x = 0), element k > 0 is P(|x| > k | x != 0). This is
+/// `gauss_1024_12289` of the reference implementation.
+const GAUSS_1024_12289: [u64; 27] = [
+    1283868770400643928,
+    6416574995475331444,
+    4078260278032692663,
+    2353523259288686585,
+    1227179971273316331,
+    575931623374121527,
+    242543240509105209,
+    91437049221049666,
+    30799446349977173,
+    9255276791179340,
+    2478152334826140,
+    590642893610164,
+    125206034929641,
+    23590435911403,
+    3948334035941,
+    586753615614,
+    77391054539,
+    9056793210,
+    940121950,
+    86539696,
+    7062824,
+    510971,
+    32764,
+    1862,
+    94,
+    4,
+    0,
+];
+
+/// Sample a polynomial like `poly_small_mkgauss` of the reference
+/// implementation: coefficients with the distribution of [`gen_poly`], but
+/// redrawn until they fit in a byte and until their sum is odd.
+fn gen_poly_reference(n: usize, rng: &mut impl XofReader) -> Polynomial<i16> {
+    let mut next_u64 = || {
+        let mut bytes = [0u8; 8];
+        rng.read(&mut bytes);
+        u64::from_le_bytes(bytes)
+    };
+    // the sum of 1024 / n samples for n = 1024
+    let mut sample = || {
+        (0..1024 / n)
+            .map(|_| {
+                let r = next_u64();
+                let negative = r >> 63 == 1;
+                let zero = r & (u64::MAX >> 1) < GAUSS_1024_12289[0];
+                let r = next_u64() & (u64::MAX >> 1);
+                let value = GAUSS_1024_12289[1..]
+                    .iter()
+                    .position(|&p| r >= p)
+                    .map_or(0, |k| k as i16 + 1);
+                match (zero, negative) {
+                    (true, _) => 0,
+                    (false, true) => -value,
+                    (false, false) => value,
+                }
+            })
+            .sum::<i16>()
+    };
+
+    let mut coefficients = Vec::with_capacity(n);
+    let mut parity = 0;
+    while coefficients.len() < n {
+        let s = sample();
+        if !(-127..=127).contains(&s) {
+            continue;
+        }
+        if coefficients.len() == n - 1 && parity ^ (s & 1) == 0 {
+            continue;
+        }
+        parity ^= s & 1;
+        coefficients.push(s);
     }
+    Polynomial::new(coefficients)
 }
 
 /// Generate a polynomial of degree at most n-1 whose coefficients are
@@ -0,0 +1,143 @@
+//! Upgrading archived serialized data to the current encodings.
+//!
+//! Every type with a byte encoding has a format version, starting at
+//! [`FORMAT_VERSION_1`]. When an encoding gains fields, its version is
+//! bumped and [`Migrate::migrate`] learns to read the old one, so that
+//! archives can be rewritten in the current format instead of re-deriving
+//! everything from raw signatures:
+//!
+//! ```
+//! use falcon_rust::{falcon512, migration::{self, Migrate}};
+//! # use rand::{thread_rng, Rng};
+//! # let (_, pk) = falcon512::keygen(thread_rng().gen());
+//! # let archived = pk.to_bytes();
+//!
+//! let current = falcon512::PublicKey::migrate(&archived, migration::FORMAT_VERSION_1)?;
+//! let pk = falcon512::PublicKey::from_bytes(&current)?;
+//! # Ok::<(), falcon_rust::falcon512::FalconDeserializationError>(())
+//! ```
+//!
+//! All encodings are still at their first version, so for now migration
+//! validates the data and re-encodes it canonically. Batches do not have a
+//! byte encoding yet; they will implement [`Migrate`] once they do.
+
+use alloc::vec::Vec;
+
+use crate::falcon::{
+    ExpandedPublicKey, ExpandedSignature, FalconDeserializationError, KeySeed, PublicKey,
+    SecretKey, Signature,
+};
+
+/// The first format version, which all encodings in this crate are at.
+pub const FORMAT_VERSION_1: u8 = 1;
+
+/// A type whose byte encoding can be upgraded from older format versions.
+pub trait Migrate: Sized {
+    /// The format version produced by the type's `to_bytes`.
+    const FORMAT_VERSION: u8;
+
+    /// Decode an encoding of the given format version.
+    ///
+    /// Fails with [`FalconDeserializationError::UnsupportedFormatVersion`] if
+    /// the version is unknown, in particular if it is newer than
+    /// [`Migrate::FORMAT_VERSION`].
+    fn from_versioned_bytes(bytes: &[u8], version: u8) -> Result<Self, FalconDeserializationError>;
+
+    /// Encode in the current format version.
+    fn to_current_bytes(&self) -> Vec<u8>;
+
+    /// Rewrite an encoding of the given format version in the current one.
+    fn migrate(bytes: &[u8], from_version: u8) -> Result<Vec<u8>, FalconDeserializationError> {
+        Ok(Self::from_versioned_bytes(bytes, from_version)?.to_current_bytes())
+    }
+}
+
+/// Rewrite an encoding of the given format version in the current one.
+/// Equivalent to [`Migrate::migrate`].
+pub fn migrate<T: Migrate>(
+    bytes: &[u8],
+    from_version: u8,
+) -> Result<Vec<u8>, FalconDeserializationError> {
+    T::migrate(bytes, from_version)
+}
+
+macro_rules! impl_migrate_v1 {
+    ($type:ty $(, $n:ident)?) => {
+        impl$(<const $n: usize>)? Migrate for $type {
+            const FORMAT_VERSION: u8 = FORMAT_VERSION_1;
+
+            fn from_versioned_bytes(
+                bytes: &[u8],
+                version: u8,
+            ) -> Result<Self, FalconDeserializationError> {
+                match version {
+                    FORMAT_VERSION_1 => Self::from_bytes(bytes),
+                    _ => Err(FalconDeserializationError::UnsupportedFormatVersion),
+                }
+            }
+
+            fn to_current_bytes(&self) -> Vec<u8> {
+                self.to_bytes().to_vec()
+            }
+        }
+    };
+}
+
+impl_migrate_v1!(SecretKey<N>, N);
+impl_migrate_v1!(PublicKey<N>, N);
+impl_migrate_v1!(ExpandedPublicKey<N>, N);
+impl_migrate_v1!(Signature<N>, N);
+impl_migrate_v1!(ExpandedSignature<N>, N);
+impl_migrate_v1!(KeySeed);
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon::FalconDeserializationError, falcon1024, falcon512};
+
+    use super::{migrate, Migrate, FORMAT_VERSION_1};
+
+    #[test]
+    fn test_migrate() {
+        let mut rng = thread_rng();
+        let seed = falcon512::KeySeed::new(rng.gen());
+        let (sk, pk) = seed.keygen::<512>().unwrap();
+        let sig = falcon512::sign(b"archived", &sk);
+        let expanded = falcon512::ExpandedPublicKey::new(&pk);
+
+        assert_eq!(
+            falcon512::SecretKey::migrate(&sk.to_bytes(), FORMAT_VERSION_1),
+            Ok(sk.to_bytes())
+        );
+        assert_eq!(
+            migrate::<falcon512::PublicKey>(pk.as_bytes(), FORMAT_VERSION_1),
+            Ok(pk.to_bytes())
+        );
+        assert_eq!(
+            migrate::<falcon512::ExpandedPublicKey>(&expanded.to_bytes(), FORMAT_VERSION_1),
+            Ok(expanded.to_bytes())
+        );
+        assert_eq!(
+            migrate::<falcon512::Signature>(sig.as_bytes(), FORMAT_VERSION_1),
+            Ok(sig.to_bytes())
+        );
+        assert_eq!(
+            migrate::<falcon512::KeySeed>(&seed.to_bytes(), FORMAT_VERSION_1),
+            Ok(seed.to_bytes().to_vec())
+        );
+
+        assert_eq!(
+            migrate::<falcon512::PublicKey>(pk.as_bytes(), FORMAT_VERSION_1 + 1),
+            Err(FalconDeserializationError::UnsupportedFormatVersion)
+        );
+        assert_eq!(
+            migrate::<falcon512::PublicKey>(pk.as_bytes(), 0),
+            Err(FalconDeserializationError::UnsupportedFormatVersion)
+        );
+        assert_eq!(
+            migrate::<falcon1024::PublicKey>(pk.as_bytes(), FORMAT_VERSION_1),
+            Err(FalconDeserializationError::WrongVariant)
+        );
+    }
+}
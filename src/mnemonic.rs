@@ -0,0 +1,113 @@
+//! Key pairs backed up as [BIP39] mnemonic phrases.
+//!
+//! The mnemonic and optional passphrase are turned into a 64-byte BIP39
+//! seed as the standard prescribes. The master seed is the first 32 bytes of
+//! `SHAKE256("falcon-rust bip39 master seed" || bip39_seed)`, and the key pair
+//! is [`keygen`](crate::falcon512::keygen) of the master seed. The master
+//! seed can also be used with [`derivation`](crate::derivation) to derive
+//! any number of further key pairs from the same phrase.
+//!
+//! ```
+//! use falcon_rust::{falcon512, mnemonic};
+//!
+//! let phrase = mnemonic::generate_mnemonic(24).unwrap().to_string();
+//! let (sk, pk) = falcon512::keygen_from_mnemonic(&phrase.parse().unwrap(), "passphrase");
+//!
+//! // later, from the written-down phrase
+//! let recovered = mnemonic::Mnemonic::parse(&phrase).unwrap();
+//! assert_eq!(falcon512::keygen_from_mnemonic(&recovered, "passphrase"), (sk, pk));
+//! ```
+//!
+//! [BIP39]: https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki
+
+use rand::RngCore;
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+use crate::{
+    entropy,
+    falcon::{self, PublicKey, SecretKey},
+};
+
+pub use bip39::{Error as MnemonicError, Mnemonic};
+
+/// Generate a fresh English mnemonic with the given number of words, which
+/// must be 12, 15, 18, 21, or 24.
+pub fn generate_mnemonic(word_count: usize) -> Result<Mnemonic, MnemonicError> {
+    if word_count % 3 != 0 {
+        return Err(MnemonicError::BadWordCount(word_count));
+    }
+    // every three words encode 32 bits of entropy and one bit of checksum
+    let mut entropy = vec![0u8; word_count / 3 * 4];
+    entropy::rng().fill_bytes(&mut entropy);
+    Mnemonic::from_entropy(&entropy)
+}
+
+/// Compute the master seed for a mnemonic and passphrase.
+pub fn master_seed(mnemonic: &Mnemonic, passphrase: &str) -> [u8; 32] {
+    let mut hasher = Shake256::default();
+    hasher.update(b"falcon-rust bip39 master seed");
+    hasher.update(&mnemonic.to_seed(passphrase));
+    let mut seed = [0u8; 32];
+    hasher.finalize_xof().read(&mut seed);
+    seed
+}
+
+/// Generate the key pair for a mnemonic and passphrase.
+///
+/// # Panics
+///
+/// If the master seed is weak, which happens with negligible probability;
+/// see [`is_weak_seed`](crate::falcon512::is_weak_seed).
+pub fn keygen_from_mnemonic<const N: usize>(
+    mnemonic: &Mnemonic,
+    passphrase: &str,
+) -> (SecretKey<N>, PublicKey<N>) {
+    falcon::keygen(master_seed(mnemonic, passphrase))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{derivation, falcon512};
+
+    use super::{generate_mnemonic, master_seed, Mnemonic, MnemonicError};
+
+    #[test]
+    fn test_mnemonic_keygen() {
+        for word_count in [12, 15, 18, 21, 24] {
+            assert_eq!(
+                generate_mnemonic(word_count).unwrap().word_count(),
+                word_count
+            );
+        }
+        for word_count in [0, 11, 13, 27] {
+            assert!(generate_mnemonic(word_count).is_err());
+        }
+        assert_eq!(
+            Mnemonic::parse("abandon abandon"),
+            Err(MnemonicError::BadWordCount(2))
+        );
+
+        // test vector from BIP39
+        let mnemonic = Mnemonic::parse(
+            "abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(&mnemonic.to_seed("TREZOR")[..8]),
+            "c55257c360c07c72"
+        );
+
+        let seed = master_seed(&mnemonic, "TREZOR");
+        assert_ne!(seed, master_seed(&mnemonic, ""));
+        let (sk, pk) = falcon512::keygen_from_mnemonic(&mnemonic, "TREZOR");
+        assert_eq!((sk, pk), falcon512::keygen(seed));
+        assert_eq!(
+            falcon512::derive_child(&seed, &["child"]),
+            falcon512::keygen(derivation::derive_seed(&seed, &["child"]))
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! NEON kernels for the arithmetic of verification after the NTT: the
+//! pointwise multiply-and-subtract `c - s2 * h` and the squared norms.
+//! NEON is part of every `aarch64` target the crate builds for, so the
+//! kernels replace the portable loops at compile time.
+//!
+//! Products modulo q are reduced with Barrett's method: for x < q^2, the
+//! quotient (x floor(2^40 / q)) >> 40 underestimates x / q by less than 2,
+//! so x minus that quotient times q lies in [0, 2q).
+
+use alloc::vec;
+use core::arch::aarch64::*;
+
+use crate::{
+    falcon_field::{Felt, Q},
+    polynomial::Polynomial,
+};
+
+const BARRETT: u32 = ((1u64 << 40) / Q as u64) as u32;
+
+/// Compute `c - s2 * h` for polynomials in NTT domain.
+pub(crate) fn sub_mul(
+    c: &Polynomial<Felt>,
+    s2: &Polynomial<Felt>,
+    h: &Polynomial<Felt>,
+) -> Polynomial<Felt> {
+    let (c, s2, h) = (&c.coefficients, &s2.coefficients, &h.coefficients);
+    assert!(c.len() == s2.len() && c.len() == h.len());
+    let mut out = vec![Felt::new(0); c.len()];
+    let split = c.len() - c.len() % 4;
+    // SAFETY: all four slices hold at least `split` elements, and `Felt` is
+    // a transparent `u32`.
+    unsafe {
+        sub_mul_neon(
+            c.as_ptr().cast(),
+            s2.as_ptr().cast(),
+            h.as_ptr().cast(),
+            out.as_mut_ptr().cast(),
+            split,
+        );
+    }
+    for i in split..c.len() {
+        out[i] = c[i] - s2[i] * h[i];
+    }
+    Polynomial::new(out)
+}
+
+/// The squared norm of the balanced representatives of the coefficients.
+pub(crate) fn norm_squared(a: &[Felt]) -> i64 {
+    let split = a.len() - a.len() % 4;
+    // SAFETY: the slice holds at least `split` elements, and `Felt` is a
+    // transparent `u32`.
+    let head = unsafe { norm_squared_neon(a.as_ptr().cast(), split) };
+    head + a[split..]
+        .iter()
+        .map(|i| i.balanced_value() as i64)
+        .map(|i| i * i)
+        .sum::<i64>()
+}
+
+/// The squared norm of a short vector.
+pub(crate) fn norm_squared_i16(a: &[i16]) -> i64 {
+    let split = a.len() - a.len() % 8;
+    // SAFETY: the slice holds at least `split` elements.
+    let head = unsafe { norm_squared_i16_neon(a.as_ptr(), split) };
+    head + a[split..]
+        .iter()
+        .map(|&i| i as i64)
+        .map(|i| i * i)
+        .sum::<i64>()
+}
+
+/// Reduce lanes in [0, 2q) to [0, q): if x < q, then x - q wraps around to
+/// a larger value.
+#[inline]
+unsafe fn reduce(x: uint32x4_t) -> uint32x4_t {
+    vminq_u32(x, vsubq_u32(x, vdupq_n_u32(Q)))
+}
+
+/// # Safety
+///
+/// The pointers must be valid for `len` elements, a multiple of 4.
+unsafe fn sub_mul_neon(c: *const u32, s2: *const u32, h: *const u32, out: *mut u32, len: usize) {
+    let q = vdupq_n_u32(Q);
+    let barrett = vdupq_n_u32(BARRETT);
+    for i in (0..len).step_by(4) {
+        // below q^2 < 2^28
+        let x = vmulq_u32(vld1q_u32(s2.add(i)), vld1q_u32(h.add(i)));
+        let lo = vshrq_n_u64::<40>(vmull_u32(vget_low_u32(x), vget_low_u32(barrett)));
+        let hi = vshrq_n_u64::<40>(vmull_high_u32(x, barrett));
+        let quotient = vcombine_u32(vmovn_u64(lo), vmovn_u64(hi));
+        let product = reduce(vmlsq_u32(x, quotient, q));
+        let difference = reduce(vsubq_u32(vaddq_u32(vld1q_u32(c.add(i)), q), product));
+        vst1q_u32(out.add(i), difference);
+    }
+}
+
+/// # Safety
+///
+/// The pointer must be valid for `len` elements, a multiple of 4, each
+/// below q.
+unsafe fn norm_squared_neon(a: *const u32, len: usize) -> i64 {
+    let q = vdupq_n_s32(Q as i32);
+    let half = vdupq_n_s32(Q as i32 / 2);
+    let mut acc = vdupq_n_s64(0);
+    for i in (0..len).step_by(4) {
+        let x = vreinterpretq_s32_u32(vld1q_u32(a.add(i)));
+        let above = vreinterpretq_s32_u32(vcgtq_s32(x, half));
+        let balanced = vsubq_s32(x, vandq_s32(q, above));
+        acc = vmlal_s32(acc, vget_low_s32(balanced), vget_low_s32(balanced));
+        acc = vmlal_high_s32(acc, balanced, balanced);
+    }
+    vaddvq_s64(acc)
+}
+
+/// # Safety
+///
+/// The pointer must be valid for `len` elements, a multiple of 8.
+unsafe fn norm_squared_i16_neon(a: *const i16, len: usize) -> i64 {
+    let mut acc = vdupq_n_s64(0);
+    for i in (0..len).step_by(8) {
+        let x = vld1q_s16(a.add(i));
+        // squares of 16-bit values fit 32 bits
+        acc = vpadalq_s32(acc, vmull_s16(vget_low_s16(x), vget_low_s16(x)));
+        acc = vpadalq_s32(acc, vmull_high_s16(x, x));
+    }
+    vaddvq_s64(acc)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon_field::Felt, polynomial::Polynomial};
+
+    use super::{norm_squared, norm_squared_i16, sub_mul};
+
+    #[test]
+    fn test_neon_matches_portable() {
+        let mut rng = thread_rng();
+        for n in [1, 2, 7, 8, 512, 1024] {
+            let mut random = || Polynomial::new((0..n).map(|_| rng.gen::<Felt>()).collect());
+            let (c, s2, h) = (random(), random(), random());
+            assert_eq!(sub_mul(&c, &s2, &h), c.clone() - s2.hadamard_mul(&h));
+
+            let expected = c
+                .coefficients
+                .iter()
+                .map(|i| i.balanced_value() as i64)
+                .map(|i| i * i)
+                .sum::<i64>();
+            assert_eq!(norm_squared(&c.coefficients), expected);
+
+            let short: Vec<i16> = (0..n).map(|_| rng.gen()).collect();
+            let expected = short.iter().map(|&i| i as i64 * i as i64).sum::<i64>();
+            assert_eq!(norm_squared_i16(&short), expected);
+        }
+    }
+}
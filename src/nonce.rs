@@ -0,0 +1,181 @@
+//! The salt that randomizes the hash of the message.
+
+use core::fmt;
+
+use crate::falcon::FalconDeserializationError;
+
+/// The 40-byte salt, called `r` in the specification, that is hashed
+/// together with the message and carried in every signature.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Nonce([u8; Nonce::LEN]);
+
+impl Nonce {
+    /// The length of a nonce in bytes.
+    pub const LEN: usize = 40;
+
+    /// Wrap the given bytes.
+    pub const fn new(bytes: [u8; Nonce::LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Read a nonce from a slice, which must be exactly [`Nonce::LEN`]
+    /// bytes long.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        bytes
+            .try_into()
+            .map(Self)
+            .map_err(|_| FalconDeserializationError::BadEncodingLength)
+    }
+
+    /// Borrow the bytes of the nonce.
+    pub const fn as_bytes(&self) -> &[u8; Nonce::LEN] {
+        &self.0
+    }
+}
+
+impl From<[u8; Nonce::LEN]> for Nonce {
+    fn from(bytes: [u8; Nonce::LEN]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Nonce> for [u8; Nonce::LEN] {
+    fn from(nonce: Nonce) -> Self {
+        nonce.0
+    }
+}
+
+impl TryFrom<&[u8]> for Nonce {
+    type Error = FalconDeserializationError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl AsRef<[u8]> for Nonce {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Nonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Nonce({})", hex::encode(self.0))
+    }
+}
+
+/// Formats the nonce as hex.
+impl fmt::Display for Nonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl fmt::LowerHex for Nonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+/// Nonces serialize as hex strings in human-readable formats and as byte
+/// strings otherwise.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Nonce {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(self.0))
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Nonce {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NonceVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for NonceVisitor {
+            type Value = Nonce;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{} bytes or their hex encoding", Nonce::LEN)
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Nonce, E> {
+                let bytes = hex::decode(v).map_err(E::custom)?;
+                self.visit_bytes(&bytes)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Nonce, E> {
+                Nonce::from_bytes(v).map_err(|_| E::invalid_length(v.len(), &self))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Nonce, A::Error> {
+                let mut bytes = [0u8; Nonce::LEN];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
+                }
+                if seq.next_element::<u8>()?.is_some() {
+                    return Err(serde::de::Error::invalid_length(Nonce::LEN + 1, &self));
+                }
+                Ok(Nonce(bytes))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(NonceVisitor)
+        } else {
+            deserializer.deserialize_bytes(NonceVisitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon::FalconDeserializationError, falcon512};
+
+    use super::Nonce;
+
+    #[test]
+    fn test_nonce() {
+        let (sk, pk) = falcon512::keygen(thread_rng().gen());
+        let msg = b"nonce";
+        let sig = falcon512::sign(msg, &sk);
+
+        let nonce = sig.nonce();
+        assert_eq!(nonce.as_bytes(), &sig.as_bytes()[1..=Nonce::LEN]);
+        assert_eq!(
+            falcon512::ExpandedSignature::from_signature(msg, &sig, &pk).nonce(),
+            nonce
+        );
+        assert_eq!(Nonce::try_from(nonce.as_ref()), Ok(nonce));
+        assert_eq!(
+            Nonce::from_bytes(&[0u8; 39]),
+            Err(FalconDeserializationError::BadEncodingLength)
+        );
+        assert_eq!(format!("{nonce}"), hex::encode(nonce.as_bytes()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_nonce_serde() {
+        use rand::RngCore;
+
+        let mut bytes = [0u8; Nonce::LEN];
+        thread_rng().fill_bytes(&mut bytes);
+        let nonce = Nonce::new(bytes);
+        let json = serde_json::to_string(&nonce).unwrap();
+        assert_eq!(json, format!("\"{nonce}\""));
+        assert_eq!(serde_json::from_str::<Nonce>(&json).unwrap(), nonce);
+        assert!(serde_json::from_str::<Nonce>("\"00\"").is_err());
+    }
+}
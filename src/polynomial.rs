@@ -1,13 +1,20 @@
+use alloc::{vec, vec::Vec};
+
+use core::default::Default;
+use core::fmt::{Debug, Display};
+use core::hash::{Hash, Hasher};
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 use num::{One, Zero};
 use sha3::digest::{ExtendableOutput, Update, XofReader};
 use sha3::Shake256;
-use std::default::Default;
-use std::fmt::{Debug, Display};
-use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use itertools::Itertools;
+use num_complex::Complex64;
+use zeroize::Zeroize;
 
 use crate::falcon_field::{Felt, Q};
+#[cfg(not(feature = "std"))]
+use crate::float::FloatExt;
 use crate::inverse::Inverse;
 
 /// Marked pub for benchmarking; not considered part of the public API.
@@ -212,6 +219,16 @@ impl<
         Polynomial::new(coefficients)
     }
 
+    /// Evaluate the polynomial at the given point with Horner's rule.
+    pub fn evaluate(&self, x: &F) -> F {
+        let mut result = F::zero();
+        for c in self.coefficients.iter().rev() {
+            result *= x.clone();
+            result += c.clone();
+        }
+        result
+    }
+
     /// Multiply the polynomial by an input polynomial and return the idx-th coefficient
     pub fn mul_coeff(&self, other: &Self, idx: usize, n: usize) -> F {
         let mut result = F::zero();
@@ -387,6 +404,17 @@ where
 
 impl<F> Eq for Polynomial<F> where F: Zero + PartialEq + Clone + AddAssign {}
 
+impl<F> Hash for Polynomial<F>
+where
+    F: Zero + PartialEq + Clone + AddAssign + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // like `eq`, disregard trailing zeros
+        let length = self.degree().map_or(0, |degree| degree + 1);
+        self.coefficients[..length].hash(state);
+    }
+}
+
 impl<F> Add for &Polynomial<F>
 where
     F: Add<Output = F> + AddAssign + Clone,
@@ -637,29 +665,73 @@ where
 
 /// Hash a string to a random polynomial in ZZ[ X ] mod <Phi(X), q>.
 /// Algorithm 3, "HashToPoint" in the spec (page 31).
+#[cfg(test)]
 pub(crate) fn hash_to_point(string: &[u8], n: usize) -> Polynomial<Felt> {
-    const K: u32 = (1u32 << 16) / Q;
+    hash_to_point_vectored(&[string], n)
+}
 
+/// Like [`hash_to_point`], but hashing the concatenation of the given parts
+/// without materializing it.
+pub(crate) fn hash_to_point_vectored(parts: &[&[u8]], n: usize) -> Polynomial<Felt> {
+    let mut coefficients = vec![Felt::new(0); n];
+    hash_to_point_into(parts, &mut coefficients);
+    Polynomial { coefficients }
+}
+
+/// Like [`hash_to_point_vectored`], but writing as many coefficients as the
+/// given slice holds into it.
+pub(crate) fn hash_to_point_into(parts: &[&[u8]], coefficients: &mut [Felt]) {
     let mut hasher = Shake256::default();
-    hasher.update(string);
+    for part in parts {
+        hasher.update(part);
+    }
+    squeeze_point_into(hasher, coefficients);
+}
+
+/// Finish hashing to a point with a SHAKE256 state that has absorbed the
+/// whole string, writing as many coefficients as the given slice holds.
+pub(crate) fn squeeze_point_into(hasher: Shake256, coefficients: &mut [Felt]) {
+    const K: u32 = (1u32 << 16) / Q;
+
     let mut reader = hasher.finalize_xof();
 
-    let mut coefficients: Vec<Felt> = vec![];
-    while coefficients.len() != n {
+    let mut i = 0;
+    while i != coefficients.len() {
         let mut randomness = [0u8; 2];
         reader.read(&mut randomness);
         // Arabic endianness but so be it
         let t = ((randomness[0] as u32) << 8) | (randomness[1] as u32);
         if t < K * Q {
-            coefficients.push(Felt::new((t % Q) as i16));
+            coefficients[i] = Felt::new((t % Q) as i16);
+            i += 1;
         }
     }
+}
 
-    Polynomial { coefficients }
+impl Zeroize for Polynomial<i16> {
+    fn zeroize(&mut self) {
+        self.coefficients.zeroize();
+    }
+}
+
+impl Zeroize for Polynomial<Complex64> {
+    fn zeroize(&mut self) {
+        zeroize_complex(&mut self.coefficients);
+    }
+}
+
+/// Overwrite complex numbers with zeros, in a way the compiler does not
+/// optimize away, and empty the vector.
+pub(crate) fn zeroize_complex(values: &mut Vec<Complex64>) {
+    for value in values.iter_mut() {
+        value.re.zeroize();
+        value.im.zeroize();
+    }
+    values.clear();
 }
 
 impl<T: Display> Display for Polynomial<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "[{}]", self.coefficients.iter().join(", "))
     }
 }
@@ -0,0 +1,250 @@
+//! Conversions to and from the types of the [`pqcrypto-falcon`] crate, which
+//! wraps the reference implementation as packaged by PQClean.
+//!
+//! Keys convert losslessly in both directions. Signatures produced by this
+//! crate are converted to the variable-length encoding the reference
+//! implementation expects; see [`Signature::to_reference_bytes`].
+//!
+//! With the `interop-tests` feature, the tests of this module also
+//! cross-check randomly generated keys, messages and signatures against the
+//! reference implementation, in both directions:
+//!
+//! ```sh
+//! cargo test --release --features interop-tests differential
+//! ```
+//!
+//! Each test checks 256 random messages by default; set `PROPTEST_CASES` to
+//! check more.
+//!
+//! [`pqcrypto-falcon`]: https://crates.io/crates/pqcrypto-falcon
+
+use pqcrypto_traits::sign::{
+    DetachedSignature as _, PublicKey as _, SecretKey as _, SignedMessage as _,
+};
+
+use crate::{
+    falcon::{FalconDeserializationError, PublicKey, SecretKey, Signature},
+    signed_message::SignedMessage,
+};
+
+macro_rules! impl_pqcrypto_conversions {
+    ($n:literal, $module:ident) => {
+        impl From<&PublicKey<$n>> for pqcrypto_falcon::$module::PublicKey {
+            fn from(pk: &PublicKey<$n>) -> Self {
+                Self::from_bytes(pk.as_bytes()).expect("public key encodings agree")
+            }
+        }
+
+        impl TryFrom<&pqcrypto_falcon::$module::PublicKey> for PublicKey<$n> {
+            type Error = FalconDeserializationError;
+
+            fn try_from(pk: &pqcrypto_falcon::$module::PublicKey) -> Result<Self, Self::Error> {
+                Self::from_bytes(pk.as_bytes())
+            }
+        }
+
+        impl From<&SecretKey<$n>> for pqcrypto_falcon::$module::SecretKey {
+            fn from(sk: &SecretKey<$n>) -> Self {
+                Self::from_bytes(&sk.to_bytes()).expect("secret key encodings agree")
+            }
+        }
+
+        impl TryFrom<&pqcrypto_falcon::$module::SecretKey> for SecretKey<$n> {
+            type Error = FalconDeserializationError;
+
+            fn try_from(sk: &pqcrypto_falcon::$module::SecretKey) -> Result<Self, Self::Error> {
+                Self::from_bytes(sk.as_bytes())
+            }
+        }
+
+        impl From<&Signature<$n>> for pqcrypto_falcon::$module::DetachedSignature {
+            fn from(sig: &Signature<$n>) -> Self {
+                Self::from_bytes(&sig.to_reference_bytes())
+                    .expect("reference signatures are no longer than the maximum")
+            }
+        }
+
+        impl TryFrom<&pqcrypto_falcon::$module::DetachedSignature> for Signature<$n> {
+            type Error = FalconDeserializationError;
+
+            fn try_from(
+                sig: &pqcrypto_falcon::$module::DetachedSignature,
+            ) -> Result<Self, Self::Error> {
+                Self::from_bytes(sig.as_bytes())
+            }
+        }
+
+        impl From<&SignedMessage<$n>> for pqcrypto_falcon::$module::SignedMessage {
+            fn from(signed_message: &SignedMessage<$n>) -> Self {
+                Self::from_bytes(&signed_message.to_bytes())
+                    .expect("signed messages of any length are accepted")
+            }
+        }
+
+        impl TryFrom<&pqcrypto_falcon::$module::SignedMessage> for SignedMessage<$n> {
+            type Error = FalconDeserializationError;
+
+            fn try_from(
+                signed_message: &pqcrypto_falcon::$module::SignedMessage,
+            ) -> Result<Self, Self::Error> {
+                Self::from_bytes(signed_message.as_bytes())
+            }
+        }
+    };
+}
+
+impl_pqcrypto_conversions!(512, falcon512);
+impl_pqcrypto_conversions!(1024, falcon1024);
+
+#[cfg(test)]
+mod test {
+    use pqcrypto_falcon::falcon512 as reference;
+    use pqcrypto_traits::sign::{DetachedSignature as _, PublicKey as _, SecretKey as _};
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        falcon::{self, PublicKey, SecretKey, Signature},
+        falcon1024, falcon512,
+        signed_message::SignedMessage,
+    };
+
+    macro_rules! check_round_trips {
+        ($n:literal, $module:ident) => {{
+            use pqcrypto_falcon::$module as reference;
+            let msg = b"round trip";
+
+            // from this crate to the reference implementation and back
+            let (sk, pk) = falcon::keygen::<$n>(thread_rng().gen());
+            let sig = falcon::sign(msg, &sk);
+            let reference_pk = reference::PublicKey::from(&pk);
+            let reference_sk = reference::SecretKey::from(&sk);
+            let reference_sig = reference::DetachedSignature::from(&sig);
+            assert_eq!(reference_pk.as_bytes(), pk.as_bytes());
+            assert_eq!(reference_sk.as_bytes(), sk.to_bytes());
+            assert_eq!(reference_sig.as_bytes(), sig.to_reference_bytes());
+            assert_eq!(PublicKey::<$n>::try_from(&reference_pk).unwrap(), pk);
+            assert_eq!(SecretKey::<$n>::try_from(&reference_sk).unwrap(), sk);
+            let sig_back = Signature::<$n>::try_from(&reference_sig).unwrap();
+            assert_eq!(sig_back.to_reference_bytes(), sig.to_reference_bytes());
+            assert!(falcon::verify(msg, &sig_back, &pk));
+
+            // from the reference implementation to this crate and back
+            let (reference_pk, reference_sk) = reference::keypair();
+            let reference_sig = reference::detached_sign(msg, &reference_sk);
+            let pk = PublicKey::<$n>::try_from(&reference_pk).unwrap();
+            let sk = SecretKey::<$n>::try_from(&reference_sk).unwrap();
+            let sig = Signature::<$n>::try_from(&reference_sig).unwrap();
+            assert_eq!(
+                reference::PublicKey::from(&pk).as_bytes(),
+                reference_pk.as_bytes()
+            );
+            assert_eq!(
+                reference::SecretKey::from(&sk).as_bytes(),
+                reference_sk.as_bytes()
+            );
+            assert_eq!(
+                reference::DetachedSignature::from(&sig).as_bytes(),
+                reference_sig.as_bytes()
+            );
+            assert!(falcon::verify(msg, &sig, &pk));
+        }};
+    }
+
+    #[test]
+    fn test_round_trips() {
+        check_round_trips!(512, falcon512);
+        check_round_trips!(1024, falcon1024);
+        // the wrong variant is rejected
+        let (reference_pk, reference_sk) = reference::keypair();
+        assert!(falcon1024::PublicKey::from_bytes(reference_pk.as_bytes()).is_err());
+        assert!(falcon1024::SecretKey::from_bytes(reference_sk.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_mixed_deployment() {
+        let msg = b"mixed deployment";
+
+        // C signer, Rust verifier
+        let (reference_pk, reference_sk) = reference::keypair();
+        let pk = falcon512::PublicKey::try_from(&reference_pk).unwrap();
+        let sk = falcon512::SecretKey::try_from(&reference_sk).unwrap();
+        assert!(falcon512::consistent(&sk, &pk));
+        let reference_sig = reference::detached_sign(msg, &reference_sk);
+        let sig = falcon512::Signature::try_from(&reference_sig).unwrap();
+        assert!(falcon512::verify(msg, &sig, &pk));
+        let signed_message = SignedMessage::try_from(&reference::sign(msg, &reference_sk));
+        assert_eq!(signed_message.unwrap().open(&pk), Some(msg.as_slice()));
+
+        // Rust signer, C verifier
+        let (sk, pk) = falcon512::keygen(thread_rng().gen());
+        let reference_pk = reference::PublicKey::from(&pk);
+        let sig = falcon512::sign(msg, &sk);
+        let reference_sig = reference::DetachedSignature::from(&sig);
+        assert!(reference::verify_detached_signature(&reference_sig, msg, &reference_pk).is_ok());
+        let reference_signed_message =
+            reference::SignedMessage::from(&SignedMessage::attach(msg, sig));
+        assert_eq!(
+            reference::open(&reference_signed_message, &reference_pk).unwrap(),
+            msg
+        );
+
+        let reference_sk = reference::SecretKey::from(&sk);
+        let reference_sig = reference::detached_sign(msg, &reference_sk);
+        let sig = falcon512::Signature::try_from(&reference_sig).unwrap();
+        assert!(falcon512::verify(msg, &sig, &pk));
+    }
+
+    /// Every signature this crate makes on a random message must verify in
+    /// C, and every signature C makes must verify here. Keys from this
+    /// crate's key generation are slow to make, so one is used throughout,
+    /// alongside a fresh key pair from C for every case.
+    #[cfg(feature = "interop-tests")]
+    macro_rules! differential_tests {
+        ($name:ident, $n:literal, $module:ident) => {
+            #[test]
+            fn $name() {
+                use pqcrypto_falcon::$module as reference;
+
+                let (sk, pk) = falcon::keygen::<$n>(thread_rng().gen());
+                let reference_sk = reference::SecretKey::from(&sk);
+                let reference_pk = reference::PublicKey::from(&pk);
+                let verifies_in_c = |msg: &[u8], sig: &Signature<$n>, pk: &reference::PublicKey| {
+                    let sig = reference::DetachedSignature::from(sig);
+                    reference::verify_detached_signature(&sig, msg, pk).is_ok()
+                };
+
+                proptest::proptest!(|(msg: Vec<u8>)| {
+                    // Rust signer, C verifier
+                    let sig = falcon::sign(&msg, &sk);
+                    proptest::prop_assert!(verifies_in_c(&msg, &sig, &reference_pk));
+                    let signed_message = SignedMessage::attach(&msg, sig);
+                    let reference_signed_message = reference::SignedMessage::from(&signed_message);
+                    proptest::prop_assert!(
+                        reference::open(&reference_signed_message, &reference_pk).is_ok()
+                    );
+
+                    // C signer, Rust verifier
+                    let reference_sig = reference::detached_sign(&msg, &reference_sk);
+                    let sig = Signature::<$n>::try_from(&reference_sig).unwrap();
+                    proptest::prop_assert!(falcon::verify(&msg, &sig, &pk));
+
+                    // a fresh key pair from C, used on both sides
+                    let (other_reference_pk, other_reference_sk) = reference::keypair();
+                    let other_pk = PublicKey::<$n>::try_from(&other_reference_pk).unwrap();
+                    let other_sk = SecretKey::<$n>::try_from(&other_reference_sk).unwrap();
+                    let reference_signed_message = reference::sign(&msg, &other_reference_sk);
+                    let signed_message =
+                        SignedMessage::<$n>::try_from(&reference_signed_message).unwrap();
+                    proptest::prop_assert_eq!(signed_message.open(&other_pk), Some(msg.as_slice()));
+                    let sig = falcon::sign(&msg, &other_sk);
+                    proptest::prop_assert!(verifies_in_c(&msg, &sig, &other_reference_pk));
+                });
+            }
+        };
+    }
+
+    #[cfg(feature = "interop-tests")]
+    differential_tests!(test_differential_512, 512, falcon512);
+    #[cfg(feature = "interop-tests")]
+    differential_tests!(test_differential_1024, 1024, falcon1024);
+}
@@ -0,0 +1,26 @@
+//! The types and traits most applications need, for glob import.
+//!
+//! ```
+//! use falcon_rust::prelude::*;
+//! # use rand::{thread_rng, Rng};
+//!
+//! let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//! let sk = FalconSecretKey::from(sk);
+//! let verifier: Box<dyn AnyVerifier> = Box::new(FalconPublicKey::from(pk));
+//! let sig = sk.sign(b"message");
+//! assert!(verifier.verify_bytes(b"message", sig.as_bytes()));
+//! ```
+
+pub use crate::{
+    dynamic::{AnyVerifier, FalconPublicKey, FalconSecretKey, FalconSignature},
+    falcon::{FalconDeserializationError, FalconError, SignError, SigningOptions},
+    falcon1024, falcon512,
+    fingerprint::Fingerprint,
+    generic::Falcon,
+    include_public_key,
+    nonce::Nonce,
+    shared::Shared,
+};
+
+#[cfg(feature = "rayon")]
+pub use crate::stream::VerifyStreamExt;
@@ -0,0 +1,265 @@
+//! A verification queue that serves urgent signatures first.
+//!
+//! Items are submitted with a priority, and a fixed set of worker threads
+//! verifies them highest priority first, in submission order within a
+//! priority. So that a steady stream of urgent items cannot starve bulk
+//! work indefinitely, every `fairness`-th item a worker takes is instead the
+//! one that has waited longest, whatever its priority.
+//!
+//! ```
+//! use falcon_rust::{falcon512, queue::VerificationQueue};
+//! # use rand::{thread_rng, Rng};
+//!
+//! # let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//! # let audit_sig = falcon512::sign(b"audit", &sk);
+//! # let urgent_sig = falcon512::sign(b"urgent", &sk);
+//! let queue = VerificationQueue::new(2);
+//! let audit = queue.submit(0, b"audit".to_vec(), audit_sig, pk.clone());
+//! let urgent = queue.submit(10, b"urgent".to_vec(), urgent_sig, pk);
+//! assert!(urgent.recv().unwrap());
+//! assert!(audit.recv().unwrap());
+//! ```
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{mpsc, Arc, Condvar, Mutex},
+    thread,
+};
+
+use crate::falcon::{self, PublicKey, Signature};
+
+/// The fairness interval used by [`VerificationQueue::new`].
+pub const DEFAULT_FAIRNESS: usize = 8;
+
+/// A priority queue of signatures to verify, drained by worker threads.
+///
+/// Dropping the queue waits for the workers to verify the items still in it.
+pub struct VerificationQueue<const N: usize> {
+    inner: Arc<Inner<Job<N>>>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+struct Job<const N: usize> {
+    m: Vec<u8>,
+    sig: Signature<N>,
+    pk: PublicKey<N>,
+    result: mpsc::Sender<bool>,
+}
+
+struct Inner<T> {
+    state: Mutex<State<T>>,
+    available: Condvar,
+}
+
+struct State<T> {
+    // per priority, items with their submission sequence number
+    queues: BTreeMap<u32, VecDeque<(u64, T)>>,
+    next_seq: u64,
+    picks: usize,
+    fairness: usize,
+    closed: bool,
+}
+
+impl<T> State<T> {
+    fn new(fairness: usize) -> Self {
+        Self {
+            queues: BTreeMap::new(),
+            next_seq: 0,
+            picks: 0,
+            fairness,
+            closed: false,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.queues.values().map(VecDeque::len).sum()
+    }
+
+    fn push(&mut self, priority: u32, item: T) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.queues
+            .entry(priority)
+            .or_default()
+            .push_back((seq, item));
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let highest = *self.queues.keys().next_back()?;
+        self.picks += 1;
+        let priority = if self.fairness != 0 && self.picks % self.fairness == 0 {
+            // the front of each queue is its oldest item
+            self.queues
+                .iter()
+                .min_by_key(|(_, queue)| queue.front().map(|(seq, _)| *seq))
+                .map_or(highest, |(&priority, _)| priority)
+        } else {
+            highest
+        };
+
+        let queue = self.queues.get_mut(&priority)?;
+        let (_, item) = queue.pop_front()?;
+        if queue.is_empty() {
+            self.queues.remove(&priority);
+        }
+        Some(item)
+    }
+}
+
+impl<const N: usize> VerificationQueue<N> {
+    /// Start a queue with the given number of worker threads and the
+    /// [default fairness interval](DEFAULT_FAIRNESS).
+    pub fn new(workers: usize) -> Self {
+        Self::with_fairness(workers, DEFAULT_FAIRNESS)
+    }
+
+    /// Start a queue with the given number of worker threads, where every
+    /// `fairness`-th item taken is the one that has waited longest. With a
+    /// `fairness` of zero, priorities are strict.
+    ///
+    /// # Panics
+    ///
+    /// If `workers` is zero.
+    pub fn with_fairness(workers: usize, fairness: usize) -> Self {
+        assert!(
+            workers > 0,
+            "a verification queue needs at least one worker"
+        );
+        let inner = Arc::new(Inner {
+            state: Mutex::new(State::new(fairness)),
+            available: Condvar::new(),
+        });
+        let workers = (0..workers)
+            .map(|_| {
+                let inner = Arc::clone(&inner);
+                thread::spawn(move || work(&inner))
+            })
+            .collect();
+        Self { inner, workers }
+    }
+
+    /// Submit a signature for verification. Higher priorities are served
+    /// first. The result is sent on the returned channel.
+    pub fn submit(
+        &self,
+        priority: u32,
+        m: Vec<u8>,
+        sig: Signature<N>,
+        pk: PublicKey<N>,
+    ) -> mpsc::Receiver<bool> {
+        let (result, receiver) = mpsc::channel();
+        let job = Job { m, sig, pk, result };
+        self.inner.state.lock().unwrap().push(priority, job);
+        self.inner.available.notify_one();
+        receiver
+    }
+
+    /// The number of items waiting for a worker.
+    pub fn len(&self) -> usize {
+        self.inner.state.lock().unwrap().len()
+    }
+
+    /// Whether no items are waiting for a worker.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<const N: usize> Drop for VerificationQueue<N> {
+    fn drop(&mut self) {
+        self.inner.state.lock().unwrap().closed = true;
+        self.inner.available.notify_all();
+        for worker in self.workers.drain(..) {
+            // a worker only panics if verification does; nothing to add here
+            let _ = worker.join();
+        }
+    }
+}
+
+fn work<const N: usize>(inner: &Inner<Job<N>>) {
+    loop {
+        let job = {
+            let mut state = inner.state.lock().unwrap();
+            loop {
+                if let Some(job) = state.pop() {
+                    break job;
+                }
+                if state.closed {
+                    return;
+                }
+                state = inner.available.wait(state).unwrap();
+            }
+        };
+        let valid = falcon::verify(&job.m, &job.sig, &job.pk);
+        // the submitter may have stopped waiting for the result
+        let _ = job.result.send(valid);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon512;
+
+    use super::{State, VerificationQueue};
+
+    #[test]
+    fn test_priority_order() {
+        let drain =
+            |mut state: State<&'static str>| std::iter::from_fn(|| state.pop()).collect::<Vec<_>>();
+
+        let mut state = State::new(0);
+        state.push(0, "audit 1");
+        state.push(0, "audit 2");
+        state.push(5, "shard 1");
+        state.push(9, "urgent");
+        state.push(5, "shard 2");
+        assert_eq!(state.len(), 5);
+        assert_eq!(
+            drain(state),
+            ["urgent", "shard 1", "shard 2", "audit 1", "audit 2"]
+        );
+
+        // every third pick goes to the oldest item
+        let mut state = State::new(3);
+        state.push(0, "audit 1");
+        state.push(0, "audit 2");
+        for urgent in ["urgent 1", "urgent 2", "urgent 3", "urgent 4", "urgent 5"] {
+            state.push(9, urgent);
+        }
+        assert_eq!(
+            drain(state),
+            ["urgent 1", "urgent 2", "audit 1", "urgent 3", "urgent 4", "audit 2", "urgent 5"]
+        );
+    }
+
+    #[test]
+    fn test_verification_queue() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let (other_sk, _) = falcon512::keygen(rng.gen());
+
+        let queue = VerificationQueue::new(2);
+        let receivers = (0..6)
+            .map(|i| {
+                let msg = vec![i as u8; 10];
+                let sig = falcon512::sign(&msg, if i % 3 == 0 { &other_sk } else { &sk });
+                queue.submit(i % 2, msg, sig, pk.clone())
+            })
+            .collect::<Vec<_>>();
+        let results = receivers
+            .iter()
+            .map(|receiver| receiver.recv().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(results, [false, true, true, false, true, true]);
+        assert!(queue.is_empty());
+
+        // dropping the queue finishes the remaining work
+        let queue = VerificationQueue::new(1);
+        let msg = b"last".to_vec();
+        let receiver = queue.submit(0, msg.clone(), falcon512::sign(&msg, &sk), pk);
+        drop(queue);
+        assert_eq!(receiver.try_recv(), Ok(true));
+    }
+}
@@ -0,0 +1,393 @@
+//! Signed receipts attesting to verified batches.
+//!
+//! A verifier that has checked a batch of signatures can sign a [`Receipt`]
+//! committing to the Merkle root of the items it accepted and to how it
+//! checked them. Downstream systems that trust the verifier's key can then
+//! accept the batch by recomputing the root, without verifying every
+//! signature again.
+//!
+//! ```
+//! use falcon_rust::{batch, falcon512, receipt::Receipt};
+//! # use rand::{thread_rng, Rng};
+//! # let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//! # let (verifier_sk, verifier_pk) = falcon512::keygen(thread_rng().gen());
+//! # let items = (0..3u8)
+//! #     .map(|i| (vec![i], falcon512::sign(&[i], &sk), pk.clone()))
+//! #     .collect::<Vec<_>>();
+//!
+//! // verifier
+//! let (results, receipt) = batch::verify_many_attested(&items, &verifier_sk);
+//! let bytes = receipt.to_bytes();
+//!
+//! // downstream
+//! let receipt = Receipt::<512>::from_bytes(&bytes)?;
+//! assert!(receipt.verify(&verifier_pk));
+//! assert!(receipt.commits_to(&items));
+//! # Ok::<(), falcon_rust::falcon512::FalconDeserializationError>(())
+//! ```
+//!
+//! Each leaf of the Merkle tree is a SHA3-256 hash of the signer's public
+//! key, the salt, the coefficients of `s2`, and the message, in that order,
+//! so that a [`Signature`] and the [`ExpandedSignature`] expanded from it
+//! commit to the same leaf.
+
+use sha3::{Digest, Sha3_256};
+
+use crate::falcon::{
+    self, ExpandedSignature, FalconDeserializationError, PublicKey, SecretKey, Signature,
+};
+
+/// Prefix of the signed part of a receipt, for domain separation.
+const DOMAIN: &[u8] = b"falcon-rust receipt v1";
+
+/// The length of the signed part of a receipt, excluding the domain.
+const BODY_LEN: usize = 1 + 2 + 8 + 32;
+
+/// How a verifier checked the items of a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VerificationMode {
+    /// Every item was fully verified.
+    Full,
+    /// Every item was screened with a fast check on a few coefficients, and
+    /// the items that passed were fully verified.
+    ScreenedFull,
+    /// Items were only screened with a fast check on a few coefficients.
+    /// Passing the screening is a necessary condition for validity, but not
+    /// a sufficient one.
+    Screened,
+}
+
+impl VerificationMode {
+    const fn to_byte(self) -> u8 {
+        match self {
+            Self::Full => 1,
+            Self::ScreenedFull => 2,
+            Self::Screened => 3,
+        }
+    }
+
+    const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Self::Full),
+            2 => Some(Self::ScreenedFull),
+            3 => Some(Self::Screened),
+            _ => None,
+        }
+    }
+}
+
+/// The verification parameters a receipt attests to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VerificationParameters {
+    /// How the items were checked.
+    pub mode: VerificationMode,
+    /// The number of coefficients checked when screening; zero if the items
+    /// were not screened.
+    pub indices: u16,
+}
+
+/// Hash an accepted item to a leaf of the Merkle tree.
+///
+/// # Panics
+///
+/// If the signature cannot be decoded, in which case it cannot have been
+/// accepted.
+pub fn leaf_hash<const N: usize>(m: &[u8], sig: &Signature<N>, pk: &PublicKey<N>) -> [u8; 32] {
+    let s2 = sig
+        .s2_reduced()
+        .expect("accepted signatures can be decoded");
+    leaf_hash_parts(m, sig.nonce().as_bytes(), &s2, pk)
+}
+
+/// Hash an accepted item with an expanded signature to a leaf of the Merkle
+/// tree. The leaf is the same as that of the signature it was expanded from.
+pub fn leaf_hash_expanded<const N: usize>(
+    m: &[u8],
+    sig: &ExpandedSignature<N>,
+    pk: &PublicKey<N>,
+) -> [u8; 32] {
+    leaf_hash_parts(m, sig.nonce().as_bytes(), &sig.s2_reduced(), pk)
+}
+
+fn leaf_hash_parts<const N: usize>(m: &[u8], r: &[u8], s2: &[u16], pk: &PublicKey<N>) -> [u8; 32] {
+    let mut hasher = Sha3_256::default();
+    hasher.update([0x00]);
+    hasher.update(pk.as_bytes());
+    hasher.update(r);
+    for c in s2 {
+        hasher.update(c.to_be_bytes());
+    }
+    hasher.update((m.len() as u64).to_le_bytes());
+    hasher.update(m);
+    hasher.finalize().into()
+}
+
+/// The root of the Merkle tree over the given leaves, in order.
+///
+/// Inner nodes hash their two children with a distinct prefix from leaves;
+/// a node without a sibling is promoted to the next level unchanged. The
+/// root of the empty tree is all zeros.
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let mut hasher = Sha3_256::default();
+                    hasher.update([0x01]);
+                    hasher.update(left);
+                    hasher.update(right);
+                    hasher.finalize().into()
+                }
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Collects the accepted items of a batch as it is being verified, and signs
+/// the receipt at the end.
+#[derive(Debug, Clone)]
+pub struct ReceiptBuilder {
+    parameters: VerificationParameters,
+    leaves: Vec<[u8; 32]>,
+}
+
+impl ReceiptBuilder {
+    /// Start a receipt for items checked with the given parameters.
+    pub fn new(parameters: VerificationParameters) -> Self {
+        Self {
+            parameters,
+            leaves: vec![],
+        }
+    }
+
+    /// Record an accepted item. See [`leaf_hash`].
+    pub fn accept<const N: usize>(&mut self, m: &[u8], sig: &Signature<N>, pk: &PublicKey<N>) {
+        self.leaves.push(leaf_hash(m, sig, pk));
+    }
+
+    /// Record an accepted item with an expanded signature.
+    pub fn accept_expanded<const N: usize>(
+        &mut self,
+        m: &[u8],
+        sig: &ExpandedSignature<N>,
+        pk: &PublicKey<N>,
+    ) {
+        self.leaves.push(leaf_hash_expanded(m, sig, pk));
+    }
+
+    /// Sign the receipt with the verifier's secret key.
+    pub fn finish<const V: usize>(self, verifier_sk: &SecretKey<V>) -> Receipt<V> {
+        let body = Body {
+            parameters: self.parameters,
+            accepted: self.leaves.len() as u64,
+            root: merkle_root(&self.leaves),
+        };
+        let signature = falcon::sign_vectored(&[DOMAIN, &body.to_bytes()], verifier_sk);
+        Receipt { body, signature }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Body {
+    parameters: VerificationParameters,
+    accepted: u64,
+    root: [u8; 32],
+}
+
+impl Body {
+    fn to_bytes(&self) -> [u8; BODY_LEN] {
+        let mut bytes = [0u8; BODY_LEN];
+        bytes[0] = self.parameters.mode.to_byte();
+        bytes[1..3].copy_from_slice(&self.parameters.indices.to_be_bytes());
+        bytes[3..11].copy_from_slice(&self.accepted.to_be_bytes());
+        bytes[11..].copy_from_slice(&self.root);
+        bytes
+    }
+}
+
+/// A verifier's signed statement that it accepted a batch of items, checked
+/// with the given parameters, whose leaves have the given Merkle root.
+///
+/// The encoding is the mode byte, the index count as a big-endian `u16`, the
+/// number of accepted items as a big-endian `u64`, and the root, followed by
+/// the verifier's signature on them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Receipt<const V: usize> {
+    body: Body,
+    signature: Signature<V>,
+}
+
+impl<const V: usize> Receipt<V> {
+    /// How the accepted items were checked.
+    pub fn parameters(&self) -> VerificationParameters {
+        self.body.parameters
+    }
+
+    /// The number of accepted items.
+    pub fn accepted(&self) -> u64 {
+        self.body.accepted
+    }
+
+    /// The Merkle root of the accepted items.
+    pub fn root(&self) -> [u8; 32] {
+        self.body.root
+    }
+
+    /// The verifier's signature.
+    pub fn signature(&self) -> &Signature<V> {
+        &self.signature
+    }
+
+    /// Verify the verifier's signature on the receipt.
+    pub fn verify(&self, verifier_pk: &PublicKey<V>) -> bool {
+        falcon::verify_vectored(
+            &[DOMAIN, &self.body.to_bytes()],
+            &self.signature,
+            verifier_pk,
+        )
+    }
+
+    /// Test whether the receipt commits to exactly the given items, in
+    /// order. This does not check the verifier's signature.
+    pub fn commits_to<const N: usize, M: AsRef<[u8]>>(
+        &self,
+        items: &[(M, Signature<N>, PublicKey<N>)],
+    ) -> bool {
+        let leaves = items
+            .iter()
+            .map(|(m, sig, pk)| {
+                sig.s2_reduced()
+                    .map(|s2| leaf_hash_parts(m.as_ref(), sig.nonce().as_bytes(), &s2, pk))
+            })
+            .collect::<Option<Vec<_>>>();
+        leaves.is_some_and(|leaves| self.commits_to_leaves(&leaves))
+    }
+
+    /// Test whether the receipt commits to exactly the given leaves, in
+    /// order. This does not check the verifier's signature.
+    pub fn commits_to_leaves(&self, leaves: &[[u8; 32]]) -> bool {
+        leaves.len() as u64 == self.body.accepted && merkle_root(leaves) == self.body.root
+    }
+
+    /// Serialize the receipt to a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.body.to_bytes().as_slice(), self.signature.as_bytes()].concat()
+    }
+
+    /// Deserialize a receipt from a slice of bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        if bytes.len() < BODY_LEN {
+            return Err(FalconDeserializationError::BadEncodingLength);
+        }
+        let (body, signature) = bytes.split_at(BODY_LEN);
+        let mode = VerificationMode::from_byte(body[0])
+            .ok_or(FalconDeserializationError::InvalidHeaderFormat)?;
+        Ok(Self {
+            body: Body {
+                parameters: VerificationParameters {
+                    mode,
+                    indices: u16::from_be_bytes([body[1], body[2]]),
+                },
+                accepted: u64::from_be_bytes(body[3..11].try_into().unwrap()),
+                root: body[11..].try_into().unwrap(),
+            },
+            signature: Signature::from_bytes(signature)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon::FalconDeserializationError, falcon1024, falcon512};
+
+    use super::{
+        leaf_hash, leaf_hash_expanded, merkle_root, Receipt, ReceiptBuilder, VerificationMode,
+        VerificationParameters,
+    };
+
+    #[test]
+    fn test_merkle_root() {
+        let leaves = (0..5u8).map(|i| [i; 32]).collect::<Vec<_>>();
+        assert_eq!(merkle_root(&[]), [0u8; 32]);
+        assert_eq!(merkle_root(&leaves[..1]), leaves[0]);
+        let root = merkle_root(&leaves);
+        for i in 0..leaves.len() {
+            let mut tampered = leaves.clone();
+            tampered[i][0] ^= 1;
+            assert_ne!(merkle_root(&tampered), root);
+        }
+        let mut swapped = leaves.clone();
+        swapped.swap(0, 1);
+        assert_ne!(merkle_root(&swapped), root);
+        assert_ne!(merkle_root(&leaves[..4]), root);
+    }
+
+    #[test]
+    fn test_receipt() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let (verifier_sk, verifier_pk) = falcon512::keygen(rng.gen());
+        let items = (0..3u8)
+            .map(|i| (vec![i], falcon512::sign([i], &sk), pk.clone()))
+            .collect::<Vec<_>>();
+
+        let (m, sig, _) = &items[0];
+        let expanded = falcon512::ExpandedSignature::from_signature(m, sig, &pk);
+        assert_eq!(
+            leaf_hash(m, sig, &pk),
+            leaf_hash_expanded(m, &expanded, &pk)
+        );
+
+        let parameters = VerificationParameters {
+            mode: VerificationMode::ScreenedFull,
+            indices: 8,
+        };
+        let mut builder = ReceiptBuilder::new(parameters);
+        for (m, sig, pk) in &items {
+            builder.accept(m, sig, pk);
+        }
+        let receipt = builder.finish(&verifier_sk);
+        assert_eq!(receipt.parameters(), parameters);
+        assert_eq!(receipt.accepted(), 3);
+        assert!(receipt.verify(&verifier_pk));
+        assert!(receipt.commits_to(&items));
+        assert!(!receipt.commits_to(&items[..2]));
+
+        let bytes = receipt.to_bytes();
+        let decoded = Receipt::<512>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, receipt);
+        assert!(!decoded.verify(&pk));
+
+        // a receipt claiming another mode no longer verifies
+        let mut altered = bytes.clone();
+        altered[0] = 1;
+        assert!(!Receipt::<512>::from_bytes(&altered)
+            .unwrap()
+            .verify(&verifier_pk));
+        altered[0] = 9;
+        assert_eq!(
+            Receipt::<512>::from_bytes(&altered),
+            Err(FalconDeserializationError::InvalidHeaderFormat)
+        );
+        assert_eq!(
+            Receipt::<1024>::from_bytes(&bytes),
+            Err(FalconDeserializationError::WrongVariant)
+        );
+
+        // the verifier key need not be of the same variant as the items
+        let (verifier_sk, verifier_pk) = falcon1024::keygen(rng.gen());
+        let receipt = ReceiptBuilder::new(parameters).finish(&verifier_sk);
+        assert!(receipt.verify(&verifier_pk));
+        assert!(receipt.commits_to::<512, Vec<u8>>(&[]));
+    }
+}
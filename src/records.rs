@@ -0,0 +1,55 @@
+//! The text format shared by the KAT files of the NIST submission package
+//! and the fixtures bundled with this crate.
+//!
+//! Records are blocks of `key = value` lines separated by blank lines.
+//! Lines starting with `#` are comments.
+
+use alloc::vec::Vec;
+
+/// A `key = value` line of a record, with its (1-based) line number.
+pub(crate) struct Field<'a> {
+    pub(crate) line: usize,
+    pub(crate) key: &'a str,
+    pub(crate) value: &'a str,
+}
+
+/// Split a text into records and convert each with `record`, which gets the
+/// fields of the record and the (1-based) line it ends on. Parsing stops at
+/// the first error; a line that is neither blank, a comment, nor
+/// `key = value` fails with `malformed` of its line number.
+pub(crate) fn parse_records<'a, T, E>(
+    text: &'a str,
+    malformed: impl Fn(usize) -> E,
+    mut record: impl FnMut(&[Field<'a>], usize) -> Result<T, E>,
+) -> Result<Vec<T>, E> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut line_number = 0;
+    for (index, line) in text.lines().enumerate() {
+        line_number = index + 1;
+        let line = line.trim();
+
+        if line.is_empty() {
+            if !fields.is_empty() {
+                records.push(record(&fields, line_number)?);
+                fields.clear();
+            }
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(|| malformed(line_number))?;
+        fields.push(Field {
+            line: line_number,
+            key: key.trim(),
+            value: value.trim(),
+        });
+    }
+    if !fields.is_empty() {
+        records.push(record(&fields, line_number)?);
+    }
+
+    Ok(records)
+}
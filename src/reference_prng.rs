@@ -0,0 +1,105 @@
+//! The pseudorandom generator that the reference implementation feeds to its
+//! Gaussian sampler when signing.
+//!
+//! It is ChaCha20, keyed with 56 bytes of SHAKE256 output, producing eight
+//! blocks at a time whose words are interleaved as in the reference AVX2
+//! code. The way the sampler draws from it is part of its definition: a
+//! 64-bit word is never split across refills. See `rng.c` and `inner.h` in
+//! the reference implementation.
+
+use sha3::digest::XofReader;
+
+const CW: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+const BUF_LEN: usize = 512;
+
+pub(crate) struct ReferencePrng {
+    /// Key and IV, as little-endian words.
+    state: [u32; 12],
+    counter: u64,
+    buf: [u8; BUF_LEN],
+    ptr: usize,
+}
+
+impl ReferencePrng {
+    /// Seed the generator with the next 56 bytes of `src`.
+    pub(crate) fn new(src: &mut impl XofReader) -> Self {
+        let mut seed = [0u8; 56];
+        src.read(&mut seed);
+        let mut state = [0u32; 12];
+        for (word, bytes) in state.iter_mut().zip(seed.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        let mut prng = Self {
+            state,
+            counter: u64::from_le_bytes(seed[48..].try_into().unwrap()),
+            buf: [0u8; BUF_LEN],
+            ptr: 0,
+        };
+        prng.refill();
+        prng
+    }
+
+    fn refill(&mut self) {
+        for u in 0..8 {
+            let mut x = [0u32; 16];
+            x[..4].copy_from_slice(&CW);
+            x[4..].copy_from_slice(&self.state);
+            x[14] ^= self.counter as u32;
+            x[15] ^= (self.counter >> 32) as u32;
+            for _ in 0..10 {
+                quarter_round(&mut x, 0, 4, 8, 12);
+                quarter_round(&mut x, 1, 5, 9, 13);
+                quarter_round(&mut x, 2, 6, 10, 14);
+                quarter_round(&mut x, 3, 7, 11, 15);
+                quarter_round(&mut x, 0, 5, 10, 15);
+                quarter_round(&mut x, 1, 6, 11, 12);
+                quarter_round(&mut x, 2, 7, 8, 13);
+                quarter_round(&mut x, 3, 4, 9, 14);
+            }
+            for (word, added) in x.iter_mut().zip(CW.iter().chain(&self.state[..10])) {
+                *word = word.wrapping_add(*added);
+            }
+            x[14] = x[14].wrapping_add(self.state[10] ^ self.counter as u32);
+            x[15] = x[15].wrapping_add(self.state[11] ^ (self.counter >> 32) as u32);
+            self.counter = self.counter.wrapping_add(1);
+
+            for (v, word) in x.iter().enumerate() {
+                let offset = (u << 2) + (v << 5);
+                self.buf[offset..offset + 4].copy_from_slice(&word.to_le_bytes());
+            }
+        }
+        self.ptr = 0;
+    }
+
+    /// The next 64-bit word, little-endian. If fewer than 9 bytes are left,
+    /// they are discarded.
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        if self.ptr >= BUF_LEN - 9 {
+            self.refill();
+        }
+        let bytes = self.buf[self.ptr..self.ptr + 8].try_into().unwrap();
+        self.ptr += 8;
+        u64::from_le_bytes(bytes)
+    }
+
+    /// The next byte.
+    pub(crate) fn next_u8(&mut self) -> u8 {
+        let byte = self.buf[self.ptr];
+        self.ptr += 1;
+        if self.ptr == BUF_LEN {
+            self.refill();
+        }
+        byte
+    }
+}
+
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
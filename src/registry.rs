@@ -0,0 +1,471 @@
+//! A registry of signer keys with validity periods and revocation.
+//!
+//! Every signer is known by a key id and may have several keys over its
+//! lifetime, one per *epoch*: when a key is rotated, the new key is
+//! registered under the next epoch, and the old one expires. A key is valid
+//! from its `not_before` time up to, but excluding, its expiry, unless it is
+//! revoked earlier. Times are seconds since the Unix epoch.
+//!
+//! ```
+//! use falcon_rust::{falcon512, registry::{KeyRecord, KeyRegistry, RegistryError}};
+//! # use rand::{thread_rng, Rng};
+//! # let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//!
+//! let mut registry = KeyRegistry::new();
+//! registry.register("alice", KeyRecord::new(0, pk, 1_000).expires_at(2_000))?;
+//!
+//! let sig = falcon512::sign(b"hello", &sk);
+//! assert_eq!(registry.verify_registered("alice", b"hello", &sig, 1_500), Ok(0));
+//! assert_eq!(
+//!     registry.verify_registered("alice", b"hello", &sig, 2_000),
+//!     Err(RegistryError::Expired)
+//! );
+//!
+//! registry.revoke("alice", 0, 1_200);
+//! assert_eq!(
+//!     registry.verify_registered("alice", b"hello", &sig, 1_500),
+//!     Err(RegistryError::Revoked)
+//! );
+//! # Ok::<(), RegistryError>(())
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    falcon::{self, PublicKey, Signature},
+    verifier_context::VerifierContext,
+};
+
+/// Reasons why a registry operation or a registered verification fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistryError {
+    /// No key is registered under the key id.
+    UnknownKey,
+    /// A key is already registered under the key id and epoch.
+    DuplicateEpoch,
+    /// The key's validity period is empty.
+    EmptyValidity,
+    /// No key of the signer is valid yet at the given time.
+    NotYetValid,
+    /// All keys of the signer that were valid by the given time have expired.
+    Expired,
+    /// All keys of the signer valid at the given time are revoked.
+    Revoked,
+    /// The signature is invalid under every key of the signer valid at the
+    /// given time.
+    InvalidSignature,
+}
+
+/// A registered key and its validity period.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRecord<const N: usize> {
+    /// The epoch of the key; successive keys of a signer have increasing
+    /// epochs.
+    pub epoch: u32,
+    /// The public key.
+    pub public_key: PublicKey<N>,
+    /// The first time at which the key is valid.
+    pub not_before: u64,
+    /// The first time at which the key is no longer valid, if any.
+    pub expires_at: Option<u64>,
+}
+
+impl<const N: usize> KeyRecord<N> {
+    /// A key that is valid from `not_before` on and does not expire.
+    pub fn new(epoch: u32, public_key: PublicKey<N>, not_before: u64) -> Self {
+        Self {
+            epoch,
+            public_key,
+            not_before,
+            expires_at: None,
+        }
+    }
+
+    /// Let the key expire at the given time.
+    pub fn expires_at(mut self, time: u64) -> Self {
+        self.expires_at = Some(time);
+        self
+    }
+
+    /// Test whether the key is valid at the given time, ignoring revocation.
+    pub fn is_valid_at(&self, time: u64) -> bool {
+        self.not_before <= time && self.expires_at.is_none_or(|expiry| time < expiry)
+    }
+}
+
+/// A list of revoked keys, each revoked from some time on.
+///
+/// Revocation takes effect at the given time: signatures checked at an
+/// earlier time, e.g., the time recorded for an archived document, are
+/// unaffected, so that revoking a compromised key does not invalidate what
+/// was legitimately signed before.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RevocationList {
+    revoked: HashMap<(String, u32), u64>,
+}
+
+impl RevocationList {
+    /// Create an empty revocation list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Revoke the key of the given id and epoch from the given time on. If
+    /// the key is already revoked, the earlier of the two times applies.
+    pub fn revoke(&mut self, key_id: &str, epoch: u32, time: u64) {
+        self.revoked
+            .entry((key_id.to_string(), epoch))
+            .and_modify(|revoked_at| *revoked_at = (*revoked_at).min(time))
+            .or_insert(time);
+    }
+
+    /// The time from which the key is revoked, if it is.
+    pub fn revoked_at(&self, key_id: &str, epoch: u32) -> Option<u64> {
+        self.revoked.get(&(key_id.to_string(), epoch)).copied()
+    }
+
+    /// Test whether the key is revoked at the given time.
+    pub fn is_revoked(&self, key_id: &str, epoch: u32, time: u64) -> bool {
+        self.revoked_at(key_id, epoch)
+            .is_some_and(|revoked_at| revoked_at <= time)
+    }
+
+    /// Add all revocations of another list to this one.
+    pub fn merge(&mut self, other: &RevocationList) {
+        for ((key_id, epoch), &time) in &other.revoked {
+            self.revoke(key_id, *epoch, time);
+        }
+    }
+
+    /// The number of revoked keys.
+    pub fn len(&self) -> usize {
+        self.revoked.len()
+    }
+
+    /// Test whether no key is revoked.
+    pub fn is_empty(&self) -> bool {
+        self.revoked.is_empty()
+    }
+}
+
+/// Signer keys by key id, with their validity periods and revocations.
+///
+/// A registry created with [`KeyRegistry::expanded`] stores every key
+/// together with its [`VerifierContext`], which takes more memory but saves
+/// the NTT of the key on every verification.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyRegistry<const N: usize> {
+    keys: HashMap<String, Vec<KeyRecord<N>>>,
+    revocations: RevocationList,
+    contexts: Option<HashMap<(String, u32), VerifierContext<N>>>,
+}
+
+impl<const N: usize> KeyRegistry<N> {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self {
+            keys: HashMap::new(),
+            revocations: RevocationList::new(),
+            contexts: None,
+        }
+    }
+
+    /// Create an empty registry that pre-expands its keys.
+    pub fn expanded() -> Self {
+        Self {
+            contexts: Some(HashMap::new()),
+            ..Self::new()
+        }
+    }
+
+    /// Test whether the registry pre-expands its keys.
+    pub fn is_expanded(&self) -> bool {
+        self.contexts.is_some()
+    }
+
+    /// Register a key under the given key id.
+    pub fn register(&mut self, key_id: &str, record: KeyRecord<N>) -> Result<(), RegistryError> {
+        if record
+            .expires_at
+            .is_some_and(|expiry| expiry <= record.not_before)
+        {
+            return Err(RegistryError::EmptyValidity);
+        }
+        let records = self.keys.entry(key_id.to_string()).or_default();
+        if records.iter().any(|other| other.epoch == record.epoch) {
+            return Err(RegistryError::DuplicateEpoch);
+        }
+        if let Some(contexts) = &mut self.contexts {
+            let context = VerifierContext::new(&record.public_key);
+            contexts.insert((key_id.to_string(), record.epoch), context);
+        }
+        records.push(record);
+        records.sort_by_key(|record| record.epoch);
+        Ok(())
+    }
+
+    /// Remove all keys registered under the given key id and return them,
+    /// by increasing epoch. Revocations are kept.
+    pub fn remove(&mut self, key_id: &str) -> Vec<KeyRecord<N>> {
+        let records = self.keys.remove(key_id).unwrap_or_default();
+        if let Some(contexts) = &mut self.contexts {
+            for record in &records {
+                contexts.remove(&(key_id.to_string(), record.epoch));
+            }
+        }
+        records
+    }
+
+    /// The keys registered under the given key id, by increasing epoch.
+    pub fn keys(&self, key_id: &str) -> &[KeyRecord<N>] {
+        self.keys.get(key_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The ids under which keys are registered, in no particular order.
+    pub fn key_ids(&self) -> impl Iterator<Item = &str> {
+        self.keys.keys().map(String::as_str)
+    }
+
+    /// The number of key ids under which keys are registered.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Test whether no key is registered.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Revoke the key of the given id and epoch from the given time on.
+    pub fn revoke(&mut self, key_id: &str, epoch: u32, time: u64) {
+        self.revocations.revoke(key_id, epoch, time);
+    }
+
+    /// The revocations the registry enforces.
+    pub fn revocations(&self) -> &RevocationList {
+        &self.revocations
+    }
+
+    /// Enforce all revocations of the given list, in addition to those
+    /// already known.
+    pub fn apply_revocations(&mut self, revocations: &RevocationList) {
+        self.revocations.merge(revocations);
+    }
+
+    /// The keys of the signer that are valid, and not revoked, at the given
+    /// time.
+    pub fn valid_keys(&self, key_id: &str, time: u64) -> Result<Vec<&KeyRecord<N>>, RegistryError> {
+        let records = self.keys(key_id);
+        if records.is_empty() {
+            return Err(RegistryError::UnknownKey);
+        }
+        let current = records
+            .iter()
+            .filter(|record| record.is_valid_at(time))
+            .collect::<Vec<_>>();
+        if current.is_empty() {
+            return Err(if records.iter().all(|record| time < record.not_before) {
+                RegistryError::NotYetValid
+            } else {
+                RegistryError::Expired
+            });
+        }
+        let valid = current
+            .into_iter()
+            .filter(|record| !self.revocations.is_revoked(key_id, record.epoch, time))
+            .collect::<Vec<_>>();
+        if valid.is_empty() {
+            return Err(RegistryError::Revoked);
+        }
+        Ok(valid)
+    }
+
+    /// Verify a signature by the signer with the given key id, as of the
+    /// given time, and return the epoch of the key it is valid under.
+    ///
+    /// While keys are being rotated, validity periods may overlap; the
+    /// signature is then accepted under any of the keys valid at the time.
+    pub fn verify_registered(
+        &self,
+        key_id: &str,
+        m: &[u8],
+        sig: &Signature<N>,
+        time: u64,
+    ) -> Result<u32, RegistryError> {
+        self.valid_keys(key_id, time)?
+            .into_iter()
+            .find(|record| self.verify_record(key_id, record, m, sig))
+            .map(|record| record.epoch)
+            .ok_or(RegistryError::InvalidSignature)
+    }
+
+    /// Verify a signature under a registered key, using its context if the
+    /// registry pre-expands its keys.
+    fn verify_record(
+        &self,
+        key_id: &str,
+        record: &KeyRecord<N>,
+        m: &[u8],
+        sig: &Signature<N>,
+    ) -> bool {
+        let context = self
+            .contexts
+            .as_ref()
+            .and_then(|contexts| contexts.get(&(key_id.to_string(), record.epoch)));
+        match context {
+            Some(context) => context.verify(m, sig),
+            None => falcon::verify(m, sig, &record.public_key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon512;
+
+    use super::{KeyRecord, KeyRegistry, RegistryError, RevocationList};
+
+    #[test]
+    fn test_key_registry() {
+        let mut rng = thread_rng();
+        let (old_sk, old_pk) = falcon512::keygen(rng.gen());
+        let (new_sk, new_pk) = falcon512::keygen(rng.gen());
+
+        // the new key overlaps the old one by 100 seconds
+        let mut registry = KeyRegistry::new();
+        registry
+            .register("signer", KeyRecord::new(1, old_pk, 1_000).expires_at(2_000))
+            .unwrap();
+        registry
+            .register("signer", KeyRecord::new(2, new_pk.clone(), 1_900))
+            .unwrap();
+        assert_eq!(
+            registry.register("signer", KeyRecord::new(2, new_pk.clone(), 0)),
+            Err(RegistryError::DuplicateEpoch)
+        );
+        assert_eq!(
+            registry.register("other", KeyRecord::new(1, new_pk, 10).expires_at(10)),
+            Err(RegistryError::EmptyValidity)
+        );
+        assert_eq!(registry.keys("signer").len(), 2);
+
+        let old_sig = falcon512::sign(b"msg", &old_sk);
+        let new_sig = falcon512::sign(b"msg", &new_sk);
+        let verify = |registry: &KeyRegistry<512>, sig, time| {
+            registry.verify_registered("signer", b"msg", sig, time)
+        };
+
+        assert_eq!(
+            verify(&registry, &old_sig, 999),
+            Err(RegistryError::NotYetValid)
+        );
+        assert_eq!(verify(&registry, &old_sig, 1_000), Ok(1));
+        assert_eq!(verify(&registry, &old_sig, 1_950), Ok(1));
+        assert_eq!(verify(&registry, &new_sig, 1_950), Ok(2));
+        assert_eq!(
+            verify(&registry, &old_sig, 2_000),
+            Err(RegistryError::InvalidSignature)
+        );
+        assert_eq!(verify(&registry, &new_sig, 5_000), Ok(2));
+        assert_eq!(
+            registry.verify_registered("nobody", b"msg", &new_sig, 5_000),
+            Err(RegistryError::UnknownKey)
+        );
+        assert_eq!(
+            registry.verify_registered("signer", b"other", &new_sig, 5_000),
+            Err(RegistryError::InvalidSignature)
+        );
+
+        // revocation applies from its time on, and the earliest time wins
+        let mut revocations = RevocationList::new();
+        revocations.revoke("signer", 2, 4_000);
+        revocations.revoke("signer", 2, 3_000);
+        revocations.revoke("signer", 2, 3_500);
+        assert_eq!(revocations.revoked_at("signer", 2), Some(3_000));
+        registry.apply_revocations(&revocations);
+        assert_eq!(verify(&registry, &new_sig, 2_999), Ok(2));
+        assert_eq!(
+            verify(&registry, &new_sig, 3_000),
+            Err(RegistryError::Revoked)
+        );
+
+        // during the overlap, only the revoked key is rejected
+        registry.revoke("signer", 1, 1_950);
+        assert_eq!(
+            verify(&registry, &old_sig, 1_960),
+            Err(RegistryError::InvalidSignature)
+        );
+        assert_eq!(verify(&registry, &new_sig, 1_960), Ok(2));
+        assert_eq!(verify(&registry, &old_sig, 1_500), Ok(1));
+    }
+
+    #[test]
+    fn test_expanded_registry() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let (_, other_pk) = falcon512::keygen(rng.gen());
+        let sig = falcon512::sign(b"msg", &sk);
+
+        for mut registry in [KeyRegistry::new(), KeyRegistry::expanded()] {
+            registry
+                .register("signer", KeyRecord::new(0, other_pk.clone(), 0))
+                .unwrap();
+            registry
+                .register("signer", KeyRecord::new(1, pk.clone(), 0))
+                .unwrap();
+            registry
+                .register("other", KeyRecord::new(0, other_pk.clone(), 0))
+                .unwrap();
+            assert_eq!(registry.len(), 2);
+            let mut key_ids = registry.key_ids().collect::<Vec<_>>();
+            key_ids.sort_unstable();
+            assert_eq!(key_ids, ["other", "signer"]);
+
+            assert_eq!(registry.verify_registered("signer", b"msg", &sig, 0), Ok(1));
+            assert_eq!(
+                registry.verify_registered("other", b"msg", &sig, 0),
+                Err(RegistryError::InvalidSignature)
+            );
+
+            let removed = registry.remove("signer");
+            assert_eq!(
+                removed
+                    .iter()
+                    .map(|record| record.epoch)
+                    .collect::<Vec<_>>(),
+                [0, 1]
+            );
+            assert!(registry.remove("signer").is_empty());
+            assert_eq!(
+                registry.verify_registered("signer", b"msg", &sig, 0),
+                Err(RegistryError::UnknownKey)
+            );
+            registry.remove("other");
+            assert!(registry.is_empty());
+            // the contexts of the removed keys are gone, too
+            let empty = if registry.is_expanded() {
+                KeyRegistry::expanded()
+            } else {
+                KeyRegistry::new()
+            };
+            assert_eq!(registry, empty);
+        }
+    }
+
+    #[test]
+    fn test_expired() {
+        let (sk, pk) = falcon512::keygen(thread_rng().gen());
+        let sig = falcon512::sign(b"msg", &sk);
+        let mut registry = KeyRegistry::new();
+        registry
+            .register("signer", KeyRecord::new(0, pk, 100).expires_at(200))
+            .unwrap();
+        assert_eq!(
+            registry.verify_registered("signer", b"msg", &sig, 200),
+            Err(RegistryError::Expired)
+        );
+        assert!(registry.keys("signer")[0].is_valid_at(199));
+    }
+}
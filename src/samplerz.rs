@@ -1,6 +1,16 @@
-use std::f64::consts::LN_2;
+use core::f64::consts::LN_2;
+#[cfg(feature = "std")]
+use core::f64::consts::LOG2_E;
 
 use rand::{Rng, RngCore};
+use zeroize::Zeroize;
+
+#[cfg(not(any(feature = "std", feature = "fixed-point")))]
+use crate::float::FloatExt;
+#[cfg(feature = "fixed-point")]
+use crate::fpr::Fpr;
+#[cfg(feature = "std")]
+use crate::reference_prng::ReferencePrng;
 
 /// Sample an integer from {0, ..., 18} according to the distribution χ, which
 /// is close to the half-Gaussian distribution on the natural numbers with mean
@@ -26,12 +36,26 @@ fn base_sampler(bytes: [u8; 9]) -> i16 {
         198,
         1,
     ];
-    let u = u128::from_be_bytes([vec![0u8; 7], bytes.to_vec()].concat().try_into().unwrap());
+    // widen on the stack, not the heap, and wipe the copy of the randomness
+    let mut padded = [0u8; 16];
+    padded[7..].copy_from_slice(&bytes);
+    let u = u128::from_be_bytes(padded);
+    padded.zeroize();
     RCDT.into_iter().filter(|r| u < *r).count() as i16
 }
 
 /// Compute an integer approximation of 2^63 * ccs * exp(-x).
+#[cfg(any(feature = "std", not(feature = "fixed-point")))]
 fn approx_exp(x: f64, ccs: f64) -> u64 {
+    let twoe63 = 1u64 << 63;
+    approx_exp_scaled(
+        f64::floor(x * (twoe63 as f64)) as u64,
+        f64::floor((twoe63 as f64) * ccs) as u64,
+    )
+}
+
+/// Like [`approx_exp`], but with x and ccs given as integers scaled by 2^63.
+fn approx_exp_scaled(x: u64, ccs: u64) -> u64 {
     // The constants C are used to approximate exp(-x); these
     // constants are taken from FACCT (up to a scaling factor
     // of 2^63):
@@ -53,29 +77,41 @@ fn approx_exp(x: f64, ccs: f64) -> u64 {
         0x8000000000000000u64,
     ];
 
-    let mut z: u64;
-    let mut y: u64;
-    let twoe63 = 1u64 << 63;
-
-    y = C[0];
-    z = f64::floor(x * (twoe63 as f64)) as u64;
+    let mut y = C[0];
     for cu in C.iter().skip(1) {
-        let zy = (z as u128) * (y as u128);
+        let zy = (x as u128) * (y as u128);
         y = cu - ((zy >> 63) as u64);
     }
 
-    z = f64::floor((twoe63 as f64) * ccs) as u64;
-
-    (((z as u128) * (y as u128)) >> 63) as u64
+    (((ccs as u128) * (y as u128)) >> 63) as u64
 }
 
 /// A random bool that is true with probability ≈ ccs · exp(−x).
+#[cfg(not(feature = "fixed-point"))]
 fn ber_exp(x: f64, ccs: f64, random_bytes: [u8; 7]) -> bool {
     // 0.69314718055994530941 = ln(2)
     let s = f64::floor(x / LN_2) as usize;
     let r = x - LN_2 * (s as f64);
+    ber_exp_reduced(s, approx_exp(r, ccs), random_bytes)
+}
+
+/// A random bool that is true with probability ≈ ccs · exp(−x), with the
+/// floating-point arithmetic emulated on integers.
+#[cfg(feature = "fixed-point")]
+fn ber_exp(x: Fpr, ccs: Fpr, random_bytes: [u8; 7]) -> bool {
+    const LN_2_FPR: Fpr = Fpr::from_f64(LN_2);
+    const TWOE63: Fpr = Fpr::from_f64((1u64 << 63) as f64);
+    let s = (x / LN_2_FPR).floor() as usize;
+    let r = x - LN_2_FPR * Fpr::from_i64(s as i64);
+    let exp = approx_exp_scaled((r * TWOE63).floor() as u64, (TWOE63 * ccs).floor() as u64);
+    ber_exp_reduced(s, exp, random_bytes)
+}
+
+/// The part of [`ber_exp`] after x = s ln(2) + r, given s and an
+/// approximation of 2^63 ccs exp(-r).
+fn ber_exp_reduced(s: usize, exp: u64, random_bytes: [u8; 7]) -> bool {
     let shamt = usize::min(s, 63);
-    let z = ((((approx_exp(r, ccs) as u128) << 1) - 1) >> shamt) as u64;
+    let z = ((((exp as u128) << 1) - 1) >> shamt) as u64;
     let mut w = 0i16;
     for (index, i) in (0..64).step_by(8).rev().enumerate() {
         let byte = random_bytes[index];
@@ -89,7 +125,13 @@ fn ber_exp(x: f64, ccs: f64, random_bytes: [u8; 7]) -> bool {
 
 /// Sample an integer from the Gaussian distribution with given mean (mu) and
 /// standard deviation (sigma).
-pub(crate) fn sampler_z(mu: f64, sigma: f64, sigma_min: f64, rng: &mut dyn RngCore) -> i16 {
+#[cfg(not(feature = "fixed-point"))]
+pub(crate) fn sampler_z<R: RngCore + ?Sized>(
+    mu: f64,
+    sigma: f64,
+    sigma_min: f64,
+    rng: &mut R,
+) -> i16 {
     const SIGMA_MAX: f64 = 1.8205;
     const INV_2SIGMA_MAX_SQ: f64 = 1f64 / (2f64 * SIGMA_MAX * SIGMA_MAX);
     let isigma = 1f64 / sigma;
@@ -111,6 +153,117 @@ pub(crate) fn sampler_z(mu: f64, sigma: f64, sigma_min: f64, rng: &mut dyn RngCo
     }
 }
 
+/// Sample an integer from the Gaussian distribution with given mean (mu) and
+/// standard deviation (sigma), with the floating-point arithmetic emulated
+/// on integers. Draws the same randomness and returns the same samples as
+/// the version on `f64`.
+#[cfg(feature = "fixed-point")]
+pub(crate) fn sampler_z<R: RngCore + ?Sized>(
+    mu: f64,
+    sigma: f64,
+    sigma_min: f64,
+    rng: &mut R,
+) -> i16 {
+    const SIGMA_MAX: f64 = 1.8205;
+    const INV_2SIGMA_MAX_SQ: Fpr = Fpr::from_f64(1f64 / (2f64 * SIGMA_MAX * SIGMA_MAX));
+    let (mu, sigma, sigma_min) = (Fpr::from(mu), Fpr::from(sigma), Fpr::from(sigma_min));
+    let isigma = Fpr::from(1f64) / sigma;
+    let dss = Fpr::from(0.5f64) * isigma * isigma;
+    let s = mu.floor();
+    let r = mu - Fpr::from_i64(s);
+    let ccs = sigma_min * isigma;
+    loop {
+        let z0 = base_sampler(rng.gen());
+        let random_byte: u8 = rng.gen();
+        let b = (random_byte & 1) as i16;
+        let z = b + ((b << 1) - 1) * z0;
+        let zf_min_r = Fpr::from_i64(z as i64) - r;
+        //    x = ((z-r)^2)/(2*sigma^2) - ((z-b)^2)/(2*sigma0^2)
+        let x = zf_min_r * zf_min_r * dss - Fpr::from_i64((z0 * z0) as i64) * INV_2SIGMA_MAX_SQ;
+        if ber_exp(x, ccs, rng.gen()) {
+            return z + (s as i16);
+        }
+    }
+}
+
+/// Like [`base_sampler`], but drawing the 72 random bits as the reference
+/// implementation does: a little-endian 64-bit word for the low bits, then a
+/// byte for the high bits.
+#[cfg(feature = "std")]
+fn base_sampler_reference(prng: &mut ReferencePrng) -> i16 {
+    let lo = prng.next_u64();
+    let hi = prng.next_u8();
+    let mut bytes = [0u8; 9];
+    bytes[0] = hi;
+    bytes[1..].copy_from_slice(&lo.to_be_bytes());
+    base_sampler(bytes)
+}
+
+/// Like [`ber_exp`], but reducing x with the reference implementation's
+/// arithmetic and drawing random bytes only until the comparison is decided.
+#[cfg(feature = "std")]
+fn ber_exp_reference(x: f64, ccs: f64, prng: &mut ReferencePrng) -> bool {
+    let s = f64::trunc(x * LOG2_E) as usize;
+    let r = x - (s as f64) * LN_2;
+    let shamt = usize::min(s, 63);
+    let z = ((((approx_exp(r, ccs) as u128) << 1) - 1) >> shamt) as u64;
+    for i in (0..64).step_by(8).rev() {
+        let w = (prng.next_u8() as i16) - (((z >> i) & 0xff) as i16);
+        if w != 0 {
+            return w < 0;
+        }
+    }
+    false
+}
+
+/// Like [`sampler_z`], but consuming the output of the reference
+/// implementation's PRNG in exactly the way its sampler does.
+#[cfg(feature = "std")]
+pub(crate) fn sampler_z_reference(
+    mu: f64,
+    sigma: f64,
+    sigma_min: f64,
+    prng: &mut ReferencePrng,
+) -> i16 {
+    const INV_2SIGMA_MAX_SQ: f64 = 0.15086504887537272;
+    let isigma = 1f64 / sigma;
+    let dss = 0.5f64 * (isigma * isigma);
+    let s = f64::floor(mu);
+    let r = mu - s;
+    let ccs = isigma * sigma_min;
+    loop {
+        let z0 = base_sampler_reference(prng);
+        let b = (prng.next_u8() & 1) as i16;
+        let z = b + ((b << 1) - 1) * z0;
+        let zf_min_r = (z as f64) - r;
+        let x = zf_min_r * zf_min_r * dss - ((z0 * z0) as f64) * INV_2SIGMA_MAX_SQ;
+        if ber_exp_reference(x, ccs, prng) {
+            return z + (s as i16);
+        }
+    }
+}
+
+/// A source of samples from discrete Gaussians, as drawn by fast Fourier
+/// sampling.
+pub(crate) trait SamplerZ {
+    /// Sample an integer from the Gaussian distribution with given mean
+    /// (mu) and standard deviation (sigma).
+    fn sample_z(&mut self, mu: f64, sigma: f64, sigma_min: f64) -> i16;
+}
+
+impl<R: RngCore + ?Sized> SamplerZ for R {
+    fn sample_z(&mut self, mu: f64, sigma: f64, sigma_min: f64) -> i16 {
+        sampler_z(mu, sigma, sigma_min, self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl SamplerZ for ReferencePrng {
+    fn sample_z(&mut self, mu: f64, sigma: f64, sigma_min: f64) -> i16 {
+        sampler_z_reference(mu, sigma, sigma_min, self)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use itertools::Itertools;
@@ -238,6 +391,8 @@ mod test {
     }
 
     #[test]
+    // the arguments are `Fpr` with the `fixed-point` feature
+    #[allow(clippy::useless_conversion)]
     fn test_ber_exp() {
         let kats = [
             (
@@ -266,7 +421,10 @@ mod test {
             ),
         ];
         for (x, ccs, bytes, answer) in kats {
-            assert_eq!(answer, ber_exp(x, ccs, bytes.try_into().unwrap()));
+            assert_eq!(
+                answer,
+                ber_exp(x.into(), ccs.into(), bytes.try_into().unwrap())
+            );
         }
     }
 
@@ -0,0 +1,110 @@
+//! Cheaply clonable handles to signatures.
+//!
+//! A [`Signature`] owns a heap buffer of up to 1.3 KB and an
+//! [`ExpandedSignature`] holds two polynomials of `N` field elements each.
+//! Pipelines that fan the same signature out to several consumers should
+//! not deep-copy it for every one of them; wrapping it in a [`Shared`]
+//! handle makes clones a reference count increment instead.
+
+use alloc::sync::Arc;
+use core::ops::Deref;
+
+use crate::falcon::{ExpandedSignature, Signature};
+
+/// A reference-counted, copy-on-write handle to a `T`.
+///
+/// Cloning the handle does not clone the value. The value is immutable while
+/// shared; [`Shared::make_mut`] clones it only if other handles to it exist.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Shared<T>(Arc<T>);
+
+/// A cheaply clonable [`Signature`].
+pub type SharedSignature<const N: usize> = Shared<Signature<N>>;
+
+/// A cheaply clonable [`ExpandedSignature`].
+pub type SharedExpandedSignature<const N: usize> = Shared<ExpandedSignature<N>>;
+
+impl<T> Shared<T> {
+    /// Move a value into a new handle.
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+
+    /// Return whether two handles point to the same value.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl<T: Clone> Shared<T> {
+    /// Get a mutable reference to the value, cloning it first if it is
+    /// shared with other handles.
+    pub fn make_mut(this: &mut Self) -> &mut T {
+        Arc::make_mut(&mut this.0)
+    }
+
+    /// Extract the value, cloning it only if it is shared with other
+    /// handles.
+    pub fn into_inner(this: Self) -> T {
+        Arc::try_unwrap(this.0).unwrap_or_else(|arc| (*arc).clone())
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> AsRef<T> for Shared<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for Shared<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> From<Arc<T>> for Shared<T> {
+    fn from(arc: Arc<T>) -> Self {
+        Self(arc)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon512;
+
+    use super::Shared;
+
+    #[test]
+    fn test_shared_signatures() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let msg = b"shared";
+        let sig = falcon512::sign(msg, &sk);
+
+        let shared: falcon512::SharedSignature = Shared::new(sig.clone());
+        let copy = shared.clone();
+        assert!(Shared::ptr_eq(&shared, &copy));
+        assert!(falcon512::verify(msg, &copy, &pk));
+
+        let expanded: falcon512::SharedExpandedSignature =
+            falcon512::ExpandedSignature::from_signature(msg, &shared, &pk).into();
+        let mut written = expanded.clone();
+        Shared::make_mut(&mut written);
+        assert!(!Shared::ptr_eq(&expanded, &written));
+        assert_eq!(expanded, written);
+        assert!(falcon512::verify_expanded(msg, &written, &pk));
+
+        drop(shared);
+        assert_eq!(Shared::into_inner(copy), sig);
+    }
+}
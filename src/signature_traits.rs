@@ -0,0 +1,88 @@
+//! Implementations of the traits of the RustCrypto [`signature`] crate, so
+//! that Falcon keys can be used by code that is generic over signers and
+//! verifiers:
+//!
+//! ```
+//! use falcon_rust::falcon512;
+//! use signature::{Signer, Verifier};
+//! # use rand::{thread_rng, Rng};
+//!
+//! fn sign_and_check<S, V, Sig>(signer: &S, verifier: &V, msg: &[u8]) -> bool
+//! where
+//!     S: Signer<Sig>,
+//!     V: Verifier<Sig>,
+//! {
+//!     let sig = signer.sign(msg);
+//!     verifier.verify(msg, &sig).is_ok()
+//! }
+//!
+//! let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//! assert!(sign_and_check::<_, _, falcon512::Signature>(&sk, &pk, b"msg"));
+//! ```
+//!
+//! The implementations are generic over the degree, so they cover the types
+//! of both [`falcon512`](crate::falcon512) and
+//! [`falcon1024`](crate::falcon1024).
+//!
+//! [`signature`]: https://crates.io/crates/signature
+
+use signature::{rand_core::CryptoRngCore, Error, Keypair, RandomizedSigner, Signer, Verifier};
+
+use crate::falcon::{self, PublicKey, SecretKey, Signature};
+
+impl<const N: usize> Signer<Signature<N>> for SecretKey<N> {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature<N>, Error> {
+        Ok(falcon::sign(msg, self))
+    }
+}
+
+impl<const N: usize> RandomizedSigner<Signature<N>> for SecretKey<N> {
+    fn try_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[u8],
+    ) -> Result<Signature<N>, Error> {
+        Ok(falcon::sign_with_rng(msg, self, rng))
+    }
+}
+
+impl<const N: usize> Keypair for SecretKey<N> {
+    type VerifyingKey = PublicKey<N>;
+
+    fn verifying_key(&self) -> PublicKey<N> {
+        self.public_key()
+    }
+}
+
+impl<const N: usize> Verifier<Signature<N>> for PublicKey<N> {
+    fn verify(&self, msg: &[u8], signature: &Signature<N>) -> Result<(), Error> {
+        if falcon::verify(msg, signature, self) {
+            Ok(())
+        } else {
+            Err(Error::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+    use signature::{Keypair, RandomizedSigner, Signer, Verifier};
+
+    use crate::{falcon1024, falcon512};
+
+    #[test]
+    fn test_signature_traits() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let sig: falcon512::Signature = sk.sign(b"msg");
+        assert!(pk.verify(b"msg", &sig).is_ok());
+        assert!(pk.verify(b"other", &sig).is_err());
+        assert!(falcon512::verify(b"msg", &sig, &pk));
+        assert_eq!(sk.verifying_key(), pk);
+
+        let (sk, pk) = falcon1024::keygen(rng.gen());
+        let sig: falcon1024::Signature = sk.sign_with_rng(&mut rng, b"msg");
+        assert!(pk.verify(b"msg", &sig).is_ok());
+    }
+}
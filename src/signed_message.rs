@@ -0,0 +1,231 @@
+//! Signed messages in the attached format of the reference implementation.
+//!
+//! The NIST API of the reference implementation, and PQClean after it,
+//! produces a *signed message* that carries the message and its signature in
+//! one buffer:
+//!
+//! ```text
+//! signature length (2 bytes, big endian) || nonce (40 bytes) || message
+//!     || header (0x20 + log n) || compressed s2
+//! ```
+//!
+//! where the signature length counts the header and the compressed vector.
+//! Unlike the detached encoding of [`Signature::to_bytes`], the compressed
+//! vector is not padded. [`SignedMessage`] converts between this framing and
+//! the detached (message, [`Signature`]) pair, and [`sign_attached`] and
+//! [`open`] mirror the reference's `crypto_sign` and `crypto_sign_open`.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use crate::falcon::SecretKey;
+use crate::{
+    falcon::{self, FalconDeserializationError, PublicKey, Signature},
+    nonce::Nonce,
+};
+
+/// A message together with its signature.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SignedMessage<const N: usize> {
+    message: Vec<u8>,
+    signature: Signature<N>,
+}
+
+impl<const N: usize> SignedMessage<N> {
+    /// Attach a detached signature to its message.
+    pub fn attach(message: &[u8], signature: Signature<N>) -> Self {
+        Self {
+            message: message.to_vec(),
+            signature,
+        }
+    }
+
+    /// Split into the message and the detached signature.
+    pub fn detach(self) -> (Vec<u8>, Signature<N>) {
+        (self.message, self.signature)
+    }
+
+    /// The message. Note that it has not been verified.
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// The detached signature.
+    pub fn signature(&self) -> &Signature<N> {
+        &self.signature
+    }
+
+    /// The salt that was hashed together with the message.
+    pub fn nonce(&self) -> Nonce {
+        self.signature.nonce()
+    }
+
+    /// Verify the signature on the attached message.
+    pub fn verify(&self, pk: &PublicKey<N>) -> bool {
+        falcon::verify(&self.message, &self.signature, pk)
+    }
+
+    /// Verify the signature and return the message if it is valid.
+    pub fn open(&self, pk: &PublicKey<N>) -> Option<&[u8]> {
+        self.verify(pk).then_some(self.message.as_slice())
+    }
+
+    /// Serialize in the attached format of the reference implementation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let compressed = self.signature.compressed_s();
+        let header = 0x20 | N.ilog2() as u8;
+        let signature_length = (1 + compressed.len()) as u16;
+
+        [
+            &signature_length.to_be_bytes(),
+            self.signature.r.as_slice(),
+            &self.message,
+            &[header],
+            compressed,
+        ]
+        .concat()
+    }
+
+    /// Deserialize from the attached format of the reference implementation.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FalconDeserializationError> {
+        if bytes.len() < 2 + Nonce::LEN {
+            return Err(FalconDeserializationError::BadEncodingLength);
+        }
+        let signature_length = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+        let sig_bytelen = Signature::<N>::BYTE_LEN;
+        if signature_length < 2
+            || signature_length > sig_bytelen - Nonce::LEN
+            || bytes.len() < 2 + Nonce::LEN + signature_length
+        {
+            return Err(FalconDeserializationError::BadEncodingLength);
+        }
+
+        let (nonce, rest) = bytes[2..].split_at(Nonce::LEN);
+        let (message, signature) = rest.split_at(rest.len() - signature_length);
+
+        // check header: 0010nnnn
+        let header = signature[0];
+        if header >> 4 != 2 {
+            return Err(FalconDeserializationError::InvalidHeaderFormat);
+        }
+        let n = match header & 15 {
+            9 => 512,
+            10 => 1024,
+            _ => return Err(FalconDeserializationError::InvalidLogN),
+        };
+        if n != N {
+            return Err(FalconDeserializationError::WrongVariant);
+        }
+
+        // pad the compressed vector with zeros to the fixed length
+        let mut s = signature[1..].to_vec();
+        s.resize(sig_bytelen - 1 - Nonce::LEN, 0);
+
+        Ok(Self {
+            message: message.to_vec(),
+            signature: Signature::new(nonce.try_into().unwrap(), s),
+        })
+    }
+}
+
+impl<const N: usize> From<SignedMessage<N>> for (Vec<u8>, Signature<N>) {
+    fn from(signed_message: SignedMessage<N>) -> Self {
+        signed_message.detach()
+    }
+}
+
+/// Sign a message and attach the signature to it.
+#[cfg(feature = "std")]
+pub fn sign_attached<const N: usize>(m: &[u8], sk: &SecretKey<N>) -> SignedMessage<N> {
+    SignedMessage::attach(m, falcon::sign(m, sk))
+}
+
+/// Decode a signed message in the attached format and verify it, returning
+/// the message if the encoding is well formed and the signature valid.
+pub fn open<const N: usize>(sm: &[u8], pk: &PublicKey<N>) -> Option<Vec<u8>> {
+    let signed_message = SignedMessage::from_bytes(sm).ok()?;
+    signed_message.verify(pk).then(|| signed_message.detach().0)
+}
+
+#[cfg(test)]
+mod test {
+    use pqcrypto_traits::sign::{PublicKey as _, SecretKey as _, SignedMessage as _};
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon::FalconDeserializationError, falcon512, fixtures::interop_corpus};
+
+    use super::{open, sign_attached, SignedMessage};
+
+    #[test]
+    fn test_attach_detach() {
+        let (sk, pk) = falcon512::keygen(thread_rng().gen());
+        let msg = b"attached";
+        let sig = falcon512::sign(msg, &sk);
+
+        let signed_message = SignedMessage::attach(msg, sig.clone());
+        assert_eq!(signed_message.open(&pk), Some(msg.as_slice()));
+        assert_eq!(signed_message.nonce(), sig.nonce());
+
+        let bytes = signed_message.to_bytes();
+        assert_eq!(&bytes[2..42], sig.nonce().as_bytes());
+        assert_eq!(&bytes[42..42 + msg.len()], msg);
+        let decoded = SignedMessage::<512>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, signed_message);
+        assert_eq!(decoded.detach(), (msg.to_vec(), sig));
+
+        let mut tampered = bytes.clone();
+        tampered[42] ^= 1;
+        let tampered = SignedMessage::<512>::from_bytes(&tampered).unwrap();
+        assert_eq!(tampered.open(&pk), None);
+
+        assert_eq!(
+            SignedMessage::<1024>::from_bytes(&bytes),
+            Err(FalconDeserializationError::WrongVariant)
+        );
+        assert_eq!(
+            SignedMessage::<512>::from_bytes(&bytes[..40]),
+            Err(FalconDeserializationError::BadEncodingLength)
+        );
+    }
+
+    #[test]
+    fn test_sign_attached_open() {
+        let (sk, pk) = falcon512::keygen(thread_rng().gen());
+        let (_, other_pk) = falcon512::keygen(thread_rng().gen());
+        let msg = b"attached";
+        let bytes = sign_attached(msg, &sk).to_bytes();
+        assert_eq!(open(&bytes, &pk), Some(msg.to_vec()));
+        assert_eq!(open(&bytes, &other_pk), None);
+        assert_eq!(open(&bytes[..40], &pk), None);
+    }
+
+    #[test]
+    fn test_reference_interop() {
+        let fixture = interop_corpus()
+            .into_iter()
+            .find(|f| f.source == "pqclean" && f.variant == 512)
+            .unwrap();
+        let pk = falcon512::PublicKey::from_bytes(&fixture.public_key).unwrap();
+        let sk = falcon512::SecretKey::from_bytes(&fixture.secret_key).unwrap();
+        let reference_pk =
+            pqcrypto_falcon::falcon512::PublicKey::from_bytes(&fixture.public_key).unwrap();
+        let reference_sk =
+            pqcrypto_falcon::falcon512::SecretKey::from_bytes(&fixture.secret_key).unwrap();
+        let msg = b"signed message interop";
+
+        // reference signs, we open
+        let reference = pqcrypto_falcon::falcon512::sign(msg, &reference_sk);
+        let signed_message = SignedMessage::<512>::from_bytes(reference.as_bytes()).unwrap();
+        assert_eq!(signed_message.open(&pk), Some(msg.as_slice()));
+        assert_eq!(signed_message.to_bytes(), reference.as_bytes());
+
+        // we sign, reference opens
+        let signed_message = SignedMessage::attach(msg, falcon512::sign(msg, &sk));
+        let bytes = signed_message.to_bytes();
+        let reference = pqcrypto_falcon::falcon512::SignedMessage::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            pqcrypto_falcon::falcon512::open(&reference, &reference_pk).unwrap(),
+            msg
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! A signer that async tasks can share.
+//!
+//! [`SignerHandle::spawn`] moves a secret key onto a dedicated signing
+//! thread, fed by a bounded [`tokio::sync::mpsc`] channel, and returns a
+//! handle whose [`sign`](SignerHandle::sign) method is an `async fn`. Every
+//! request carries a [`oneshot`] channel for its signature. Handles are cheap
+//! to clone and can be moved into any number of tasks, and signing, which is
+//! CPU-bound, does not run on the executor's threads.
+//!
+//! Tokio's channels only rely on [`Waker`](std::task::Waker)s, so the
+//! futures work with any executor:
+//!
+//! ```
+//! use falcon_rust::{falcon512, signing_actor::{SignerError, SignerHandle}};
+//!
+//! async fn sign_both(
+//!     signer: SignerHandle<512>,
+//! ) -> Result<[falcon512::Signature; 2], SignerError> {
+//!     let other = signer.clone();
+//!     Ok([signer.sign(b"first").await?, other.sign(b"second").await?])
+//! }
+//! ```
+//!
+//! The thread exits once the last handle is dropped and the queue is empty.
+
+use std::thread;
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::falcon::{self, SecretKey, Signature};
+
+/// The queue capacity used by [`SignerHandle::spawn`].
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// Reasons why a signing request fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignerError {
+    /// The signing thread has stopped, e.g., because it panicked, so the
+    /// request was not or will not be answered.
+    Closed,
+}
+
+struct Job<const N: usize> {
+    msg: Vec<u8>,
+    reply: oneshot::Sender<Signature<N>>,
+}
+
+/// A handle to a signing thread that owns a secret key.
+///
+/// Requests are queued in a bounded channel; when it is full,
+/// [`sign`](SignerHandle::sign) waits for room, so that a burst of requests
+/// applies backpressure instead of growing the queue without bound.
+#[derive(Clone)]
+pub struct SignerHandle<const N: usize> {
+    jobs: mpsc::Sender<Job<N>>,
+}
+
+impl<const N: usize> SignerHandle<N> {
+    /// Move the secret key onto a new signing thread, with a queue of
+    /// [`DEFAULT_CAPACITY`] requests.
+    pub fn spawn(sk: SecretKey<N>) -> Self {
+        Self::with_capacity(sk, DEFAULT_CAPACITY)
+    }
+
+    /// Move the secret key onto a new signing thread, with a queue of the
+    /// given capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    pub fn with_capacity(sk: SecretKey<N>, capacity: usize) -> Self {
+        Self::with_signer(capacity, move |m| falcon::sign(m, &sk))
+    }
+
+    /// Spawn a signing thread that answers every request with `sign`.
+    fn with_signer<F>(capacity: usize, mut sign: F) -> Self
+    where
+        F: FnMut(&[u8]) -> Signature<N> + Send + 'static,
+    {
+        assert!(capacity > 0, "queue capacity must be positive");
+        let (jobs, mut queue) = mpsc::channel::<Job<N>>(capacity);
+        thread::spawn(move || {
+            while let Some(job) = queue.blocking_recv() {
+                // the requesting future may have been dropped
+                let _ = job.reply.send(sign(&job.msg));
+            }
+        });
+        Self { jobs }
+    }
+
+    /// Sign a message.
+    ///
+    /// Fails with [`SignerError::Closed`] if the signing thread has stopped.
+    /// If the future is dropped after the request was queued, the message is
+    /// still signed, but the signature is discarded.
+    pub async fn sign(&self, m: &[u8]) -> Result<Signature<N>, SignerError> {
+        let (reply, signature) = oneshot::channel();
+        let job = Job {
+            msg: m.to_vec(),
+            reply,
+        };
+        self.jobs.send(job).await.map_err(|_| SignerError::Closed)?;
+        signature.await.map_err(|_| SignerError::Closed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        future::Future,
+        pin::pin,
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+    };
+
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon512;
+
+    use super::{SignerError, SignerHandle};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// A minimal executor: poll on the current thread, parking in between.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker: Waker = Arc::new(ThreadWaker(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_signing_actor() {
+        let (sk, pk) = falcon512::keygen(thread_rng().gen());
+        // a tiny queue, so that tasks have to wait for room
+        let signer = SignerHandle::with_capacity(sk, 1);
+
+        let handles = (0..4)
+            .map(|i| {
+                let signer = signer.clone();
+                thread::spawn(move || {
+                    let msgs = (0..3).map(|j| [i, j]).collect::<Vec<_>>();
+                    let sigs = msgs
+                        .iter()
+                        .map(|msg| block_on(signer.sign(msg)).unwrap())
+                        .collect::<Vec<_>>();
+                    (msgs, sigs)
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(signer);
+
+        for handle in handles {
+            let (msgs, sigs) = handle.join().unwrap();
+            for (msg, sig) in msgs.iter().zip(&sigs) {
+                assert!(falcon512::verify(msg, sig, &pk));
+            }
+        }
+    }
+
+    #[test]
+    fn test_signing_actor_worker_dies() {
+        let (sk, pk) = falcon512::keygen(thread_rng().gen());
+        let signer = SignerHandle::with_signer(1, move |m| {
+            assert_ne!(m, b"crash", "the signing thread panics");
+            falcon512::sign(m, &sk)
+        });
+
+        let sig = block_on(signer.sign(b"before")).unwrap();
+        assert!(falcon512::verify(b"before", &sig, &pk));
+
+        // the request in flight is dropped by the panicking thread
+        assert_eq!(block_on(signer.sign(b"crash")), Err(SignerError::Closed));
+        // and later requests find the queue closed instead of hanging
+        let other = signer.clone();
+        assert_eq!(block_on(signer.sign(b"after")), Err(SignerError::Closed));
+        assert_eq!(block_on(other.sign(b"after")), Err(SignerError::Closed));
+    }
+}
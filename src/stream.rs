@@ -0,0 +1,108 @@
+//! Parallel verification of a stream of signatures, with results in
+//! arrival order.
+//!
+//! ```
+//! use falcon_rust::{falcon512, stream::VerifyStreamExt};
+//! # use rand::{thread_rng, Rng};
+//!
+//! # let (sk, pk) = falcon512::keygen(thread_rng().gen());
+//! let incoming = (0..10u8).map(|i| {
+//!     let msg = vec![i; 8];
+//!     let sig = falcon512::sign(&msg, &sk);
+//!     (msg, sig, pk.clone())
+//! });
+//! assert!(incoming.verify_ordered(4).all(|valid| valid));
+//! ```
+
+use rayon::prelude::*;
+
+use crate::falcon::{self, PublicKey, Signature};
+
+/// Adds [`verify_ordered`](VerifyStreamExt::verify_ordered) to iterators of
+/// `(message, signature, public key)` triples.
+pub trait VerifyStreamExt<const N: usize, M>:
+    Iterator<Item = (M, Signature<N>, PublicKey<N>)> + Sized
+{
+    /// Verify the items in parallel, yielding one result per item in input
+    /// order.
+    ///
+    /// Items are pulled from the input a window at a time and verified on
+    /// the rayon thread pool, whose work stealing keeps all threads busy even
+    /// when some items take longer than others. Larger windows give more
+    /// parallelism at the cost of latency and memory.
+    ///
+    /// # Panics
+    ///
+    /// If `window` is zero.
+    fn verify_ordered(self, window: usize) -> OrderedVerify<Self, N> {
+        assert!(window > 0, "the window must hold at least one item");
+        OrderedVerify {
+            inner: self,
+            window,
+            results: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<const N: usize, M, I> VerifyStreamExt<N, M> for I where
+    I: Iterator<Item = (M, Signature<N>, PublicKey<N>)>
+{
+}
+
+/// Iterator returned by [`VerifyStreamExt::verify_ordered`].
+pub struct OrderedVerify<I, const N: usize> {
+    inner: I,
+    window: usize,
+    results: std::vec::IntoIter<bool>,
+}
+
+impl<const N: usize, M, I> Iterator for OrderedVerify<I, N>
+where
+    M: AsRef<[u8]> + Send + Sync,
+    I: Iterator<Item = (M, Signature<N>, PublicKey<N>)>,
+{
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if let Some(valid) = self.results.next() {
+            return Some(valid);
+        }
+        let window = self.inner.by_ref().take(self.window).collect::<Vec<_>>();
+        // an indexed parallel collect keeps the input order
+        self.results = window
+            .par_iter()
+            .map(|(m, sig, pk)| falcon::verify(m.as_ref(), sig, pk))
+            .collect::<Vec<_>>()
+            .into_iter();
+        self.results.next()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon512;
+
+    use super::VerifyStreamExt;
+
+    #[test]
+    fn test_verify_ordered() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let (other_sk, _) = falcon512::keygen(rng.gen());
+        let items = (0..7)
+            .map(|i| {
+                let msg = vec![i as u8; 10];
+                let sig = falcon512::sign(&msg, if i % 3 == 1 { &other_sk } else { &sk });
+                (msg, sig, pk.clone())
+            })
+            .collect::<Vec<_>>();
+
+        let expected = [true, false, true, true, false, true, true];
+        for window in [1, 3, 7, 100] {
+            let results = items.clone().into_iter().verify_ordered(window);
+            assert_eq!(results.collect::<Vec<_>>(), expected);
+        }
+    }
+}
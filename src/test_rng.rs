@@ -0,0 +1,146 @@
+//! Reproducible randomness for tests and fuzzing.
+//!
+//! When the crate is built with `--cfg falcon_test_rng`, all randomness it
+//! draws on its own, i.e., for
+//! [`SecretKey::generate`](crate::falcon512::SecretKey::generate), the salt
+//! and sampler of [`sign`](crate::falcon512::sign), and so on, comes from a
+//! ChaCha20 PRNG seeded from a single 32-byte seed instead of from the
+//! operating system. This is a `rustc` flag rather than a Cargo feature, so
+//! that no dependency can turn it on for a production build.
+//!
+//! The seed is taken from the [`SEED_VARIABLE`] environment variable, as 64
+//! hex digits, or, if it is unset, drawn at random and printed to standard
+//! error once, so that a failing run can be repeated exactly. [`set_seed`]
+//! overrides it programmatically.
+//!
+//! Every thread has its own PRNG, derived from the seed and the thread's
+//! name. Since the test harness names threads after the tests they run, a
+//! test draws the same randomness whatever other tests run alongside it.
+//!
+//! ```sh
+//! RUSTFLAGS="--cfg falcon_test_rng" FALCON_RUST_TEST_SEED=00…00 cargo test failing_test
+//! ```
+
+use std::{cell::RefCell, sync::OnceLock, thread};
+
+use rand::{CryptoRng, Error, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+/// The environment variable the seed is read from.
+pub const SEED_VARIABLE: &str = "FALCON_RUST_TEST_SEED";
+
+/// The seed of threads that do not call [`set_seed`].
+fn default_seed() -> [u8; 32] {
+    static SEED: OnceLock<[u8; 32]> = OnceLock::new();
+    *SEED.get_or_init(|| match std::env::var(SEED_VARIABLE) {
+        Ok(value) => hex::decode(value.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .unwrap_or_else(|| panic!("{SEED_VARIABLE} must be 32 hex-encoded bytes")),
+        Err(_) => {
+            let mut seed = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut seed);
+            eprintln!(
+                "falcon-rust test RNG seed: {} (set {SEED_VARIABLE} to reproduce)",
+                hex::encode(seed)
+            );
+            seed
+        }
+    })
+}
+
+/// Derive the PRNG of the current thread from the seed.
+fn thread_rng_from_seed(seed: [u8; 32]) -> ChaCha20Rng {
+    let mut hasher = Shake256::default();
+    hasher.update(b"falcon-rust test rng");
+    hasher.update(&seed);
+    hasher.update(thread::current().name().unwrap_or_default().as_bytes());
+    let mut thread_seed = [0u8; 32];
+    hasher.finalize_xof().read(&mut thread_seed);
+    ChaCha20Rng::from_seed(thread_seed)
+}
+
+thread_local! {
+    static STATE: RefCell<Option<([u8; 32], ChaCha20Rng)>> = const { RefCell::new(None) };
+}
+
+fn with_rng<T>(f: impl FnOnce(&mut ChaCha20Rng) -> T) -> T {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let (_, rng) = state.get_or_insert_with(|| {
+            let seed = default_seed();
+            (seed, thread_rng_from_seed(seed))
+        });
+        f(rng)
+    })
+}
+
+/// Restart the current thread's PRNG from the given seed.
+pub fn set_seed(seed: [u8; 32]) {
+    STATE.with(|state| *state.borrow_mut() = Some((seed, thread_rng_from_seed(seed))));
+}
+
+/// The seed the current thread's PRNG was started from.
+pub fn seed() -> [u8; 32] {
+    STATE.with(|state| {
+        state
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                let seed = default_seed();
+                (seed, thread_rng_from_seed(seed))
+            })
+            .0
+    })
+}
+
+/// A handle to the current thread's PRNG.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TestRng;
+
+impl RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        with_rng(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        with_rng(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        with_rng(|rng| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        with_rng(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
+impl CryptoRng for TestRng {}
+
+#[cfg(test)]
+mod test {
+    use crate::falcon512;
+
+    use super::{seed, set_seed};
+
+    #[test]
+    fn test_reproducible() {
+        let run = || {
+            let sk = falcon512::SecretKey::generate();
+            let sig = falcon512::sign(b"reproduce me", &sk);
+            (sk.to_bytes(), sig)
+        };
+
+        set_seed([7u8; 32]);
+        assert_eq!(seed(), [7u8; 32]);
+        let first = run();
+        set_seed([7u8; 32]);
+        assert_eq!(run(), first);
+        set_seed([8u8; 32]);
+        assert_ne!(run(), first);
+    }
+}
@@ -33,13 +33,21 @@ impl TestData512 {
             .iter()
             .map(|item| {
                 falcon512::ExpandedSignature::from_signature(
-                    &item.message,
+                    item.message,
                     &item.signature,
                     &self.public_key,
                 )
             })
             .collect()
     }
+
+    /// Generate expanded signatures for all items, as cheaply clonable handles.
+    pub fn shared_expanded_signatures(&self) -> Vec<falcon512::SharedExpandedSignature> {
+        self.expanded_signatures()
+            .into_iter()
+            .map(falcon512::SharedExpandedSignature::new)
+            .collect()
+    }
 }
 
 /// A test data item containing a message, signature, and validity flag for Falcon1024.
@@ -68,13 +76,21 @@ impl TestData1024 {
             .iter()
             .map(|item| {
                 falcon1024::ExpandedSignature::from_signature(
-                    &item.message,
+                    item.message,
                     &item.signature,
                     &self.public_key,
                 )
             })
             .collect()
     }
+
+    /// Generate expanded signatures for all items, as cheaply clonable handles.
+    pub fn shared_expanded_signatures(&self) -> Vec<falcon1024::SharedExpandedSignature> {
+        self.expanded_signatures()
+            .into_iter()
+            .map(falcon1024::SharedExpandedSignature::new)
+            .collect()
+    }
 }
 
 /// Generate test data with a mix of valid and invalid signatures for Falcon512.
@@ -101,7 +117,7 @@ pub fn generate_test_data_512<R: Rng>(
     // Generate valid signatures
     for _ in 0..num_valid {
         let message: [u8; 32] = rng.gen();
-        let signature = falcon512::sign(&message, &sk);
+        let signature = falcon512::sign(message, &sk);
         items.push(TestDataItem512 {
             is_valid: true,
             message,
@@ -112,7 +128,7 @@ pub fn generate_test_data_512<R: Rng>(
     // Generate invalid signatures (signed with wrong key)
     for _ in 0..num_invalid {
         let message: [u8; 32] = rng.gen();
-        let signature = falcon512::sign(&message, &sk_invalid);
+        let signature = falcon512::sign(message, &sk_invalid);
         items.push(TestDataItem512 {
             is_valid: false,
             message,
@@ -154,7 +170,7 @@ pub fn generate_test_data_1024<R: Rng>(
     // Generate valid signatures
     for _ in 0..num_valid {
         let message: [u8; 32] = rng.gen();
-        let signature = falcon1024::sign(&message, &sk);
+        let signature = falcon1024::sign(message, &sk);
         items.push(TestDataItem1024 {
             is_valid: true,
             message,
@@ -165,7 +181,7 @@ pub fn generate_test_data_1024<R: Rng>(
     // Generate invalid signatures (signed with wrong key)
     for _ in 0..num_invalid {
         let message: [u8; 32] = rng.gen();
-        let signature = falcon1024::sign(&message, &sk_invalid);
+        let signature = falcon1024::sign(message, &sk_invalid);
         items.push(TestDataItem1024 {
             is_valid: false,
             message,
@@ -203,7 +219,7 @@ mod tests {
 
         // Verify that valid signatures actually verify
         for item in &data.items {
-            let verified = falcon512::verify(&item.message, &item.signature, &data.public_key);
+            let verified = falcon512::verify(item.message, &item.signature, &data.public_key);
             assert_eq!(verified, item.is_valid);
         }
     }
@@ -224,7 +240,7 @@ mod tests {
 
         // Verify correctness
         for item in &data.items {
-            let verified = falcon1024::verify(&item.message, &item.signature, &data.public_key);
+            let verified = falcon1024::verify(item.message, &item.signature, &data.public_key);
             assert_eq!(verified, item.is_valid);
         }
     }
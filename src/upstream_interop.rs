@@ -0,0 +1,94 @@
+//! Conversions to and from the types of the upstream [`falcon-rust`] crate,
+//! from which this crate is forked.
+//!
+//! Both crates use the same serialization for keys and signatures, so data
+//! stored by one can be read by the other unchanged; the conversions here
+//! save the round trip through bytes.
+//!
+//! [`falcon-rust`]: https://crates.io/crates/falcon-rust
+
+use crate::falcon::{FalconDeserializationError, PublicKey, SecretKey, Signature};
+
+macro_rules! impl_upstream_conversions {
+    ($n:literal, $module:ident) => {
+        impl From<&PublicKey<$n>> for upstream::$module::PublicKey {
+            fn from(pk: &PublicKey<$n>) -> Self {
+                Self::from_bytes(pk.as_bytes()).expect("public key encodings agree")
+            }
+        }
+
+        impl TryFrom<&upstream::$module::PublicKey> for PublicKey<$n> {
+            type Error = FalconDeserializationError;
+
+            fn try_from(pk: &upstream::$module::PublicKey) -> Result<Self, Self::Error> {
+                Self::from_bytes(&pk.to_bytes())
+            }
+        }
+
+        impl From<&SecretKey<$n>> for upstream::$module::SecretKey {
+            fn from(sk: &SecretKey<$n>) -> Self {
+                Self::from_bytes(&sk.to_bytes()).expect("secret key encodings agree")
+            }
+        }
+
+        impl TryFrom<&upstream::$module::SecretKey> for SecretKey<$n> {
+            type Error = FalconDeserializationError;
+
+            fn try_from(sk: &upstream::$module::SecretKey) -> Result<Self, Self::Error> {
+                Self::from_bytes(&sk.to_bytes())
+            }
+        }
+
+        impl From<&Signature<$n>> for upstream::$module::Signature {
+            fn from(sig: &Signature<$n>) -> Self {
+                Self::from_bytes(sig.as_bytes()).expect("signature encodings agree")
+            }
+        }
+
+        impl TryFrom<&upstream::$module::Signature> for Signature<$n> {
+            type Error = FalconDeserializationError;
+
+            fn try_from(sig: &upstream::$module::Signature) -> Result<Self, Self::Error> {
+                Self::from_bytes(&sig.to_bytes())
+            }
+        }
+    };
+}
+
+impl_upstream_conversions!(512, falcon512);
+impl_upstream_conversions!(1024, falcon1024);
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon512;
+
+    #[test]
+    fn test_upstream_interop() {
+        let msg = b"switching forks";
+        let seed: [u8; 32] = thread_rng().gen();
+
+        // keys generated from the same seed agree
+        let (upstream_sk, upstream_pk) = upstream::falcon512::keygen(seed);
+        let (sk, pk) = falcon512::keygen(seed);
+        assert_eq!(falcon512::PublicKey::try_from(&upstream_pk).unwrap(), pk);
+        assert_eq!(falcon512::SecretKey::try_from(&upstream_sk).unwrap(), sk);
+        assert_eq!(upstream::falcon512::PublicKey::from(&pk), upstream_pk);
+        assert_eq!(upstream::falcon512::SecretKey::from(&sk), upstream_sk);
+
+        let upstream_sig = upstream::falcon512::sign(msg, &upstream_sk);
+        let sig = falcon512::Signature::try_from(&upstream_sig).unwrap();
+        assert!(falcon512::verify(msg, &sig, &pk));
+        let expanded = falcon512::ExpandedSignature::from_signature(msg, &sig, &pk);
+        assert!(falcon512::fverify(msg, &expanded, &pk, &[0, 1, 2]));
+
+        let sig = falcon512::sign(msg, &sk);
+        let upstream_sig = upstream::falcon512::Signature::from(&sig);
+        assert!(upstream::falcon512::verify(
+            msg,
+            &upstream_sig,
+            &upstream_pk
+        ));
+    }
+}
@@ -0,0 +1,192 @@
+//! A verification context that can be shared across threads.
+
+use crate::falcon::{self, ExpandedPublicKey, ExpandedSignature, PublicKey, Signature};
+
+/// A public key together with the data that verification precomputes from
+/// it, most notably the public key in NTT domain; see [`ExpandedPublicKey`].
+///
+/// A context is immutable after construction and is `Send + Sync`, so it can
+/// be wrapped in an [`Arc`](std::sync::Arc) and shared by any number of
+/// verification threads without locking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifierContext<const N: usize> {
+    key: ExpandedPublicKey<N>,
+}
+
+// Contexts are meant to be shared across threads; keep it that way.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<VerifierContext<512>>();
+    assert_send_sync::<VerifierContext<1024>>();
+};
+
+impl<const N: usize> VerifierContext<N> {
+    /// Precompute the verification context for a public key.
+    pub fn new(pk: &PublicKey<N>) -> Self {
+        Self {
+            key: ExpandedPublicKey::new(pk),
+        }
+    }
+
+    /// The public key this context verifies against.
+    pub fn public_key(&self) -> &PublicKey<N> {
+        self.key.public_key()
+    }
+
+    /// The expanded public key this context verifies against.
+    pub fn expanded_public_key(&self) -> &ExpandedPublicKey<N> {
+        &self.key
+    }
+
+    /// Verify a signature. Equivalent to [`falcon::verify`].
+    pub fn verify(&self, m: &[u8], sig: &Signature<N>) -> bool {
+        falcon::verify_with_h_ntt(&[m], sig, self.key.h_ntt())
+    }
+
+    /// Verify a signature on a message given as a sequence of parts.
+    /// Equivalent to [`falcon::verify_vectored`].
+    pub fn verify_vectored(&self, m: &[&[u8]], sig: &Signature<N>) -> bool {
+        falcon::verify_with_h_ntt(m, sig, self.key.h_ntt())
+    }
+
+    /// Verify a signature against several candidate contexts, returning the
+    /// index of the first one for which it is valid. Equivalent to
+    /// [`falcon::verify_any`].
+    pub fn verify_any(m: &[u8], sig: &Signature<N>, contexts: &[Self]) -> Option<usize> {
+        falcon::verify_any_with_h_ntt(
+            &[m],
+            sig,
+            contexts.iter().map(|context| context.key.h_ntt()),
+        )
+    }
+
+    /// Verify an expanded signature. Equivalent to [`falcon::verify_expanded`].
+    pub fn verify_expanded(&self, m: &[u8], sig: &ExpandedSignature<N>) -> bool {
+        falcon::verify_expanded_with_h_ntt(m, sig, self.key.h_ntt())
+    }
+
+    /// Fast verify an expanded signature. Equivalent to [`falcon::fverify`].
+    pub fn fverify(&self, m: &[u8], sig: &ExpandedSignature<N>, indices: &[usize]) -> bool {
+        falcon::fverify(m, sig, self.key.public_key(), indices)
+    }
+
+    /// Fast verify an expanded signature and, if it passes, do a full
+    /// verify. Equivalent to [`falcon::fverify_fullverify`].
+    pub fn fverify_fullverify(
+        &self,
+        m: &[u8],
+        sig: &ExpandedSignature<N>,
+        indices: &[usize],
+    ) -> bool {
+        falcon::fverify_fullverify_with_h_ntt(
+            m,
+            sig,
+            self.key.public_key(),
+            Some(self.key.h_ntt()),
+            indices,
+        )
+    }
+}
+
+impl<const N: usize> From<&PublicKey<N>> for VerifierContext<N> {
+    fn from(pk: &PublicKey<N>) -> Self {
+        Self::new(pk)
+    }
+}
+
+/// Wrap a precomputed, e.g., deserialized, expanded public key without
+/// recomputing anything.
+impl<const N: usize> From<ExpandedPublicKey<N>> for VerifierContext<N> {
+    fn from(key: ExpandedPublicKey<N>) -> Self {
+        Self { key }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{sync::Arc, thread};
+
+    use rand::{thread_rng, Rng};
+
+    use crate::{falcon::FalconDeserializationError, falcon1024, falcon512};
+
+    use super::VerifierContext;
+
+    #[test]
+    fn test_shared_context() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let (other_sk, _) = falcon512::keygen(rng.gen());
+        let context = Arc::new(VerifierContext::new(&pk));
+
+        let handles = (0..4)
+            .map(|i| {
+                let context = Arc::clone(&context);
+                let msg = [i as u8; 8];
+                let sig = falcon512::sign(msg, &sk);
+                let forged = falcon512::sign(msg, &other_sk);
+                thread::spawn(move || {
+                    let expanded = falcon512::ExpandedSignature::from_signature(
+                        msg,
+                        &sig,
+                        context.public_key(),
+                    );
+                    let indices = [0, 17, 511];
+                    context.verify(&msg, &sig)
+                        && VerifierContext::verify_any(&msg, &sig, &[(*context).clone()]) == Some(0)
+                        && !context.verify(&msg, &forged)
+                        && context.verify_expanded(&msg, &expanded)
+                        && context.fverify(&msg, &expanded, &indices)
+                        && context.fverify_fullverify(&msg, &expanded, &indices)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_shipped_expanded_public_key() {
+        let mut rng = thread_rng();
+        let (sk, pk) = falcon512::keygen(rng.gen());
+        let msg = b"precomputed once";
+        let sig = falcon512::sign(msg, &sk);
+
+        // ingestion service
+        let bytes = falcon512::ExpandedPublicKey::new(&pk).to_bytes();
+        assert_eq!(bytes.len(), falcon512::ExpandedPublicKey::BYTE_LEN);
+
+        // verifier worker
+        let key = falcon512::ExpandedPublicKey::from_bytes(&bytes).unwrap();
+        assert!(key.is_consistent());
+        assert_eq!(key.public_key(), &pk);
+        let context = VerifierContext::from(key);
+        assert_eq!(context, VerifierContext::new(&pk));
+        assert!(context.verify(msg, &sig));
+
+        assert_eq!(
+            falcon1024::ExpandedPublicKey::from_bytes(&bytes),
+            Err(FalconDeserializationError::WrongVariant)
+        );
+        let mut corrupted = bytes.clone();
+        corrupted[0] = 0x09;
+        assert_eq!(
+            falcon512::ExpandedPublicKey::from_bytes(&corrupted),
+            Err(FalconDeserializationError::InvalidHeaderFormat)
+        );
+        let mut corrupted = bytes.clone();
+        corrupted[1] = 0xff;
+        assert_eq!(
+            falcon512::ExpandedPublicKey::from_bytes(&corrupted),
+            Err(FalconDeserializationError::BadFieldElementEncoding)
+        );
+        let mut corrupted = bytes;
+        let value = u16::from_be_bytes([corrupted[1025], corrupted[1026]]);
+        corrupted[1025..1027].copy_from_slice(&((value + 1) % 12289).to_be_bytes());
+        assert!(!falcon512::ExpandedPublicKey::from_bytes(&corrupted)
+            .unwrap()
+            .is_consistent());
+    }
+}
@@ -0,0 +1,150 @@
+//! Bindings for JavaScript through `wasm-bindgen`, with keys, signatures and
+//! messages passed as byte arrays. The variant is chosen at key generation
+//! and inferred from the encodings afterwards.
+//!
+//! Randomness comes from `crypto.getRandomValues` through `getrandom`. A
+//! `cdylib` crate that depends on this one with the `wasm` feature and
+//! re-exports this module can be built with `wasm-pack`.
+//!
+//! ```js
+//! const keys = keygen(512);
+//! const sig = sign(keys.secretKey, message);
+//! verify(keys.publicKey, message, sig); // true
+//! ```
+
+use alloc::vec::Vec;
+
+use rand::Rng;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    dynamic::{FalconPublicKey, FalconSecretKey, FalconSignature},
+    entropy,
+    falcon::{self, ExpandedSignature, PublicKey},
+};
+
+/// A freshly generated key pair, in encoded form.
+#[wasm_bindgen]
+pub struct KeyPair {
+    secret_key: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl KeyPair {
+    #[wasm_bindgen(getter, js_name = secretKey)]
+    pub fn secret_key(&self) -> Vec<u8> {
+        self.secret_key.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+}
+
+/// Generate a key pair for Falcon-`n`, where `n` is 512 or 1024.
+#[wasm_bindgen]
+pub fn keygen(n: u32) -> Result<KeyPair, JsValue> {
+    let seed = entropy::rng().gen();
+    let (secret_key, public_key) = match n {
+        512 => {
+            let (sk, pk) = falcon::keygen::<512>(seed);
+            (sk.to_bytes(), pk.to_bytes())
+        }
+        1024 => {
+            let (sk, pk) = falcon::keygen::<1024>(seed);
+            (sk.to_bytes(), pk.to_bytes())
+        }
+        _ => return Err(JsValue::from_str("n must be 512 or 1024")),
+    };
+    Ok(KeyPair {
+        secret_key,
+        public_key,
+    })
+}
+
+/// Sign a message with an encoded secret key, returning the encoded
+/// signature.
+#[wasm_bindgen]
+pub fn sign(secret_key: &[u8], message: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let sk = FalconSecretKey::from_bytes(secret_key)
+        .map_err(|_| JsValue::from_str("malformed secret key"))?;
+    Ok(sk.sign(message).to_bytes())
+}
+
+/// Verify an encoded signature on a message. Malformed keys and signatures
+/// are rejected.
+#[wasm_bindgen]
+pub fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    match (
+        FalconPublicKey::from_bytes(public_key),
+        FalconSignature::from_bytes(signature),
+    ) {
+        (Ok(pk), Ok(sig)) => pk.verify(message, &sig),
+        _ => false,
+    }
+}
+
+/// Fast verify an encoded expanded signature on a message, checking only
+/// the coefficients at the given indices; see [`falcon::fverify`].
+/// Malformed keys and signatures are rejected.
+#[wasm_bindgen]
+pub fn fverify(
+    public_key: &[u8],
+    message: &[u8],
+    expanded_signature: &[u8],
+    indices: &[u32],
+) -> bool {
+    let indices = indices.iter().map(|&i| i as usize).collect::<Vec<_>>();
+    match FalconPublicKey::from_bytes(public_key) {
+        Ok(FalconPublicKey::F512(pk)) => fverify_bytes(&pk, message, expanded_signature, &indices),
+        Ok(FalconPublicKey::F1024(pk)) => fverify_bytes(&pk, message, expanded_signature, &indices),
+        Err(_) => false,
+    }
+}
+
+fn fverify_bytes<const N: usize>(
+    pk: &PublicKey<N>,
+    m: &[u8],
+    sig: &[u8],
+    indices: &[usize],
+) -> bool {
+    ExpandedSignature::<N>::from_bytes(sig).is_ok_and(|sig| falcon::fverify(m, &sig, pk, indices))
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{thread_rng, Rng};
+
+    use crate::falcon::{ExpandedSignature, PublicKey, Signature};
+
+    use super::{fverify, keygen, sign, verify};
+
+    fn expand<const N: usize>(pk: &[u8], sig: &[u8]) -> Vec<u8> {
+        let pk = PublicKey::<N>::from_bytes(pk).unwrap();
+        let sig = Signature::<N>::from_bytes(sig).unwrap();
+        ExpandedSignature::from_signature(b"msg", &sig, &pk).to_bytes()
+    }
+
+    #[test]
+    fn test_wasm_round_trip() {
+        for n in [512, 1024] {
+            let keys = keygen(n).unwrap();
+            let sig = sign(&keys.secret_key(), b"msg").unwrap();
+            assert!(verify(&keys.public_key(), b"msg", &sig));
+            assert!(!verify(&keys.public_key(), b"other", &sig));
+            assert!(!verify(&keys.public_key(), b"msg", &sig[..10]));
+
+            let expanded = match n {
+                512 => expand::<512>(&keys.public_key(), &sig),
+                _ => expand::<1024>(&keys.public_key(), &sig),
+            };
+            let indices = (0..16)
+                .map(|_| thread_rng().gen_range(0..n))
+                .collect::<Vec<_>>();
+            assert!(fverify(&keys.public_key(), b"msg", &expanded, &indices));
+            assert!(!fverify(&keys.public_key(), b"msg", &sig, &indices));
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! X.509 certificates and certificate signing requests signed with Falcon,
+//! through the RustCrypto [`x509-cert`] crate:
+//!
+//! ```
+//! use core::{str::FromStr, time::Duration};
+//!
+//! use falcon_rust::{falcon512, x509};
+//! use x509_cert::{name::Name, serial_number::SerialNumber, time::Validity};
+//! # use rand::{thread_rng, Rng};
+//!
+//! let (sk, _) = falcon512::keygen(thread_rng().gen());
+//! let cert = x509::self_signed_certificate(
+//!     &sk,
+//!     Name::from_str("CN=Falcon test root").unwrap(),
+//!     SerialNumber::from(1u32),
+//!     Validity::from_now(Duration::from_secs(86400)).unwrap(),
+//! )
+//! .unwrap();
+//! let issuer = &cert.tbs_certificate.subject_public_key_info;
+//! assert!(x509::verify_certificate::<512>(&cert, issuer));
+//! ```
+//!
+//! Certificates and requests use the draft OIDs of
+//! [`pkcs8_traits`](crate::pkcs8_traits) both for the keys and for the
+//! signature algorithm, without parameters, as the Open Quantum Safe
+//! project does. The signature bit string holds the encoding of
+//! [`Signature::to_bytes`].
+//!
+//! For anything beyond these helpers, such as CA certificates or
+//! extensions, [`SecretKey`] implements the traits that the builders of
+//! `x509-cert` expect of a signer, and [`Signature`] those of a signature.
+//!
+//! [`x509-cert`]: https://crates.io/crates/x509-cert
+
+use pkcs8::{
+    der::{
+        asn1::{AnyRef, BitString},
+        referenced::OwnedToRef,
+        Encode,
+    },
+    spki::{
+        AlgorithmIdentifier, AlgorithmIdentifierOwned, SignatureAlgorithmIdentifier,
+        SignatureBitStringEncoding, SubjectPublicKeyInfoOwned,
+    },
+};
+use x509_cert::{
+    builder::{Builder, CertificateBuilder, Error, Profile, RequestBuilder},
+    name::Name,
+    request::CertReq,
+    serial_number::SerialNumber,
+    time::Validity,
+    Certificate,
+};
+
+use crate::{
+    falcon::{self, PublicKey, SecretKey, Signature},
+    pkcs8_traits::algorithm_identifier,
+};
+
+impl<const N: usize> SignatureAlgorithmIdentifier for SecretKey<N> {
+    type Params = AnyRef<'static>;
+
+    const SIGNATURE_ALGORITHM_IDENTIFIER: AlgorithmIdentifier<Self::Params> =
+        algorithm_identifier(N);
+}
+
+impl<const N: usize> SignatureBitStringEncoding for Signature<N> {
+    fn to_bitstring(&self) -> pkcs8::der::Result<BitString> {
+        BitString::from_bytes(&self.to_bytes())
+    }
+}
+
+/// Create a self-signed certificate for the key, as a root CA.
+pub fn self_signed_certificate<const N: usize>(
+    sk: &SecretKey<N>,
+    subject: Name,
+    serial_number: SerialNumber,
+    validity: Validity,
+) -> Result<Certificate, Error> {
+    let spki = SubjectPublicKeyInfoOwned::from_key(sk.public_key())?;
+    CertificateBuilder::new(Profile::Root, serial_number, validity, subject, spki, sk)?
+        .build::<Signature<N>>()
+}
+
+/// Create a certificate signing request for the key.
+pub fn certificate_signing_request<const N: usize>(
+    sk: &SecretKey<N>,
+    subject: Name,
+) -> Result<CertReq, Error> {
+    RequestBuilder::new(subject, sk)?.build::<Signature<N>>()
+}
+
+/// Verify the signature on a certificate, given the subject public key info
+/// of its issuer, which must be a Falcon-N key. The validity period,
+/// extensions and chain are not checked.
+pub fn verify_certificate<const N: usize>(
+    cert: &Certificate,
+    issuer: &SubjectPublicKeyInfoOwned,
+) -> bool {
+    verify_signed::<N>(
+        &cert.signature_algorithm,
+        &cert.tbs_certificate,
+        &cert.signature,
+        issuer,
+    )
+}
+
+/// Verify the signature on a certificate signing request against the
+/// public key it contains, which must be a Falcon-N key.
+pub fn verify_signing_request<const N: usize>(req: &CertReq) -> bool {
+    verify_signed::<N>(
+        &req.algorithm,
+        &req.info,
+        &req.signature,
+        &req.info.public_key,
+    )
+}
+
+fn verify_signed<const N: usize>(
+    algorithm: &AlgorithmIdentifierOwned,
+    tbs: &impl Encode,
+    signature: &BitString,
+    spki: &SubjectPublicKeyInfoOwned,
+) -> bool {
+    let expected = SecretKey::<N>::SIGNATURE_ALGORITHM_IDENTIFIER;
+    if algorithm.oid != expected.oid || algorithm.parameters.is_some() {
+        return false;
+    }
+    let (Ok(tbs), Some(signature), Ok(pk)) = (
+        tbs.to_der(),
+        signature.as_bytes(),
+        PublicKey::<N>::try_from(spki.owned_to_ref()),
+    ) else {
+        return false;
+    };
+    Signature::<N>::from_bytes(signature).is_ok_and(|sig| falcon::verify(tbs, &sig, &pk))
+}
+
+#[cfg(test)]
+mod test {
+    use core::{str::FromStr, time::Duration};
+
+    use pkcs8::der::{Decode, Encode};
+    use rand::{thread_rng, Rng};
+    use x509_cert::{
+        name::Name, request::CertReq, serial_number::SerialNumber, time::Validity, Certificate,
+    };
+
+    use crate::falcon::keygen;
+
+    use super::{
+        certificate_signing_request, self_signed_certificate, verify_certificate,
+        verify_signing_request,
+    };
+
+    #[test]
+    fn test_x509() {
+        let (sk, _) = keygen::<512>(thread_rng().gen());
+        let subject = Name::from_str("CN=Falcon test,O=falcon-rust").unwrap();
+        let cert = self_signed_certificate(
+            &sk,
+            subject.clone(),
+            SerialNumber::from(42u32),
+            Validity::from_now(Duration::from_secs(3600)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(cert.tbs_certificate.issuer, subject);
+        let der = cert.to_der().unwrap();
+        let cert = Certificate::from_der(&der).unwrap();
+        let spki = cert.tbs_certificate.subject_public_key_info.clone();
+        assert!(verify_certificate::<512>(&cert, &spki));
+        assert!(!verify_certificate::<1024>(&cert, &spki));
+
+        let mut tampered = cert.clone();
+        tampered.tbs_certificate.serial_number = SerialNumber::from(43u32);
+        assert!(!verify_certificate::<512>(&tampered, &spki));
+        let (other_sk, _) = keygen::<512>(thread_rng().gen());
+        let other = certificate_signing_request(&other_sk, subject.clone()).unwrap();
+        assert!(!verify_certificate::<512>(&cert, &other.info.public_key));
+
+        let req = certificate_signing_request(&sk, subject).unwrap();
+        let req = CertReq::from_der(&req.to_der().unwrap()).unwrap();
+        assert!(verify_signing_request::<512>(&req));
+        assert_eq!(req.info.public_key, spki);
+        let mut tampered = req.clone();
+        tampered.info.public_key = other.info.public_key;
+        assert!(!verify_signing_request::<512>(&tampered));
+
+        let (sk, _) = keygen::<1024>(thread_rng().gen());
+        let req = certificate_signing_request(&sk, Name::default()).unwrap();
+        assert!(verify_signing_request::<1024>(&req));
+        assert!(!verify_signing_request::<512>(&req));
+    }
+}